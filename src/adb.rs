@@ -0,0 +1,61 @@
+//! Android devices through `adb`, for when MTP is missing or unreliable.
+
+use crate::remote::{self, RemoteEntry};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdbDevice {
+    pub serial: String,
+    pub model: String,
+}
+
+/// Devices that are connected and authorized; none if `adb` isn't installed.
+pub fn devices() -> Vec<AdbDevice> {
+    let Ok(output) = Command::new("adb").args(["devices", "-l"]).output() else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let serial = fields.next()?;
+            if fields.next()? != "device" {
+                return None;
+            }
+            let model = fields.find_map(|field| field.strip_prefix("model:")).unwrap_or(serial);
+            Some(AdbDevice {
+                serial: serial.to_string(),
+                model: model.replace('_', " "),
+            })
+        })
+        .collect()
+}
+
+pub fn list(serial: &str, path: &str) -> Result<Vec<RemoteEntry>, String> {
+    // The trailing slash lists a symlinked folder such as /sdcard rather than the link itself.
+    let target = format!("{}/", path.trim_end_matches('/'));
+    let output = Command::new("adb")
+        .args(["-s", serial, "shell", "ls", "-la", &remote::quote(&target)])
+        .output()
+        .map_err(|err| err.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(remote::parse_listing(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Copies device paths into a local folder.
+pub fn pull(serial: &str, paths: &[String], local_dir: &Path) -> Result<(), String> {
+    let mut command = Command::new("adb");
+    command.args(["-s", serial, "pull"]).args(paths).arg(local_dir);
+    remote::run(command)
+}
+
+/// Copies local paths into a folder on the device.
+pub fn push(serial: &str, local: &[PathBuf], remote_dir: &str) -> Result<(), String> {
+    let mut command = Command::new("adb");
+    command.args(["-s", serial, "push"]).args(local).arg(remote_dir);
+    remote::run(command)
+}
@@ -1,12 +1,54 @@
-use crate::config::{self, AppConfig};
-use crate::file_system::{self, FileSystemEvent, FileSystemItem};
-use chrono::{DateTime, Local};
+use crate::adb::AdbDevice;
+use crate::archive::{self, ArchiveOptions};
+use crate::checksums::{self, Reference, Verification};
+use crate::cloud;
+use crate::color_rules::{ColorCondition, ColorRule};
+use crate::compare::Comparison;
+use crate::config::{self, AppConfig, OverwriteBackup, WindowGeometry, Workspace};
+use crate::daemon::{self, Job, JobState};
+use crate::docker::Container;
+use crate::download::{self, DownloadJob, DownloadedArchive};
+use crate::duplicates;
+use crate::elevation::ElevatedOperation;
+use crate::encoding::{Encoding, LineEnding, TextInfo};
+use crate::file_system::{
+    self, ConflictResolution, DiskSpace, FileSystemEvent, FileSystemItem, FileSystemResponse, Mount,
+};
+use crate::i18n::{self, t, tf, Language};
+use crate::icons::Icons;
+use crate::journal::{self, JournalEntry, TransferKind};
+use crate::keymap::{Action, Profile, Shortcut, VimCommand, VimKeys};
+use crate::navigation::{self, NavigationHistory};
+use crate::notifications;
+use crate::open_with::{self, FolderApp, FolderKind};
+use crate::peers::{Offer, Peer, PeerEvent, Peers};
+use crate::photos::{self, PhotoImport};
+use crate::picker::{self, PickMode};
+use crate::share::Share;
+use crate::shell_menu::{self, ShellMenuItem};
+use crate::shortcuts;
+use crate::sorter::{self, PlannedMove, SortOutcome, SortRule, SortTarget};
+use crate::tabs::{self, Tab};
+use crate::taskbar::TaskbarProgress;
+use crate::templates;
+use crate::profiling::{self, Stage};
+use crate::remote::{BrowserRequest, Remote, RemoteBrowser};
+use crate::theme::ThemePreference;
+use crate::thumbnails::{self, ThumbnailSize};
+use crate::tray::{Tray, TrayCommand, TrayLocations};
+use crate::usage::UsageLog;
+use crate::versions::{DiffLine, Version};
+use chrono::{DateTime, Local, Utc};
 use eframe::egui::{self, Align, Key, Layout, Margin, Sense, TextEdit};
 use egui_extras::{Column, TableBuilder};
 use human_bytes::human_bytes;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::{Receiver, Sender};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
 #[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy, Default)]
 pub enum SortBy {
@@ -16,151 +58,821 @@ pub enum SortBy {
     Modified,
 }
 
-#[derive(PartialEq)]
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy, Default)]
+pub enum ViewMode {
+    #[default]
+    Details,
+    Columns,
+    Compact,
+    Grid,
+}
+
+impl ViewMode {
+    fn next(self) -> Self {
+        match self {
+            ViewMode::Details => ViewMode::Columns,
+            ViewMode::Columns => ViewMode::Compact,
+            ViewMode::Compact => ViewMode::Grid,
+            ViewMode::Grid => ViewMode::Details,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            ViewMode::Details => "Details",
+            ViewMode::Columns => "Columns",
+            ViewMode::Compact => "Compact",
+            ViewMode::Grid => "Grid",
+        }
+    }
+}
+
+/// The details view's optional columns; Name is always shown first.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy)]
+pub enum TableColumn {
+    Size,
+    Modified,
+    Created,
+    Type,
+    Permissions,
+    Owner,
+    /// A heatmap of how long ago the item was modified.
+    Age,
+    LastOpened,
+}
+
+impl TableColumn {
+    const ALL: [TableColumn; 8] = [
+        TableColumn::Size,
+        TableColumn::Modified,
+        TableColumn::Created,
+        TableColumn::Type,
+        TableColumn::Permissions,
+        TableColumn::Owner,
+        TableColumn::Age,
+        TableColumn::LastOpened,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            TableColumn::Size => "Size",
+            TableColumn::Modified => "Last Modified",
+            TableColumn::Created => "Created",
+            TableColumn::Type => "Type",
+            TableColumn::Permissions => "Permissions",
+            TableColumn::Owner => "Owner",
+            TableColumn::Age => "Age",
+            TableColumn::LastOpened => "Last Opened",
+        }
+    }
+
+    fn sort_by(self) -> Option<SortBy> {
+        match self {
+            TableColumn::Size => Some(SortBy::Size),
+            TableColumn::Modified => Some(SortBy::Modified),
+            _ => None,
+        }
+    }
+
+    fn width(self) -> f32 {
+        match self {
+            TableColumn::Size | TableColumn::Permissions | TableColumn::Owner => 80.0,
+            TableColumn::Type => 100.0,
+            TableColumn::Age => 60.0,
+            TableColumn::Modified | TableColumn::Created | TableColumn::LastOpened => 150.0,
+        }
+    }
+}
+
+const ROW_HEIGHT: f32 = 18.0;
+const ELEVATED_RETRY_LABEL: &str = if cfg!(windows) { "Retry as Administrator" } else { "Retry as Root" };
+const MIN_ZOOM: f32 = 0.5;
+const MAX_ZOOM: f32 = 3.0;
+const MILLER_COLUMN_WIDTH: f32 = 200.0;
+const MILLER_PARENT_COLUMNS: usize = 3;
+/// Room left for the icon and padding when fitting a name into a fixed width.
+const ICON_ALLOWANCE: f32 = 28.0;
+const DROP_STACK_WIDTH: f32 = 180.0;
+/// Icon size in the grid view at 100% zoom.
+const GRID_ICON_SIZE: f32 = 96.0;
+/// Seconds without typing after which type-ahead starts a new name.
+const TYPE_AHEAD_TIMEOUT: f64 = 1.0;
+/// Offers from other copies waiting for an answer; more are declined straight away.
+const MAX_PEER_OFFERS: usize = 5;
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Clone, Copy, Default)]
+pub struct FilterOptions {
+    pub folders_always_shown: bool,
+    pub recursive: bool,
+    pub extensions_only: bool,
+}
+
+#[derive(PartialEq, Clone, Copy)]
 enum ClipboardAction {
     Copy,
     Cut,
 }
 
+#[derive(PartialEq, Clone, Copy)]
+enum PropertiesTab {
+    General,
+    PreviousVersions,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum SettingsTab {
+    General,
+    Shortcuts,
+}
+
+/// Where items being dragged inside the window came from.
+enum DragSource {
+    FileList,
+    DropStack,
+}
+
 struct ClipboardItem {
     action: ClipboardAction,
     path: PathBuf,
 }
 
+/// A file in the way of a paste, an extraction or a new archive, waiting for the user to say what to do.
+enum Conflict {
+    Paste(ClipboardItem, PathBuf),
+    /// An entry of the archive being extracted, relative to the folder it's going into.
+    Extract(PathBuf),
+    /// An archive about to be made over files already there.
+    Compress(Vec<PathBuf>, PathBuf, ArchiveOptions),
+}
+
+/// Paths waiting to be zipped into the current folder, with how.
+struct CompressDialog {
+    paths: Vec<PathBuf>,
+    name: String,
+    split: bool,
+    volume_mb: u64,
+    self_extracting: bool,
+}
+
+/// The Import Photos window, from picking the card to the summary.
+struct PhotoImportWizard {
+    source: PathBuf,
+    destination: String,
+    layout: String,
+    step: ImportStep,
+    /// Set when the window closes, to stop the worker.
+    cancel: Arc<AtomicBool>,
+}
+
+enum ImportStep {
+    Choose,
+    /// Photos checked or copied so far, and the total.
+    Working(usize, usize),
+    Review(PhotoImport),
+    /// How many photos were copied, and the ones that couldn't be.
+    Done(usize, Vec<(PathBuf, String)>),
+}
+
+/// A URL pasted into a folder, waiting to be downloaded there.
+struct PendingDownload {
+    url: String,
+    name: String,
+    checksum: String,
+}
+
 pub struct FileManager {
     items: Vec<FileSystemItem>,
     current_path: PathBuf,
-    history: Vec<PathBuf>,
-    history_index: usize,
+    navigation: NavigationHistory,
+    /// Every open tab; the active one's entry is only brought up to date when switching away.
+    tabs: Vec<Tab>,
+    active_tab: usize,
+    recent_paths: Vec<PathBuf>,
     favorites: Vec<PathBuf>,
     status_message: String,
-    rx: Receiver<Vec<FileSystemItem>>,
+    rx: Receiver<FileSystemResponse>,
     event_tx: Sender<FileSystemEvent>,
     selected_items: HashSet<PathBuf>,
+    /// The row the arrow keys move from, which isn't necessarily selected.
+    focused_item: Option<PathBuf>,
+    /// Where a Shift+arrow selection range starts.
+    selection_anchor: Option<PathBuf>,
+    scroll_to_focused: bool,
+    /// Tiles per row in the grid view as last drawn, which is how far Up and Down move there.
+    grid_columns: usize,
+    /// What's been typed to jump to a name, and when the last character was typed.
+    type_ahead: String,
+    type_ahead_at: f64,
+    vim_keys: VimKeys,
     show_hidden_files: bool,
     config: AppConfig,
     search_query: String,
+    filter_options: FilterOptions,
+    showing_search_results: bool,
+    /// Set to stop the recursive search still running, so its results never replace newer ones.
+    search_cancel: Option<Arc<AtomicBool>>,
     sort_by: SortBy,
     sort_ascending: bool,
+    view_mode: ViewMode,
+    child_listings: HashMap<PathBuf, Vec<FileSystemItem>>,
+    child_counts: HashMap<PathBuf, Option<usize>>,
     show_new_file_dialog: bool,
     new_file_name: String,
     show_new_folder_dialog: bool,
+    /// The template a new project is being named for, and the name so far.
+    new_project: Option<(PathBuf, String)>,
     new_folder_name: String,
     show_delete_confirmation: bool,
-    item_to_delete: Option<PathBuf>,
+    items_to_delete: Vec<PathBuf>,
+    delete_permanently: bool,
+    pending_conflict: Option<Conflict>,
+    /// Whether the conflict dialog's answer goes for the rest of the paste or extraction too.
+    conflict_apply_all: bool,
+    /// The answer given for all remaining conflicts.
+    conflict_for_all: Option<ConflictResolution>,
+    /// A downloaded archive whose conflicts are being asked about, with the answers so far.
+    extraction: Option<(DownloadedArchive, HashMap<PathBuf, ConflictResolution>)>,
+    pending_run: Option<(PathBuf, bool)>,
+    /// A shortcut that could run a program, by the path of its file.
+    pending_launch: Option<(PathBuf, shortcuts::Shortcut)>,
+    pending_download: Option<PendingDownload>,
+    compress_dialog: Option<CompressDialog>,
+    photo_import: Option<PhotoImportWizard>,
+    installed_folder_apps: Option<Vec<FolderApp>>,
+    shell_menu: Option<(PathBuf, Vec<ShellMenuItem>)>,
+    children_loading: HashSet<PathBuf>,
+    tree_roots: Vec<PathBuf>,
+    mounts: Vec<Mount>,
+    last_mounts_query: Option<Instant>,
+    mounts_query_pending: bool,
+    /// Set while an ADB device query is running, so a slow one isn't queued up behind.
+    adb_query_pending: bool,
+    tree_expanded: HashSet<PathBuf>,
+    /// The folder the tree last expanded itself to; it follows `current_path` when that changes.
+    tree_synced_path: Option<PathBuf>,
+    hover_started: Option<(PathBuf, Instant, u64)>,
+    menu_bar_revealed: bool,
+    menu_bar_rect: Option<egui::Rect>,
+    /// Whether one of the bar's menus was open last frame.
+    menu_open: bool,
+    alt_held: bool,
+    alt_alone: bool,
+    window_options_applied: bool,
+    tray: Option<Tray>,
+    tray_locations: TrayLocations,
+    window_position: Option<egui::Pos2>,
+    /// Opened with an explicit position, which wins over the saved placement.
+    opened_at_position: bool,
+    pixels_per_point: f32,
+    /// Whether a pinch or Ctrl+scroll has changed the zoom since it was last saved.
+    pinch_zooming: bool,
+    /// The text scale the style was last built for.
+    applied_text_scale: Option<f32>,
+    notification_tx: Sender<PathBuf>,
+    notification_clicks: Receiver<PathBuf>,
     renaming_item: Option<PathBuf>,
     renaming_text: String,
     show_go_to_dialog: bool,
     go_to_path: String,
+    go_to_selected: Option<usize>,
     show_properties_dialog: bool,
     properties_item: Option<FileSystemItem>,
-    clipboard: Option<ClipboardItem>,
+    properties_text_info: Option<TextInfo>,
+    properties_tab: PropertiesTab,
+    /// `None` until the Previous Versions tab is first shown and the snapshots have been searched.
+    properties_versions: Option<Vec<Version>>,
+    version_diff: Option<(Version, PathBuf, Option<Result<Vec<DiffLine>, String>>)>,
+    /// A version waiting for confirmation before it replaces the path.
+    pending_restore: Option<(Version, PathBuf)>,
+    clipboard: Vec<ClipboardItem>,
+    paste_queue: Vec<ClipboardItem>,
+    pending_drop_move: Vec<PathBuf>,
     context_menu_pos: Option<egui::Pos2>,
     context_menu_item: Option<FileSystemItem>,
     file_op_progress: f32,
+    taskbar_progress: TaskbarProgress,
+    delete_cancel: Option<Arc<AtomicBool>>,
+    /// Downloads still running, oldest first. Only the newest one's progress is shown.
+    downloads: Vec<DownloadJob>,
+    next_download_id: u64,
+    failed_deletions: Vec<(PathBuf, String)>,
+    locked_operations: Vec<(FileSystemEvent, PathBuf, Vec<String>)>,
+    denied_operations: Vec<(FileSystemEvent, PathBuf)>,
+    interrupted_transfers: Vec<JournalEntry>,
+    show_jobs_dialog: bool,
+    pick_mode: Option<PickMode>,
+    picked_paths: Option<Vec<PathBuf>>,
+    // `None` while a thumbnail is loading or when the file has none.
+    thumbnails: HashMap<PathBuf, Option<egui::TextureHandle>>,
+    /// The size everything in `thumbnails` was loaded at.
+    thumbnails_size: ThumbnailSize,
+    icons: Icons,
+    daemon_jobs: Result<Vec<Job>, String>,
+    last_jobs_query: Option<Instant>,
+    last_job_submitted: Option<Instant>,
     show_settings_dialog: bool,
+    settings_tab: SettingsTab,
+    /// The action whose new shortcut is being waited for.
+    recording_shortcut: Option<Action>,
+    shortcut_conflict: Option<String>,
     show_about_dialog: bool,
+    /// Files being served to other devices on the network, until the window is closed.
+    lan_share: Option<Share>,
+    peers: Option<Peers>,
+    /// Files other copies want to send here, each waiting for an answer.
+    peer_offers: Vec<(Offer, IpAddr, Sender<Option<PathBuf>>)>,
+    show_profiling_panel: bool,
+    worker_stopped: bool,
+    show_workspaces_dialog: bool,
+    new_workspace_name: String,
+    disk_space: Option<DiskSpace>,
+    show_disk_usage_dialog: bool,
+    disk_usage: Option<(PathBuf, Vec<(PathBuf, u64)>)>,
+    /// The folder being analyzed and the flag that stops its walk.
+    disk_usage_pending: Option<(PathBuf, Arc<AtomicBool>)>,
+    show_largest_files_dialog: bool,
+    show_duplicates_dialog: bool,
+    duplicates: Option<(PathBuf, Vec<(u64, Vec<PathBuf>)>)>,
+    largest_files: Option<(PathBuf, Vec<(PathBuf, u64)>)>,
+    largest_files_count: usize,
+    show_sort_dialog: bool,
+    sort_folder: PathBuf,
+    /// `None` while the worker works out where each file would go.
+    sort_plan: Option<Result<Vec<PlannedMove>, String>>,
+    /// Counts plans asked for, so one for rules since edited is dropped.
+    sort_generation: u64,
+    /// The last sort and the folder it tidied, until it's undone.
+    last_sort: Option<(PathBuf, SortOutcome)>,
+    /// The library the Organize Music window is tidying, while it's open.
+    music_folder: Option<PathBuf>,
+    music_plan: Option<Result<Vec<PlannedMove>, String>>,
+    old_downloads: Option<Vec<FileSystemItem>>,
+    show_old_downloads_dialog: bool,
+    /// Files in the current folder that a checksum manifest or signature next to them vouches for.
+    checksums: HashMap<PathBuf, (Reference, Verification)>,
+    comparison: Option<Comparison>,
+    adb_devices: Vec<AdbDevice>,
+    containers: Vec<Container>,
+    volumes: Vec<String>,
+    containers_query_pending: bool,
+    /// A volume waiting for the user to agree to start a helper container for it.
+    pending_volume: Option<String>,
+    remote_browser: Option<RemoteBrowser>,
+    show_convert_text_dialog: bool,
+    /// `None` until the worker has looked at the files.
+    convert_text_files: Option<Vec<(PathBuf, Option<TextInfo>)>>,
+    convert_encoding: Encoding,
+    /// `None` keeps each file's line endings.
+    convert_line_ending: Option<LineEnding>,
+    usage: UsageLog,
+    /// Files gathered from any folder to act on together; not kept between sessions.
+    basket: Vec<PathBuf>,
+    showing_basket: bool,
+    drop_stack: Vec<PathBuf>,
+    item_drag: Option<(DragSource, Vec<PathBuf>)>,
+    file_list_rect: Option<egui::Rect>,
+    focus_address_bar: bool,
+    focus_search: bool,
     drag_start_pos: Option<egui::Pos2>,
     drag_rect: Option<egui::Rect>,
     context_menu_rect: Option<egui::Rect>,
 }
 
 impl FileManager {
-    pub fn new(rx: Receiver<Vec<FileSystemItem>>, event_tx: Sender<FileSystemEvent>) -> Self {
+    pub fn new(
+        rx: Receiver<FileSystemResponse>,
+        event_tx: Sender<FileSystemEvent>,
+        initial_path: Option<PathBuf>,
+        pick_mode: Option<PickMode>,
+        opened_at_position: bool,
+    ) -> Self {
+        let (notification_tx, notification_clicks) = mpsc::channel();
         let config = config::load_config().unwrap_or_default();
-        let current_path =
-            config.history.last().cloned().unwrap_or_else(|| dirs::home_dir().unwrap_or_else(|| PathBuf::from("/")));
+        i18n::set_language(config.language);
+        let current_path = initial_path.filter(|path| path.is_dir()).unwrap_or_else(|| {
+            config.history.last().cloned().unwrap_or_else(|| dirs::home_dir().unwrap_or_else(|| PathBuf::from("/")))
+        });
 
         let mut fm = Self {
             items: Vec::new(),
             current_path: PathBuf::new(),
-            history: config.history.clone(),
-            history_index: if config.history.is_empty() { 0 } else { config.history.len() - 1 },
+            navigation: NavigationHistory::default(),
+            tabs: Vec::new(),
+            active_tab: 0,
+            recent_paths: config.history.clone(),
             favorites: config.favorites.clone(),
             status_message: String::new(),
             rx,
             event_tx,
             selected_items: HashSet::new(),
+            focused_item: None,
+            selection_anchor: None,
+            scroll_to_focused: false,
+            grid_columns: 1,
+            type_ahead: String::new(),
+            type_ahead_at: 0.0,
+            vim_keys: VimKeys::default(),
             show_hidden_files: config.show_hidden_files,
+            filter_options: config.filter_options,
+            showing_search_results: false,
+            view_mode: config.view_mode,
             config,
             search_query: String::new(),
+            search_cancel: None,
             sort_by: SortBy::Name,
             sort_ascending: true,
+            child_listings: HashMap::new(),
+            child_counts: HashMap::new(),
             show_new_file_dialog: false,
             new_file_name: String::new(),
             show_new_folder_dialog: false,
+            new_project: None,
             new_folder_name: String::new(),
             show_delete_confirmation: false,
-            item_to_delete: None,
+            items_to_delete: Vec::new(),
+            delete_permanently: false,
+            pending_conflict: None,
+            conflict_apply_all: false,
+            conflict_for_all: None,
+            extraction: None,
+            pending_run: None,
+            pending_launch: None,
+            pending_download: None,
+            compress_dialog: None,
+            photo_import: None,
+            installed_folder_apps: None,
+            shell_menu: None,
+            children_loading: HashSet::new(),
+            tree_roots: file_system::tree_roots(),
+            mounts: Vec::new(),
+            last_mounts_query: None,
+            mounts_query_pending: false,
+            adb_query_pending: false,
+            tree_expanded: HashSet::new(),
+            tree_synced_path: None,
+            hover_started: None,
+            menu_bar_revealed: false,
+            menu_bar_rect: None,
+            menu_open: false,
+            alt_held: false,
+            alt_alone: false,
+            window_options_applied: false,
+            tray: None,
+            tray_locations: TrayLocations::default(),
+            window_position: None,
+            opened_at_position,
+            pixels_per_point: 1.0,
+            pinch_zooming: false,
+            applied_text_scale: None,
+            notification_tx,
+            notification_clicks,
             renaming_item: None,
             renaming_text: String::new(),
             show_go_to_dialog: false,
             go_to_path: String::new(),
+            go_to_selected: None,
             show_properties_dialog: false,
             properties_item: None,
-            clipboard: None,
+            properties_text_info: None,
+            properties_tab: PropertiesTab::General,
+            properties_versions: None,
+            version_diff: None,
+            pending_restore: None,
+            clipboard: Vec::new(),
+            paste_queue: Vec::new(),
+            pending_drop_move: Vec::new(),
             context_menu_pos: None,
             context_menu_item: None,
             file_op_progress: 0.0,
+            taskbar_progress: TaskbarProgress::default(),
+            delete_cancel: None,
+            downloads: Vec::new(),
+            next_download_id: 0,
+            failed_deletions: Vec::new(),
+            locked_operations: Vec::new(),
+            denied_operations: Vec::new(),
+            interrupted_transfers: journal::interrupted(),
+            show_jobs_dialog: false,
+            pick_mode,
+            picked_paths: None,
+            thumbnails: HashMap::new(),
+            icons: Icons::default(),
+            thumbnails_size: ThumbnailSize::Normal,
+            daemon_jobs: Ok(Vec::new()),
+            last_jobs_query: None,
+            last_job_submitted: None,
             show_settings_dialog: false,
+            settings_tab: SettingsTab::General,
+            recording_shortcut: None,
+            shortcut_conflict: None,
             show_about_dialog: false,
+            lan_share: None,
+            peers: None,
+            peer_offers: Vec::new(),
+            show_profiling_panel: false,
+            worker_stopped: false,
+            show_workspaces_dialog: false,
+            new_workspace_name: String::new(),
+            disk_space: None,
+            show_disk_usage_dialog: false,
+            disk_usage: None,
+            disk_usage_pending: None,
+            show_largest_files_dialog: false,
+            largest_files: None,
+            largest_files_count: 50,
+            show_sort_dialog: false,
+            sort_folder: PathBuf::new(),
+            sort_plan: None,
+            sort_generation: 0,
+            last_sort: None,
+            music_folder: None,
+            music_plan: None,
+            show_duplicates_dialog: false,
+            duplicates: None,
+            comparison: None,
+            adb_devices: Vec::new(),
+            containers: Vec::new(),
+            volumes: Vec::new(),
+            containers_query_pending: false,
+            pending_volume: None,
+            remote_browser: None,
+            show_convert_text_dialog: false,
+            convert_text_files: None,
+            convert_encoding: Encoding::Utf8,
+            convert_line_ending: None,
+            old_downloads: None,
+            show_old_downloads_dialog: false,
+            checksums: HashMap::new(),
+            basket: Vec::new(),
+            showing_basket: false,
+            drop_stack: Vec::new(),
+            item_drag: None,
+            file_list_rect: None,
+            usage: if config.track_usage { UsageLog::load() } else { UsageLog::default() },
+            focus_address_bar: false,
+            focus_search: false,
             drag_start_pos: None,
             drag_rect: None,
             context_menu_rect: None,
         };
 
+        fm.send_event(FileSystemEvent::SetBatteryPolicy(fm.config.battery));
+        fm.send_event(FileSystemEvent::SetOverwriteBackup(fm.config.overwrite_backup));
+        fm.check_old_downloads();
         fm.navigate_to(&current_path.clone());
+        fm.tabs.push(fm.current_tab());
         fm
     }
 
+    /// Sends to the worker; a stopped worker is reported by `update`, so a failed send is not fatal.
+    fn send_event(&self, event: FileSystemEvent) {
+        let _ = self.event_tx.send(event);
+    }
+
     fn navigate_to(&mut self, path: &Path) {
-        if path.is_dir() {
-            self.current_path = path.to_path_buf();
-            self.event_tx.send(FileSystemEvent::ListDirectory(self.current_path.clone())).unwrap();
-            self.status_message = format!("Navigated to {}", self.current_path.display());
-            self.selected_items.clear();
-            self.search_query.clear();
+        if self.open_directory(path) {
+            self.navigation.visit(path);
+            navigation::record_recent(&mut self.recent_paths, path);
+            self.config.history = self.recent_paths.clone();
+            config::save_config(&self.config).unwrap();
+        }
+    }
+
+    fn open_directory(&mut self, path: &Path) -> bool {
+        if !path.is_dir() {
+            return false;
+        }
+        self.current_path = path.to_path_buf();
+        self.send_event(FileSystemEvent::ListDirectory(self.current_path.clone()));
+        self.send_event(FileSystemEvent::QueryDiskSpace(self.current_path.clone()));
+        self.prefetch_neighbours();
+        self.status_message = tf("Navigated to {path}", &[("path", &self.current_path.display())]);
+        self.selected_items.clear();
+        self.focused_item = None;
+        self.selection_anchor = None;
+        self.search_query.clear();
+        self.showing_search_results = false;
+        if let Some(cancel) = self.search_cancel.take() {
+            cancel.store(true, Ordering::Relaxed);
+        }
+        self.showing_basket = false;
+        true
+    }
+
+    /// Warms the worker's listing cache so Up and Back are instant.
+    fn prefetch_neighbours(&mut self) {
+        const PREFETCHED_RECENT_PATHS: usize = 5;
+        let mut paths: Vec<PathBuf> = self.current_path.parent().map(Path::to_path_buf).into_iter().collect();
+        paths.extend(
+            self.recent_paths
+                .iter()
+                .rev()
+                .filter(|path| **path != self.current_path)
+                .take(PREFETCHED_RECENT_PATHS)
+                .cloned(),
+        );
+        self.send_event(FileSystemEvent::Prefetch(paths));
+    }
+
+    fn current_tab(&self) -> Tab {
+        Tab {
+            path: self.current_path.clone(),
+            navigation: self.navigation.clone(),
+            selected_items: self.selected_items.clone(),
+            sort_by: self.sort_by,
+            sort_ascending: self.sort_ascending,
+        }
+    }
+
+    /// Opens `path` in a new tab next to the current one, switching to it if `activate`.
+    fn open_tab(&mut self, path: &Path, activate: bool) {
+        let index = self.active_tab + 1;
+        self.tabs.insert(index, Tab::new(path, self.sort_by, self.sort_ascending));
+        if activate {
+            self.switch_tab(index);
+        }
+    }
+
+    fn switch_tab(&mut self, index: usize) {
+        if index == self.active_tab || index >= self.tabs.len() {
+            return;
+        }
+        self.tabs[self.active_tab] = self.current_tab();
+        self.active_tab = index;
+        self.show_tab();
+    }
+
+    fn close_tab(&mut self, index: usize) {
+        if self.tabs.len() < 2 || index >= self.tabs.len() {
+            return;
+        }
+        let closing_active = index == self.active_tab;
+        if !closing_active {
+            self.tabs[self.active_tab] = self.current_tab();
+        }
+        self.tabs.remove(index);
+        self.active_tab = tabs::active_after_close(self.active_tab, index, self.tabs.len());
+        if closing_active {
+            self.show_tab();
+        }
+    }
 
-            if self.history.last() != Some(&self.current_path) {
-                if self.history_index < self.history.len() - 1 {
-                    self.history.truncate(self.history_index + 1);
+    /// Loads the active tab's saved state into the view.
+    fn show_tab(&mut self) {
+        let tab = self.tabs[self.active_tab].clone();
+        self.navigation = tab.navigation;
+        self.sort_by = tab.sort_by;
+        self.sort_ascending = tab.sort_ascending;
+        if self.open_directory(&tab.path) {
+            self.selected_items = tab.selected_items;
+        } else if let Some(home_dir) = dirs::home_dir() {
+            self.navigate_to(&home_dir);
+        }
+    }
+
+    fn draw_tab_bar(&mut self, ui: &mut egui::Ui) {
+        let mut switch_to = None;
+        let mut close = None;
+        ui.horizontal(|ui| {
+            for (index, tab) in self.tabs.iter().enumerate() {
+                let is_active = index == self.active_tab;
+                // The active tab's entry is stale until switching away, so it's labelled from the view.
+                let path = if is_active { &self.current_path } else { &tab.path };
+                let response =
+                    ui.selectable_label(is_active, tabs::title(path)).on_hover_text(path.display().to_string());
+                if response.clicked() {
+                    switch_to = Some(index);
                 }
-                self.history.push(self.current_path.clone());
-                self.history_index = self.history.len() - 1;
+                if response.middle_clicked() || ui.small_button("×").on_hover_text(t("Close Tab")).clicked() {
+                    close = Some(index);
+                }
+                ui.separator();
             }
-
-            self.config.history = self.history.clone();
-            config::save_config(&self.config).unwrap();
+            if ui.small_button("+").on_hover_text(self.with_shortcut("New Tab", Action::NewTab)).clicked() {
+                self.open_tab(&self.current_path.clone(), true);
+            }
+        });
+        if let Some(index) = switch_to {
+            self.switch_tab(index);
+        }
+        if let Some(index) = close {
+            self.close_tab(index);
         }
     }
 
     fn go_back(&mut self) {
-        if self.history_index > 0 {
-            self.history_index -= 1;
-            let path = self.history[self.history_index].clone();
-            self.navigate_to(&path);
+        if let Some(path) = self.navigation.back() {
+            self.open_directory(&path);
         } else if let Some(parent) = self.current_path.parent().map(|p| p.to_path_buf()) {
             self.navigate_to(&parent);
         }
     }
 
     fn go_forward(&mut self) {
-        if self.history_index < self.history.len() - 1 {
-            self.history_index += 1;
-            let path = self.history[self.history_index].clone();
-            self.navigate_to(&path);
+        if let Some(path) = self.navigation.forward() {
+            self.open_directory(&path);
         }
     }
 
     fn refresh(&mut self) {
-        self.event_tx.send(FileSystemEvent::ListDirectory(self.current_path.clone())).unwrap();
-        self.status_message = "Refreshed".to_string();
+        self.child_listings.clear();
+        self.child_counts.clear();
+        self.children_loading.clear();
+        self.thumbnails.clear();
+        self.send_event(FileSystemEvent::ListDirectory(self.current_path.clone()));
+        self.send_event(FileSystemEvent::QueryDiskSpace(self.current_path.clone()));
+        self.status_message = t("Refreshed");
+    }
+
+    fn zoom(&self) -> f32 {
+        self.config.zoom_levels.get(&self.current_path).copied().unwrap_or(1.0)
+    }
+
+    fn set_zoom(&mut self, zoom: f32) {
+        self.zoom_to(zoom);
+        config::save_config(&self.config).unwrap();
+    }
+
+    /// Changes the current folder's zoom without saving it, for pinches that change it every frame.
+    fn zoom_to(&mut self, zoom: f32) {
+        let zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        if (zoom - 1.0).abs() < f32::EPSILON {
+            self.config.zoom_levels.remove(&self.current_path);
+        } else {
+            self.config.zoom_levels.insert(self.current_path.clone(), zoom);
+        }
+        self.status_message = tf("Zoom {percent}%", &[("percent", &format!("{:.0}", zoom * 100.0))]);
+    }
+
+    /// Row height in the current folder, which grows with the text and the folder's zoom.
+    fn row_height(&self) -> f32 {
+        ROW_HEIGHT * self.config.text_scale * self.zoom()
+    }
+
+    fn set_ui_scale(&mut self, scale: f32) {
+        self.config.ui_scale = (scale * 10.0).round().clamp(MIN_ZOOM * 10.0, MAX_ZOOM * 10.0) / 10.0;
+        config::save_config(&self.config).unwrap();
+        self.status_message = tf("Interface zoom {percent}%", &[
+            ("percent", &format!("{:.0}", self.config.ui_scale * 100.0)),
+        ]);
+    }
+
+    /// Rebuilds the text styles from egui's defaults when the text scale changes.
+    fn apply_text_scale(&mut self, ctx: &egui::Context) {
+        let scale = self.config.text_scale;
+        if self.applied_text_scale == Some(scale) {
+            return;
+        }
+        let mut style = (*ctx.style()).clone();
+        style.text_styles = egui::Style::default().text_styles;
+        for font in style.text_styles.values_mut() {
+            font.size *= scale;
+        }
+        ctx.set_style(style);
+        self.applied_text_scale = Some(scale);
+    }
+
+    fn folder_item_count(&mut self, path: &Path) -> String {
+        if !self.config.show_folder_item_counts {
+            return String::new();
+        }
+        match self.child_counts.get(path) {
+            Some(Some(1)) => "1 item".to_string(),
+            Some(Some(count)) => format!("{} items", count),
+            Some(None) => "…".to_string(),
+            None => {
+                self.child_counts.insert(path.to_path_buf(), None);
+                self.send_event(FileSystemEvent::CountChildren(path.to_path_buf()));
+                "…".to_string()
+            }
+        }
+    }
+
+    fn apply_search(&mut self) {
+        if let Some(cancel) = self.search_cancel.take() {
+            cancel.store(true, Ordering::Relaxed);
+        }
+        if self.filter_options.recursive && !self.search_query.is_empty() {
+            let cancel = Arc::new(AtomicBool::new(false));
+            self.search_cancel = Some(cancel.clone());
+            self.send_event(FileSystemEvent::SearchDirectory(
+                self.current_path.clone(),
+                self.search_query.clone(),
+                self.filter_options.extensions_only,
+                cancel,
+            ));
+            self.status_message = tf("Searching for \"{query}\"...", &[("query", &self.search_query)]);
+            self.showing_search_results = true;
+        } else if self.showing_search_results {
+            self.showing_search_results = false;
+            self.refresh();
+        }
     }
 
     fn create_file(&mut self) {
         if !self.new_file_name.is_empty() {
             let path = self.current_path.join(&self.new_file_name);
-            self.event_tx.send(FileSystemEvent::CreateFile(path)).unwrap();
+            self.send_event(FileSystemEvent::CreateFile(path));
             self.show_new_file_dialog = false;
             self.new_file_name.clear();
         }
@@ -169,285 +881,1374 @@ impl FileManager {
     fn create_folder(&mut self) {
         if !self.new_folder_name.is_empty() {
             let path = self.current_path.join(&self.new_folder_name);
-            self.event_tx.send(FileSystemEvent::CreateFolder(path)).unwrap();
+            self.send_event(FileSystemEvent::CreateFolder(path));
             self.show_new_folder_dialog = false;
             self.new_folder_name.clear();
         }
     }
 
+    /// Lists the project templates to start a new project from, and a way to add more. Returns
+    /// whether one was picked.
+    fn draw_template_menu(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut picked = false;
+        ui.menu_button(t("Project from Template"), |ui| {
+            let templates = templates::list();
+            if templates.is_empty() {
+                ui.weak(t("No templates yet"));
+            }
+            for template in templates {
+                if ui.button(template.file_name().unwrap_or_default().to_string_lossy()).clicked() {
+                    self.new_project = Some((template, String::new()));
+                    picked = true;
+                }
+            }
+            ui.separator();
+            if ui.button(t("Open Templates Folder")).on_hover_text(t("Each folder in it is a template")).clicked() {
+                let dir = templates::templates_dir();
+                let _ = std::fs::create_dir_all(&dir);
+                self.navigate_to(&dir);
+                picked = true;
+            }
+        });
+        picked
+    }
+
+    /// Saves the URL on the clipboard as an internet shortcut in the current folder.
+    fn create_link_from_clipboard(&mut self) {
+        let text = arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_text()).unwrap_or_default();
+        let url = text.trim();
+        if !url.contains("://") || url.contains(char::is_whitespace) {
+            self.status_message = t("The clipboard doesn't hold a URL");
+            return;
+        }
+        let path = shortcuts::internet_shortcut_path(&self.current_path, url);
+        self.status_message = tf("Created {name}", &[
+            ("name", &path.file_name().unwrap_or_default().to_string_lossy()),
+        ]);
+        self.send_event(FileSystemEvent::CreateInternetShortcut(path, url.to_string()));
+    }
+
     fn delete_item(&mut self) {
-        if let Some(path) = self.item_to_delete.take() {
-            self.event_tx.send(FileSystemEvent::DeleteItem(path)).unwrap();
+        let paths = std::mem::take(&mut self.items_to_delete);
+        if self.delete_permanently {
+            let cancel = Arc::new(AtomicBool::new(false));
+            self.delete_cancel = Some(cancel.clone());
+            self.send_event(FileSystemEvent::DeleteItems(paths, cancel));
+        } else {
+            for path in paths {
+                self.send_event(FileSystemEvent::TrashItem(path));
+            }
         }
         self.show_delete_confirmation = false;
     }
 
+    fn request_delete(&mut self, paths: Vec<PathBuf>, permanently: bool) {
+        if paths.is_empty() {
+            return;
+        }
+        self.items_to_delete = paths;
+        self.delete_permanently = permanently;
+        let confirm = if permanently {
+            self.config.confirmations.permanent_delete
+        } else {
+            self.config.confirmations.trash
+        };
+        if confirm {
+            self.show_delete_confirmation = true;
+        } else {
+            self.delete_item();
+        }
+    }
+
+    fn request_run(&mut self, path: PathBuf, in_terminal: bool) {
+        if self.config.confirmations.run_executable {
+            self.pending_run = Some((path, in_terminal));
+        } else {
+            self.send_event(FileSystemEvent::RunExecutable(path, in_terminal));
+        }
+    }
+
     fn rename_item(&mut self) {
         if let Some(path) = self.renaming_item.take() {
             let new_path = path.with_file_name(&self.renaming_text);
-            self.event_tx.send(FileSystemEvent::RenameItem(path, new_path)).unwrap();
+            self.send_event(FileSystemEvent::RenameItem(path, new_path));
             self.renaming_text.clear();
         }
     }
 
     fn copy_selection(&mut self) {
-        if let Some(item) = self.selected_items.iter().next() {
-            self.clipboard = Some(ClipboardItem {
-                action: ClipboardAction::Copy,
-                path: item.clone(),
-            });
-            self.status_message = "Copied to clipboard".to_string();
+        self.set_clipboard(ClipboardAction::Copy);
+        if !self.clipboard.is_empty() {
+            self.status_message = t("Copied to clipboard");
         }
     }
 
-    fn cut_selection(&mut self) {
-        if let Some(item) = self.selected_items.iter().next() {
-            self.clipboard = Some(ClipboardItem {
-                action: ClipboardAction::Cut,
-                path: item.clone(),
-            });
-            self.status_message = "Cut to clipboard".to_string();
+    fn add_selection_to_basket(&mut self) {
+        let mut added: Vec<PathBuf> =
+            self.selected_items.iter().filter(|path| !self.basket.contains(path)).cloned().collect();
+        added.sort();
+        self.basket.extend(added);
+        self.status_message = tf("{count} items in the basket", &[("count", &self.basket.len())]);
+    }
+
+    fn remove_from_basket(&mut self, path: &Path) {
+        self.basket.retain(|item| item != path);
+        if self.showing_basket {
+            self.show_basket();
         }
     }
 
-    fn paste(&mut self) {
-        if let Some(clipboard_item) = self.clipboard.take() {
-            let dest_path = self.current_path.join(clipboard_item.path.file_name().unwrap());
-            match clipboard_item.action {
-                ClipboardAction::Copy => {
-                    self.event_tx.send(FileSystemEvent::CopyItem(clipboard_item.path, dest_path)).unwrap();
+    fn show_basket(&mut self) {
+        self.send_event(FileSystemEvent::ListPaths(self.current_path.clone(), self.basket.clone()));
+        self.selected_items.clear();
+        self.showing_search_results = true;
+        self.showing_basket = true;
+        self.status_message = t("Basket");
+    }
+
+    /// Copies or moves everything in the basket into the current folder.
+    fn paste_basket(&mut self, action: ClipboardAction) {
+        self.paste_paths(self.basket.clone(), action);
+        if action == ClipboardAction::Cut {
+            self.basket.clear();
+        }
+    }
+
+    fn paste_paths(&mut self, paths: Vec<PathBuf>, action: ClipboardAction) {
+        self.paste_queue = paths.into_iter().map(|path| ClipboardItem { action, path }).collect();
+        self.continue_paste();
+    }
+
+    /// A floating shelf at the right edge: drag items onto it, navigate elsewhere, then drag
+    /// them back out onto the file list (Shift moves instead of copying).
+    fn draw_drop_stack(&mut self, ctx: &egui::Context) {
+        let dragging = self.item_drag.is_some();
+        if self.drop_stack.is_empty() && !dragging {
+            return;
+        }
+        let mut removed = None;
+        let mut action = None;
+        let area = egui::Area::new("drop_stack")
+            .anchor(egui::Align2::RIGHT_CENTER, egui::vec2(-12.0, 0.0))
+            .order(egui::Order::Foreground);
+        let response = area.show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.set_width(DROP_STACK_WIDTH);
+                let title = egui::RichText::new(format!("📥 {}", t("Drop Stack"))).strong();
+                let all =
+                    ui.add(egui::Label::new(title).sense(Sense::drag())).on_hover_text(t("Drag to take everything"));
+                if all.drag_started() && !self.drop_stack.is_empty() {
+                    self.item_drag = Some((DragSource::DropStack, self.drop_stack.clone()));
+                }
+                if self.drop_stack.is_empty() {
+                    ui.weak(t("Drop items here to keep them while you browse"));
+                }
+                for (index, path) in self.drop_stack.clone().iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("✖").clicked() {
+                            removed = Some(index);
+                        }
+                        let name = path.file_name().unwrap_or_default().to_string_lossy();
+                        let name = elide_middle(ui, &name, ui.available_width());
+                        let entry = ui.add(egui::Label::new(name).sense(Sense::drag()));
+                        if entry.on_hover_text(path.display().to_string()).drag_started() {
+                            self.item_drag = Some((DragSource::DropStack, vec![path.clone()]));
+                        }
+                    });
                 }
-                ClipboardAction::Cut => {
-                    self.event_tx.send(FileSystemEvent::MoveItem(clipboard_item.path, dest_path)).unwrap();
+                if !self.drop_stack.is_empty() {
+                    ui.horizontal(|ui| {
+                        if ui.small_button(t("Copy Here")).clicked() {
+                            action = Some(ClipboardAction::Copy);
+                        }
+                        if ui.small_button(t("Move Here")).clicked() {
+                            action = Some(ClipboardAction::Cut);
+                        }
+                        if ui.small_button(t("Clear")).clicked() {
+                            self.drop_stack.clear();
+                        }
+                    });
+                }
+            });
+        });
+        let stack_rect = response.response.rect;
+        if let Some(index) = removed {
+            self.drop_stack.remove(index);
+        }
+        if let Some(action) = action {
+            self.paste_paths(std::mem::take(&mut self.drop_stack), action);
+        }
+
+        let Some(count) = self.item_drag.as_ref().map(|(_, paths)| paths.len()) else {
+            return;
+        };
+        let (released, pointer, shift) =
+            ctx.input(|i| (i.pointer.any_released(), i.pointer.hover_pos(), i.modifiers.shift));
+        if let Some(pointer) = pointer {
+            let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Tooltip, egui::Id::new("item_drag")));
+            let label = if count == 1 { t("1 item") } else { tf("{count} items", &[("count", &count)]) };
+            let label = format!("📄 {}", label);
+            painter.text(
+                pointer + egui::vec2(12.0, 12.0),
+                egui::Align2::LEFT_TOP,
+                label,
+                egui::TextStyle::Body.resolve(&ctx.style()),
+                ctx.style().visuals.strong_text_color(),
+            );
+        }
+        if !released {
+            return;
+        }
+        let Some((source, paths)) = self.item_drag.take() else {
+            return;
+        };
+        let over_stack = pointer.is_some_and(|pointer| stack_rect.contains(pointer));
+        let over_list = pointer.is_some_and(|pointer| self.file_list_rect.is_some_and(|rect| rect.contains(pointer)));
+        match source {
+            DragSource::FileList if over_stack => {
+                for path in paths {
+                    if !self.drop_stack.contains(&path) {
+                        self.drop_stack.push(path);
+                    }
+                }
+            }
+            DragSource::DropStack if over_list && !over_stack => {
+                self.drop_stack.retain(|path| !paths.contains(path));
+                if shift && self.config.confirmations.drag_move {
+                    self.pending_drop_move = paths;
+                } else {
+                    let action = if shift { ClipboardAction::Cut } else { ClipboardAction::Copy };
+                    self.paste_paths(paths, action);
                 }
             }
+            _ => {}
         }
     }
 
-    fn open_item(&mut self, path: &Path) {
-        if path.is_dir() {
-            self.navigate_to(path);
-        } else {
-            self.event_tx.send(FileSystemEvent::OpenFile(path.to_path_buf())).unwrap();
+    /// Opens the Compress dialog for `paths`, suggesting an archive name after `stem` that's free.
+    fn compress(&mut self, paths: Vec<PathBuf>, stem: &str) {
+        let name = (1..)
+            .map(|n| if n == 1 { format!("{}.zip", stem) } else { format!("{} ({}).zip", stem, n) })
+            .find(|name| !self.current_path.join(name).exists())
+            .unwrap();
+        self.compress_dialog = Some(CompressDialog {
+            paths,
+            name,
+            split: false,
+            volume_mb: 100,
+            self_extracting: false,
+        });
+    }
+
+    fn start_compress(&mut self, paths: Vec<PathBuf>, archive: PathBuf, options: ArchiveOptions) {
+        let name = archive.file_name().unwrap_or_default().to_string_lossy();
+        self.status_message = tf("Compressing into {name}...", &[("name", &name)]);
+        self.send_event(FileSystemEvent::Compress(paths, archive, options));
+    }
+
+    /// Compresses the selection, or `item` if it isn't selected.
+    fn compress_items(&mut self, item: &Path) {
+        let paths = self.selection_or(item);
+        let stem = match paths.as_slice() {
+            [path] => path.file_stem().unwrap_or_default().to_string_lossy().into_owned(),
+            _ => "Archive".to_string(),
+        };
+        self.compress(paths, &stem);
+    }
+
+    fn draw_basket_panel(&mut self, ctx: &egui::Context) {
+        if self.basket.is_empty() {
+            return;
         }
+        egui::TopBottomPanel::bottom("basket_panel").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("🧺 {}", tf("{count} items in the basket", &[("count", &self.basket.len())])));
+                if ui.button(t("Show")).clicked() {
+                    self.show_basket();
+                }
+                let here = self.current_path.display().to_string();
+                if ui.button(t("Copy Here")).on_hover_text(&here).clicked() {
+                    self.paste_basket(ClipboardAction::Copy);
+                }
+                if ui.button(t("Move Here")).on_hover_text(&here).clicked() {
+                    self.paste_basket(ClipboardAction::Cut);
+                }
+                if ui.button(t("Compress Here")).on_hover_text(&here).clicked() {
+                    self.compress(self.basket.clone(), "Basket");
+                }
+                if ui.button(t("Clear")).clicked() {
+                    self.basket.clear();
+                    if self.showing_basket {
+                        self.show_basket();
+                    }
+                }
+            });
+        });
     }
 
-    fn open_in_terminal(&mut self, path: &Path) {
-        let terminal_path = if path.is_dir() { path } else { path.parent().unwrap_or(path) };
-        self.event_tx.send(FileSystemEvent::OpenTerminal(terminal_path.to_path_buf())).unwrap();
+    fn cut_selection(&mut self) {
+        self.set_clipboard(ClipboardAction::Cut);
+        if !self.clipboard.is_empty() {
+            self.status_message = t("Cut to clipboard");
+        }
     }
 
-    fn is_dialog_open(&self) -> bool {
-        self.show_new_file_dialog
-            || self.show_new_folder_dialog
-            || self.show_delete_confirmation
-            || self.show_go_to_dialog
-            || self.show_properties_dialog
-            || self.show_settings_dialog
-            || self.show_about_dialog
-            || self.renaming_item.is_some()
+    fn set_clipboard(&mut self, action: ClipboardAction) {
+        let mut paths: Vec<PathBuf> = self.selected_items.iter().cloned().collect();
+        paths.sort();
+        if paths.is_empty() {
+            return;
+        }
+        self.send_event(FileSystemEvent::CopyFilesToClipboard(paths.clone(), action == ClipboardAction::Cut));
+        self.clipboard = paths
+            .into_iter()
+            .map(|path| ClipboardItem { action, path })
+            .collect();
     }
 
-    fn handle_key_shortcuts(&mut self, ctx: &egui::Context) {
-        if self.is_dialog_open() {
+    /// Pastes files from the desktop clipboard when it has any, otherwise our own clipboard. The
+    /// worker reads the desktop clipboard, then `paste_files` carries on.
+    fn paste(&mut self) {
+        self.send_event(FileSystemEvent::ReadClipboardFiles(self.current_path.clone()));
+    }
+
+    fn paste_files(&mut self, files: Option<(Vec<PathBuf>, bool)>) {
+        if files.is_none() && self.clipboard.is_empty() {
+            self.offer_download_from_clipboard();
             return;
         }
-        ctx.input(|i| {
-            if i.key_pressed(Key::Backspace) {
-                self.go_back();
+        self.paste_queue = match files {
+            Some((paths, cut)) => {
+                self.clipboard.clear();
+                let action = if cut { ClipboardAction::Cut } else { ClipboardAction::Copy };
+                paths.into_iter().map(|path| ClipboardItem { action, path }).collect()
             }
-            if i.key_pressed(Key::Home) {
-                if let Some(home_dir) = dirs::home_dir() {
-                    self.navigate_to(&home_dir);
+            None => std::mem::take(&mut self.clipboard),
+        };
+        self.continue_paste();
+    }
+
+    /// Offers to download the URL on the clipboard, if it holds one, into the current folder.
+    fn offer_download_from_clipboard(&mut self) {
+        let text = arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_text()).unwrap_or_default();
+        if download::is_url(&text) {
+            let url = text.trim().to_string();
+            self.pending_download = Some(PendingDownload {
+                name: download::file_name(&url),
+                url,
+                checksum: String::new(),
+            });
+        }
+    }
+
+    /// Pastes queued items until one needs an overwrite confirmation.
+    fn continue_paste(&mut self) {
+        while self.pending_conflict.is_none() && !self.paste_queue.is_empty() {
+            let clipboard_item = self.paste_queue.remove(0);
+            let Some(name) = clipboard_item.path.file_name() else {
+                continue;
+            };
+            let dest_path = self.current_path.join(name);
+            if dest_path.exists() && dest_path != clipboard_item.path && self.config.confirmations.overwrite {
+                match self.conflict_for_all {
+                    Some(resolution) => self.paste_resolved(clipboard_item, dest_path, resolution),
+                    None => self.pending_conflict = Some(Conflict::Paste(clipboard_item, dest_path)),
                 }
+            } else {
+                self.perform_paste(clipboard_item, dest_path);
             }
-            if i.key_pressed(Key::F5) {
-                self.refresh();
-            }
-            if i.key_pressed(Key::Delete) && !self.selected_items.is_empty() {
-                self.item_to_delete = self.selected_items.iter().next().cloned();
-                self.show_delete_confirmation = true;
+        }
+        if self.paste_queue.is_empty() {
+            self.end_conflicts();
+        }
+    }
+
+    fn paste_resolved(&mut self, clipboard_item: ClipboardItem, dest_path: PathBuf, resolution: ConflictResolution) {
+        match resolution {
+            ConflictResolution::Replace => self.perform_paste(clipboard_item, dest_path),
+            ConflictResolution::Skip => {}
+            ConflictResolution::KeepBoth => {
+                let renamed = sorter::available(&dest_path, &HashSet::new());
+                self.perform_paste(clipboard_item, renamed);
             }
-            if i.key_pressed(Key::F2) && self.selected_items.len() == 1 {
-                if let Some(item) = self.selected_items.iter().next().cloned() {
-                    self.renaming_item = Some(item.clone());
-                    self.renaming_text = item.file_name().unwrap().to_str().unwrap().to_string();
+        }
+    }
+
+    /// Asks about the archive's conflicting entries one at a time, then unpacks it.
+    fn continue_extraction(&mut self) {
+        let Some((archive, resolutions)) = &mut self.extraction else {
+            return;
+        };
+        while self.pending_conflict.is_none() {
+            let Some(path) = archive.conflicts.iter().find(|path| !resolutions.contains_key(*path)).cloned() else {
+                let (archive, resolutions) = self.extraction.take().unwrap();
+                self.status_message = tf("Extracting into {folder}...", &[("folder", &archive.dir.display())]);
+                self.send_event(FileSystemEvent::UnpackArchive(archive, resolutions));
+                self.end_conflicts();
+                return;
+            };
+            match self.conflict_for_all {
+                Some(resolution) => {
+                    resolutions.insert(path, resolution);
                 }
+                None => self.pending_conflict = Some(Conflict::Extract(path)),
+            }
+        }
+    }
+
+    /// Answers the conflict dialog; `None` cancels the rest of the paste or extraction.
+    fn resolve_conflict(&mut self, resolution: Option<ConflictResolution>) {
+        let Some(conflict) = self.pending_conflict.take() else {
+            return;
+        };
+        let Some(resolution) = resolution else {
+            self.paste_queue.clear();
+            if let Some((archive, _)) = self.extraction.take() {
+                self.send_event(FileSystemEvent::DiscardArchive(archive));
             }
-            if i.key_pressed(Key::Enter) && self.selected_items.len() == 1 {
-                if let Some(item) = self.selected_items.iter().next().cloned() {
-                    self.open_item(&item);
+            self.end_conflicts();
+            return;
+        };
+        if self.conflict_apply_all {
+            self.conflict_for_all = Some(resolution);
+        }
+        match conflict {
+            Conflict::Paste(clipboard_item, dest_path) => self.paste_resolved(clipboard_item, dest_path, resolution),
+            Conflict::Extract(path) => {
+                if let Some((_, resolutions)) = &mut self.extraction {
+                    resolutions.insert(path, resolution);
                 }
             }
+            Conflict::Compress(paths, archive, options) => match resolution {
+                ConflictResolution::Replace => self.start_compress(paths, archive, options),
+                ConflictResolution::Skip => {}
+                ConflictResolution::KeepBoth => {
+                    let free = archive::available(&archive, options);
+                    self.start_compress(paths, free, options);
+                }
+            },
+        }
+        // Either may have been waiting on the other's conflict.
+        self.continue_paste();
+        self.continue_extraction();
+    }
 
-            let ctrl = i.modifiers.ctrl;
-            if ctrl && i.key_pressed(Key::H) {
+    /// Forgets an answer given for all conflicts once what it was for is done.
+    fn end_conflicts(&mut self) {
+        if self.pending_conflict.is_none() && self.paste_queue.is_empty() && self.extraction.is_none() {
+            self.conflict_apply_all = false;
+            self.conflict_for_all = None;
+        }
+    }
+
+    /// Copies files dropped onto the window into the current folder, or moves them with Shift.
+    fn handle_dropped_files(&mut self, ctx: &egui::Context) {
+        let (dropped, shift, hovering) = ctx.input(|i| {
+            let dropped: Vec<PathBuf> = i.raw.dropped_files.iter().filter_map(|file| file.path.clone()).collect();
+            (dropped, i.modifiers.shift, !i.raw.hovered_files.is_empty())
+        });
+        if hovering {
+            let screen = ctx.screen_rect();
+            let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("drop_target")));
+            painter.rect_filled(screen, 0.0, egui::Color32::from_black_alpha(160));
+            painter.text(
+                screen.center(),
+                egui::Align2::CENTER_CENTER,
+                "Drop to copy here (hold Shift to move)",
+                egui::TextStyle::Heading.resolve(&ctx.style()),
+                egui::Color32::WHITE,
+            );
+        }
+        if dropped.is_empty() {
+            return;
+        }
+        if shift && self.config.confirmations.drag_move {
+            self.pending_drop_move = dropped;
+        } else {
+            let action = if shift { ClipboardAction::Cut } else { ClipboardAction::Copy };
+            self.paste_queue = dropped.into_iter().map(|path| ClipboardItem { action, path }).collect();
+            self.continue_paste();
+        }
+    }
+
+    fn perform_paste(&mut self, clipboard_item: ClipboardItem, dest_path: PathBuf) {
+        if self.config.use_daemon {
+            let kind = match clipboard_item.action {
+                ClipboardAction::Copy => TransferKind::Copy,
+                ClipboardAction::Cut => TransferKind::Move,
+            };
+            self.send_event(FileSystemEvent::SubmitJob(kind, clipboard_item.path, dest_path));
+            self.last_job_submitted = Some(Instant::now());
+            self.status_message = t("Submitted to the background daemon");
+            return;
+        }
+        match clipboard_item.action {
+            ClipboardAction::Copy => {
+                self.send_event(FileSystemEvent::CopyItem(clipboard_item.path, dest_path));
+            }
+            ClipboardAction::Cut => {
+                self.send_event(FileSystemEvent::MoveItem(clipboard_item.path, dest_path));
+            }
+        }
+    }
+
+    fn go_to_suggestions(&mut self) -> Vec<(&'static str, PathBuf)> {
+        const MAX_SUGGESTIONS: usize = 12;
+        let query = self.go_to_path.to_lowercase();
+        let matches = |path: &Path| path.to_string_lossy().to_lowercase().contains(&query);
+
+        let mut suggestions: Vec<(&'static str, PathBuf)> = Vec::new();
+        for path in self.recent_paths.iter().rev() {
+            if matches(path) && !suggestions.iter().any(|(_, p)| p == path) {
+                suggestions.push(("🕘", path.clone()));
+            }
+        }
+        suggestions.truncate(MAX_SUGGESTIONS / 2);
+        for path in &self.favorites {
+            if matches(path) && !suggestions.iter().any(|(_, p)| p == path) {
+                suggestions.push(("⭐", path.clone()));
+            }
+        }
+
+        // Autocomplete against the folder being typed, listed through the worker.
+        let typed = PathBuf::from(&self.go_to_path);
+        let (parent, prefix) = if self.go_to_path.ends_with(std::path::is_separator) {
+            (Some(typed.clone()), String::new())
+        } else {
+            (
+                typed.parent().map(Path::to_path_buf),
+                typed.file_name().map(|name| name.to_string_lossy().to_lowercase()).unwrap_or_default(),
+            )
+        };
+        if let Some(parent) = parent.filter(|parent| !parent.as_os_str().is_empty()) {
+            match self.child_listings.get(&parent) {
+                Some(children) => {
+                    let mut completions: Vec<PathBuf> = children
+                        .iter()
+                        .filter(|child| child.is_dir && (self.show_hidden_files || !child.is_hidden))
+                        .filter(|child| {
+                            child.path.file_name().unwrap_or_default().to_string_lossy().to_lowercase().starts_with(&prefix)
+                        })
+                        .map(|child| child.path.clone())
+                        .collect();
+                    completions.sort_by(|a, b| self.compare_names(a, b));
+                    for path in completions {
+                        if !suggestions.iter().any(|(_, p)| p == &path) {
+                            suggestions.push(("📁", path));
+                        }
+                    }
+                }
+                None => {
+                    self.child_listings.insert(parent.clone(), Vec::new());
+                    self.send_event(FileSystemEvent::ListChildren(parent));
+                }
+            }
+        }
+
+        suggestions.truncate(MAX_SUGGESTIONS);
+        suggestions
+    }
+
+    fn open_item(&mut self, path: &Path) {
+        if path.is_dir() {
+            self.navigate_to(path);
+        } else if self.pick_mode == Some(PickMode::File) {
+            self.picked_paths = Some(vec![path.to_path_buf()]);
+        } else {
+            if self.config.track_usage {
+                self.usage.record(path);
+            }
+            self.send_event(FileSystemEvent::OpenFile(path.to_path_buf()));
+        }
+    }
+
+    fn toggle_starred(&mut self, path: &Path) {
+        if let Some(index) = self.config.starred.iter().position(|starred| starred == path) {
+            self.config.starred.remove(index);
+        } else {
+            self.config.starred.push(path.to_path_buf());
+        }
+        config::save_config(&self.config).unwrap();
+    }
+
+    fn show_starred(&mut self) {
+        let paths = self.config.starred.iter().filter(|path| path.exists()).cloned().collect();
+        self.send_event(FileSystemEvent::ListPaths(self.current_path.clone(), paths));
+        self.selected_items.clear();
+        self.showing_search_results = true;
+        self.status_message = t("Starred files");
+    }
+
+    /// Lists files modified since `since` under the user folders and favorites, in place of the
+    /// current folder.
+    fn show_modified_since(&mut self, label: &str, since: DateTime<Local>) {
+        let mut roots: Vec<PathBuf> = [
+            dirs::desktop_dir(),
+            dirs::document_dir(),
+            dirs::download_dir(),
+            dirs::picture_dir(),
+            dirs::audio_dir(),
+            dirs::video_dir(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let extra: Vec<PathBuf> = self
+            .favorites
+            .iter()
+            .filter(|favorite| !roots.iter().any(|root| favorite.starts_with(root)))
+            .cloned()
+            .collect();
+        roots.extend(extra);
+        self.send_event(FileSystemEvent::ListModifiedSince(self.current_path.clone(), roots, since.into()));
+        self.selected_items.clear();
+        self.showing_search_results = true;
+        self.status_message = tf("Files modified {when}", &[("when", &label.to_lowercase())]);
+    }
+
+    /// Lists the most opened files in place of the current folder, the way search results are.
+    fn show_frequently_used(&mut self) {
+        const FREQUENTLY_USED_COUNT: usize = 50;
+        let paths = self.usage.most_used(FREQUENTLY_USED_COUNT);
+        self.send_event(FileSystemEvent::ListPaths(self.current_path.clone(), paths));
+        self.selected_items.clear();
+        self.showing_search_results = true;
+        self.status_message = t("Frequently used files");
+    }
+
+    fn open_workspace(&mut self, workspace: &Workspace) {
+        let mut folders = workspace.folders.iter().filter(|folder| folder.is_dir());
+        if let Some(first) = folders.next() {
+            self.navigate_to(first);
+        }
+        for folder in folders {
+            self.open_in_new_window(folder);
+        }
+        self.status_message = tf("Opened workspace \"{name}\"", &[("name", &workspace.name)]);
+    }
+
+    fn open_in_new_window(&mut self, path: &Path) {
+        // Cascade from this window so the new one lands on the same monitor.
+        const CASCADE_OFFSET: f32 = 32.0;
+        let position = self.window_position.map(|pos| [pos.x + CASCADE_OFFSET, pos.y + CASCADE_OFFSET]);
+        self.send_event(FileSystemEvent::NewWindowAt(path.to_path_buf(), position));
+    }
+
+    fn open_in_terminal(&mut self, path: &Path) {
+        let terminal_path = if path.is_dir() { path } else { path.parent().unwrap_or(path) };
+        self.send_event(FileSystemEvent::OpenTerminal(terminal_path.to_path_buf()));
+    }
+
+    fn is_dialog_open(&self) -> bool {
+        self.show_new_file_dialog
+            || self.show_new_folder_dialog
+            || self.new_project.is_some()
+            || self.show_delete_confirmation
+            || self.pending_conflict.is_some()
+            || self.pending_run.is_some()
+            || self.pending_launch.is_some()
+            || self.pending_volume.is_some()
+            || self.pending_download.is_some()
+            || self.compress_dialog.is_some()
+            || !self.pending_drop_move.is_empty()
+            || self.show_go_to_dialog
+            || self.show_properties_dialog
+            || self.show_settings_dialog
+            || self.show_about_dialog
+            || self.renaming_item.is_some()
+    }
+
+    fn handle_key_shortcuts(&mut self, ctx: &egui::Context) {
+        if self.is_dialog_open() {
+            return;
+        }
+        let screen_center = ctx.screen_rect().center();
+        let typing_elsewhere = ctx.wants_keyboard_input();
+        let mut vim_commands = Vec::new();
+        let actions = ctx.input(|i| {
+            if !typing_elsewhere && !i.modifiers.ctrl && !i.modifiers.alt && !i.modifiers.command {
+                for event in &i.events {
+                    if let egui::Event::Text(text) = event {
+                        match self.config.key_profile {
+                            Profile::Standard => self.jump_to_typed(text, i.time),
+                            Profile::Vim => vim_commands.extend(self.vim_keys.feed(text)),
+                        }
+                    }
+                }
+            }
+            if i.key_pressed(Key::Escape) {
+                self.context_menu_pos = None;
+                self.vim_keys.cancel();
+            }
+            if !typing_elsewhere && !i.modifiers.alt && !i.modifiers.ctrl {
+                let row = if self.view_mode == ViewMode::Grid { self.grid_columns as isize } else { 1 };
+                if i.key_pressed(Key::ArrowUp) {
+                    self.move_focus(-row, i.modifiers.shift);
+                }
+                if i.key_pressed(Key::ArrowDown) {
+                    self.move_focus(row, i.modifiers.shift);
+                }
+            }
+            // Saved once the pinch or Ctrl+scroll stops.
+            let zoom_delta = i.zoom_delta();
+            if zoom_delta != 1.0 {
+                self.zoom_to(self.zoom() * zoom_delta);
+                self.pinch_zooming = true;
+            } else if std::mem::take(&mut self.pinch_zooming) {
+                config::save_config(&self.config).unwrap();
+            }
+            self.config.keymap.pressed(i)
+        });
+        for action in actions {
+            self.run_action(action, screen_center);
+        }
+        for command in vim_commands {
+            match command {
+                VimCommand::Move(delta) => self.move_focus(delta, false),
+                // With nothing focused, moving down lands on the first row and up on the last.
+                VimCommand::First => {
+                    self.focused_item = None;
+                    self.move_focus(1, false);
+                }
+                VimCommand::Last => {
+                    self.focused_item = None;
+                    self.move_focus(-1, false);
+                }
+                VimCommand::Run(action) => self.run_action(action, screen_center),
+            }
+        }
+    }
+
+    fn run_action(&mut self, action: Action, screen_center: egui::Pos2) {
+        let selected_item = self.items.iter().find(|item| self.selected_items.contains(&item.path)).cloned();
+        match action {
+            Action::Back | Action::BackAlternate => self.go_back(),
+            Action::Forward => self.go_forward(),
+            Action::Up => {
+                if let Some(parent) = self.current_path.parent().map(|p| p.to_path_buf()) {
+                    self.navigate_to(&parent);
+                }
+            }
+            Action::Home => {
+                if let Some(home_dir) = dirs::home_dir() {
+                    self.navigate_to(&home_dir);
+                }
+            }
+            Action::Refresh => self.refresh(),
+            Action::Open => {
+                if self.selected_items.len() == 1 {
+                    if let Some(item) = self.selected_items.iter().next().cloned() {
+                        self.open_item(&item);
+                    }
+                }
+            }
+            Action::Properties => {
+                if let Some(item) = selected_item {
+                    self.show_properties(item);
+                }
+            }
+            Action::ContextMenu => {
+                self.context_menu_pos = Some(screen_center);
+                self.context_menu_item = selected_item;
+            }
+            Action::Rename => {
+                if self.selected_items.len() == 1 {
+                    if let Some(item) = self.selected_items.iter().next().cloned() {
+                        self.renaming_text = item.file_name().unwrap().to_str().unwrap().to_string();
+                        self.renaming_item = Some(item);
+                    }
+                }
+            }
+            Action::MoveToTrash | Action::DeletePermanently => {
+                let mut paths: Vec<PathBuf> = self.selected_items.iter().cloned().collect();
+                paths.sort();
+                self.request_delete(paths, action == Action::DeletePermanently);
+            }
+            Action::FocusAddressBar => self.focus_address_bar = true,
+            Action::Search => self.focus_search = true,
+            Action::GoTo => {
+                self.show_go_to_dialog = true;
+                self.go_to_path = self.current_path.to_str().unwrap().to_string();
+                self.go_to_selected = None;
+            }
+            Action::ToggleHiddenFiles => {
                 self.show_hidden_files = !self.show_hidden_files;
                 self.config.show_hidden_files = self.show_hidden_files;
                 config::save_config(&self.config).unwrap();
                 self.refresh();
             }
-            if ctrl && i.key_pressed(Key::N) {
-                self.show_new_file_dialog = true;
-            }
-            if ctrl && i.modifiers.shift && i.key_pressed(Key::N) {
-                self.show_new_folder_dialog = true;
+            Action::ToggleSidebar => {
+                self.config.show_folder_tree = !self.config.show_folder_tree;
+                config::save_config(&self.config).unwrap();
             }
-            if ctrl && i.key_pressed(Key::A) {
+            // Needs the frame, so it's handled with the other window options.
+            Action::ToggleFullScreen => {}
+            Action::NewFile => self.show_new_file_dialog = true,
+            Action::NewFolder => self.show_new_folder_dialog = true,
+            Action::SelectAll => {
                 self.selected_items = self.items.iter().map(|item| item.path.clone()).collect();
             }
-            if ctrl && i.key_pressed(Key::G) {
-                self.show_go_to_dialog = true;
-                self.go_to_path = self.current_path.to_str().unwrap().to_string();
+            Action::Copy => self.copy_selection(),
+            Action::Cut => self.cut_selection(),
+            Action::Paste => self.paste(),
+            Action::AddToBasket => self.add_selection_to_basket(),
+            Action::NewTab => self.open_tab(&self.current_path.clone(), true),
+            Action::CloseTab => self.close_tab(self.active_tab),
+            Action::ZoomIn => self.set_zoom(self.zoom() + 0.1),
+            Action::ZoomOut => self.set_zoom(self.zoom() - 0.1),
+            Action::ResetZoom => self.set_zoom(1.0),
+            Action::ScaleUp => self.set_ui_scale(self.config.ui_scale + 0.1),
+            Action::ScaleDown => self.set_ui_scale(self.config.ui_scale - 0.1),
+            Action::ResetScale => self.set_ui_scale(1.0),
+        }
+    }
+
+    /// A menu label followed by the action's current shortcut, e.g. "New Tab (Ctrl+T)".
+    fn with_shortcut(&self, label: &str, action: Action) -> String {
+        format!("{} ({})", t(label), self.config.keymap.shortcut(action).label())
+    }
+
+    fn apply_window_options(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        let info = frame.info();
+        let window = info.window_info;
+        let pixels_per_point = info.native_pixels_per_point.unwrap_or(1.0);
+        // eframe measures the window in the zoomed points currently in effect; geometry is kept in
+        // the display's own points, so it doesn't change with the interface zoom.
+        let zoom = ctx.pixels_per_point() / pixels_per_point;
+        let monitor = window.monitor_size.map(|size| format!("{}x{}", size.x.round(), size.y.round()));
+        if !self.window_options_applied {
+            frame.set_always_on_top(self.config.always_on_top);
+            let saved = monitor.as_ref().and_then(|monitor| self.config.window_geometry.get(monitor)).copied();
+            // Startup used the last monitor setup's placement; switch if this one has its own. A position
+            // saved at another scale is converted so the window returns to the same physical spot.
+            let other_monitor = monitor.is_some() && monitor != self.config.last_monitor;
+            if let Some(geometry) = saved.filter(|_| !self.opened_at_position) {
+                if other_monitor || geometry.pixels_per_point != pixels_per_point {
+                    frame.set_window_size(egui::vec2(geometry.size[0], geometry.size[1]) / zoom);
+                    if let Some([x, y]) = geometry.position {
+                        let scale = geometry.pixels_per_point / pixels_per_point / zoom;
+                        frame.set_window_pos(egui::pos2(x * scale, y * scale));
+                    }
+                    frame.set_maximized(geometry.maximized);
+                }
             }
-            if ctrl && i.key_pressed(Key::C) {
-                self.copy_selection();
+            self.window_options_applied = true;
+        }
+        self.window_position = window.position.map(|pos| egui::pos2(pos.x * zoom, pos.y * zoom));
+        if let Some(monitor) = monitor.filter(|_| !window.fullscreen) {
+            let size = window.size * zoom;
+            let geometry = self.config.window_geometry.entry(monitor.clone()).or_insert(WindowGeometry {
+                position: None,
+                size: [size.x, size.y],
+                maximized: false,
+                pixels_per_point,
+            });
+            // Keep the restored size while maximized so un-maximizing next launch still works.
+            geometry.maximized = window.maximized;
+            if !window.maximized {
+                geometry.size = [size.x, size.y];
+                geometry.position = window.position.map(|pos| [pos.x * zoom, pos.y * zoom]);
+                geometry.pixels_per_point = pixels_per_point;
             }
-            if ctrl && i.key_pressed(Key::X) {
-                self.cut_selection();
+            self.config.last_monitor = Some(monitor);
+        }
+        // Window geometry stays in the display's own scale; only the contents are zoomed.
+        let scaled = pixels_per_point * self.config.ui_scale;
+        if ctx.pixels_per_point() != scaled {
+            ctx.set_pixels_per_point(scaled);
+        }
+        self.pixels_per_point = scaled;
+        if ctx.input(|i| self.config.keymap.shortcut(Action::ToggleFullScreen).pressed(i)) {
+            frame.set_fullscreen(!frame.info().window_info.fullscreen);
+        }
+    }
+
+    /// Starts or stops finding other copies on the network to match the config, and collects
+    /// what they've sent.
+    fn update_peers(&mut self, ctx: &egui::Context) {
+        if !self.config.lan_peers {
+            self.peers = None;
+            return;
+        }
+        if self.peers.is_none() {
+            match Peers::start(ctx.clone()) {
+                Ok(peers) => self.peers = Some(peers),
+                Err(err) => {
+                    self.status_message = tf("Could not look for other copies on the network: {error}", &[
+                        ("error", &err),
+                    ]);
+                    self.config.lan_peers = false;
+                    return;
+                }
             }
-            if ctrl && i.key_pressed(Key::V) {
-                self.paste();
+        }
+        while let Some(event) = self.peers.as_ref().and_then(Peers::poll) {
+            match event {
+                PeerEvent::Offer(offer, sender, reply) => {
+                    // Dropping the reply declines it.
+                    if self.peer_offers.len() < MAX_PEER_OFFERS {
+                        self.peer_offers.push((offer, sender, reply));
+                    }
+                }
+                PeerEvent::Received(from, dir, result) => {
+                    self.status_message = match result {
+                        Ok(count) => tf("Received {count} file(s) from {from} in {folder}", &[
+                            ("count", &count),
+                            ("from", &from),
+                            ("folder", &dir.display()),
+                        ]),
+                        Err(err) => tf("Could not receive files from {from}: {error}", &[
+                            ("from", &from),
+                            ("error", &err),
+                        ]),
+                    };
+                    if dir == self.current_path {
+                        self.refresh();
+                    }
+                }
+            }
+        }
+    }
+
+    /// What a context menu action on `item` applies to: the selection if `item` is part of it.
+    fn selection_or(&self, item: &Path) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = if self.selected_items.contains(item) {
+            self.selected_items.iter().cloned().collect()
+        } else {
+            vec![item.to_path_buf()]
+        };
+        paths.sort();
+        paths
+    }
+
+    /// Offers the selection, or `item` if it isn't selected, to another copy of the app.
+    fn send_to_peer(&mut self, peer: Peer, item: &Path) {
+        let paths = self.selection_or(item);
+        self.status_message = tf("Waiting for {name} to accept...", &[("name", &peer.name)]);
+        self.send_event(FileSystemEvent::SendToPeer(peer, paths));
+    }
+
+    /// Attaches the selected files, or `item` if it isn't selected, to a new message. Mail clients
+    /// can't attach folders, so those are left out.
+    fn send_by_email(&mut self, item: &Path) {
+        let mut paths = self.selection_or(item);
+        paths.retain(|path| !path.is_dir());
+        if paths.is_empty() {
+            self.status_message = t("Only files can be sent by email");
+            return;
+        }
+        self.send_event(FileSystemEvent::EmailFiles(paths));
+    }
+
+    /// Creates or drops the tray icon to match the config, keeps its menu current and runs its commands.
+    fn update_tray(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        const TRAY_RECENT_FOLDERS: usize = 10;
+        if !self.config.show_tray_icon {
+            self.tray = None;
+            return;
+        }
+        let locations = TrayLocations {
+            favorites: self.config.favorites.clone(),
+            recent: self.recent_paths.iter().rev().take(TRAY_RECENT_FOLDERS).cloned().collect(),
+        };
+        match &self.tray {
+            None => match Tray::new(locations.clone()) {
+                Ok(tray) => {
+                    self.tray = Some(tray);
+                    self.tray_locations = locations;
+                }
+                Err(e) => {
+                    self.status_message = tf("Could not create tray icon: {error}", &[("error", &e)]);
+                    self.config.show_tray_icon = false;
+                    return;
+                }
+            },
+            Some(tray) if locations != self.tray_locations => {
+                tray.set_locations(locations.clone());
+                self.tray_locations = locations;
+            }
+            Some(_) => {}
+        }
+        while let Some(command) = self.tray.as_ref().and_then(Tray::poll) {
+            match command {
+                // Minimized rather than hidden: a hidden window stops updating, so nothing would
+                // poll the tray to bring it back.
+                TrayCommand::ToggleWindow => {
+                    let minimized = frame.info().window_info.minimized;
+                    frame.set_minimized(!minimized);
+                    if minimized {
+                        frame.focus();
+                    }
+                }
+                TrayCommand::Open(path) => {
+                    frame.set_minimized(false);
+                    frame.focus();
+                    self.navigate_to(&path);
+                }
+                TrayCommand::Quit => frame.close(),
             }
+        }
+        // Menu clicks don't wake the event loop, so keep polling while the icon is shown.
+        ctx.request_repaint_after(Duration::from_millis(250));
+    }
+
+    /// Notifies about a finished transfer unless the user is looking at the window.
+    fn notify_transfer(
+        &self,
+        ctx: &egui::Context,
+        frame: &eframe::Frame,
+        kind: TransferKind,
+        destination: &Path,
+        result: &Result<(), String>,
+    ) {
+        let window = frame.info().window_info;
+        if window.focused && !window.minimized {
+            return;
+        }
+        let (finished, failed) = match kind {
+            TransferKind::Copy => ("Copy finished", "Copy failed"),
+            TransferKind::Move => ("Move finished", "Move failed"),
+        };
+        let name = destination.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let folder = destination.parent().unwrap_or(destination).to_path_buf();
+        let (summary, body) = match result {
+            Ok(()) => (t(finished), tf("{name} is in {folder}", &[("name", &name), ("folder", &folder.display())])),
+            Err(err) => (t(failed), format!("{}: {}", name, err)),
+        };
+        notifications::notify(summary, body, folder, self.notification_tx.clone(), ctx.clone());
+    }
+
+    /// Brings the window back and shows the folder of a clicked notification.
+    fn handle_notification_clicks(&mut self, frame: &mut eframe::Frame) {
+        while let Ok(folder) = self.notification_clicks.try_recv() {
+            frame.set_minimized(false);
+            frame.focus();
+            self.navigate_to(&folder);
+        }
+    }
+
+    /// With the menu bar hidden, a lone Alt press (no other key or click in between) toggles it.
+    fn handle_menu_bar_reveal(&mut self, ctx: &egui::Context) {
+        let (alt, other_input, clicked_at) = ctx.input(|i| {
+            let other_input = i
+                .events
+                .iter()
+                .any(|event| matches!(event, egui::Event::Key { .. } | egui::Event::PointerButton { .. }));
+            let clicked_at = if i.pointer.any_click() { i.pointer.interact_pos() } else { None };
+            (i.modifiers.alt, other_input, clicked_at)
         });
+        if alt && !self.alt_held {
+            self.alt_alone = true;
+        }
+        if other_input {
+            self.alt_alone = false;
+        }
+        if !alt && self.alt_held && self.alt_alone && self.config.hide_menu_bar {
+            self.menu_bar_revealed = !self.menu_bar_revealed;
+        }
+        self.alt_held = alt;
+
+        // A click anywhere but the bar itself hides it again, unless it's in one of its menus.
+        let outside_bar = clicked_at.is_some_and(|pos| self.menu_bar_rect.is_none_or(|rect| !rect.contains(pos)));
+        if (outside_bar && !self.menu_open) || ctx.input(|i| i.key_pressed(Key::Escape)) {
+            self.menu_bar_revealed = false;
+        }
     }
 
     fn draw_menu_bar(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
-        egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
+        if self.config.hide_menu_bar && !self.menu_bar_revealed {
+            self.menu_bar_rect = None;
+            self.menu_open = false;
+            return;
+        }
+        let mut menus = Vec::new();
+        let panel = egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
-                ui.menu_button("File", |ui| {
-                    if ui.button("New Window").clicked() {
-                        self.event_tx.send(FileSystemEvent::NewWindow).unwrap();
+                menus.push(ui.menu_button(t("File"), |ui| {
+                    if ui.button(t("New Window")).clicked() {
+                        self.open_in_new_window(&self.current_path.clone());
+                        ui.close_menu();
+                    }
+                    if ui.button(self.with_shortcut("New Tab", Action::NewTab)).clicked() {
+                        self.open_tab(&self.current_path.clone(), true);
+                        ui.close_menu();
+                    }
+                    let close_tab = egui::Button::new(self.with_shortcut("Close Tab", Action::CloseTab));
+                    if ui.add_enabled(self.tabs.len() > 1, close_tab).clicked() {
+                        self.close_tab(self.active_tab);
                         ui.close_menu();
                     }
-                    ui.menu_button("New", |ui| {
-                        if ui.button("File").clicked() {
+                    ui.menu_button(t("New"), |ui| {
+                        if ui.button(t("File")).clicked() {
                             self.show_new_file_dialog = true;
                             ui.close_menu();
                         }
-                        if ui.button("Folder").clicked() {
+                        if ui.button(t("Folder")).clicked() {
                             self.show_new_folder_dialog = true;
                             ui.close_menu();
                         }
+                        if ui.button(t("Link from Clipboard")).clicked() {
+                            self.create_link_from_clipboard();
+                            ui.close_menu();
+                        }
+                        if self.draw_template_menu(ui) {
+                            ui.close_menu();
+                        }
                     });
-                    if ui.button("Go To...").clicked() {
+                    if ui.button(t("Go To...")).clicked() {
                         self.show_go_to_dialog = true;
                         self.go_to_path = self.current_path.to_str().unwrap().to_string();
+                        self.go_to_selected = None;
+                        ui.close_menu();
+                    }
+                    if ui.button(t("Background Jobs")).clicked() {
+                        self.show_jobs_dialog = true;
+                        self.last_jobs_query = None;
+                        ui.close_menu();
+                    }
+                    if ui.button(t("Settings...")).clicked() {
+                        self.show_settings_dialog = true;
                         ui.close_menu();
                     }
                     ui.separator();
-                    if ui.button("Exit").clicked() {
+                    if ui.button(t("Exit")).clicked() {
                         frame.close();
                     }
-                });
-                ui.menu_button("Edit", |ui| {
-                    if ui.button("Copy").clicked() {
+                }));
+                menus.push(ui.menu_button(t("Edit"), |ui| {
+                    if ui.button(t("Copy")).clicked() {
                         self.copy_selection();
                         ui.close_menu();
                     }
-                    if ui.button("Cut").clicked() {
+                    if ui.button(t("Cut")).clicked() {
                         self.cut_selection();
                         ui.close_menu();
                     }
-                    if ui.button("Paste").clicked() {
+                    if ui.button(t("Paste")).clicked() {
                         self.paste();
                         ui.close_menu();
                     }
                     ui.separator();
-                    if ui.button("Select All").clicked() {
+                    if ui.button(self.with_shortcut("Add to Basket", Action::AddToBasket)).clicked() {
+                        self.add_selection_to_basket();
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(!self.basket.is_empty(), egui::Button::new(t("Show Basket"))).clicked() {
+                        self.show_basket();
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui.button(t("Select All")).clicked() {
                         self.selected_items = self.items.iter().map(|item| item.path.clone()).collect();
                         ui.close_menu();
                     }
-                    if ui.button("Select None").clicked() {
+                    if ui.button(t("Select None")).clicked() {
                         self.selected_items.clear();
                         ui.close_menu();
                     }
-                    if ui.button("Invert Selection").clicked() {
+                    if ui.button(t("Invert Selection")).clicked() {
                         let all_items: HashSet<_> = self.items.iter().map(|item| item.path.clone()).collect();
                         self.selected_items = all_items.difference(&self.selected_items).cloned().collect();
                         ui.close_menu();
                     }
-                });
-                ui.menu_button("View", |ui| {
-                    if ui.checkbox(&mut self.show_hidden_files, "Show Hidden Files").clicked() {
+                }));
+                menus.push(ui.menu_button(t("View"), |ui| {
+                    if ui.checkbox(&mut self.show_hidden_files, t("Show Hidden Files")).clicked() {
                         self.config.show_hidden_files = self.show_hidden_files;
                         config::save_config(&self.config).unwrap();
                         self.refresh();
                         ui.close_menu();
                     }
-                    ui.menu_button("Sort By", |ui| {
-                        if ui.radio_value(&mut self.sort_by, SortBy::Name, "Name").clicked() {
+                    if ui.checkbox(&mut self.config.hide_partial_downloads, t("Hide Partial Downloads")).clicked() {
+                        config::save_config(&self.config).unwrap();
+                        ui.close_menu();
+                    }
+                    if ui.checkbox(&mut self.config.show_folder_item_counts, t("Show Folder Item Counts")).clicked() {
+                        config::save_config(&self.config).unwrap();
+                        ui.close_menu();
+                    }
+                    let mut show_menu_bar = !self.config.hide_menu_bar;
+                    if ui.checkbox(&mut show_menu_bar, t("Show Menu Bar (Alt)")).clicked() {
+                        self.config.hide_menu_bar = !show_menu_bar;
+                        self.menu_bar_revealed = false;
+                        config::save_config(&self.config).unwrap();
+                        ui.close_menu();
+                    }
+                    if ui.checkbox(&mut self.config.compact_chrome, t("Compact Chrome")).clicked() {
+                        config::save_config(&self.config).unwrap();
+                        ui.close_menu();
+                    }
+                    let sidebar = self.with_shortcut("Sidebar", Action::ToggleSidebar);
+                    if ui.checkbox(&mut self.config.show_folder_tree, sidebar).clicked() {
+                        config::save_config(&self.config).unwrap();
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    let mut fullscreen = frame.info().window_info.fullscreen;
+                    let full_screen = self.with_shortcut("Full Screen", Action::ToggleFullScreen);
+                    if ui.checkbox(&mut fullscreen, full_screen).clicked() {
+                        frame.set_fullscreen(fullscreen);
+                        ui.close_menu();
+                    }
+                    if ui.checkbox(&mut self.config.always_on_top, t("Always on Top")).clicked() {
+                        frame.set_always_on_top(self.config.always_on_top);
+                        config::save_config(&self.config).unwrap();
+                        ui.close_menu();
+                    }
+                    if ui.checkbox(&mut self.config.size_on_disk, t("Size on Disk")).clicked() {
+                        config::save_config(&self.config).unwrap();
+                        ui.close_menu();
+                    }
+                    ui.menu_button(t("Columns"), |ui| {
+                        for column in TableColumn::ALL {
+                            let mut shown = self.config.columns.contains(&column);
+                            let label = if column == TableColumn::Age { t("Age Heatmap") } else { t(column.label()) };
+                            let enabled = column != TableColumn::LastOpened || self.config.track_usage;
+                            if ui
+                                .add_enabled(enabled, egui::Checkbox::new(&mut shown, label))
+                                .on_disabled_hover_text(t("Turn on remembering opened files in Settings"))
+                                .clicked()
+                            {
+                                self.toggle_column(column);
+                            }
+                        }
+                    });
+                    ui.menu_button(t("View Mode"), |ui| {
+                        let details = ui.radio_value(&mut self.view_mode, ViewMode::Details, t("Details")).clicked();
+                        let columns = ui.radio_value(&mut self.view_mode, ViewMode::Columns, t("Columns")).clicked();
+                        let compact = ui.radio_value(&mut self.view_mode, ViewMode::Compact, t("Compact")).clicked();
+                        let grid = ui.radio_value(&mut self.view_mode, ViewMode::Grid, t("Grid")).clicked();
+                        if details || columns || compact || grid {
+                            self.config.view_mode = self.view_mode;
+                            config::save_config(&self.config).unwrap();
+                            ui.close_menu();
+                        }
+                    });
+                    ui.menu_button(t("Zoom"), |ui| {
+                        if ui.button(self.with_shortcut("Zoom In", Action::ScaleUp)).clicked() {
+                            self.set_ui_scale(self.config.ui_scale + 0.1);
+                        }
+                        if ui.button(self.with_shortcut("Zoom Out", Action::ScaleDown)).clicked() {
+                            self.set_ui_scale(self.config.ui_scale - 0.1);
+                        }
+                        if ui.button(self.with_shortcut("Reset Zoom", Action::ResetScale)).clicked() {
+                            self.set_ui_scale(1.0);
+                            ui.close_menu();
+                        }
+                        ui.separator();
+                        if ui.button(self.with_shortcut("Larger Icons", Action::ZoomIn)).clicked() {
+                            self.set_zoom(self.zoom() + 0.1);
+                        }
+                        if ui.button(self.with_shortcut("Smaller Icons", Action::ZoomOut)).clicked() {
+                            self.set_zoom(self.zoom() - 0.1);
+                        }
+                        if ui.button(self.with_shortcut("Reset Icon Size", Action::ResetZoom)).clicked() {
+                            self.set_zoom(1.0);
+                            ui.close_menu();
+                        }
+                    });
+                    if ui.checkbox(&mut self.config.folders_first, t("Folders First")).clicked() {
+                        config::save_config(&self.config).unwrap();
+                        ui.close_menu();
+                    }
+                    ui.menu_button(t("Sort By"), |ui| {
+                        if ui.radio_value(&mut self.sort_by, SortBy::Name, t("Name")).clicked() {
                             self.config.sort_by = self.sort_by;
                             config::save_config(&self.config).unwrap();
                             self.refresh();
                             ui.close_menu();
                         }
-                        if ui.radio_value(&mut self.sort_by, SortBy::Size, "Size").clicked() {
+                        if ui.radio_value(&mut self.sort_by, SortBy::Size, t("Size")).clicked() {
                             self.config.sort_by = self.sort_by;
                             config::save_config(&self.config).unwrap();
                             self.refresh();
                             ui.close_menu();
                         }
-                        if ui.radio_value(&mut self.sort_by, SortBy::Modified, "Modified").clicked() {
+                        if ui.radio_value(&mut self.sort_by, SortBy::Modified, t("Modified")).clicked() {
                             self.config.sort_by = self.sort_by;
                             config::save_config(&self.config).unwrap();
                             self.refresh();
                             ui.close_menu();
                         }
+                        ui.separator();
+                        let natural = ui.checkbox(&mut self.config.natural_sort, t("Natural Order"));
+                        if natural.on_hover_text(t("Sort file2 before file10")).clicked() {
+                            config::save_config(&self.config).unwrap();
+                            ui.close_menu();
+                        }
                     });
-                    ui.menu_button("Sort Order", |ui| {
-                        if ui.radio_value(&mut self.sort_ascending, true, "Ascending").clicked() {
+                    ui.menu_button(t("Sort Order"), |ui| {
+                        if ui.radio_value(&mut self.sort_ascending, true, t("Ascending")).clicked() {
                             self.config.sort_ascending = self.sort_ascending;
                             config::save_config(&self.config).unwrap();
                             self.refresh();
                             ui.close_menu();
                         }
-                        if ui.radio_value(&mut self.sort_ascending, false, "Descending").clicked() {
+                        if ui.radio_value(&mut self.sort_ascending, false, t("Descending")).clicked() {
                             self.config.sort_ascending = self.sort_ascending;
                             config::save_config(&self.config).unwrap();
                             self.refresh();
                             ui.close_menu();
                         }
                     });
-                });
-                ui.menu_button("Go", |ui| {
-                    if ui.button("Back").clicked() {
+                }));
+                menus.push(ui.menu_button(t("Go"), |ui| {
+                    if ui.button(t("Back")).clicked() {
                         self.go_back();
                         ui.close_menu();
                     }
-                    if ui.button("Forward").clicked() {
+                    if ui.button(t("Forward")).clicked() {
                         self.go_forward();
                         ui.close_menu();
                     }
-                    if ui.button("Up").clicked() {
+                    if ui.button(t("Up")).clicked() {
                         if let Some(parent) = self.current_path.parent().map(|p| p.to_path_buf()) {
                             self.navigate_to(&parent);
                         }
                         ui.close_menu();
                     }
-                    if ui.button("Home").clicked() {
+                    if ui.button(t("Home")).clicked() {
                         if let Some(home_dir) = dirs::home_dir() {
                             self.navigate_to(&home_dir);
                         }
                         ui.close_menu();
                     }
+                    if ui.button(t("Today")).clicked() {
+                        let midnight = Local::now().date_naive().and_hms_opt(0, 0, 0).unwrap();
+                        if let Some(midnight) = midnight.and_local_timezone(Local).earliest() {
+                            self.show_modified_since(&t("Today"), midnight);
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button(t("This Week")).clicked() {
+                        self.show_modified_since(&t("This Week"), Local::now() - chrono::Duration::days(7));
+                        ui.close_menu();
+                    }
+                    if ui.button(t("Starred")).clicked() {
+                        self.show_starred();
+                        ui.close_menu();
+                    }
+                    if self.config.track_usage && ui.button(t("Frequently Used")).clicked() {
+                        self.show_frequently_used();
+                        ui.close_menu();
+                    }
                     ui.separator();
-                    if ui.button("Refresh").clicked() {
+                    if ui.button(t("Refresh")).clicked() {
                         self.refresh();
                         ui.close_menu();
                     }
-                });
-                ui.menu_button("History", |ui| {
-                    let history = self.history.clone();
+                }));
+                menus.push(ui.menu_button(t("History"), |ui| {
+                    let history = self.recent_paths.clone();
                     for path in history.iter().rev().take(10) {
                         if ui.button(path.display().to_string()).clicked() {
                             self.navigate_to(path);
                             ui.close_menu();
                         }
                     }
-                });
-                ui.menu_button("Favorites", |ui| {
-                    if ui.button("Add to Favorites").clicked() {
+                }));
+                menus.push(ui.menu_button(t("Favorites"), |ui| {
+                    if ui.button(t("Add to Favorites")).clicked() {
                         if !self.favorites.contains(&self.current_path) {
                             self.favorites.push(self.current_path.clone());
                             self.config.favorites = self.favorites.clone();
@@ -463,26 +2264,421 @@ impl FileManager {
                             ui.close_menu();
                         }
                     }
-                });
-                ui.menu_button("Help", |ui| {
-                    if ui.button("About").clicked() {
+                }));
+                menus.push(ui.menu_button(t("Workspaces"), |ui| {
+                    for workspace in self.config.workspaces.clone() {
+                        if ui.button(&workspace.name).clicked() {
+                            self.open_workspace(&workspace);
+                            ui.close_menu();
+                        }
+                    }
+                    if !self.config.workspaces.is_empty() {
+                        ui.separator();
+                    }
+                    if ui.button(t("Manage Workspaces...")).clicked() {
+                        self.show_workspaces_dialog = true;
+                        ui.close_menu();
+                    }
+                }));
+                menus.push(ui.menu_button(t("Tools"), |ui| {
+                    if ui.button(t("Largest Files...")).clicked() {
+                        self.find_largest_files();
+                        ui.close_menu();
+                    }
+                    if ui.button(t("Find Duplicates...")).clicked() {
+                        self.find_duplicates();
+                        ui.close_menu();
+                    }
+                    if ui.button(t("Sort Folder...")).clicked() {
+                        self.sort_folder = self.current_path.clone();
+                        self.show_sort_dialog = true;
+                        self.plan_sort();
+                        ui.close_menu();
+                    }
+                    if ui.add_enabled(self.last_sort.is_some(), egui::Button::new(t("Undo Sort"))).clicked() {
+                        self.undo_sort();
+                        ui.close_menu();
+                    }
+                    if ui.button(t("Organize Music...")).clicked() {
+                        self.music_folder = Some(self.current_path.clone());
+                        self.plan_music();
+                        ui.close_menu();
+                    }
+                    if ui.button(t("Import Photos...")).clicked() {
+                        self.open_photo_import();
+                        ui.close_menu();
+                    }
+                    let has_files = self.selected_items.iter().any(|path| !path.is_dir());
+                    if ui.add_enabled(has_files, egui::Button::new(t("Convert Encoding / Line Endings..."))).clicked() {
+                        self.convert_selected_text();
+                        ui.close_menu();
+                    }
+                    let pair = self.selected_image_pair();
+                    if ui.add_enabled(pair.is_some(), egui::Button::new(t("Compare Images..."))).clicked() {
+                        if let Some((first, second)) = pair {
+                            self.compare_images(first, second);
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button(t("Share over LAN…")).clicked() {
+                        let mut paths: Vec<PathBuf> = self.selected_items.iter().cloned().collect();
+                        if paths.is_empty() {
+                            paths.push(self.current_path.clone());
+                        }
+                        self.share_over_lan(paths);
+                        ui.close_menu();
+                    }
+                }));
+                menus.push(ui.menu_button(t("Help"), |ui| {
+                    if cfg!(feature = "profiling") && ui.button(t("Performance")).clicked() {
+                        self.show_profiling_panel = true;
+                        ui.close_menu();
+                    }
+                    if ui.button(t("About")).clicked() {
                         self.show_about_dialog = true;
                         ui.close_menu();
                     }
+                }));
+            });
+        });
+        self.menu_bar_rect = Some(panel.response.rect);
+        // Choosing an entry, or clicking away, hides the bar once its menu has closed.
+        let menu_open = menus.iter().any(|menu| menu.inner.is_some());
+        if self.menu_open && !menu_open {
+            self.menu_bar_revealed = false;
+        }
+        self.menu_open = menu_open;
+    }
+
+    fn draw_old_downloads_banner(&mut self, ctx: &egui::Context) {
+        const SNOOZE_DAYS: i64 = 7;
+        let Some(old) = self.old_downloads.as_ref().filter(|_| !self.show_old_downloads_dialog) else {
+            return;
+        };
+        let template = "📥 {count} items in Downloads are older than {days} days, {size}";
+        let message = tf(template, &[
+            ("count", &old.len()),
+            ("days", &self.config.downloads_reminder.older_than_days),
+            ("size", &human_bytes(old.iter().map(|item| item.size).sum::<u64>() as f64)),
+        ]);
+        egui::TopBottomPanel::top("old_downloads_banner").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(message);
+                if ui.button(t("Review")).clicked() {
+                    self.show_old_downloads_dialog = true;
+                }
+                if ui.button(t("Not Now")).clicked() {
+                    let until = Utc::now() + chrono::Duration::days(SNOOZE_DAYS);
+                    self.config.downloads_reminder.snoozed_until = Some(until);
+                    config::save_config(&self.config).unwrap();
+                    self.old_downloads = None;
+                }
+            });
+        });
+    }
+
+    fn draw_checksums_banner(&mut self, ctx: &egui::Context) {
+        if self.checksums.is_empty() {
+            return;
+        }
+        let count = |wanted: fn(&Verification) -> bool| self.checksums.values().filter(|(_, v)| wanted(v)).count();
+        let verified = count(|verification| matches!(verification, Verification::Verified(_)));
+        let mismatched = count(|verification| *verification == Verification::Mismatch);
+        let unchecked = count(|verification| *verification == Verification::Unchecked);
+        let checking = count(|verification| *verification == Verification::Checking);
+        let template = "🔏 {count} files here have published checksums or signatures.";
+        let mut message = tf(template, &[("count", &self.checksums.len())]);
+        if verified + mismatched > 0 {
+            let counts = tf("{verified} verified, {mismatched} don't match.", &[
+                ("verified", &verified),
+                ("mismatched", &mismatched),
+            ]);
+            message = format!("{} {}", message, counts);
+        }
+        egui::TopBottomPanel::top("checksums_banner").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(message);
+                if checking > 0 {
+                    ui.spinner();
+                } else if unchecked > 0 && ui.button(t("Verify All")).clicked() {
+                    let mut files: Vec<PathBuf> = self.checksums.keys().cloned().collect();
+                    files.sort();
+                    self.verify_checksums(files);
+                }
+            });
+        });
+    }
+
+    /// Checks the files against what vouches for them, in the background.
+    fn verify_checksums(&mut self, files: Vec<PathBuf>) {
+        let mut references = Vec::new();
+        for file in files {
+            if let Some((reference, verification)) = self.checksums.get_mut(&file) {
+                *verification = Verification::Checking;
+                references.push(reference.clone());
+            }
+        }
+        self.send_event(FileSystemEvent::VerifyChecksums(references));
+    }
+
+    fn draw_sidebar(&mut self, ctx: &egui::Context) {
+        if !self.config.show_folder_tree {
+            return;
+        }
+        // Reveal the current folder whenever it changes, without collapsing what the user opened.
+        let reveal = self.tree_synced_path.as_ref() != Some(&self.current_path);
+        if reveal {
+            self.tree_expanded.extend(self.current_path.ancestors().skip(1).map(Path::to_path_buf));
+            self.tree_synced_path = Some(self.current_path.clone());
+        }
+        // Polled rather than watched, so plugged in media shows up within a few seconds.
+        const MOUNTS_POLL_INTERVAL: Duration = Duration::from_secs(5);
+        if self.last_mounts_query.is_none_or(|queried| queried.elapsed() >= MOUNTS_POLL_INTERVAL) {
+            if !self.mounts_query_pending {
+                self.mounts_query_pending = true;
+                self.send_event(FileSystemEvent::ListMounts);
+            }
+            if self.config.adb_devices && !self.adb_query_pending {
+                self.adb_query_pending = true;
+                self.send_event(FileSystemEvent::ListAdbDevices);
+            }
+            if self.config.docker_containers && !self.containers_query_pending {
+                self.containers_query_pending = true;
+                self.send_event(FileSystemEvent::ListContainers);
+            }
+            self.last_mounts_query = Some(Instant::now());
+        }
+        ctx.request_repaint_after(MOUNTS_POLL_INTERVAL);
+
+        egui::SidePanel::left("folder_tree").resizable(true).default_width(200.0).show(ctx, |ui| {
+            egui::ScrollArea::both().auto_shrink([false, false]).show(ui, |ui| {
+                egui::CollapsingHeader::new(t("Places")).default_open(true).show(ui, |ui| {
+                    self.draw_places(ui);
+                });
+                egui::CollapsingHeader::new(t("Devices")).default_open(true).show(ui, |ui| {
+                    self.draw_devices(ui);
+                });
+                if !self.containers.is_empty() || !self.volumes.is_empty() {
+                    egui::CollapsingHeader::new(t("Containers")).default_open(true).show(ui, |ui| {
+                        self.draw_containers(ui);
+                    });
+                }
+                egui::CollapsingHeader::new(t("Folders")).default_open(true).show(ui, |ui| {
+                    for root in self.tree_roots.clone() {
+                        let label = if dirs::home_dir().as_ref() == Some(&root) {
+                            format!("🏠 {}", t("Home"))
+                        } else {
+                            format!("🖴 {}", root.display())
+                        };
+                        self.draw_tree_node(ui, &root, label, 0, reveal);
+                    }
                 });
             });
         });
     }
 
+    fn draw_places(&mut self, ui: &mut egui::Ui) {
+        let places = [
+            ("🏠", "Home", dirs::home_dir()),
+            ("🖥", "Desktop", dirs::desktop_dir()),
+            ("📄", "Documents", dirs::document_dir()),
+            ("📥", "Downloads", dirs::download_dir()),
+        ];
+        for (icon, label, path) in places {
+            let Some(path) = path.filter(|path| path.is_dir()) else {
+                continue;
+            };
+            let response = ui.selectable_label(path == self.current_path, format!("{} {}", icon, t(label)));
+            if response.clicked() {
+                self.navigate_to(&path);
+            }
+            if response.middle_clicked() {
+                self.open_tab(&path, false);
+            }
+        }
+    }
+
+    fn draw_devices(&mut self, ui: &mut egui::Ui) {
+        for mount in self.mounts.clone() {
+            let space = &mount.space;
+            let icon = if mount.removable { "⏏" } else { "🖴" };
+            let label = format!("{} {}", icon, mount.name);
+            let response = ui.selectable_label(self.current_path == space.mount_point, label);
+            if response.clicked() {
+                self.navigate_to(&space.mount_point);
+            }
+            if response.middle_clicked() {
+                self.open_tab(&space.mount_point, false);
+            }
+            let used = space.total.saturating_sub(space.free);
+            let fraction = if space.total > 0 { used as f32 / space.total as f32 } else { 0.0 };
+            ui.add(egui::ProgressBar::new(fraction).desired_width(ui.available_width().min(160.0)))
+                .on_hover_text(format!(
+                    "{} free of {}",
+                    human_bytes(space.free as f64),
+                    human_bytes(space.total as f64)
+                ));
+        }
+        for device in self.adb_devices.clone() {
+            let remote = Remote::Adb(device);
+            let response = ui.selectable_label(self.is_browsing(&remote), remote.title());
+            if response.on_hover_text(t("Browse over ADB")).clicked() {
+                self.browse_remote(remote);
+            }
+        }
+    }
+
+    fn draw_containers(&mut self, ui: &mut egui::Ui) {
+        for container in self.containers.clone() {
+            let image = container.image.clone();
+            let remote = Remote::Container(container);
+            let response = ui.selectable_label(self.is_browsing(&remote), remote.title());
+            if response.on_hover_text(image).clicked() {
+                self.browse_remote(remote);
+            }
+        }
+        for volume in self.volumes.clone() {
+            let browsing = self.remote_browser.as_ref().is_some_and(|browser| {
+                matches!(&browser.remote, Remote::Container(helper) if helper.volume.as_ref() == Some(&volume))
+            });
+            if ui.selectable_label(browsing, format!("🛢 {}", volume)).clicked() && !browsing {
+                self.pending_volume = Some(volume);
+            }
+        }
+    }
+
+    fn is_browsing(&self, remote: &Remote) -> bool {
+        self.remote_browser.as_ref().is_some_and(|browser| &browser.remote == remote)
+    }
+
+    /// Opens the device or container window, replacing whichever was open.
+    fn browse_remote(&mut self, remote: Remote) {
+        if self.is_browsing(&remote) {
+            return;
+        }
+        self.close_remote_browser();
+        self.send_event(FileSystemEvent::ListRemoteDirectory(remote.clone(), remote.home().to_string()));
+        self.remote_browser = Some(RemoteBrowser::new(remote));
+    }
+
+    fn close_remote_browser(&mut self) {
+        if let Some(browser) = self.remote_browser.take() {
+            if let Remote::Container(helper) = browser.remote {
+                if helper.volume.is_some() {
+                    self.send_event(FileSystemEvent::CloseVolume(helper));
+                }
+            }
+        }
+    }
+
+    fn draw_tree_node(&mut self, ui: &mut egui::Ui, path: &Path, label: String, depth: usize, reveal: bool) {
+        const INDENT: f32 = 12.0;
+        let expanded = self.tree_expanded.contains(path);
+        let subfolders: Option<Vec<PathBuf>> = self.child_listings.get(path).map(|children| {
+            let mut folders: Vec<PathBuf> = children
+                .iter()
+                .filter(|child| child.is_dir && (self.show_hidden_files || !child.is_hidden))
+                .map(|child| child.path.clone())
+                .collect();
+            folders.sort_by(|a, b| self.compare_names(a, b));
+            folders
+        });
+        let loading = self.children_loading.contains(path);
+        ui.horizontal(|ui| {
+            ui.add_space(depth as f32 * INDENT);
+            let leaf = !loading && subfolders.as_ref().is_some_and(|folders| folders.is_empty());
+            if leaf {
+                ui.add_space(INDENT + ui.spacing().item_spacing.x);
+            } else if ui.small_button(if expanded { "⏷" } else { "⏵" }).clicked() {
+                if expanded {
+                    self.tree_expanded.remove(path);
+                } else {
+                    self.tree_expanded.insert(path.to_path_buf());
+                }
+            }
+            let response = ui.selectable_label(path == self.current_path, label);
+            if response.clicked() && path != self.current_path {
+                self.navigate_to(path);
+            }
+            if response.middle_clicked() {
+                self.open_tab(path, false);
+            }
+            if reveal && path == self.current_path {
+                response.scroll_to_me(None);
+            }
+        });
+        if !self.tree_expanded.contains(path) {
+            return;
+        }
+        match subfolders {
+            None => {
+                self.child_listings.insert(path.to_path_buf(), Vec::new());
+                self.children_loading.insert(path.to_path_buf());
+                self.send_event(FileSystemEvent::ListChildren(path.to_path_buf()));
+            }
+            Some(_) if loading => {
+                ui.horizontal(|ui| {
+                    ui.add_space((depth + 1) as f32 * INDENT);
+                    ui.weak(t("Loading…"));
+                });
+            }
+            Some(folders) => {
+                for folder in folders {
+                    let name = folder.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                    self.draw_tree_node(ui, &folder, format!("📁 {}", name), depth + 1, reveal);
+                }
+            }
+        }
+    }
+
+    fn draw_toolbar(&mut self, ui: &mut egui::Ui) {
+        let first_selected = self.selected_items.iter().min().cloned();
+        ui.horizontal(|ui| {
+            let tool = |ui: &mut egui::Ui, icon: &str, name: &str, enabled: bool| {
+                let name = t(name);
+                accessible(ui.add_enabled(enabled, egui::Button::new(icon)), &name).on_hover_text(name).clicked()
+            };
+            if tool(ui, "🗀", "New Folder", true) {
+                self.show_new_folder_dialog = true;
+            }
+            if tool(ui, "🗐", "Copy", first_selected.is_some()) {
+                self.copy_selection();
+            }
+            if tool(ui, "✂", "Cut", first_selected.is_some()) {
+                self.cut_selection();
+            }
+            if tool(ui, "📋", "Paste", true) {
+                self.paste();
+            }
+            if tool(ui, "🗑", "Move to Trash", first_selected.is_some()) {
+                let mut paths: Vec<PathBuf> = self.selected_items.iter().cloned().collect();
+                paths.sort();
+                self.request_delete(paths, false);
+            }
+            ui.separator();
+            if tool(ui, ">_", "Open in Terminal", true) {
+                self.open_in_terminal(&self.current_path.clone());
+            }
+            let next = self.view_mode.next();
+            let (mode, next_mode) = (t(self.view_mode.label()), t(next.label()));
+            let view = tf("{mode} View (switch to {next})", &[("mode", &mode), ("next", &next_mode)]);
+            if tool(ui, "☰", &view, true) {
+                self.view_mode = next;
+                self.config.view_mode = next;
+                config::save_config(&self.config).unwrap();
+            }
+        });
+    }
+
     fn draw_address_bar(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
-            if ui.button("⬅").clicked() {
+            if accessible(ui.button("⬅"), &t("Back")).clicked() {
                 self.go_back();
             }
-            if ui.button("➡").clicked() {
+            if accessible(ui.button("➡"), &t("Forward")).clicked() {
                 self.go_forward();
             }
-            if ui.button("⬆").clicked() {
+            if accessible(ui.button("⬆"), &t("Up")).clicked() {
                 if let Some(parent) = self.current_path.parent().map(|p| p.to_path_buf()) {
                     self.navigate_to(&parent);
                 }
@@ -490,299 +2686,2899 @@ impl FileManager {
 
             let mut path_str = self.current_path.to_str().unwrap_or("").to_string();
             let response = ui.add(TextEdit::singleline(&mut path_str).desired_width(f32::INFINITY));
-            if response.lost_focus() {
-                ui.input(|i| {
-                    if i.key_pressed(Key::Enter) {
-                        self.navigate_to(&PathBuf::from(path_str));
-                    }
-                });
+            response.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::TextEdit, "Location"));
+            if std::mem::take(&mut self.focus_address_bar) {
+                response.request_focus();
+            }
+            if response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                // Archive addresses are downloaded and unpacked here rather than navigated to.
+                if download::is_archive_url(&path_str) {
+                    self.download_archive(path_str.trim().to_string());
+                } else {
+                    self.navigate_to(&PathBuf::from(path_str));
+                }
             }
 
 
             ui.add_space(10.0);
             let mut search_query = self.search_query.clone();
-            if ui.add(TextEdit::singleline(&mut search_query).hint_text("Search...")).changed() {
+            let response = ui.add(TextEdit::singleline(&mut search_query).hint_text(t("Search...")));
+            response.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::TextEdit, "Search"));
+            if std::mem::take(&mut self.focus_search) {
+                response.request_focus();
+            }
+            if response.changed() {
                 self.search_query = search_query;
+                self.apply_search();
             }
+            let filter_menu = ui.menu_button("▾", |ui| {
+                let mut changed = false;
+                changed |= ui.checkbox(&mut self.filter_options.folders_always_shown, "Always Show Folders").changed();
+                changed |= ui.checkbox(&mut self.filter_options.recursive, "Search Subfolders").changed();
+                changed |= ui.checkbox(&mut self.filter_options.extensions_only, "Match Extensions Only").changed();
+                if changed {
+                    self.config.filter_options = self.filter_options;
+                    config::save_config(&self.config).unwrap();
+                    self.apply_search();
+                }
+            });
+            accessible(filter_menu.response, "Filter Options");
         });
     }
 
-    fn draw_file_list(&mut self, ui: &mut egui::Ui) {
-        let mut filtered_items = self.items.clone();
+    fn visible_items(&self, items: &[FileSystemItem]) -> Vec<FileSystemItem> {
+        let mut visible_items = items.to_vec();
         if !self.search_query.is_empty() {
-            filtered_items.retain(|item| {
-                item.path
-                    .file_name()
-                    .unwrap_or_default()
-                    .to_str()
-                    .unwrap_or_default()
-                    .to_lowercase()
-                    .contains(&self.search_query.to_lowercase())
+            visible_items.retain(|item| {
+                (item.is_dir && self.filter_options.folders_always_shown)
+                    || file_system::name_matches(&item.path, &self.search_query, self.filter_options.extensions_only)
             });
         }
         if !self.show_hidden_files {
-            filtered_items.retain(|item| !item.is_hidden);
+            visible_items.retain(|item| !item.is_hidden);
+        }
+        if self.config.hide_partial_downloads {
+            visible_items.retain(|item| !item.is_partial_download());
+        }
+        profiling::time(&self.current_path, Stage::Sort, || self.sort_items(&mut visible_items));
+        visible_items
+    }
+
+    fn compare_names(&self, a: &Path, b: &Path) -> std::cmp::Ordering {
+        if self.config.natural_sort {
+            let name = |path: &Path| path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            file_system::natural_cmp(&name(a), &name(b))
+        } else {
+            a.file_name().cmp(&b.file_name())
         }
+    }
 
+    fn sort_items(&self, items: &mut [FileSystemItem]) {
         match self.sort_by {
-            SortBy::Name => filtered_items.sort_by(|a, b| a.path.file_name().cmp(&b.path.file_name())),
-            SortBy::Size => filtered_items.sort_by_key(|a| a.size),
-            SortBy::Modified => filtered_items.sort_by_key(|a| a.modified),
+            SortBy::Name => items.sort_by(|a, b| self.compare_names(&a.path, &b.path)),
+            SortBy::Size => items.sort_by_key(|a| a.shown_size(self.config.size_on_disk)),
+            SortBy::Modified => items.sort_by_key(|a| a.modified),
         }
         if !self.sort_ascending {
-            filtered_items.reverse();
+            items.reverse();
+        }
+        if self.config.folders_first {
+            // Stable, so each group keeps the order it was just sorted into.
+            items.sort_by_key(|item| !item.is_dir);
         }
+    }
 
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            let available_rect = ui.available_rect_before_wrap();
-            let response = ui.interact(
-                available_rect,
-                egui::Id::new("file_list_background"),
-                Sense::click_and_drag(),
-            );
+    fn name_color(&self, item: &FileSystemItem, ui: &egui::Ui) -> egui::Color32 {
+        self.config.color_rules.color_for(item).unwrap_or_else(|| ui.visuals().text_color())
+    }
 
-            if response.drag_started() {
-                if !ui.ctx().input(|i| i.modifiers.ctrl) {
-                    self.selected_items.clear();
-                }
-                self.drag_start_pos = response.hover_pos();
-            }
-            if response.dragged() {
-                if let Some(start_pos) = self.drag_start_pos {
-                    let current_pos = response.hover_pos().unwrap_or(start_pos);
-                    self.drag_rect = Some(egui::Rect::from_two_pos(start_pos, current_pos));
-                }
-            }
-            if response.drag_released() {
-                self.drag_start_pos = None;
-                self.drag_rect = None;
+    fn handle_item_response(&mut self, ui: &egui::Ui, response: &egui::Response, item: &FileSystemItem) {
+        let is_selected = self.selected_items.contains(&item.path);
+        if let Some(drag_rect) = self.drag_rect {
+            if drag_rect.intersects(response.rect) {
+                self.selected_items.insert(item.path.clone());
             }
-
-            if response.clicked() {
+        } else if response.clicked() {
+            if !ui.input(|i| i.modifiers.ctrl) {
                 self.selected_items.clear();
             }
-            if response.secondary_clicked() {
-                self.context_menu_pos = Some(response.hover_pos().unwrap());
-                self.context_menu_item = None;
+            if is_selected {
+                self.selected_items.remove(&item.path);
+            } else {
+                self.selected_items.insert(item.path.clone());
             }
-
-            if let Some(rect) = self.drag_rect {
-                ui.painter().rect_filled(
-                    rect,
-                    egui::Rounding::none(),
-                    ui.style().visuals.selection.bg_fill.gamma_multiply(0.5),
-                );
+            self.focused_item = Some(item.path.clone());
+            self.selection_anchor = Some(item.path.clone());
+        }
+        if self.focused_item.as_ref() == Some(&item.path) {
+            ui.painter().rect_stroke(response.rect, 2.0, ui.visuals().selection.stroke);
+            if self.scroll_to_focused {
+                response.scroll_to_me(None);
+                self.scroll_to_focused = false;
             }
+        }
+        if response.interact(Sense::drag()).drag_started() && self.drag_rect.is_none() {
+            let mut paths: Vec<PathBuf> = if is_selected {
+                self.selected_items.iter().cloned().collect()
+            } else {
+                vec![item.path.clone()]
+            };
+            paths.sort();
+            self.item_drag = Some((DragSource::FileList, paths));
+        }
+        if response.double_clicked() {
+            self.open_item(&item.path.clone());
+        }
+        if response.middle_clicked() && item.is_dir {
+            self.open_tab(&item.path, false);
+        }
+        if response.secondary_clicked() {
+            self.context_menu_pos = Some(response.hover_pos().unwrap());
+            self.context_menu_item = Some(item.clone());
+        }
+        if response.hovered() && self.drag_rect.is_none() && self.hover_delay_elapsed(ui.ctx(), &item.path) {
+            if item.is_dir {
+                self.folder_preview_tooltip(response, &item.path);
+            } else {
+                file_details_tooltip(response, item);
+            }
+        }
+    }
 
-            let table = TableBuilder::new(ui)
-                .striped(true)
-                .resizable(true)
-                .column(Column::initial(250.0).at_least(100.0))
-                .column(Column::initial(80.0).at_least(40.0))
-                .column(Column::initial(150.0).at_least(80.0))
-                .min_scrolled_height(0.0);
-
-            table
-                .header(20.0, |mut header| {
-                    header.col(|ui| {
-                        ui.strong("Name");
-                    });
-                    header.col(|ui| {
-                        ui.strong("Size");
-                    });
-                    header.col(|ui| {
-                        ui.strong("Last Modified");
-                    });
-                })
-                .body(|body| {
-                    body.rows(18.0, filtered_items.len(), |row_index, mut row| {
-                        let item = &filtered_items[row_index];
-                        let is_selected = self.selected_items.contains(&item.path);
-
-                        row.col(|ui| {
-                            let icon = if item.is_dir { "📁" } else { "📄" };
-                            let label = format!("{} {}", icon, item.path.file_name().unwrap().to_str().unwrap());
-                            let response =
-                                ui.add(egui::SelectableLabel::new(is_selected, label));
-
-                            if let Some(drag_rect) = self.drag_rect {
-                                if drag_rect.intersects(response.rect) {
-                                    self.selected_items.insert(item.path.clone());
-                                }
-                            } else if response.clicked() {
-                                if !ui.input(|i| i.modifiers.ctrl) {
-                                    self.selected_items.clear();
-                                }
-                                if is_selected {
-                                    self.selected_items.remove(&item.path);
-                                } else {
-                                    self.selected_items.insert(item.path.clone());
-                                }
-                            }
-                            if response.double_clicked() {
-                                self.open_item(&item.path.clone());
-                            }
-                            if response.secondary_clicked() {
-                                self.context_menu_pos = Some(response.hover_pos().unwrap());
-                                self.context_menu_item = Some(item.clone());
-                            }
-
-                            if let Some(renaming_path) = &self.renaming_item {
-                                if renaming_path == &item.path {
-                                    if ui.add(TextEdit::singleline(&mut self.renaming_text)).lost_focus() {
-                                        self.rename_item();
-                                    }
-                                }
-                            }
-                        });
-
-                        row.col(|ui| {
-                            ui.label(if item.is_dir {
-                                "".to_string()
-                            } else {
-                                human_bytes(item.size as f64)
-                            });
-                        });
+    /// Where the focused item is in `items` when the list should scroll to it; only asked once.
+    fn focused_row(&mut self, items: &[FileSystemItem]) -> Option<usize> {
+        if !self.scroll_to_focused {
+            return None;
+        }
+        self.scroll_to_focused = false;
+        let focused = self.focused_item.as_ref()?;
+        items.iter().position(|item| &item.path == focused)
+    }
 
-                        row.col(|ui| {
-                            let modified_time =
-                                DateTime::<Local>::from(item.modified).format("%Y-%m-%d %H:%M:%S").to_string();
-                            ui.label(modified_time);
-                        });
-                    });
-                });
+    /// Adds `text` to what's been typed, starting afresh after a pause, and selects the first
+    /// item whose name starts with it.
+    fn jump_to_typed(&mut self, text: &str, time: f64) {
+        if time - self.type_ahead_at > TYPE_AHEAD_TIMEOUT {
+            self.type_ahead.clear();
+        }
+        self.type_ahead_at = time;
+        self.type_ahead.push_str(&text.to_lowercase());
+        let found = self.visible_items(&self.items).into_iter().find(|item| {
+            let name = item.path.file_name().unwrap_or_default().to_string_lossy().to_lowercase();
+            name.starts_with(&self.type_ahead)
         });
+        if let Some(item) = found {
+            self.selected_items = HashSet::from([item.path.clone()]);
+            self.selection_anchor = Some(item.path.clone());
+            self.focused_item = Some(item.path);
+            self.scroll_to_focused = true;
+        }
     }
 
-    fn draw_status_bar(&mut self, ui: &mut egui::Ui) {
-        ui.with_layout(Layout::left_to_right(Align::Center), |ui| {
-            ui.label(&self.status_message);
-            if self.file_op_progress > 0.0 && self.file_op_progress < 1.0 {
-                ui.add(egui::ProgressBar::new(self.file_op_progress).show_percentage());
-            }
-        });
+    /// Moves the focused row by `delta` and selects it, or with `extend` selects from the anchor to it.
+    fn move_focus(&mut self, delta: isize, extend: bool) {
+        let visible = self.visible_items(&self.items);
+        if visible.is_empty() {
+            return;
+        }
+        let position = |path: &PathBuf| visible.iter().position(|item| &item.path == path);
+        let current = self.focused_item.as_ref().and_then(position);
+        let index = match current {
+            Some(index) => index.saturating_add_signed(delta).min(visible.len() - 1),
+            None if delta < 0 => visible.len() - 1,
+            None => 0,
+        };
+        let focused = visible[index].path.clone();
+        let anchor = match self.selection_anchor.as_ref().and_then(position) {
+            Some(anchor) if extend => anchor,
+            _ => index,
+        };
+        let range = anchor.min(index)..=anchor.max(index);
+        self.selected_items = visible[range].iter().map(|item| item.path.clone()).collect();
+        self.selection_anchor = Some(visible[anchor].path.clone());
+        self.focused_item = Some(focused);
+        self.scroll_to_focused = true;
     }
 
-    fn draw_dialogs(&mut self, ctx: &egui::Context) {
-        if self.show_new_file_dialog {
-            egui::Window::new("Create New File").collapsible(false).resizable(false).show(ctx, |ui| {
+    /// Whether `path` has been hovered continuously for the configured tooltip delay.
+    fn hover_delay_elapsed(&mut self, ctx: &egui::Context, path: &Path) -> bool {
+        let delay = Duration::from_millis(u64::from(self.config.tooltip_delay_ms));
+        let frame = ctx.frame_nr();
+        // Restart the timer unless the same item was also hovered last frame.
+        let still_hovered = matches!(
+            &self.hover_started,
+            Some((hovered, _, seen)) if hovered == path && *seen + 1 >= frame
+        );
+        if !still_hovered {
+            self.hover_started = Some((path.to_path_buf(), Instant::now(), frame));
+            ctx.request_repaint_after(delay);
+            return delay.is_zero();
+        }
+        let Some((_, started, seen)) = &mut self.hover_started else {
+            return false;
+        };
+        *seen = frame;
+        let elapsed = started.elapsed();
+        if elapsed < delay {
+            ctx.request_repaint_after(delay - elapsed);
+        }
+        elapsed >= delay
+    }
+
+    /// Peeks at a folder's first few entries, listed through the worker on first hover.
+    fn folder_preview_tooltip(&mut self, response: &egui::Response, folder: &Path) {
+        const PREVIEW_ENTRIES: usize = 8;
+        let Some(children) = self.child_listings.get(folder) else {
+            self.child_listings.insert(folder.to_path_buf(), Vec::new());
+            self.children_loading.insert(folder.to_path_buf());
+            self.send_event(FileSystemEvent::ListChildren(folder.to_path_buf()));
+            return;
+        };
+        let loading = self.children_loading.contains(folder);
+        let mut names: Vec<(bool, String)> = children
+            .iter()
+            .filter(|child| self.show_hidden_files || !child.is_hidden)
+            .map(|child| (!child.is_dir, child.display_name()))
+            .collect();
+        names.sort();
+        response.clone().on_hover_ui(|ui| {
+            ui.strong(folder.file_name().unwrap_or_default().to_string_lossy());
+            if loading {
+                ui.weak(t("Loading…"));
+            } else if names.is_empty() {
+                ui.weak(t("Empty folder"));
+            } else {
+                for (is_file, name) in names.iter().take(PREVIEW_ENTRIES) {
+                    ui.label(format!("{} {}", if *is_file { "📄" } else { "📁" }, name));
+                }
+                if names.len() > PREVIEW_ENTRIES {
+                    ui.weak(tf("…and {count} more", &[("count", &(names.len() - PREVIEW_ENTRIES))]));
+                }
+                ui.separator();
+                ui.weak(tf("{count} items", &[("count", &names.len())]));
+            }
+        });
+    }
+
+    fn draw_breadcrumbs(&mut self, ui: &mut egui::Ui) {
+        let mut ancestors: Vec<PathBuf> = self.current_path.ancestors().map(Path::to_path_buf).collect();
+        ancestors.reverse();
+
+        ui.horizontal_wrapped(|ui| {
+            ui.spacing_mut().item_spacing.x = 2.0;
+            for (index, ancestor) in ancestors.iter().enumerate() {
+                if index > 0 {
+                    ui.label("›");
+                }
+                let name = ancestor
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| ancestor.display().to_string());
+                let response = ui.small_button(name);
+                if response.clicked() {
+                    self.navigate_to(ancestor);
+                }
+                response.context_menu(|ui| {
+                    if ui.button(t("Copy Path")).clicked() {
+                        ui.ctx().output_mut(|o| o.copied_text = ancestor.display().to_string());
+                        ui.close_menu();
+                    }
+                    if ui.button(t("Open in New Tab")).clicked() {
+                        self.open_tab(ancestor, false);
+                        ui.close_menu();
+                    }
+                    if ui.button(t("Open in New Window")).clicked() {
+                        self.open_in_new_window(ancestor);
+                        ui.close_menu();
+                    }
+                    if ui.button(t("Open in Terminal")).clicked() {
+                        self.open_in_terminal(ancestor);
+                        ui.close_menu();
+                    }
+                    if ui.button(t("Properties")).clicked() {
+                        if let Ok(item) = file_system::read_item(ancestor) {
+                            self.show_properties(item);
+                        }
+                        ui.close_menu();
+                    }
+                });
+            }
+        });
+    }
+
+    fn handle_list_background(&mut self, ui: &mut egui::Ui) {
+        let available_rect = ui.available_rect_before_wrap();
+        self.file_list_rect = Some(available_rect);
+        let response = ui.interact(
+            available_rect,
+            egui::Id::new("file_list_background"),
+            Sense::click_and_drag(),
+        );
+
+        if response.drag_started() {
+            if !ui.ctx().input(|i| i.modifiers.ctrl) {
+                self.selected_items.clear();
+            }
+            self.drag_start_pos = response.hover_pos();
+        }
+        if response.dragged() {
+            if let Some(start_pos) = self.drag_start_pos {
+                let current_pos = response.hover_pos().unwrap_or(start_pos);
+                self.drag_rect = Some(egui::Rect::from_two_pos(start_pos, current_pos));
+            }
+        }
+        if response.drag_released() {
+            self.drag_start_pos = None;
+            self.drag_rect = None;
+        }
+
+        if response.clicked() {
+            self.selected_items.clear();
+        }
+        if response.secondary_clicked() {
+            self.context_menu_pos = Some(response.hover_pos().unwrap());
+            self.context_menu_item = None;
+        }
+
+        if let Some(rect) = self.drag_rect {
+            ui.painter().rect_filled(
+                rect,
+                egui::Rounding::none(),
+                ui.style().visuals.selection.bg_fill.gamma_multiply(0.5),
+            );
+        }
+    }
+
+    /// While a rubber band or Shift selection is in progress, shows the count and total size
+    /// of what's selected next to the pointer.
+    fn draw_selection_badge(&self, ctx: &egui::Context) {
+        let (shift, pointer) = ctx.input(|i| (i.modifiers.shift, i.pointer.hover_pos()));
+        let Some(pointer) = pointer else {
+            return;
+        };
+        if self.drag_rect.is_none() && !(shift && !self.selected_items.is_empty()) {
+            return;
+        }
+        let selected: Vec<&FileSystemItem> =
+            self.items.iter().filter(|item| self.selected_items.contains(&item.path)).collect();
+        let on_disk = self.config.size_on_disk;
+        let size: u64 = selected.iter().filter(|item| !item.is_dir).map(|item| item.shown_size(on_disk)).sum();
+        let folders = selected.iter().filter(|item| item.is_dir).count();
+        let size = human_bytes(size as f64);
+        let mut text = tf("{count} selected, {size}", &[("count", &selected.len()), ("size", &size)]);
+        if folders > 0 {
+            text = format!("{} {}", text, tf("(plus {count} folders)", &[("count", &folders)]));
+        }
+        egui::Area::new("selection_badge")
+            .order(egui::Order::Tooltip)
+            .fixed_pos(pointer + egui::vec2(16.0, 16.0))
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(text);
+                });
+            });
+    }
+
+    fn draw_file_list(&mut self, ui: &mut egui::Ui) {
+        let filtered_items = self.visible_items(&self.items);
+
+        // A new display scale or grid zoom: reload thumbnails at a size that stays sharp.
+        let icon_zoom = if self.view_mode == ViewMode::Grid { self.zoom() } else { 1.0 };
+        let wanted_size = thumbnail_size(self.pixels_per_point * icon_zoom);
+        if wanted_size != self.thumbnails_size {
+            self.thumbnails.clear();
+            self.thumbnails_size = wanted_size;
+        }
+
+        let current_path = self.current_path.clone();
+        profiling::time(&current_path, Stage::Render, || match self.view_mode {
+            ViewMode::Details => self.draw_details_view(ui, &filtered_items),
+            ViewMode::Columns => self.draw_column_view(ui, &filtered_items),
+            ViewMode::Compact => self.draw_compact_view(ui, &filtered_items),
+            ViewMode::Grid => self.draw_grid_view(ui, &filtered_items),
+        });
+    }
+
+    fn draw_grid_view(&mut self, ui: &mut egui::Ui, filtered_items: &[FileSystemItem]) {
+        const NAME_ROWS: f32 = 2.0;
+        let icon_size = GRID_ICON_SIZE * self.zoom();
+        let spacing = ui.spacing().item_spacing;
+        let text_height = ui.text_style_height(&egui::TextStyle::Body);
+        let tile_size = egui::vec2(icon_size + 24.0, icon_size + text_height * NAME_ROWS + 12.0);
+        let columns = (((ui.available_width() + spacing.x) / (tile_size.x + spacing.x)).floor() as usize).max(1);
+        self.grid_columns = columns;
+        let rows = filtered_items.len().div_ceil(columns);
+
+        egui::ScrollArea::vertical().auto_shrink([false, false]).show_rows(ui, tile_size.y, rows, |ui, row_range| {
+            self.handle_list_background(ui);
+            // The focused tile may be in a row that isn't laid out, so scroll to where it would be.
+            if let Some(index) = self.focused_row(filtered_items) {
+                let offset = (index / columns) as f32 - row_range.start as f32;
+                let top = ui.max_rect().top() + offset * (tile_size.y + spacing.y);
+                let rect = egui::Rect::from_min_size(egui::pos2(ui.max_rect().left(), top), tile_size);
+                ui.scroll_to_rect(rect, None);
+            }
+            for row in row_range {
+                let start = row * columns;
+                let end = (start + columns).min(filtered_items.len());
+                ui.horizontal(|ui| {
+                    for item in &filtered_items[start..end] {
+                        self.draw_grid_tile(ui, item, tile_size, icon_size);
+                    }
+                });
+            }
+        });
+    }
+
+    fn draw_grid_tile(&mut self, ui: &mut egui::Ui, item: &FileSystemItem, tile_size: egui::Vec2, icon_size: f32) {
+        let (rect, response) = ui.allocate_exact_size(tile_size, Sense::click());
+        let is_selected = self.selected_items.contains(&item.path);
+        let visuals = ui.style().interact_selectable(&response, is_selected);
+        if is_selected || response.hovered() {
+            ui.painter().rect_filled(rect, visuals.rounding, visuals.weak_bg_fill);
+        }
+
+        let icon_rect = egui::Rect::from_center_size(
+            egui::pos2(rect.center().x, rect.top() + 4.0 + icon_size / 2.0),
+            egui::vec2(icon_size, icon_size),
+        );
+        let texture = match item.shortcut.as_ref().and_then(|shortcut| shortcut.icon.clone()) {
+            Some(icon) => self.icon_texture(&icon),
+            None => self.icon_texture(&item.path),
+        };
+        let texture = texture.unwrap_or_else(|| self.icons.texture(ui.ctx(), item, icon_size));
+        let size = texture.size_vec2();
+        let scale = (icon_size / size.x).min(icon_size / size.y);
+        let image_rect = egui::Rect::from_center_size(icon_rect.center(), size * scale);
+        let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+        ui.painter().image(texture.id(), image_rect, uv, egui::Color32::WHITE);
+        paint_emblems(ui, icon_rect, item, self.config.starred.contains(&item.path));
+
+        let name_rect = egui::Rect::from_min_max(egui::pos2(rect.left() + 2.0, icon_rect.bottom() + 4.0), rect.max);
+        if self.renaming_item.as_ref() == Some(&item.path) {
+            let edit_rect = name_rect.with_max_y(name_rect.top() + ui.spacing().interact_size.y);
+            let edit = ui.put(edit_rect, TextEdit::singleline(&mut self.renaming_text));
+            if edit.lost_focus() {
+                self.rename_item();
+            }
+        } else {
+            // Long names wrap onto a second line before being cut short.
+            let color = self.name_color(item, ui);
+            let mut job = highlight_matches("", &item.display_name(), &self.search_query, color, ui.style());
+            job.wrap = egui::text::TextWrapping {
+                max_width: name_rect.width() - 2.0,
+                max_rows: 2,
+                break_anywhere: true,
+                overflow_character: Some('…'),
+            };
+            job.halign = Align::Center;
+            let galley = ui.fonts(|fonts| fonts.layout_job(job));
+            ui.painter().galley(egui::pos2(name_rect.center().x, name_rect.top()), galley);
+        }
+
+        let response = response.on_hover_text_at_pointer(item.display_name());
+        self.handle_item_response(ui, &response, item);
+    }
+
+    fn draw_compact_view(&mut self, ui: &mut egui::Ui, filtered_items: &[FileSystemItem]) {
+        let item_height = self.row_height();
+        let row_height = item_height + ui.spacing().item_spacing.y;
+        let rows_per_column = ((ui.available_height() / row_height).floor() as usize).max(1);
+        let font_id = egui::TextStyle::Body.resolve(ui.style());
+        let char_width = ui.fonts(|f| f.glyph_width(&font_id, 'M'));
+        let longest_name = filtered_items
+            .iter()
+            .map(|item| item.path.file_name().unwrap_or_default().to_string_lossy().chars().count())
+            .max()
+            .unwrap_or(0);
+        let column_width = (longest_name as f32 * char_width + 30.0).clamp(120.0, 320.0);
+
+        egui::ScrollArea::horizontal().show(ui, |ui| {
+            self.handle_list_background(ui);
+            ui.horizontal_top(|ui| {
+                for column in filtered_items.chunks(rows_per_column) {
+                    ui.allocate_ui(egui::vec2(column_width, ui.available_height()), |ui| {
+                        ui.set_width(column_width);
+                        for item in column {
+                            let is_selected = self.selected_items.contains(&item.path);
+                            let icon = self.icons.texture(ui.ctx(), item, ui.text_style_height(&egui::TextStyle::Body));
+                            let name = &elide_middle(ui, &item.display_name(), column_width - ICON_ALLOWANCE);
+                            let label =
+                                highlight_matches("", name, &self.search_query, self.name_color(item, ui), ui.style());
+                            let size = egui::vec2(column_width, item_height);
+                            let response = icon_row(ui, size, is_selected, &icon, label);
+                            paint_emblems(ui, response.rect, item, self.config.starred.contains(&item.path));
+                            self.handle_item_response(ui, &response, item);
+                        }
+                    });
+                }
+            });
+        });
+    }
+
+    /// Loads image previews and launcher icons through the thumbnail worker.
+    fn icon_texture(&mut self, path: &Path) -> Option<egui::TextureHandle> {
+        if !thumbnails::is_supported(path) {
+            return None;
+        }
+        match self.thumbnails.get(path) {
+            Some(texture) => texture.clone(),
+            None => {
+                self.thumbnails.insert(path.to_path_buf(), None);
+                self.send_event(FileSystemEvent::LoadThumbnail(path.to_path_buf(), self.thumbnails_size));
+                None
+            }
+        }
+    }
+
+    /// A column header that sorts by its column when clicked, and reverses the order when clicked again.
+    fn sort_header(&mut self, ui: &mut egui::Ui, label: &str, sort_by: Option<SortBy>) {
+        let label = t(label);
+        let Some(sort_by) = sort_by else {
+            ui.strong(label);
+            return;
+        };
+        let text = match (self.sort_by == sort_by, self.sort_ascending) {
+            (true, true) => format!("{} ⏶", label),
+            (true, false) => format!("{} ⏷", label),
+            (false, _) => label,
+        };
+        let header = ui.add(egui::Label::new(egui::RichText::new(text).strong()).sense(Sense::click()));
+        if header.on_hover_cursor(egui::CursorIcon::PointingHand).clicked() {
+            if self.sort_by == sort_by {
+                self.sort_ascending = !self.sort_ascending;
+            } else {
+                self.sort_by = sort_by;
+                self.sort_ascending = true;
+            }
+            self.config.sort_by = self.sort_by;
+            self.config.sort_ascending = self.sort_ascending;
+            config::save_config(&self.config).unwrap();
+        }
+    }
+
+    /// Shows or hides a details view column, keeping the columns in their usual order.
+    fn toggle_column(&mut self, column: TableColumn) {
+        if let Some(index) = self.config.columns.iter().position(|shown| *shown == column) {
+            self.config.columns.remove(index);
+        } else {
+            self.config.columns.push(column);
+            self.config.columns.sort_by_key(|shown| TableColumn::ALL.iter().position(|c| c == shown));
+        }
+        config::save_config(&self.config).unwrap();
+    }
+
+    fn draw_details_view(&mut self, ui: &mut egui::Ui, filtered_items: &[FileSystemItem]) {
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            self.handle_list_background(ui);
+
+            let columns: Vec<TableColumn> = self
+                .config
+                .columns
+                .iter()
+                .copied()
+                .filter(|column| *column != TableColumn::LastOpened || self.config.track_usage)
+                .collect();
+            let mut table = TableBuilder::new(ui)
+                .striped(true)
+                .resizable(true)
+                .column(Column::initial(250.0).at_least(100.0))
+                .min_scrolled_height(0.0);
+            for column in &columns {
+                table = table.column(Column::initial(column.width()).at_least(column.width() / 2.0));
+            }
+            if let Some(row) = self.focused_row(filtered_items) {
+                table = table.scroll_to_row(row, None);
+            }
+
+            table
+                .header(20.0, |mut header| {
+                    header.col(|ui| {
+                        self.sort_header(ui, "Name", Some(SortBy::Name));
+                    });
+                    for column in &columns {
+                        header.col(|ui| {
+                            let label = match column {
+                                TableColumn::Size if self.config.size_on_disk => "Size on Disk",
+                                column => column.label(),
+                            };
+                            self.sort_header(ui, label, column.sort_by());
+                        });
+                    }
+                })
+                .body(|body| {
+                    body.rows(self.row_height(), filtered_items.len(), |row_index, mut row| {
+                        let item = &filtered_items[row_index];
+                        let is_selected = self.selected_items.contains(&item.path);
+
+                        row.col(|ui| {
+                            let icon_size = ui.text_style_height(&egui::TextStyle::Body);
+                            let icon_texture = item
+                                .shortcut
+                                .as_ref()
+                                .and_then(|shortcut| shortcut.icon.clone())
+                                .and_then(|icon| self.icon_texture(&icon))
+                                .unwrap_or_else(|| self.icons.texture(ui.ctx(), item, icon_size));
+                            let url = item.shortcut.as_ref().and_then(|shortcut| shortcut.url());
+                            // Internet shortcuts give some of the name's room to their address.
+                            let name_share = if url.is_some() { 0.5 } else { 1.0 };
+                            let max_width = (ui.available_width() - ICON_ALLOWANCE) * name_share;
+                            let name = &elide_middle(ui, &item.display_name(), max_width);
+                            let label =
+                                highlight_matches("", name, &self.search_query, self.name_color(item, ui), ui.style());
+                            ui.image(&icon_texture, [icon_size, icon_size]);
+                            let response =
+                                ui.add(egui::SelectableLabel::new(is_selected, label));
+                            paint_emblems(ui, response.rect, item, self.config.starred.contains(&item.path));
+                            self.handle_item_response(ui, &response, item);
+                            let checksum = self.checksums.get(&item.path);
+                            if checksum.is_some_and(|(reference, verification)| {
+                                verification_badge(ui, reference, verification).clicked()
+                                    && *verification != Verification::Checking
+                            }) {
+                                self.verify_checksums(vec![item.path.clone()]);
+                            }
+                            if let Some(url) = url {
+                                ui.weak(elide_middle(ui, url, ui.available_width()));
+                            }
+
+                            if let Some(renaming_path) = &self.renaming_item {
+                                if renaming_path == &item.path {
+                                    if ui.add(TextEdit::singleline(&mut self.renaming_text)).lost_focus() {
+                                        self.rename_item();
+                                    }
+                                }
+                            }
+                        });
+
+                        for column in &columns {
+                            row.col(|ui| self.draw_column_cell(ui, item, *column));
+                        }
+                    });
+                });
+        });
+    }
+
+    fn draw_column_cell(&mut self, ui: &mut egui::Ui, item: &FileSystemItem, column: TableColumn) {
+        let format_time = |time: SystemTime| DateTime::<Local>::from(time).format("%Y-%m-%d %H:%M:%S").to_string();
+        let needs_metadata = match column {
+            TableColumn::Size => !item.is_dir,
+            TableColumn::Type | TableColumn::LastOpened => false,
+            _ => true,
+        };
+        if needs_metadata && !item.metadata_loaded {
+            ui.weak("…");
+            return;
+        }
+        match column {
+            TableColumn::Size => {
+                ui.label(if item.is_dir {
+                    self.folder_item_count(&item.path)
+                } else {
+                    human_bytes(item.shown_size(self.config.size_on_disk) as f64)
+                });
+            }
+            TableColumn::Modified => {
+                ui.label(format_time(item.modified));
+            }
+            TableColumn::Created => match item.created {
+                Some(created) => {
+                    ui.label(format_time(created));
+                }
+                None => {
+                    ui.weak("—");
+                }
+            },
+            TableColumn::Type => {
+                ui.label(item.type_description());
+            }
+            TableColumn::Permissions => {
+                ui.monospace(item.permissions.as_deref().unwrap_or("—"));
+            }
+            TableColumn::Owner => {
+                ui.label(item.owner.as_deref().unwrap_or("—"));
+            }
+            TableColumn::Age => paint_age_cell(ui, item.modified),
+            TableColumn::LastOpened => match self.usage.get(&item.path) {
+                Some(usage) => {
+                    let last_opened = usage.last_opened.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+                    ui.label(last_opened.to_string()).on_hover_text(format!("Opened {} times", usage.count));
+                }
+                None => {
+                    ui.weak("—");
+                }
+            },
+        }
+    }
+
+    fn draw_column_view(&mut self, ui: &mut egui::Ui, filtered_items: &[FileSystemItem]) {
+        let mut parents: Vec<PathBuf> = self
+            .current_path
+            .ancestors()
+            .skip(1)
+            .take(MILLER_PARENT_COLUMNS)
+            .map(Path::to_path_buf)
+            .collect();
+        parents.reverse();
+
+        for parent in &parents {
+            if !self.child_listings.contains_key(parent) {
+                self.child_listings.insert(parent.clone(), Vec::new());
+                self.send_event(FileSystemEvent::ListChildren(parent.clone()));
+            }
+        }
+
+        egui::ScrollArea::horizontal().stick_to_right(true).show(ui, |ui| {
+            ui.horizontal_top(|ui| {
+                for parent in &parents {
+                    let mut children = self.child_listings.get(parent).cloned().unwrap_or_default();
+                    if !self.show_hidden_files {
+                        children.retain(|item| !item.is_hidden);
+                    }
+                    self.sort_items(&mut children);
+                    self.draw_miller_column(ui, parent, &children, false);
+                    ui.separator();
+                }
+                let current_path = self.current_path.clone();
+                self.draw_miller_column(ui, &current_path, filtered_items, true);
+            });
+        });
+    }
+
+    fn draw_miller_column(&mut self, ui: &mut egui::Ui, path: &Path, items: &[FileSystemItem], is_current: bool) {
+        ui.allocate_ui(egui::vec2(MILLER_COLUMN_WIDTH, ui.available_height()), |ui| {
+            ui.set_width(MILLER_COLUMN_WIDTH);
+            egui::ScrollArea::vertical().id_source(path).show(ui, |ui| {
+                for item in items {
+                    let is_selected = if is_current {
+                        self.selected_items.contains(&item.path)
+                    } else {
+                        self.current_path.starts_with(&item.path)
+                    };
+                    let icon = self.icons.texture(ui.ctx(), item, ui.text_style_height(&egui::TextStyle::Body));
+                    let name = &elide_middle(ui, &item.display_name(), MILLER_COLUMN_WIDTH - 2.0 * ICON_ALLOWANCE);
+                    let suffix = if item.is_dir { " ⏵" } else { "" };
+                    let query = if is_current { self.search_query.as_str() } else { "" };
+                    let mut label = highlight_matches("", name, query, self.name_color(item, ui), ui.style());
+                    label.append(suffix, 0.0, egui::text::TextFormat::simple(
+                        egui::TextStyle::Body.resolve(ui.style()),
+                        ui.style().visuals.weak_text_color(),
+                    ));
+                    let size = egui::vec2(MILLER_COLUMN_WIDTH, self.row_height());
+                    let response = icon_row(ui, size, is_selected, &icon, label);
+                    paint_emblems(ui, response.rect, item, self.config.starred.contains(&item.path));
+
+                    if is_current {
+                        if response.clicked() && item.is_dir && !ui.input(|i| i.modifiers.ctrl) {
+                            self.navigate_to(&item.path.clone());
+                        } else {
+                            self.handle_item_response(ui, &response, item);
+                        }
+                    } else if response.clicked() {
+                        if item.is_dir {
+                            self.navigate_to(&item.path.clone());
+                        } else {
+                            self.navigate_to(path);
+                            self.selected_items.insert(item.path.clone());
+                        }
+                    } else if response.secondary_clicked() {
+                        self.context_menu_pos = Some(response.hover_pos().unwrap());
+                        self.context_menu_item = Some(item.clone());
+                    }
+                }
+            });
+        });
+    }
+
+    fn draw_status_bar(&mut self, ui: &mut egui::Ui) {
+        ui.with_layout(Layout::left_to_right(Align::Center), |ui| {
+            ui.label(&self.status_message);
+            if self.file_op_progress > 0.0 && self.file_op_progress < 1.0 {
+                ui.add(egui::ProgressBar::new(self.file_op_progress).show_percentage());
+            }
+            if let Some(cancel) = &self.delete_cancel {
+                if ui.button(t("Cancel")).clicked() {
+                    cancel.store(true, Ordering::Relaxed);
+                }
+            }
+            if let Some(job) = self.downloads.last() {
+                if ui.button(t("Cancel Download")).clicked() {
+                    job.cancel.store(true, Ordering::Relaxed);
+                }
+            }
+            if let Some(space) = self.disk_space.clone() {
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    let used = space.total.saturating_sub(space.free);
+                    let fraction = if space.total > 0 { used as f32 / space.total as f32 } else { 0.0 };
+                    let bar = ui
+                        .add(egui::ProgressBar::new(fraction).desired_width(60.0))
+                        .interact(Sense::click());
+                    let label = ui.add(
+                        egui::Label::new(format!(
+                            "{} free of {}",
+                            human_bytes(space.free as f64),
+                            human_bytes(space.total as f64)
+                        ))
+                        .sense(Sense::click()),
+                    );
+                    if (bar.clicked() || label.clicked()) && !self.show_disk_usage_dialog {
+                        self.analyze_disk_usage(&space.mount_point);
+                    }
+                    bar.on_hover_text(format!("{} - click to analyze", space.mount_point.display()));
+                });
+            }
+        });
+    }
+
+    /// Asks the worker for Downloads items older than the reminder's threshold, unless the
+    /// reminder is off or snoozed.
+    fn check_old_downloads(&mut self) {
+        let reminder = self.config.downloads_reminder;
+        self.old_downloads = None;
+        if !reminder.enabled || reminder.snoozed_until.is_some_and(|until| until > Utc::now()) {
+            return;
+        }
+        if let Some(downloads) = dirs::download_dir() {
+            let condition = ColorCondition::OlderThanDays(reminder.older_than_days);
+            self.send_event(FileSystemEvent::FindCleanupCandidates(downloads, condition));
+        }
+    }
+
+    /// Serves `paths` over HTTP to the local network, replacing anything shared before.
+    fn share_over_lan(&mut self, mut paths: Vec<PathBuf>) {
+        paths.sort();
+        // Stop the old server first so it never outlives its window.
+        self.lan_share = None;
+        match Share::start(paths) {
+            Ok(share) => self.lan_share = Some(share),
+            Err(err) => self.status_message = tf("Could not share over the network: {error}", &[("error", &err)]),
+        }
+    }
+
+    /// The selection, if it is exactly two images, in name order.
+    fn selected_image_pair(&self) -> Option<(PathBuf, PathBuf)> {
+        if self.selected_items.len() != 2 || !self.selected_items.iter().all(|path| thumbnails::is_supported(path)) {
+            return None;
+        }
+        let mut paths: Vec<PathBuf> = self.selected_items.iter().cloned().collect();
+        paths.sort();
+        let second = paths.pop()?;
+        Some((paths.pop()?, second))
+    }
+
+    fn show_properties(&mut self, item: FileSystemItem) {
+        self.properties_text_info = None;
+        self.properties_tab = PropertiesTab::General;
+        self.properties_versions = None;
+        if !item.is_dir {
+            self.send_event(FileSystemEvent::InspectText(vec![item.path.clone()]));
+        }
+        self.properties_item = Some(item);
+        self.show_properties_dialog = true;
+    }
+
+    fn draw_previous_versions(&mut self, ui: &mut egui::Ui, item: &FileSystemItem) {
+        let Some(versions) = self.properties_versions.clone() else {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(t("Looking for snapshots..."));
+            });
+            return;
+        };
+        if versions.is_empty() {
+            ui.label(t("No previous versions were found in snapshots."));
+            return;
+        }
+        egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+            egui::Grid::new("versions_grid").striped(true).show(ui, |ui| {
+                ui.strong(t("Snapshot"));
+                ui.strong(t("Modified"));
+                ui.strong(if item.is_dir { String::new() } else { t("Size") });
+                ui.end_row();
+                for version in &versions {
+                    ui.label(&version.snapshot);
+                    ui.label(DateTime::<Local>::from(version.modified).format("%Y-%m-%d %H:%M").to_string());
+                    ui.label(if item.is_dir { String::new() } else { human_bytes(version.size as f64) });
+                    ui.horizontal(|ui| {
+                        if ui.small_button(t("Open")).clicked() {
+                            if item.is_dir {
+                                self.show_properties_dialog = false;
+                                self.navigate_to(&version.path);
+                            } else {
+                                self.send_event(FileSystemEvent::OpenFile(version.path.clone()));
+                            }
+                        }
+                        if !item.is_dir && ui.small_button(t("Compare")).clicked() {
+                            self.version_diff = Some((version.clone(), item.path.clone(), None));
+                            self.send_event(FileSystemEvent::DiffVersion(version.path.clone(), item.path.clone()));
+                        }
+                        let restore =
+                            ui.small_button(t("Restore")).on_hover_text(t("The current version goes to the trash"));
+                        if restore.clicked() {
+                            self.pending_restore = Some((version.clone(), item.path.clone()));
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
+    fn convert_selected_text(&mut self) {
+        let mut paths: Vec<PathBuf> = self.selected_items.iter().filter(|path| !path.is_dir()).cloned().collect();
+        paths.sort();
+        self.convert_text_files = None;
+        self.show_convert_text_dialog = true;
+        self.send_event(FileSystemEvent::InspectText(paths));
+    }
+
+    fn compare_images(&mut self, first: PathBuf, second: PathBuf) {
+        self.comparison = Some(Comparison::new(first.clone(), second.clone()));
+        self.send_event(FileSystemEvent::CompareImages(first, second));
+    }
+
+    /// Downloads the tar archive at `url` and unpacks it into the current folder.
+    fn download_archive(&mut self, url: String) {
+        self.status_message = tf("Downloading {name}...", &[("name", &url)]);
+        let job = self.start_download();
+        self.send_event(FileSystemEvent::DownloadArchive(job, url, self.current_path.clone()));
+    }
+
+    fn start_download(&mut self) -> DownloadJob {
+        self.next_download_id += 1;
+        let job = DownloadJob { id: self.next_download_id, cancel: Arc::new(AtomicBool::new(false)) };
+        self.downloads.push(job.clone());
+        job
+    }
+
+    fn find_duplicates(&mut self) {
+        self.duplicates = None;
+        self.show_duplicates_dialog = true;
+        self.send_event(FileSystemEvent::FindDuplicates(self.current_path.clone()));
+    }
+
+    /// Makes the groups' copies share storage with their first file; they drop off the list.
+    fn deduplicate_with_reflinks(&mut self, groups: Vec<Vec<PathBuf>>) {
+        if let Some((_, found)) = &mut self.duplicates {
+            found.retain(|(_, paths)| !groups.contains(paths));
+        }
+        self.status_message = t("Replacing copies with reflinks...");
+        self.send_event(FileSystemEvent::DeduplicateWithReflinks(groups));
+    }
+
+    fn plan_sort(&mut self) {
+        self.sort_plan = None;
+        self.sort_generation += 1;
+        let rules = self.config.sort_rules.clone();
+        self.send_event(FileSystemEvent::PlanSort(self.sort_folder.clone(), rules, self.sort_generation));
+    }
+
+    fn undo_sort(&mut self) {
+        if let Some((dir, outcome)) = self.last_sort.take() {
+            self.status_message = tf("Moving {count} files back...", &[("count", &outcome.moved.len())]);
+            self.send_event(FileSystemEvent::UndoSort(dir, outcome));
+        }
+    }
+
+    fn plan_music(&mut self) {
+        self.music_plan = None;
+        if let Some(library) = self.music_folder.clone() {
+            self.send_event(FileSystemEvent::PlanMusic(library, self.config.music_layout.clone()));
+        }
+    }
+
+    fn open_photo_import(&mut self) {
+        // The card the current folder is on, or else the first one plugged in.
+        let cards: Vec<&PathBuf> =
+            self.mounts.iter().filter(|mount| mount.removable).map(|mount| &mount.space.mount_point).collect();
+        let card = cards.iter().find(|point| self.current_path.starts_with(point)).or(cards.first());
+        let settings = &self.config.photo_import;
+        self.photo_import = Some(PhotoImportWizard {
+            source: card.map_or_else(|| self.current_path.clone(), |point| point.to_path_buf()),
+            destination: settings.destination.as_ref().map(|path| path.display().to_string()).unwrap_or_default(),
+            layout: settings.layout.clone(),
+            step: ImportStep::Choose,
+            cancel: Arc::new(AtomicBool::new(false)),
+        });
+    }
+
+    /// The Import Photos window, if it's waiting on the worker rather than the user.
+    fn waiting_photo_import(&mut self) -> Option<&mut PhotoImportWizard> {
+        self.photo_import.as_mut().filter(|wizard| matches!(wizard.step, ImportStep::Working(..)))
+    }
+
+    fn find_largest_files(&mut self) {
+        self.largest_files = None;
+        self.show_largest_files_dialog = true;
+        self.send_event(FileSystemEvent::FindLargestFiles(self.current_path.clone(), self.largest_files_count));
+    }
+
+    fn analyze_disk_usage(&mut self, path: &Path) {
+        self.disk_usage = None;
+        self.show_disk_usage_dialog = true;
+        self.cancel_disk_usage();
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.disk_usage_pending = Some((path.to_path_buf(), cancel.clone()));
+        self.send_event(FileSystemEvent::AnalyzeDiskUsage(path.to_path_buf(), cancel));
+    }
+
+    fn cancel_disk_usage(&mut self) {
+        if let Some((_, cancel)) = self.disk_usage_pending.take() {
+            cancel.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn draw_dialogs(&mut self, ctx: &egui::Context) {
+        if self.show_new_file_dialog {
+            egui::Window::new(t("Create New File")).collapsible(false).resizable(false).show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(t("File Name:"));
+                    ui.text_edit_singleline(&mut self.new_file_name).request_focus();
+                });
+                ui.horizontal(|ui| {
+                    if ui.button(t("Create")).clicked() || ui.input(|i| i.key_pressed(Key::Enter)) {
+                        self.create_file();
+                    }
+                    if ui.button(t("Cancel")).clicked() || ui.input(|i| i.key_pressed(Key::Escape)) {
+                        self.show_new_file_dialog = false;
+                        self.new_file_name.clear();
+                    }
+                });
+            });
+        }
+
+        if self.show_new_folder_dialog {
+            egui::Window::new(t("Create New Folder")).collapsible(false).resizable(false).show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(t("Folder Name:"));
+                    ui.text_edit_singleline(&mut self.new_folder_name).request_focus();
+                });
+                ui.horizontal(|ui| {
+                    if ui.button(t("Create")).clicked() || ui.input(|i| i.key_pressed(Key::Enter)) {
+                        self.create_folder();
+                    }
+                    if ui.button(t("Cancel")).clicked() || ui.input(|i| i.key_pressed(Key::Escape)) {
+                        self.show_new_folder_dialog = false;
+                        self.new_folder_name.clear();
+                    }
+                });
+            });
+        }
+
+        if let Some((template, name)) = &mut self.new_project {
+            let (mut create, mut cancel) = (false, false);
+            let template_name = template.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            let title = tf("New {template} Project", &[("template", &template_name)]);
+            egui::Window::new(title).collapsible(false).resizable(false).show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(t("Project Name:"));
+                    ui.text_edit_singleline(name).request_focus();
+                });
+                ui.weak(t("{{name}}, {{date}}, {{year}} and {{user}} are filled in throughout the template."));
+                ui.horizontal(|ui| {
+                    let valid_name = !name.is_empty() && !name.contains(['/', '\\']);
+                    create = ui.add_enabled(valid_name, egui::Button::new(t("Create"))).clicked()
+                        || (valid_name && ui.input(|i| i.key_pressed(Key::Enter)));
+                    cancel = ui.button(t("Cancel")).clicked() || ui.input(|i| i.key_pressed(Key::Escape));
+                });
+            });
+            if create || cancel {
+                let (template, name) = self.new_project.take().unwrap();
+                if create {
+                    self.send_event(FileSystemEvent::CreateFromTemplate(template, self.current_path.clone(), name));
+                }
+            }
+        }
+
+        if self.show_delete_confirmation {
+            egui::Window::new(t("Confirm Deletion")).collapsible(false).resizable(false).show(ctx, |ui| {
+                let count = self.items_to_delete.len();
+                if self.delete_permanently {
+                    ui.label(tf("Permanently delete these {count} item(s)?", &[("count", &count)]));
+                } else {
+                    ui.label(tf("Move these {count} item(s) to the trash?", &[("count", &count)]));
+                }
+                egui::ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+                    for path in &self.items_to_delete {
+                        ui.label(path.file_name().unwrap_or(path.as_os_str()).to_string_lossy());
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui.button(t("Yes")).clicked() {
+                        self.delete_item();
+                    }
+                    if ui.button(t("No")).clicked() {
+                        self.show_delete_confirmation = false;
+                        self.items_to_delete.clear();
+                    }
+                });
+            });
+        }
+
+        if let Some(conflict) = &self.pending_conflict {
+            let (name, more) = match conflict {
+                Conflict::Paste(_, dest_path) => {
+                    let name = dest_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+                    (name, !self.paste_queue.is_empty())
+                }
+                Conflict::Extract(path) => {
+                    let remaining = self.extraction.as_ref().map_or(0, |(archive, resolutions)| {
+                        archive.conflicts.len() - resolutions.len()
+                    });
+                    (path.display().to_string(), remaining > 1)
+                }
+                Conflict::Compress(_, archive, _) => {
+                    (archive.file_name().unwrap_or_default().to_string_lossy().to_string(), false)
+                }
+            };
+            let mut answer = None;
+            egui::Window::new(t("Confirm Overwrite")).collapsible(false).resizable(false).show(ctx, |ui| {
+                ui.label(tf("\"{name}\" already exists. Replace it?", &[("name", &name)]));
+                if more {
+                    ui.checkbox(&mut self.conflict_apply_all, t("Do the same for the rest"));
+                }
+                ui.horizontal(|ui| {
+                    if ui.button(t("Replace")).clicked() {
+                        answer = Some(Some(ConflictResolution::Replace));
+                    }
+                    if ui.button(t("Skip")).clicked() {
+                        answer = Some(Some(ConflictResolution::Skip));
+                    }
+                    if ui.button(t("Keep Both")).clicked() {
+                        answer = Some(Some(ConflictResolution::KeepBoth));
+                    }
+                    if ui.button(t("Cancel")).clicked() {
+                        answer = Some(None);
+                    }
+                });
+            });
+            if let Some(resolution) = answer {
+                self.resolve_conflict(resolution);
+            }
+        }
+
+        if !self.pending_drop_move.is_empty() {
+            egui::Window::new(t("Confirm Move")).collapsible(false).resizable(false).show(ctx, |ui| {
+                let count = self.pending_drop_move.len();
+                ui.label(tf("Move {count} dropped item(s) into this folder?", &[("count", &count)]));
+                ui.horizontal(|ui| {
+                    if ui.button(t("Move")).clicked() {
+                        self.paste_queue = self
+                            .pending_drop_move
+                            .drain(..)
+                            .map(|path| ClipboardItem { action: ClipboardAction::Cut, path })
+                            .collect();
+                        self.continue_paste();
+                    }
+                    if ui.button(t("Cancel")).clicked() {
+                        self.pending_drop_move.clear();
+                    }
+                });
+            });
+        }
+
+        if let Some((path, _)) = &self.pending_run {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            egui::Window::new(t("Run Program")).collapsible(false).resizable(false).show(ctx, |ui| {
+                ui.label(tf("Run \"{name}\"? Only run programs you trust.", &[("name", &name)]));
+                ui.horizontal(|ui| {
+                    if ui.button(t("Run")).clicked() {
+                        if let Some((path, in_terminal)) = self.pending_run.take() {
+                            self.send_event(FileSystemEvent::RunExecutable(path, in_terminal));
+                        }
+                    }
+                    if ui.button(t("Cancel")).clicked() {
+                        self.pending_run = None;
+                    }
+                });
+            });
+        }
+
+        if let Some(volume) = &self.pending_volume {
+            let mut answer = None;
+            egui::Window::new(t("Browse Volume")).collapsible(false).resizable(false).show(ctx, |ui| {
+                ui.label(tf(
+                    "Browsing \"{volume}\" starts a helper container from the busybox image, which Docker \
+                     downloads first if it isn't already there.",
+                    &[("volume", volume)],
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button(t("Browse")).clicked() {
+                        answer = Some(true);
+                    }
+                    if ui.button(t("Cancel")).clicked() {
+                        answer = Some(false);
+                    }
+                });
+            });
+            if let Some(browse) = answer {
+                let volume = self.pending_volume.take().unwrap();
+                if browse {
+                    self.status_message = tf("Mounting volume {volume}...", &[("volume", &volume)]);
+                    self.send_event(FileSystemEvent::OpenVolume(volume));
+                }
+            }
+        }
+
+        if let Some((path, shortcut)) = &self.pending_launch {
+            // The file's own name, not the one it gives itself.
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let command = shortcut.describe_target().to_string();
+            egui::Window::new(t("Run Program")).collapsible(false).resizable(false).show(ctx, |ui| {
+                ui.label(tf("Run \"{name}\"? Only run programs you trust.", &[("name", &name)]));
+                ui.monospace(command);
+                ui.horizontal(|ui| {
+                    if ui.button(t("Run")).clicked() {
+                        if let Some((_, shortcut)) = self.pending_launch.take() {
+                            self.send_event(FileSystemEvent::LaunchShortcut(shortcut));
+                        }
+                    }
+                    if ui.button(t("Cancel")).clicked() {
+                        self.pending_launch = None;
+                    }
+                });
+            });
+        }
+
+        if let Some(pending) = &mut self.pending_download {
+            let path = self.current_path.join(&pending.name);
+            let (mut save, mut extract, mut cancel) = (false, false, false);
+            egui::Window::new(t("Download")).collapsible(false).resizable(false).show(ctx, |ui| {
+                ui.label(&pending.url);
+                egui::Grid::new("download_grid").num_columns(2).show(ui, |ui| {
+                    ui.label(t("Save as:"));
+                    ui.text_edit_singleline(&mut pending.name);
+                    ui.end_row();
+                    ui.label(t("Checksum:"));
+                    ui.add(TextEdit::singleline(&mut pending.checksum).hint_text(t("MD5 or SHA-256, optional")));
+                    ui.end_row();
+                });
+                if download::partial_path(&path).exists() {
+                    ui.label(t("The earlier, unfinished download will be resumed."));
+                } else if path.exists() {
+                    ui.label(tf("\"{name}\" already exists and will be replaced.", &[("name", &pending.name)]));
+                }
                 ui.horizontal(|ui| {
-                    ui.label("File Name:");
-                    ui.text_edit_singleline(&mut self.new_file_name);
+                    let valid_name = download::is_valid_name(&pending.name);
+                    save = ui.add_enabled(valid_name, egui::Button::new(t("Download Here"))).clicked();
+                    extract = download::is_archive_url(&pending.url) && ui.button(t("Extract Here")).clicked();
+                    cancel = ui.button(t("Cancel")).clicked();
                 });
+            });
+            if save || extract || cancel {
+                let pending = self.pending_download.take().unwrap();
+                if save {
+                    let checksum = Some(pending.checksum.trim().to_string()).filter(|checksum| !checksum.is_empty());
+                    self.status_message = tf("Downloading {name}...", &[("name", &pending.name)]);
+                    let job = self.start_download();
+                    self.send_event(FileSystemEvent::DownloadFile(job, pending.url, path, checksum));
+                } else if extract {
+                    self.download_archive(pending.url);
+                }
+            }
+        }
+
+        // Another overwrite question has to be answered before this one can be asked.
+        let asking = self.pending_conflict.is_some();
+        if let Some(dialog) = &mut self.compress_dialog {
+            let (mut create, mut cancel) = (false, false);
+            egui::Window::new(t("Compress")).collapsible(false).resizable(false).show(ctx, |ui| {
+                let (count, dir) = (dialog.paths.len(), self.current_path.display());
+                ui.label(tf("{count} item(s) into {dir}", &[("count", &count), ("dir", &dir)]));
                 ui.horizontal(|ui| {
-                    if ui.button("Create").clicked() || ui.input(|i| i.key_pressed(Key::Enter)) {
-                        self.create_file();
+                    ui.label(t("Archive name:"));
+                    ui.text_edit_singleline(&mut dialog.name);
+                });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut dialog.split, t("Split into parts of"));
+                    ui.add_enabled(dialog.split, egui::DragValue::new(&mut dialog.volume_mb).clamp_range(1..=100_000));
+                    ui.label(t("MB"));
+                });
+                if dialog.split {
+                    ui.weak(t("Parts are numbered .001, .002…; 7-Zip opens them, or join them with cat or copy /b."));
+                }
+                if cfg!(windows) {
+                    ui.checkbox(&mut dialog.self_extracting, t("Self-extracting (.exe)"))
+                        .on_hover_text(t("Unpacks itself next to where it's run, without any other tools"));
+                }
+                ui.horizontal(|ui| {
+                    let valid_name = !dialog.name.is_empty() && !dialog.name.contains(['/', '\\']);
+                    create = ui.add_enabled(valid_name && !asking, egui::Button::new(t("Compress"))).clicked();
+                    cancel = ui.button(t("Cancel")).clicked();
+                });
+            });
+            if create || cancel {
+                let dialog = self.compress_dialog.take().unwrap();
+                if create {
+                    let options = ArchiveOptions {
+                        volume_size: dialog.split.then_some(dialog.volume_mb * 1024 * 1024),
+                        self_extracting: dialog.self_extracting,
+                    };
+                    let archive = self.current_path.join(&dialog.name);
+                    if self.config.confirmations.overwrite && !archive::existing_outputs(&archive, options).is_empty() {
+                        self.pending_conflict = Some(Conflict::Compress(dialog.paths, archive, options));
+                    } else {
+                        self.start_compress(dialog.paths, archive, options);
+                    }
+                }
+            }
+        }
+
+        if let Some((offer, sender, _)) = self.peer_offers.first() {
+            let mut answer = None;
+            // The name in the offer is the sender's to choose, so it's only trusted when the
+            // address belongs to a copy found on the network under that name.
+            let peers = self.peers.as_ref().map(Peers::peers).unwrap_or_default();
+            let known = peers.iter().any(|peer| peer.addr.ip() == *sender && peer.name == offer.from);
+            let from = if known {
+                format!("{} ({})", offer.from, sender)
+            } else {
+                tf(
+                    "An unknown device at {address}, calling itself \"{name}\",",
+                    &[("address", sender), ("name", &offer.from)],
+                )
+            };
+            egui::Window::new(t("Incoming Files")).collapsible(false).resizable(false).show(ctx, |ui| {
+                ui.label(tf(
+                    "{from} wants to send you {count} file(s), {size}.",
+                    &[
+                        ("from", &from),
+                        ("count", &offer.files.len()),
+                        ("size", &human_bytes(offer.size() as f64)),
+                    ],
+                ));
+                for (name, _) in offer.files.iter().take(5) {
+                    ui.weak(name);
+                }
+                if offer.files.len() > 5 {
+                    ui.weak(tf("and {count} more", &[("count", &(offer.files.len() - 5))]));
+                }
+                ui.label(tf("They'll be saved in {dir}.", &[("dir", &self.current_path.display())]));
+                ui.horizontal(|ui| {
+                    if ui.button(t("Accept")).clicked() {
+                        answer = Some(Some(self.current_path.clone()));
+                    }
+                    if ui.button(t("Decline")).clicked() {
+                        answer = Some(None);
+                    }
+                });
+            });
+            if let Some(answer) = answer {
+                let (_, _, reply) = self.peer_offers.remove(0);
+                let _ = reply.send(answer);
+            }
+        }
+
+        if let Some((_, path, holders)) = self.locked_operations.first() {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let holders = if holders.is_empty() { t("another process") } else { holders.join(", ") };
+            egui::Window::new(t("File In Use")).collapsible(false).resizable(false).show(ctx, |ui| {
+                ui.label(tf("\"{name}\" is in use by {holders}.", &[("name", &name), ("holders", &holders)]));
+                ui.label(t("Close the file in that program and retry."));
+                ui.horizontal(|ui| {
+                    if ui.button(t("Retry")).clicked() {
+                        let (event, _, _) = self.locked_operations.remove(0);
+                        self.send_event(event);
+                    }
+                    if ui.button(t("Skip")).clicked() {
+                        self.locked_operations.remove(0);
+                    }
+                    if ui.button(t("Abort")).clicked() {
+                        self.locked_operations.clear();
+                    }
+                });
+            });
+        }
+
+        if let Some((_, path)) = self.denied_operations.first() {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            egui::Window::new(t("Permission Denied")).collapsible(false).resizable(false).show(ctx, |ui| {
+                ui.label(tf("You don't have permission to modify \"{name}\".", &[("name", &name)]));
+                ui.horizontal(|ui| {
+                    if ui.button(ELEVATED_RETRY_LABEL).clicked() {
+                        let (event, _) = self.denied_operations.remove(0);
+                        let operation = match event {
+                            FileSystemEvent::CopyItem(from, to) => Some(ElevatedOperation::Copy(from, to)),
+                            FileSystemEvent::MoveItem(from, to) | FileSystemEvent::RenameItem(from, to) => {
+                                Some(ElevatedOperation::Move(from, to))
+                            }
+                            _ => None,
+                        };
+                        if let Some(operation) = operation {
+                            self.send_event(FileSystemEvent::RunElevated(operation));
+                        }
+                    }
+                    if ui.button(t("Cancel")).clicked() {
+                        self.denied_operations.remove(0);
+                    }
+                });
+            });
+        }
+
+        if !self.interrupted_transfers.is_empty() {
+            egui::Window::new(t("Interrupted Transfers")).collapsible(false).show(ctx, |ui| {
+                ui.label(t("These copies or moves did not finish the last time the app ran."));
+                let mut resolved = None;
+                egui::Grid::new("interrupted_transfers_grid").striped(true).show(ui, |ui| {
+                    for (index, entry) in self.interrupted_transfers.iter().enumerate() {
+                        ui.label(entry.describe());
+                        if ui.button(t("Resume")).clicked() {
+                            resolved = Some((index, true));
+                        }
+                        if ui.button(t("Roll Back")).clicked() {
+                            resolved = Some((index, false));
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some((index, resume)) = resolved {
+                    let entry = self.interrupted_transfers.remove(index);
+                    // Another window may have resumed or rolled it back already.
+                    if resume && journal::finish(entry.id) {
+                        self.send_event(match entry.kind {
+                            TransferKind::Copy => FileSystemEvent::CopyItem(entry.from, entry.to),
+                            TransferKind::Move => FileSystemEvent::MoveItem(entry.from, entry.to),
+                        });
+                    } else if !resume {
+                        self.send_event(FileSystemEvent::RollBackTransfer(entry));
+                    }
+                }
+                if ui.button(t("Decide Later")).clicked() {
+                    self.interrupted_transfers.clear();
+                }
+            });
+        }
+
+        // Keep polling while jobs are unfinished so their completion can be notified. A job just
+        // submitted may not be listed yet, so polling also covers a short while after submitting.
+        const JOB_SUBMIT_GRACE: Duration = Duration::from_secs(5);
+        let jobs_unfinished = self.daemon_jobs.as_ref().is_ok_and(|jobs| {
+            jobs.iter().any(|job| matches!(job.state, JobState::Queued | JobState::Running))
+        }) || self.last_job_submitted.is_some_and(|submitted| submitted.elapsed() < JOB_SUBMIT_GRACE);
+        if self.show_jobs_dialog || jobs_unfinished {
+            const JOBS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+            if self.last_jobs_query.is_none_or(|queried| queried.elapsed() >= JOBS_POLL_INTERVAL) {
+                self.send_event(FileSystemEvent::QueryJobs);
+                self.last_jobs_query = Some(Instant::now());
+            }
+            ctx.request_repaint_after(JOBS_POLL_INTERVAL);
+        }
+        if self.show_jobs_dialog {
+            let mut open = true;
+            egui::Window::new(t("Background Jobs")).open(&mut open).default_width(450.0).show(ctx, |ui| {
+                match &self.daemon_jobs {
+                    Ok(jobs) if jobs.is_empty() => {
+                        ui.label(t("No jobs have been submitted."));
+                    }
+                    Ok(jobs) => {
+                        egui::Grid::new("daemon_jobs_grid").striped(true).show(ui, |ui| {
+                            for job in jobs {
+                                ui.label(job.describe());
+                                ui.label(match &job.state {
+                                    JobState::Queued => t("Queued"),
+                                    JobState::Running => t("Running"),
+                                    JobState::Done => t("Done"),
+                                    JobState::Failed(err) => tf("Failed: {error}", &[("error", err)]),
+                                });
+                                ui.end_row();
+                            }
+                        });
+                    }
+                    Err(err) => {
+                        ui.label(tf("The daemon is not reachable: {error}", &[("error", &err)]));
+                        if ui.button(t("Start Daemon")).clicked() {
+                            if let Err(err) = daemon::spawn() {
+                                self.status_message = tf("Failed to start the daemon: {error}", &[("error", &err)]);
+                            }
+                        }
+                    }
+                }
+            });
+            self.show_jobs_dialog = open;
+        }
+
+        if !self.failed_deletions.is_empty() {
+            egui::Window::new(t("Some Items Were Not Deleted")).collapsible(false).show(ctx, |ui| {
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    egui::Grid::new("failed_deletions_grid").striped(true).show(ui, |ui| {
+                        for (path, error) in &self.failed_deletions {
+                            ui.label(path.display().to_string());
+                            ui.label(error);
+                            ui.end_row();
+                        }
+                    });
+                });
+                ui.horizontal(|ui| {
+                    if ui.button(ELEVATED_RETRY_LABEL).clicked() {
+                        let paths = self.failed_deletions.drain(..).map(|(path, _)| path).collect();
+                        self.send_event(FileSystemEvent::RunElevated(ElevatedOperation::Delete(paths)));
+                    }
+                    if ui.button(t("Close")).clicked() {
+                        self.failed_deletions.clear();
+                    }
+                });
+            });
+        }
+
+        if self.show_go_to_dialog {
+            egui::Window::new(t("Go To Path")).collapsible(false).resizable(false).show(ctx, |ui| {
+                let response = ui.text_edit_singleline(&mut self.go_to_path);
+                if response.changed() {
+                    self.go_to_selected = None;
+                }
+                response.request_focus();
+
+                let suggestions = self.go_to_suggestions();
+                ui.input(|i| {
+                    if i.key_pressed(Key::ArrowDown) && !suggestions.is_empty() {
+                        self.go_to_selected =
+                            Some(self.go_to_selected.map_or(0, |index| (index + 1).min(suggestions.len() - 1)));
+                    }
+                    if i.key_pressed(Key::ArrowUp) {
+                        self.go_to_selected = self.go_to_selected.and_then(|index| index.checked_sub(1));
+                    }
+                });
+                if ui.input(|i| i.key_pressed(Key::Tab)) {
+                    if let Some((_, path)) = self.go_to_selected.and_then(|index| suggestions.get(index)) {
+                        self.go_to_path = format!("{}{}", path.display(), std::path::MAIN_SEPARATOR);
+                        self.go_to_selected = None;
+                    }
+                }
+
+                let mut chosen = None;
+                for (index, (kind, path)) in suggestions.iter().enumerate() {
+                    let selected = self.go_to_selected == Some(index);
+                    let response = ui.selectable_label(selected, format!("{}  {}", kind, path.display()));
+                    if selected {
+                        response.scroll_to_me(None);
+                    }
+                    if response.clicked() {
+                        chosen = Some(path.clone());
+                    }
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button(t("Go")).clicked() || ui.input(|i| i.key_pressed(Key::Enter)) {
+                        let selected = self.go_to_selected.and_then(|index| suggestions.get(index));
+                        chosen = Some(selected.map_or_else(|| PathBuf::from(&self.go_to_path), |(_, path)| path.clone()));
+                    }
+                    if ui.button(t("Cancel")).clicked() || ui.input(|i| i.key_pressed(Key::Escape)) {
+                        self.show_go_to_dialog = false;
+                    }
+                });
+
+                if let Some(path) = chosen {
+                    self.navigate_to(&path);
+                    self.show_go_to_dialog = false;
+                }
+            });
+        }
+
+        if self.show_properties_dialog {
+            if let Some(item) = &self.properties_item.clone() {
+                egui::Window::new(t("Properties")).collapsible(false).resizable(false).show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.selectable_value(&mut self.properties_tab, PropertiesTab::General, t("General"));
+                        let previous = PropertiesTab::PreviousVersions;
+                        let versions = ui.selectable_value(&mut self.properties_tab, previous, t("Previous Versions"));
+                        if versions.clicked() && self.properties_versions.is_none() {
+                            self.send_event(FileSystemEvent::ListVersions(item.path.clone()));
+                        }
+                    });
+                    ui.separator();
+                    if self.properties_tab == PropertiesTab::PreviousVersions {
+                        self.draw_previous_versions(ui, item);
+                    } else {
+                        egui::Grid::new("properties_grid").show(ui, |ui| {
+                            ui.label(t("Name:"));
+                            ui.label(item.path.file_name().unwrap().to_str().unwrap());
+                            ui.end_row();
+                            ui.label(t("Path:"));
+                            ui.label(item.path.to_str().unwrap());
+                            ui.end_row();
+                            ui.label(t("Type:"));
+                            ui.label(if item.is_dir {
+                                t("Folder")
+                            } else if item.shortcut.is_some() {
+                                t("Shortcut")
+                            } else {
+                                t("File")
+                            });
+                            ui.end_row();
+                            if let Some(shortcut) = &item.shortcut {
+                                ui.label(t("Target:"));
+                                ui.label(shortcut.describe_target());
+                                ui.end_row();
+                            }
+                            if !item.is_dir {
+                                ui.label(t("Size:"));
+                                ui.label(format!("{} ({} bytes)", human_bytes(item.size as f64), item.size));
+                                ui.end_row();
+                                ui.label(t("Size on Disk:"));
+                                ui.label(format!(
+                                    "{} ({} bytes)",
+                                    human_bytes(item.allocated_size as f64),
+                                    item.allocated_size
+                                ));
+                                ui.end_row();
+                            }
+                            if let Some(info) = &self.properties_text_info {
+                                ui.label(t("Encoding:"));
+                                ui.label(info.describe());
+                                ui.end_row();
+                            }
+                            ui.label(t("Modified:"));
+                            let modified_time = DateTime::<Local>::from(item.modified);
+                            ui.label(modified_time.format("%Y-%m-%d %H:%M:%S").to_string());
+                            ui.end_row();
+                            if let Some(created) = item.created {
+                                ui.label(t("Created:"));
+                                ui.label(DateTime::<Local>::from(created).format("%Y-%m-%d %H:%M:%S").to_string());
+                                ui.end_row();
+                            }
+                            if let Some(permissions) = &item.permissions {
+                                ui.label(t("Permissions:"));
+                                ui.monospace(permissions);
+                                ui.end_row();
+                            }
+                            if let Some(owner) = &item.owner {
+                                ui.label(t("Owner:"));
+                                ui.label(owner);
+                                ui.end_row();
+                            }
+                        });
+                        if let Some(texture) = self.icon_texture(&item.path) {
+                            ui.image(&texture, texture.size_vec2());
+                        }
+                    }
+                    if ui.button(t("Close")).clicked() {
+                        self.show_properties_dialog = false;
+                        self.properties_item = None;
+                    }
+                });
+            }
+        }
+
+        if let Some((version, path)) = &self.pending_restore {
+            let mut answer = None;
+            egui::Window::new(t("Restore Version")).collapsible(false).resizable(false).show(ctx, |ui| {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                ui.label(tf(
+                    "Replace \"{name}\" with the version from {snapshot}? The current version goes to the trash.",
+                    &[("name", &name), ("snapshot", &version.snapshot)],
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button(t("Restore")).clicked() {
+                        answer = Some(true);
+                    }
+                    if ui.button(t("Cancel")).clicked() {
+                        answer = Some(false);
+                    }
+                });
+            });
+            if let Some(restore) = answer {
+                let (version, path) = self.pending_restore.take().unwrap();
+                if restore {
+                    self.status_message = tf("Restoring {path}...", &[("path", &path.display())]);
+                    self.send_event(FileSystemEvent::RestoreVersion(version.path, path));
+                }
+            }
+        }
+
+        if let Some((version, path, diff)) = &self.version_diff {
+            let mut open = true;
+            let title = tf("Changes Since {snapshot}", &[("snapshot", &version.snapshot)]);
+            egui::Window::new(title).open(&mut open).default_size([600.0, 400.0]).show(ctx, |ui| {
+                ui.label(path.display().to_string());
+                ui.separator();
+                match diff {
+                    None => {
+                        ui.spinner();
+                    }
+                    Some(Err(err)) => {
+                        ui.label(err);
+                    }
+                    Some(Ok(lines)) if lines.iter().all(|line| matches!(line, DiffLine::Same(_))) => {
+                        ui.label(t("The contents are the same."));
+                    }
+                    Some(Ok(lines)) => {
+                        let removed = ui.visuals().error_fg_color;
+                        let added = egui::Color32::from_rgb(80, 170, 80);
+                        egui::ScrollArea::both().auto_shrink([false; 2]).show(ui, |ui| {
+                            for line in lines {
+                                let (text, color) = match line {
+                                    DiffLine::Same(text) => (format!("  {}", text), ui.visuals().text_color()),
+                                    DiffLine::Removed(text) => (format!("- {}", text), removed),
+                                    DiffLine::Added(text) => (format!("+ {}", text), added),
+                                };
+                                ui.label(egui::RichText::new(text).monospace().color(color));
+                            }
+                        });
+                    }
+                }
+            });
+            if !open {
+                self.version_diff = None;
+            }
+        }
+
+        if self.show_workspaces_dialog {
+            let mut open = true;
+            let mut changed = false;
+            egui::Window::new(t("Workspaces")).open(&mut open).resizable(false).show(ctx, |ui| {
+                let current_path = self.current_path.clone();
+                let mut remove = None;
+                for (index, workspace) in self.config.workspaces.iter_mut().enumerate() {
+                    ui.push_id(index, |ui| {
+                        ui.horizontal(|ui| {
+                            changed |= ui.text_edit_singleline(&mut workspace.name).changed();
+                            let add = ui.button(t("Add Current Folder"));
+                            if add.clicked() && !workspace.folders.contains(&current_path) {
+                                workspace.folders.push(current_path.clone());
+                                changed = true;
+                            }
+                            if accessible(ui.button("🗑"), &t("Delete Workspace")).clicked() {
+                                remove = Some(index);
+                            }
+                        });
+                        let folder_count = workspace.folders.len();
+                        workspace.folders.retain(|folder| {
+                            ui.horizontal(|ui| {
+                                ui.add_space(16.0);
+                                ui.label(folder.display().to_string());
+                                !accessible(ui.small_button("✖"), &t("Remove Folder")).clicked()
+                            })
+                            .inner
+                        });
+                        changed |= workspace.folders.len() != folder_count;
+                    });
+                    ui.separator();
+                }
+                if let Some(index) = remove {
+                    self.config.workspaces.remove(index);
+                    changed = true;
+                }
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.new_workspace_name);
+                    if ui.button(t("New Workspace")).clicked() && !self.new_workspace_name.is_empty() {
+                        self.config.workspaces.push(Workspace {
+                            name: std::mem::take(&mut self.new_workspace_name),
+                            folders: vec![current_path.clone()],
+                        });
+                        changed = true;
+                    }
+                });
+            });
+            if changed {
+                config::save_config(&self.config).unwrap();
+            }
+            if !open {
+                self.show_workspaces_dialog = false;
+            }
+        }
+
+        if self.show_profiling_panel {
+            profiling::draw_panel(ctx, &mut self.show_profiling_panel);
+        }
+
+        if self.show_about_dialog {
+            egui::Window::new(t("About")).collapsible(false).resizable(false).show(ctx, |ui| {
+                ui.label(t("File Manager v0.1.0"));
+                ui.label(t("A simple file manager built with Rust and egui."));
+                if ui.button(t("Close")).clicked() {
+                    self.show_about_dialog = false;
+                }
+            });
+        }
+
+        if let Some(share) = &self.lan_share {
+            let mut open = true;
+            let mut stop = false;
+            egui::Window::new(t("Share over LAN")).open(&mut open).resizable(false).show(ctx, |ui| {
+                ui.label(t("Open this address on a device on the same network:"));
+                ui.hyperlink(&share.url);
+                if let Some((width, dark)) = &share.qr_code {
+                    draw_qr_code(ui, *width, dark);
+                }
+                ui.label(tf("Sharing {count} item(s):", &[("count", &share.paths.len())]));
+                for path in share.paths.iter().take(10) {
+                    ui.weak(path.file_name().unwrap_or_default().to_string_lossy());
+                }
+                stop = ui.button(t("Stop Sharing")).clicked();
+            });
+            if !open || stop {
+                self.lan_share = None;
+            }
+        }
+
+        if self.show_disk_usage_dialog {
+            let mut open = true;
+            egui::Window::new(t("Disk Usage")).open(&mut open).default_width(400.0).show(ctx, |ui| {
+                match self.disk_usage.clone() {
+                    None => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(t("Analyzing..."));
+                        });
+                    }
+                    Some((root, usage)) => {
+                        ui.label(root.display().to_string());
+                        ui.separator();
+                        let largest = usage.first().map(|(_, size)| *size).unwrap_or(0).max(1);
+                        egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                            egui::Grid::new("disk_usage_grid").striped(true).show(ui, |ui| {
+                                for (path, size) in &usage {
+                                    let name = path.file_name().unwrap_or_default().to_string_lossy();
+                                    if ui.link(name).clicked() {
+                                        let target = if path.is_dir() { path.clone() } else { root.clone() };
+                                        self.navigate_to(&target);
+                                    }
+                                    ui.add(
+                                        egui::ProgressBar::new(*size as f32 / largest as f32)
+                                            .desired_width(150.0)
+                                            .text(human_bytes(*size as f64)),
+                                    );
+                                    if path.is_dir() && ui.small_button(t("Analyze")).clicked() {
+                                        self.analyze_disk_usage(path);
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                        });
+                    }
+                }
+            });
+            if !open {
+                self.show_disk_usage_dialog = false;
+                self.cancel_disk_usage();
+            }
+        }
+
+        if let Some(comparison) = &mut self.comparison {
+            if !comparison.show(ctx) {
+                self.comparison = None;
+            }
+        }
+
+        if let Some(browser) = &mut self.remote_browser {
+            let mut open = true;
+            let request = browser.show(ctx, &self.current_path, &mut open);
+            let remote = browser.remote.clone();
+            let remote_dir = browser.path.clone();
+            match request {
+                Some(BrowserRequest::List(path)) => self.send_event(FileSystemEvent::ListRemoteDirectory(remote, path)),
+                Some(BrowserRequest::Pull(paths)) => {
+                    self.status_message = tf("Copying {count} items from {remote}...", &[
+                        ("count", &paths.len()),
+                        ("remote", &remote.title()),
+                    ]);
+                    self.send_event(FileSystemEvent::RemotePull(remote, paths, self.current_path.clone()));
+                }
+                Some(BrowserRequest::PushSelection) => {
+                    let mut paths: Vec<PathBuf> = self.selected_items.iter().cloned().collect();
+                    paths.sort();
+                    if !paths.is_empty() {
+                        self.status_message = tf("Copying {count} items to {remote}...", &[
+                            ("count", &paths.len()),
+                            ("remote", &remote.title()),
+                        ]);
+                        self.send_event(FileSystemEvent::RemotePush(remote, paths, remote_dir));
+                    }
+                }
+                None => {}
+            }
+            if !open {
+                self.close_remote_browser();
+            }
+        }
+
+        if self.show_convert_text_dialog {
+            let mut open = true;
+            let title = t("Convert Encoding / Line Endings");
+            egui::Window::new(title).open(&mut open).default_width(450.0).show(ctx, |ui| {
+                let Some(files) = &self.convert_text_files else {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(t("Detecting encodings..."));
+                    });
+                    return;
+                };
+                egui::ScrollArea::vertical().max_height(250.0).show(ui, |ui| {
+                    egui::Grid::new("convert_text_grid").striped(true).show(ui, |ui| {
+                        for (path, info) in files {
+                            ui.label(path.file_name().unwrap_or_default().to_string_lossy());
+                            match info {
+                                Some(info) => ui.label(info.describe()),
+                                None => ui.weak(t("Not a text file")),
+                            };
+                            ui.end_row();
+                        }
+                    });
+                });
+                ui.separator();
+                egui::ComboBox::from_label(t("Encoding"))
+                    .selected_text(self.convert_encoding.label())
+                    .show_ui(ui, |ui| {
+                        for encoding in Encoding::ALL {
+                            ui.selectable_value(&mut self.convert_encoding, encoding, encoding.label());
+                        }
+                    });
+                egui::ComboBox::from_label(t("Line Endings"))
+                    .selected_text(self.convert_line_ending.map_or_else(|| t("Keep"), |ending| ending.label().into()))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.convert_line_ending, None, t("Keep"));
+                        for ending in LineEnding::ALL {
+                            ui.selectable_value(&mut self.convert_line_ending, Some(ending), ending.label());
+                        }
+                    });
+                let text_files: Vec<PathBuf> =
+                    files.iter().filter(|(_, info)| info.is_some()).map(|(path, _)| path.clone()).collect();
+                let label = tf("Convert {count} Files", &[("count", &text_files.len())]);
+                if ui.add_enabled(!text_files.is_empty(), egui::Button::new(label)).clicked() {
+                    self.status_message = tf("Converting {count} files...", &[("count", &text_files.len())]);
+                    let (encoding, line_ending) = (self.convert_encoding, self.convert_line_ending);
+                    self.send_event(FileSystemEvent::ConvertText(text_files, encoding, line_ending));
+                    self.show_convert_text_dialog = false;
+                }
+            });
+            self.show_convert_text_dialog &= open;
+        }
+
+        if self.show_largest_files_dialog {
+            let mut open = true;
+            egui::Window::new(t("Largest Files")).open(&mut open).default_width(500.0).show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(t("Show the"));
+                    ui.add(egui::DragValue::new(&mut self.largest_files_count).clamp_range(10..=1000));
+                    ui.label(t("largest files"));
+                    if ui.button(t("Refresh")).clicked() {
+                        self.find_largest_files();
+                    }
+                });
+                ui.separator();
+                match self.largest_files.clone() {
+                    None => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(t("Scanning..."));
+                        });
+                    }
+                    Some((root, files)) if files.is_empty() => {
+                        ui.label(tf("No files under {dir}", &[("dir", &root.display())]));
+                    }
+                    Some((root, files)) => {
+                        ui.label(root.display().to_string());
+                        egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                            egui::Grid::new("largest_files_grid").striped(true).show(ui, |ui| {
+                                for (path, size) in &files {
+                                    ui.label(human_bytes(*size as f64));
+                                    let relative = path.strip_prefix(&root).unwrap_or(path);
+                                    let link =
+                                        ui.link(relative.display().to_string()).on_hover_text(t("Show in folder"));
+                                    if link.clicked() {
+                                        if let Some(parent) = path.parent() {
+                                            self.navigate_to(parent);
+                                            self.selected_items.insert(path.clone());
+                                        }
+                                    }
+                                    if ui.small_button("🗑").on_hover_text(t("Move to Trash")).clicked() {
+                                        self.request_delete(vec![path.clone()], false);
+                                        // With confirmation on the file stays listed until a refresh.
+                                        if let (false, Some((_, files))) =
+                                            (self.show_delete_confirmation, &mut self.largest_files)
+                                        {
+                                            files.retain(|(file, _)| file != path);
+                                        }
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                        });
                     }
-                    if ui.button("Cancel").clicked() || ui.input(|i| i.key_pressed(Key::Escape)) {
-                        self.show_new_file_dialog = false;
-                        self.new_file_name.clear();
+                }
+            });
+            self.show_largest_files_dialog = open;
+        }
+
+        if self.show_sort_dialog {
+            let mut open = true;
+            egui::Window::new(t("Sort Folder")).open(&mut open).default_width(500.0).show(ctx, |ui| {
+                ui.label(self.sort_folder.display().to_string());
+                egui::CollapsingHeader::new(t("Rules")).show(ui, |ui| {
+                    ui.label(t("Each file goes where the first rule that fits it says."));
+                    if self.draw_sort_rules(ui) {
+                        config::save_config(&self.config).unwrap();
+                        self.plan_sort();
                     }
                 });
+                ui.separator();
+                match self.sort_plan.clone() {
+                    None => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(t("Looking at files..."));
+                        });
+                    }
+                    Some(Err(err)) => {
+                        ui.label(err);
+                    }
+                    Some(Ok(moves)) if moves.is_empty() => {
+                        ui.label(t("Nothing to sort."));
+                    }
+                    Some(Ok(moves)) => {
+                        egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                            egui::Grid::new("sort_plan_grid").striped(true).show(ui, |ui| {
+                                for planned in &moves {
+                                    ui.label(planned.from.file_name().unwrap_or_default().to_string_lossy().into_owned());
+                                    ui.label("→");
+                                    let to = planned.to.strip_prefix(&self.sort_folder).unwrap_or(&planned.to);
+                                    ui.label(to.display().to_string());
+                                    ui.end_row();
+                                }
+                            });
+                        });
+                        if ui.button(tf("Sort {count} Files", &[("count", &moves.len())])).clicked() {
+                            self.sort_plan = None;
+                            self.send_event(FileSystemEvent::ApplySort(self.sort_folder.clone(), moves));
+                        }
+                    }
+                }
+                if let Some(count) = self.last_sort.as_ref().map(|(_, outcome)| outcome.moved.len()) {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label(tf("Sorted {count} files.", &[("count", &count)]));
+                        if ui.button(t("Undo")).clicked() {
+                            self.undo_sort();
+                        }
+                    });
+                }
             });
+            self.show_sort_dialog = open;
         }
 
-        if self.show_new_folder_dialog {
-            egui::Window::new("Create New Folder").collapsible(false).resizable(false).show(ctx, |ui| {
+        if let Some(library) = self.music_folder.clone() {
+            let mut open = true;
+            egui::Window::new(t("Organize Music")).open(&mut open).default_width(500.0).show(ctx, |ui| {
+                ui.label(library.display().to_string());
                 ui.horizontal(|ui| {
-                    ui.label("Folder Name:");
-                    ui.text_edit_singleline(&mut self.new_folder_name);
+                    ui.label(t("Layout"));
+                    let layout = ui.text_edit_singleline(&mut self.config.music_layout);
+                    if layout.lost_focus() {
+                        config::save_config(&self.config).unwrap();
+                        self.plan_music();
+                    }
                 });
-                ui.horizontal(|ui| {
-                    if ui.button("Create").clicked() || ui.input(|i| i.key_pressed(Key::Enter)) {
-                        self.create_folder();
+                ui.weak(t("Fields: {artist}, {album}, {track}, {title} and {year}."));
+                ui.separator();
+                match self.music_plan.clone() {
+                    None => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(t("Reading tags..."));
+                        });
                     }
-                    if ui.button("Cancel").clicked() || ui.input(|i| i.key_pressed(Key::Escape)) {
-                        self.show_new_folder_dialog = false;
-                        self.new_folder_name.clear();
+                    Some(Err(err)) => {
+                        ui.label(err);
                     }
-                });
+                    Some(Ok(moves)) if moves.is_empty() => {
+                        ui.label(t("Every tagged track is already in place."));
+                    }
+                    Some(Ok(moves)) => {
+                        egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                            egui::Grid::new("music_plan_grid").striped(true).show(ui, |ui| {
+                                for planned in &moves {
+                                    let from = planned.from.strip_prefix(&library).unwrap_or(&planned.from);
+                                    ui.label(from.display().to_string());
+                                    ui.label("→");
+                                    let to = planned.to.strip_prefix(&library).unwrap_or(&planned.to);
+                                    ui.label(to.display().to_string());
+                                    ui.end_row();
+                                }
+                            });
+                        });
+                        if ui.button(tf("Organize {count} Tracks", &[("count", &moves.len())])).clicked() {
+                            self.music_plan = None;
+                            self.send_event(FileSystemEvent::ApplySort(library.clone(), moves));
+                        }
+                    }
+                }
+                let last_sort = self.last_sort.as_ref().filter(|(dir, _)| *dir == library);
+                if let Some(count) = last_sort.map(|(_, outcome)| outcome.moved.len()) {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label(tf("Moved {count} tracks.", &[("count", &count)]));
+                        if ui.button(t("Undo")).clicked() {
+                            self.undo_sort();
+                        }
+                    });
+                }
             });
+            if !open {
+                self.music_folder = None;
+            }
         }
 
-        if self.show_delete_confirmation {
-            egui::Window::new("Confirm Deletion").collapsible(false).resizable(false).show(ctx, |ui| {
-                ui.label("Are you sure you want to delete the selected item(s)?");
-                ui.horizontal(|ui| {
-                    if ui.button("Yes").clicked() {
-                        self.delete_item();
+        if let Some(wizard) = &mut self.photo_import {
+            let mut open = true;
+            let (mut next_step, mut event, mut open_folder, mut finished) = (None, None, None, false);
+            egui::Window::new(t("Import Photos")).open(&mut open).default_width(450.0).show(ctx, |ui| {
+                match &wizard.step {
+                    ImportStep::Choose => {
+                        egui::Grid::new("photo_import_grid").num_columns(2).show(ui, |ui| {
+                            ui.label(t("From:"));
+                            egui::ComboBox::from_id_source("photo_import_source")
+                                .selected_text(wizard.source.display().to_string())
+                                .show_ui(ui, |ui| {
+                                    for mount in self.mounts.iter().filter(|mount| mount.removable) {
+                                        let point = &mount.space.mount_point;
+                                        let label = format!("{} ({})", mount.name, point.display());
+                                        ui.selectable_value(&mut wizard.source, point.clone(), label);
+                                    }
+                                    let current = self.current_path.clone();
+                                    ui.selectable_value(&mut wizard.source, current, t("Current Folder"));
+                                });
+                            ui.end_row();
+                            ui.label(t("To:"));
+                            ui.text_edit_singleline(&mut wizard.destination);
+                            ui.end_row();
+                            ui.label(t("Folders:"));
+                            ui.add(TextEdit::singleline(&mut wizard.layout).hint_text("%Y/%Y-%m-%d"));
+                            ui.end_row();
+                        });
+                        let example = photos::folder_for(Local::now().naive_local(), &wizard.layout);
+                        match &example {
+                            Ok(folder) => ui.weak(tf("Today's photos would go in {folder}", &[("folder", folder)])),
+                            Err(err) => ui.colored_label(ui.visuals().error_fg_color, err),
+                        };
+                        let ready = example.is_ok() && !wizard.destination.trim().is_empty();
+                        if ui.add_enabled(ready, egui::Button::new(t("Next"))).clicked() {
+                            let destination = PathBuf::from(wizard.destination.trim());
+                            self.config.photo_import.destination = Some(destination.clone());
+                            self.config.photo_import.layout = wizard.layout.clone();
+                            config::save_config(&self.config).unwrap();
+                            event = Some(FileSystemEvent::PlanPhotoImport(
+                                wizard.source.clone(),
+                                destination,
+                                wizard.layout.clone(),
+                                wizard.cancel.clone(),
+                            ));
+                            next_step = Some(ImportStep::Working(0, 0));
+                        }
                     }
-                    if ui.button("No").clicked() {
-                        self.show_delete_confirmation = false;
-                        self.item_to_delete = None;
+                    ImportStep::Working(done, total) => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(tf("{done} of {total} photos", &[("done", done), ("total", total)]));
+                        });
+                        if *total > 0 {
+                            ui.add(egui::ProgressBar::new(*done as f32 / *total as f32));
+                        }
                     }
-                });
+                    ImportStep::Review(import) => {
+                        ui.label(tf("{count} new photo(s) to import.", &[("count", &import.photos.len())]));
+                        if import.already_imported > 0 {
+                            let count = import.already_imported;
+                            ui.weak(tf("{count} were imported before and will be skipped.", &[("count", &count)]));
+                        }
+                        if !import.unreadable.is_empty() {
+                            let count = import.unreadable.len();
+                            ui.colored_label(
+                                ui.visuals().warn_fg_color,
+                                tf("{count} could not be read and will be skipped:", &[("count", &count)]),
+                            );
+                            let scroll = egui::ScrollArea::vertical().id_source("photo_import_unreadable");
+                            scroll.max_height(100.0).show(ui, |ui| {
+                                for (path, err) in &import.unreadable {
+                                    ui.label(format!("{}: {}", path.display(), err));
+                                }
+                            });
+                        }
+                        egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                            egui::Grid::new("photo_import_plan_grid").striped(true).show(ui, |ui| {
+                                for photo in &import.photos {
+                                    let name = photo.source.file_name().unwrap_or_default();
+                                    ui.label(name.to_string_lossy().into_owned());
+                                    ui.label("→");
+                                    let target =
+                                        photo.target.strip_prefix(&import.destination).unwrap_or(&photo.target);
+                                    ui.label(target.display().to_string());
+                                    ui.end_row();
+                                }
+                            });
+                        });
+                        ui.horizontal(|ui| {
+                            if ui.button(t("Back")).clicked() {
+                                next_step = Some(ImportStep::Choose);
+                            }
+                            let label = tf("Import {count} Photos", &[("count", &import.photos.len())]);
+                            if ui.add_enabled(!import.photos.is_empty(), egui::Button::new(label)).clicked() {
+                                event = Some(FileSystemEvent::ImportPhotos(import.clone(), wizard.cancel.clone()));
+                                next_step = Some(ImportStep::Working(0, import.photos.len()));
+                            }
+                        });
+                    }
+                    ImportStep::Done(copied, failed) => {
+                        ui.label(tf("Imported {count} photo(s).", &[("count", copied)]));
+                        if !failed.is_empty() {
+                            ui.label(tf("{count} could not be copied:", &[("count", &failed.len())]));
+                            egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                                for (path, err) in failed {
+                                    ui.label(format!("{}: {}", path.display(), err));
+                                }
+                            });
+                        }
+                        ui.horizontal(|ui| {
+                            if ui.button(t("Open Destination")).clicked() {
+                                open_folder = Some(PathBuf::from(wizard.destination.trim()));
+                                finished = true;
+                            }
+                            finished |= ui.button(t("Close")).clicked();
+                        });
+                    }
+                }
             });
+            if let Some(step) = next_step {
+                wizard.step = step;
+            }
+            if !open || finished {
+                wizard.cancel.store(true, Ordering::Relaxed);
+                self.photo_import = None;
+            }
+            if let Some(event) = event {
+                self.send_event(event);
+            }
+            if let Some(folder) = open_folder {
+                self.navigate_to(&folder);
+            }
         }
 
-        if self.show_go_to_dialog {
-            egui::Window::new("Go To Path").collapsible(false).resizable(false).show(ctx, |ui| {
-                ui.text_edit_singleline(&mut self.go_to_path);
-                ui.horizontal(|ui| {
-                    if ui.button("Go").clicked() || ui.input(|i| i.key_pressed(Key::Enter)) {
-                        self.navigate_to(&PathBuf::from(&self.go_to_path));
-                        self.show_go_to_dialog = false;
+        if self.show_duplicates_dialog {
+            let mut open = true;
+            egui::Window::new(t("Duplicates")).open(&mut open).default_width(500.0).show(ctx, |ui| {
+                match self.duplicates.clone() {
+                    None => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(t("Comparing files..."));
+                        });
                     }
-                    if ui.button("Cancel").clicked() || ui.input(|i| i.key_pressed(Key::Escape)) {
-                        self.show_go_to_dialog = false;
+                    Some((root, groups)) if groups.is_empty() => {
+                        ui.label(tf("No duplicate files under {dir}", &[("dir", &root.display())]));
                     }
-                });
+                    Some((root, groups)) => {
+                        let wasted: u64 = groups.iter().map(|(size, paths)| size * (paths.len() as u64 - 1)).sum();
+                        ui.horizontal(|ui| {
+                            let saved = human_bytes(wasted as f64);
+                            let template = "{size} in {count} groups could be saved";
+                            ui.label(tf(template, &[("size", &saved), ("count", &groups.len())]));
+                            if duplicates::reflinks_supported() {
+                                let all = ui
+                                    .button(t("Deduplicate All via Reflink"))
+                                    .on_hover_text(t("Copies keep their names but share storage with the first file"));
+                                if all.clicked() {
+                                    let all = groups.iter().map(|(_, paths)| paths.clone()).collect();
+                                    self.deduplicate_with_reflinks(all);
+                                }
+                            }
+                        });
+                        ui.separator();
+                        egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                            for (index, (size, paths)) in groups.iter().enumerate() {
+                                ui.push_id(index, |ui| {
+                                    ui.horizontal(|ui| {
+                                        let size = human_bytes(*size as f64);
+                                        let template = "{count} copies of {size}";
+                                        ui.strong(tf(template, &[("count", &paths.len()), ("size", &size)]));
+                                        if duplicates::reflinks_supported() && ui.small_button(t("Reflink")).clicked() {
+                                            self.deduplicate_with_reflinks(vec![paths.clone()]);
+                                        }
+                                    });
+                                    for path in paths {
+                                        let relative = path.strip_prefix(&root).unwrap_or(path);
+                                        let link = ui.link(relative.display().to_string());
+                                        if link.on_hover_text(t("Show in folder")).clicked() {
+                                            if let Some(parent) = path.parent() {
+                                                self.navigate_to(parent);
+                                                self.selected_items.insert(path.clone());
+                                            }
+                                        }
+                                    }
+                                    ui.separator();
+                                });
+                            }
+                        });
+                    }
+                }
             });
+            self.show_duplicates_dialog = open;
         }
 
-        if self.show_properties_dialog {
-            if let Some(item) = &self.properties_item.clone() {
-                egui::Window::new("Properties").collapsible(false).resizable(false).show(ctx, |ui| {
-                    egui::Grid::new("properties_grid").show(ui, |ui| {
-                        ui.label("Name:");
-                        ui.label(item.path.file_name().unwrap().to_str().unwrap());
-                        ui.end_row();
-                        ui.label("Path:");
-                        ui.label(item.path.to_str().unwrap());
-                        ui.end_row();
-                        ui.label("Type:");
-                        ui.label(if item.is_dir { "Folder" } else { "File" });
-                        ui.end_row();
-                        if !item.is_dir {
-                            ui.label("Size:");
+        if self.show_old_downloads_dialog {
+            let mut open = true;
+            let mut trashed = Vec::new();
+            egui::Window::new(t("Old Downloads")).open(&mut open).default_width(500.0).show(ctx, |ui| {
+                let items = self.old_downloads.clone().unwrap_or_default();
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    egui::Grid::new("old_downloads_grid").striped(true).show(ui, |ui| {
+                        for item in &items {
+                            let icon_size = ui.text_style_height(&egui::TextStyle::Body);
+                            let icon = self.icons.texture(ui.ctx(), item, icon_size);
+                            let link = ui.horizontal(|ui| {
+                                ui.image(&icon, [icon_size, icon_size]);
+                                ui.link(item.display_name())
+                            });
+                            if link.inner.clicked() {
+                                if let Some(parent) = item.path.parent() {
+                                    self.navigate_to(parent);
+                                    self.selected_items.insert(item.path.clone());
+                                }
+                            }
                             ui.label(human_bytes(item.size as f64));
+                            ui.label(DateTime::<Local>::from(item.modified).format("%Y-%m-%d").to_string());
+                            if ui.small_button("🗑").on_hover_text(t("Move to Trash")).clicked() {
+                                trashed.push(item.path.clone());
+                            }
                             ui.end_row();
                         }
-                        ui.label("Modified:");
-                        let modified_time = DateTime::<Local>::from(item.modified).format("%Y-%m-%d %H:%M:%S").to_string();
-                        ui.label(modified_time);
-                        ui.end_row();
                     });
-                    if ui.button("Close").clicked() {
-                        self.show_properties_dialog = false;
-                        self.properties_item = None;
+                });
+                ui.separator();
+                if ui.button(t("Move All to Trash")).clicked() {
+                    trashed = items.iter().map(|item| item.path.clone()).collect();
+                }
+            });
+            for path in &trashed {
+                self.send_event(FileSystemEvent::TrashItem(path.clone()));
+            }
+            if let Some(old) = &mut self.old_downloads {
+                old.retain(|item| !trashed.contains(&item.path));
+            }
+            if self.old_downloads.as_ref().is_some_and(Vec::is_empty) {
+                self.old_downloads = None;
+                open = false;
+            }
+            self.show_old_downloads_dialog = open;
+        }
+
+        if self.show_settings_dialog {
+            egui::Window::new(t("Settings")).collapsible(false).resizable(false).show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.settings_tab, SettingsTab::General, t("General"));
+                    ui.selectable_value(&mut self.settings_tab, SettingsTab::Shortcuts, t("Keyboard Shortcuts"));
+                });
+                ui.separator();
+                match self.settings_tab {
+                    SettingsTab::General => self.draw_general_settings(ui),
+                    SettingsTab::Shortcuts => self.draw_shortcut_settings(ui),
+                }
+                ui.separator();
+                if ui.button(t("Close")).clicked() {
+                    self.show_settings_dialog = false;
+                }
+            });
+        }
+    }
+
+    fn draw_general_settings(&mut self, ui: &mut egui::Ui) {
+        let mut language_changed = false;
+        egui::ComboBox::from_label(t("Language")).selected_text(t(self.config.language.label())).show_ui(ui, |ui| {
+            for language in Language::ALL {
+                language_changed |=
+                    ui.selectable_value(&mut self.config.language, language, t(language.label())).changed();
+            }
+        });
+        if language_changed {
+            i18n::set_language(self.config.language);
+            config::save_config(&self.config).unwrap();
+        }
+        ui.checkbox(&mut self.show_hidden_files, t("Show Hidden Files"));
+        ui.separator();
+        ui.strong(t("Theme"));
+        let mut theme_changed = false;
+        egui::ComboBox::from_label(t("Appearance"))
+            .selected_text(t(self.config.theme.preference.label()))
+            .show_ui(ui, |ui| {
+                for preference in [
+                    ThemePreference::System,
+                    ThemePreference::Schedule,
+                    ThemePreference::Light,
+                    ThemePreference::Dark,
+                ] {
+                    theme_changed |= ui
+                        .selectable_value(&mut self.config.theme.preference, preference, t(preference.label()))
+                        .changed();
+                }
+            });
+        if self.config.theme.preference == ThemePreference::Schedule {
+            ui.horizontal(|ui| {
+                ui.label(t("Dark from"));
+                theme_changed |= ui
+                    .add(egui::DragValue::new(&mut self.config.theme.dark_from_hour).clamp_range(0..=23).suffix(":00"))
+                    .changed();
+                ui.label(t("Light from"));
+                theme_changed |= ui
+                    .add(egui::DragValue::new(&mut self.config.theme.light_from_hour).clamp_range(0..=23).suffix(":00"))
+                    .changed();
+            });
+        }
+        ui.horizontal(|ui| {
+            let mut custom_accent = self.config.theme.accent.is_some();
+            if ui.checkbox(&mut custom_accent, t("Accent color")).changed() {
+                let default = ui.visuals().selection.bg_fill;
+                self.config.theme.accent = custom_accent.then_some([default.r(), default.g(), default.b()]);
+                theme_changed = true;
+            }
+            if let Some(accent) = &mut self.config.theme.accent {
+                theme_changed |= ui.color_edit_button_srgb(accent).changed();
+            }
+        });
+        if theme_changed {
+            config::save_config(&self.config).unwrap();
+        }
+        ui.separator();
+        ui.strong(t("Size"));
+        // Saved once a drag or typed value is done, not on every step.
+        egui::Grid::new("size_grid").num_columns(2).show(ui, |ui| {
+            let percent = |value: f64| format!("{:.0}%", value * 100.0);
+            ui.label(t("Zoom"));
+            let zoom = egui::DragValue::new(&mut self.config.ui_scale)
+                .clamp_range(MIN_ZOOM..=MAX_ZOOM)
+                .speed(0.01)
+                .custom_formatter(|value, _| percent(value));
+            let zoom = ui.add(zoom);
+            if zoom.drag_released() || zoom.lost_focus() {
+                config::save_config(&self.config).unwrap();
+            }
+            ui.end_row();
+            ui.label(t("Text size"));
+            let text = egui::DragValue::new(&mut self.config.text_scale)
+                .clamp_range(0.75..=2.0)
+                .speed(0.01)
+                .custom_formatter(|value, _| percent(value));
+            let text = ui.add(text);
+            if text.drag_released() || text.lost_focus() {
+                config::save_config(&self.config).unwrap();
+            }
+            ui.end_row();
+        });
+        ui.separator();
+        ui.strong(t("Tooltips"));
+        let delay = egui::DragValue::new(&mut self.config.tooltip_delay_ms).clamp_range(0..=5000).suffix(" ms");
+        ui.horizontal(|ui| {
+            ui.label(t("Show file details after"));
+            if ui.add(delay).changed() {
+                config::save_config(&self.config).unwrap();
+            }
+        });
+        ui.separator();
+        ui.strong(t("Confirmations"));
+        let confirmations = &mut self.config.confirmations;
+        let mut confirmations_changed = false;
+        confirmations_changed |= ui.checkbox(&mut confirmations.trash, t("Confirm moving to trash")).changed();
+        confirmations_changed |=
+            ui.checkbox(&mut confirmations.permanent_delete, t("Confirm permanent deletion")).changed();
+        confirmations_changed |= ui.checkbox(&mut confirmations.overwrite, t("Confirm overwriting files")).changed();
+        confirmations_changed |=
+            ui.checkbox(&mut confirmations.drag_move, t("Confirm moving by drag and drop")).changed();
+        confirmations_changed |=
+            ui.checkbox(&mut confirmations.run_executable, t("Confirm running programs")).changed();
+        if confirmations_changed {
+            config::save_config(&self.config).unwrap();
+        }
+        ui.horizontal(|ui| {
+            ui.label(t("When pasting over an existing item:"));
+            let before = self.config.overwrite_backup;
+            egui::ComboBox::from_id_source("overwrite_backup")
+                .selected_text(t(before.label()))
+                .show_ui(ui, |ui| {
+                    for backup in OverwriteBackup::ALL {
+                        ui.selectable_value(&mut self.config.overwrite_backup, backup, t(backup.label()));
+                    }
+                });
+            if self.config.overwrite_backup != before {
+                self.send_event(FileSystemEvent::SetOverwriteBackup(self.config.overwrite_backup));
+                config::save_config(&self.config).unwrap();
+            }
+        });
+        ui.separator();
+        ui.strong(t("Downloads"));
+        let reminder = &mut self.config.downloads_reminder;
+        let mut rescan = ui.checkbox(&mut reminder.enabled, t("Remind me about old files in Downloads")).changed();
+        ui.add_enabled_ui(reminder.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(t("Older than"));
+                let days = egui::DragValue::new(&mut reminder.older_than_days).clamp_range(1..=3650);
+                // Rescan once the value is settled rather than on every step of a drag.
+                let response = ui.add(days.suffix(" days"));
+                rescan |= response.drag_released() || (response.changed() && !response.dragged());
+            });
+        });
+        if rescan {
+            self.config.downloads_reminder.snoozed_until = None;
+            config::save_config(&self.config).unwrap();
+            self.check_old_downloads();
+        }
+        ui.separator();
+        ui.strong(t("Privacy"));
+        ui.horizontal(|ui| {
+            if ui.checkbox(&mut self.config.track_usage, t("Remember which files I open")).changed() {
+                if self.config.track_usage {
+                    self.usage = UsageLog::load();
+                }
+                config::save_config(&self.config).unwrap();
+            }
+            if ui.button(t("Clear")).on_hover_text(t("Forget all opened files")).clicked() {
+                self.usage.clear();
+            }
+        });
+        ui.separator();
+        ui.strong(t("Battery"));
+        let battery = &mut self.config.battery;
+        let mut battery_changed = ui.checkbox(&mut battery.enabled, t("Ease off while on battery")).changed();
+        ui.add_enabled_ui(battery.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(t("Copies and moves at once"));
+                battery_changed |=
+                    ui.add(egui::DragValue::new(&mut battery.max_transfers).clamp_range(1..=8)).changed();
+            });
+            battery_changed |=
+                ui.checkbox(&mut battery.pause_prefetch, t("Pause preloading nearby folders")).changed();
+        });
+        if battery_changed {
+            self.send_event(FileSystemEvent::SetBatteryPolicy(self.config.battery));
+            config::save_config(&self.config).unwrap();
+        }
+        ui.separator();
+        ui.strong(t("Tray"));
+        if ui
+            .checkbox(&mut self.config.show_tray_icon, t("Show tray icon with favorites and recent folders"))
+            .changed()
+        {
+            config::save_config(&self.config).unwrap();
+        }
+        ui.separator();
+        ui.strong(t("Network"));
+        if ui
+            .checkbox(&mut self.config.lan_peers, t("Send and receive files with other copies on the network"))
+            .on_hover_text(t("Receiving always asks first"))
+            .changed()
+        {
+            config::save_config(&self.config).unwrap();
+        }
+        ui.separator();
+        ui.strong(t("Devices"));
+        let adb = ui
+            .checkbox(&mut self.config.adb_devices, t("Show Android devices connected over ADB"))
+            .on_hover_text(t("Starts the ADB server if it isn't running"));
+        if adb.changed() {
+            if !self.config.adb_devices {
+                self.adb_devices.clear();
+            }
+            config::save_config(&self.config).unwrap();
+        }
+        if ui.checkbox(&mut self.config.docker_containers, t("Show Docker containers and volumes")).changed() {
+            if !self.config.docker_containers {
+                self.containers.clear();
+                self.volumes.clear();
+            }
+            config::save_config(&self.config).unwrap();
+        }
+        ui.separator();
+        ui.strong(t("Background Daemon"));
+        if ui
+            .checkbox(&mut self.config.use_daemon, t("Run copies and moves in the background daemon"))
+            .on_hover_text(t("Jobs keep running after this window is closed"))
+            .changed()
+        {
+            config::save_config(&self.config).unwrap();
+        }
+        ui.separator();
+        ui.strong(t("Color Rules"));
+        if self.draw_color_rules(ui) {
+            config::save_config(&self.config).unwrap();
+        }
+        ui.separator();
+        ui.strong(t("Open Folder With"));
+        if self.draw_folder_apps(ui) {
+            config::save_config(&self.config).unwrap();
+        }
+        if cfg!(windows) {
+            ui.separator();
+            ui.strong(t("Shell Integration"));
+            let label = t("Show Explorer context menu entries (7-Zip, TortoiseGit, ...)");
+            if ui.checkbox(&mut self.config.show_shell_menu, label).changed() {
+                config::save_config(&self.config).unwrap();
+            }
+        }
+        ui.separator();
+        if ui.button(t("Reset Configuration")).clicked() {
+            self.config = AppConfig::default();
+            self.send_event(FileSystemEvent::SetBatteryPolicy(self.config.battery));
+            self.send_event(FileSystemEvent::SetOverwriteBackup(self.config.overwrite_backup));
+            config::save_config(&self.config).unwrap();
+        }
+    }
+
+    /// Lists every action's shortcut; clicking one waits for the new key combination.
+    fn draw_shortcut_settings(&mut self, ui: &mut egui::Ui) {
+        if let Some(action) = self.recording_shortcut {
+            let pressed = ui.input(|i| {
+                i.events.iter().find_map(|event| match event {
+                    egui::Event::Key { key, pressed: true, modifiers, .. } => Some(Shortcut::new(*key, *modifiers)),
+                    _ => None,
+                })
+            });
+            if let Some(shortcut) = pressed {
+                self.recording_shortcut = None;
+                if shortcut.key != Key::Escape {
+                    let result = self.config.keymap.set(action, shortcut);
+                    self.shortcut_changed(action, shortcut, result);
+                }
+            }
+        }
+        let mut profile_changed = false;
+        egui::ComboBox::from_label(t("Profile")).selected_text(t(self.config.key_profile.label())).show_ui(ui, |ui| {
+            for profile in [Profile::Standard, Profile::Vim] {
+                profile_changed |=
+                    ui.selectable_value(&mut self.config.key_profile, profile, t(profile.label())).changed();
+            }
+        });
+        if profile_changed {
+            self.vim_keys.cancel();
+            config::save_config(&self.config).unwrap();
+        }
+        if self.config.key_profile == Profile::Vim {
+            ui.label(t("While no text field has focus, these keys work alongside the shortcuts below:"));
+            egui::Grid::new("vim_keys_grid").num_columns(2).show(ui, |ui| {
+                for (keys, description) in VimKeys::HELP {
+                    ui.monospace(keys);
+                    ui.label(t(description));
+                    ui.end_row();
+                }
+            });
+            ui.separator();
+        }
+        egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+            egui::Grid::new("shortcuts_grid").num_columns(3).striped(true).show(ui, |ui| {
+                for action in Action::ALL {
+                    ui.label(t(action.label()));
+                    let recording = self.recording_shortcut == Some(action);
+                    let label = match recording {
+                        true => t("Press a key…"),
+                        false => self.config.keymap.shortcut(action).label(),
+                    };
+                    let response = ui.selectable_label(recording, label);
+                    if response.on_hover_text(t("Click, then press the new shortcut")).clicked() {
+                        self.recording_shortcut = Some(action);
+                        self.shortcut_conflict = None;
+                    }
+                    if !self.config.keymap.is_default(action) && ui.small_button(t("Reset")).clicked() {
+                        let result = self.config.keymap.reset(action);
+                        self.shortcut_changed(action, action.default_shortcut(), result);
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+        if let Some(conflict) = &self.shortcut_conflict {
+            ui.colored_label(ui.visuals().warn_fg_color, conflict);
+        }
+        if ui.button(t("Reset All Shortcuts")).clicked() {
+            self.config.keymap.reset_all();
+            self.shortcut_conflict = None;
+            config::save_config(&self.config).unwrap();
+        }
+    }
+
+    /// Saves a rebinding, or explains which action already has the shortcut.
+    fn shortcut_changed(&mut self, action: Action, shortcut: Shortcut, result: Result<(), Action>) {
+        match result {
+            Ok(()) => {
+                self.shortcut_conflict = None;
+                config::save_config(&self.config).unwrap();
+            }
+            Err(other) => {
+                self.shortcut_conflict = Some(tf(
+                    "{shortcut} is already used by {other}; change that first to give it to {action}.",
+                    &[("shortcut", &shortcut.label()), ("other", &t(other.label())), ("action", &t(action.label()))],
+                ));
+            }
+        }
+    }
+
+    /// Lists the apps suggested for this kind of folder first; returns the one clicked.
+    fn draw_open_with_menu(&mut self, ui: &mut egui::Ui, folder: &Path) -> Option<FolderApp> {
+        let kinds = FolderKind::detect(folder);
+        let installed = self.installed_folder_apps.get_or_insert_with(open_with::installed_folder_apps);
+        let (suggested, others): (Vec<&FolderApp>, Vec<&FolderApp>) =
+            self.config.folder_apps.0.iter().partition(|app| app.applies_to(&kinds));
+
+        let mut chosen = None;
+        for app in &suggested {
+            if ui.button(app.name.as_str()).clicked() {
+                chosen = Some((*app).clone());
+            }
+        }
+        let others: Vec<&FolderApp> = others
+            .into_iter()
+            .chain(installed.iter().filter(|app| !suggested.iter().any(|s| s.name == app.name)))
+            .collect();
+        if !others.is_empty() {
+            ui.menu_button(t("Other Applications"), |ui| {
+                for app in others {
+                    if ui.button(app.name.as_str()).clicked() {
+                        chosen = Some(app.clone());
+                    }
+                }
+            });
+        }
+        chosen
+    }
+
+    fn draw_folder_apps(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        let mut remove = None;
+        egui::Grid::new("folder_apps_grid").show(ui, |ui| {
+            for (index, app) in self.config.folder_apps.0.iter_mut().enumerate() {
+                let name = TextEdit::singleline(&mut app.name).hint_text(t("Name")).desired_width(120.0);
+                changed |= ui.add(name).changed();
+                let command = TextEdit::singleline(&mut app.command).hint_text(t("Command")).desired_width(120.0);
+                changed |= ui.add(command).changed();
+                ui.menu_button(t("Folder Types"), |ui| {
+                    for kind in FolderKind::ALL {
+                        let mut enabled = app.kinds.contains(&kind);
+                        if ui.checkbox(&mut enabled, t(kind.label())).changed() {
+                            if enabled {
+                                app.kinds.push(kind);
+                            } else {
+                                app.kinds.retain(|k| *k != kind);
+                            }
+                            changed = true;
+                        }
                     }
+                    ui.weak(t("None checked: suggested for every folder"));
                 });
+                if accessible(ui.button("🗑"), &t("Remove Application")).clicked() {
+                    remove = Some(index);
+                }
+                ui.end_row();
             }
+        });
+        if let Some(index) = remove {
+            self.config.folder_apps.0.remove(index);
+            changed = true;
+        }
+        if ui.button(t("Add Application")).clicked() {
+            self.config.folder_apps.0.push(FolderApp {
+                name: String::new(),
+                command: String::new(),
+                kinds: Vec::new(),
+            });
+            changed = true;
         }
+        changed
+    }
 
-        if self.show_about_dialog {
-            egui::Window::new("About").collapsible(false).resizable(false).show(ctx, |ui| {
-                ui.label("File Manager v0.1.0");
-                ui.label("A simple file manager built with Rust and egui.");
-                if ui.button("Close").clicked() {
-                    self.show_about_dialog = false;
+    fn draw_color_rules(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        let mut remove = None;
+        egui::Grid::new("color_rules_grid").show(ui, |ui| {
+            for (index, rule) in self.config.color_rules.0.iter_mut().enumerate() {
+                changed |= ui.checkbox(&mut rule.enabled, "").changed();
+                egui::ComboBox::from_id_source(("color_rule_condition", index))
+                    .selected_text(t(rule.condition.label()))
+                    .show_ui(ui, |ui| {
+                        let options = [
+                            ColorCondition::Extensions(String::new()),
+                            ColorCondition::Executable,
+                            ColorCondition::OlderThanDays(365),
+                            ColorCondition::Directory,
+                            ColorCondition::Hidden,
+                        ];
+                        for option in options {
+                            let label = option.label();
+                            let selected = rule.condition.label() == label;
+                            if ui.selectable_label(selected, t(label)).clicked() && !selected {
+                                rule.condition = option;
+                                changed = true;
+                            }
+                        }
+                    });
+                match &mut rule.condition {
+                    ColorCondition::Extensions(extensions) => {
+                        changed |= ui.add(TextEdit::singleline(extensions).hint_text("zip, tar, gz")).changed();
+                    }
+                    ColorCondition::OlderThanDays(days) => {
+                        changed |= ui.add(egui::DragValue::new(days).clamp_range(1..=36500)).changed();
+                    }
+                    _ => {
+                        ui.label("");
+                    }
+                }
+                changed |= ui.color_edit_button_srgb(&mut rule.color).changed();
+                if accessible(ui.button("🗑"), &t("Delete Rule")).clicked() {
+                    remove = Some(index);
                 }
+                ui.end_row();
+            }
+        });
+        if let Some(index) = remove {
+            self.config.color_rules.0.remove(index);
+            changed = true;
+        }
+        if ui.button(t("Add Rule")).clicked() {
+            self.config.color_rules.0.push(ColorRule {
+                enabled: true,
+                condition: ColorCondition::Extensions(String::new()),
+                color: [255, 255, 255],
             });
+            changed = true;
         }
+        changed
+    }
 
-        if self.show_settings_dialog {
-            egui::Window::new("Settings").collapsible(false).resizable(false).show(ctx, |ui| {
-                ui.checkbox(&mut self.show_hidden_files, "Show Hidden Files");
-                if ui.button("Reset Configuration").clicked() {
-                    self.config = AppConfig::default();
-                    config::save_config(&self.config).unwrap();
-                }
-                if ui.button("Close").clicked() {
-                    self.show_settings_dialog = false;
+    fn draw_sort_rules(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        let mut remove = None;
+        egui::Grid::new("sort_rules_grid").show(ui, |ui| {
+            for (index, rule) in self.config.sort_rules.0.iter_mut().enumerate() {
+                changed |= ui.checkbox(&mut rule.enabled, "").changed();
+                egui::ComboBox::from_id_source(("sort_rule_target", index))
+                    .selected_text(t(rule.target.label()))
+                    .show_ui(ui, |ui| {
+                        let options = [
+                            SortTarget::Date("%Y-%m".to_string()),
+                            SortTarget::Extension,
+                            SortTarget::Pattern(String::new(), String::new()),
+                        ];
+                        for option in options {
+                            let label = option.label();
+                            let selected = rule.target.label() == label;
+                            if ui.selectable_label(selected, t(label)).clicked() && !selected {
+                                rule.target = option;
+                                changed = true;
+                            }
+                        }
+                    });
+                let valid = rule.target.is_valid();
+                ui.horizontal(|ui| {
+                    match &mut rule.target {
+                        SortTarget::Date(format) => {
+                            changed |= ui.add(TextEdit::singleline(format).hint_text("%Y-%m")).lost_focus();
+                        }
+                        SortTarget::Extension => {}
+                        SortTarget::Pattern(pattern, folder) => {
+                            let pattern = TextEdit::singleline(pattern).hint_text(r"^IMG_(\d{4})").desired_width(120.0);
+                            changed |= ui.add(pattern).lost_focus();
+                            ui.label("→");
+                            let folder = TextEdit::singleline(folder).hint_text("Photos/$1").desired_width(100.0);
+                            changed |= ui.add(folder).lost_focus();
+                        }
+                    }
+                    if !valid {
+                        let error = ui.visuals().error_fg_color;
+                        ui.colored_label(error, "⚠").on_hover_text(t("This rule can't be used"));
+                    }
+                });
+                if accessible(ui.button("🗑"), &t("Delete Rule")).clicked() {
+                    remove = Some(index);
                 }
+                ui.end_row();
+            }
+        });
+        if let Some(index) = remove {
+            self.config.sort_rules.0.remove(index);
+            changed = true;
+        }
+        if ui.button(t("Add Rule")).clicked() {
+            self.config.sort_rules.0.push(SortRule {
+                enabled: true,
+                target: SortTarget::Pattern(String::new(), String::new()),
             });
+            changed = true;
         }
+        changed
     }
 
     fn draw_context_menu(&mut self, ctx: &egui::Context) {
@@ -794,52 +5590,157 @@ impl FileManager {
                     if let Some(item) = &self.context_menu_item.clone() {
                         ui.label(item.path.file_name().unwrap().to_str().unwrap());
                         ui.separator();
-                        if ui.button("Open").clicked() {
+                        if ui.button(t("Open")).clicked() {
                             self.open_item(&item.path);
                             self.context_menu_pos = None;
                         }
-                        if ui.button("Rename").clicked() {
+                        if item.is_dir && ui.button(t("Open in New Tab")).clicked() {
+                            self.open_tab(&item.path, false);
+                            self.context_menu_pos = None;
+                        }
+                        if item.is_dir && ui.button(t("Open in New Window")).clicked() {
+                            self.open_in_new_window(&item.path);
+                            self.context_menu_pos = None;
+                        }
+                        if item.is_dir {
+                            ui.menu_button(t("Open Folder With…"), |ui| {
+                                if let Some(app) = self.draw_open_with_menu(ui, &item.path) {
+                                    self.send_event(FileSystemEvent::OpenWith(app.command, item.path.clone()));
+                                    self.context_menu_pos = None;
+                                }
+                            });
+                        }
+                        if item.is_executable {
+                            if ui.button(t("Run")).clicked() {
+                                self.request_run(item.path.clone(), false);
+                                self.context_menu_pos = None;
+                            }
+                            if ui.button(t("Run in Terminal")).clicked() {
+                                self.request_run(item.path.clone(), true);
+                                self.context_menu_pos = None;
+                            }
+                        }
+                        if !item.is_dir {
+                            let starred = self.config.starred.contains(&item.path);
+                            if ui.button(if starred { t("Unstar") } else { t("Star") }).clicked() {
+                                self.toggle_starred(&item.path);
+                                self.context_menu_pos = None;
+                            }
+                        }
+                        if self.selected_items.contains(&item.path) {
+                            if let Some((first, second)) = self.selected_image_pair() {
+                                if ui.button(t("Compare Images")).clicked() {
+                                    self.compare_images(first, second);
+                                    self.context_menu_pos = None;
+                                }
+                            }
+                        }
+                        if self.basket.contains(&item.path) {
+                            if ui.button(t("Remove from Basket")).clicked() {
+                                self.remove_from_basket(&item.path);
+                                self.context_menu_pos = None;
+                            }
+                        } else if ui.button(t("Add to Basket")).clicked() {
+                            self.basket.push(item.path.clone());
+                            self.context_menu_pos = None;
+                        }
+                        if ui.button(t("Compress…")).clicked() {
+                            self.compress_items(&item.path);
+                            self.context_menu_pos = None;
+                        }
+                        if ui.button(t("Share over LAN…")).clicked() {
+                            self.share_over_lan(self.selection_or(&item.path));
+                            self.context_menu_pos = None;
+                        }
+                        let in_cloud = item.is_placeholder || file_system::sync_provider(&item.path).is_some();
+                        if in_cloud && cloud::is_supported() {
+                            for (label, keep) in [(t("Keep on This Device"), true), (t("Free Up Space"), false)] {
+                                if ui.button(label).clicked() {
+                                    let paths = self.selection_or(&item.path);
+                                    self.send_event(FileSystemEvent::SetKeptOnDevice(paths, keep));
+                                    self.context_menu_pos = None;
+                                }
+                            }
+                        }
+                        if ui.button(t("Send by Email")).clicked() {
+                            self.send_by_email(&item.path);
+                            self.context_menu_pos = None;
+                        }
+                        for peer in self.peers.as_ref().map(Peers::peers).unwrap_or_default() {
+                            if ui.button(tf("Send to {peer}", &[("peer", &peer.name)])).clicked() {
+                                self.send_to_peer(peer, &item.path);
+                                self.context_menu_pos = None;
+                            }
+                        }
+                        if ui.button(t("Rename")).clicked() {
                             self.renaming_item = Some(item.path.clone());
                             self.renaming_text =
                                 item.path.file_name().unwrap().to_str().unwrap().to_string();
                             self.context_menu_pos = None;
                         }
-                        if ui.button("Delete").clicked() {
-                            self.item_to_delete = Some(item.path.clone());
-                            self.show_delete_confirmation = true;
+                        if ui.button(t("Move to Trash")).clicked() {
+                            let paths = self.selection_or(&item.path);
+                            self.request_delete(paths, false);
                             self.context_menu_pos = None;
                         }
-                        if ui.button("Properties").clicked() {
-                            self.properties_item = Some(item.clone());
-                            self.show_properties_dialog = true;
+                        if ui.button(t("Delete Permanently")).clicked() {
+                            let paths = self.selection_or(&item.path);
+                            self.request_delete(paths, true);
+                            self.context_menu_pos = None;
+                        }
+                        if ui.button(t("Properties")).clicked() {
+                            self.show_properties(item.clone());
                             self.context_menu_pos = None;
                         }
                         ui.separator();
-                        if ui.button("Copy Path").clicked() {
+                        if ui.button(t("Copy Path")).clicked() {
                             ctx.output_mut(|o| o.copied_text = item.path.to_str().unwrap().to_string());
                             self.context_menu_pos = None;
                         }
-                        if ui.button("Open in Terminal").clicked() {
+                        if ui.button(t("Open in Terminal")).clicked() {
                             self.open_in_terminal(&item.path);
                             self.context_menu_pos = None;
                         }
+                        if cfg!(windows) && self.config.show_shell_menu {
+                            if self.shell_menu.as_ref().is_none_or(|(path, _)| *path != item.path) {
+                                self.shell_menu = Some((item.path.clone(), shell_menu::entries(&item.path)));
+                            }
+                            let entries =
+                                self.shell_menu.as_ref().map(|(_, entries)| entries.clone()).unwrap_or_default();
+                            if !entries.is_empty() {
+                                ui.separator();
+                                ui.menu_button(t("More Options"), |ui| {
+                                    if let Some(id) = draw_shell_menu(ui, &entries) {
+                                        shell_menu::invoke(&item.path, id);
+                                        self.context_menu_pos = None;
+                                    }
+                                });
+                            }
+                        }
                     } else {
-                        if ui.button("New File").clicked() {
+                        if ui.button(t("New File")).clicked() {
                             self.show_new_file_dialog = true;
                             self.context_menu_pos = None;
                         }
-                        if ui.button("New Folder").clicked() {
+                        if ui.button(t("New Folder")).clicked() {
                             self.show_new_folder_dialog = true;
                             self.context_menu_pos = None;
                         }
+                        if ui.button(t("New Link from Clipboard")).clicked() {
+                            self.create_link_from_clipboard();
+                            self.context_menu_pos = None;
+                        }
+                        if self.draw_template_menu(ui) {
+                            self.context_menu_pos = None;
+                        }
                         ui.separator();
-                        if ui.button("Paste").clicked() {
+                        if ui.button(t("Paste")).clicked() {
                             self.paste();
                             self.context_menu_pos = None;
                         }
                         ui.separator();
                         let current_path = self.current_path.clone();
-                        if ui.button("Open in Terminal").clicked() {
+                        if ui.button(t("Open in Terminal")).clicked() {
                             self.open_in_terminal(&current_path);
                             self.context_menu_pos = None;
                         }
@@ -855,21 +5756,553 @@ impl FileManager {
 
 impl eframe::App for FileManager {
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
-        if let Ok(items) = self.rx.try_recv() {
-            self.items = items;
-            self.status_message = format!("Listed {} items", self.items.len());
+        if self.worker_stopped {
+            self.status_message = t("Background worker stopped; restart to keep browsing");
+        }
+        loop {
+            let response = match self.rx.try_recv() {
+                Ok(response) => response,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.worker_stopped = true;
+                    break;
+                }
+            };
+            match response {
+                FileSystemResponse::Listing(path, items) => {
+                    // Ignore late listings for folders we've already navigated away from.
+                    if path != self.current_path {
+                        continue;
+                    }
+                    self.items = items;
+                    self.status_message = tf("Listed {count} items", &[("count", &self.items.len())]);
+                    if self.items.iter().any(|item| checksums::is_manifest(&item.name)) {
+                        self.send_event(FileSystemEvent::ReadChecksums(path));
+                    } else {
+                        self.checksums.clear();
+                    }
+                }
+                FileSystemResponse::ListingChanged(path, delta) => {
+                    // Keep the folder tree's copy of this folder in step, dropping what it knew
+                    // about subfolders that are gone.
+                    if !self.children_loading.contains(&path) && self.child_listings.contains_key(&path) {
+                        for removed in &delta.removed {
+                            self.child_listings.retain(|listed, _| !listed.starts_with(removed));
+                        }
+                        if let Some(children) = self.child_listings.get_mut(&path) {
+                            delta.clone().apply(children);
+                        }
+                    }
+                    if path == self.current_path && !self.showing_search_results {
+                        delta.apply(&mut self.items);
+                    }
+                }
+                FileSystemResponse::Children(path, items) => {
+                    self.children_loading.remove(&path);
+                    self.child_listings.insert(path, items);
+                }
+                FileSystemResponse::ChildCount(path, count) => {
+                    self.child_counts.insert(path, Some(count));
+                }
+                FileSystemResponse::DiskSpace(space) => {
+                    self.disk_space = Some(space);
+                }
+                FileSystemResponse::Mounts(mounts) => {
+                    self.mounts_query_pending = false;
+                    // What was listed last stays shown while a share holds things up.
+                    if let Some(mounts) = mounts {
+                        self.mounts = mounts;
+                    }
+                }
+                FileSystemResponse::AdbDevices(devices) => {
+                    self.adb_query_pending = false;
+                    // Devices found just before the setting was turned off aren't shown.
+                    if self.config.adb_devices {
+                        self.adb_devices = devices;
+                    }
+                }
+                FileSystemResponse::Containers(containers, volumes) => {
+                    self.containers_query_pending = false;
+                    if self.config.docker_containers {
+                        self.containers = containers;
+                        self.volumes = volumes;
+                    }
+                }
+                FileSystemResponse::VolumeOpened(volume, helper) => match helper {
+                    Ok(helper) => {
+                        self.status_message = tf("Mounted volume {volume}", &[("volume", &volume)]);
+                        self.browse_remote(Remote::Container(helper));
+                    }
+                    Err(err) => self.status_message = tf("Could not mount volume {volume}: {error}", &[
+                        ("volume", &volume),
+                        ("error", &err),
+                    ]),
+                },
+                FileSystemResponse::RemoteListing(remote, path, entries) => {
+                    if let Some(browser) = &mut self.remote_browser {
+                        if browser.remote == remote {
+                            browser.listed(&path, entries);
+                        }
+                    }
+                }
+                FileSystemResponse::RemoteTransferred(result) => {
+                    self.status_message = match result {
+                        Ok(()) => t("Copied"),
+                        Err(err) => tf("Copy failed: {error}", &[("error", &err)]),
+                    };
+                    // Pushed files should show up on the remote side too.
+                    if let Some(browser) = &self.remote_browser {
+                        let (remote, path) = (browser.remote.clone(), browser.path.clone());
+                        self.send_event(FileSystemEvent::ListRemoteDirectory(remote, path));
+                    }
+                }
+                FileSystemResponse::DiskUsage(path, usage) => {
+                    // A walk that finished just as it was cancelled or replaced is left unshown.
+                    if self.disk_usage_pending.as_ref().is_some_and(|(pending, _)| *pending == path) {
+                        self.disk_usage_pending = None;
+                        self.disk_usage = Some((path, usage));
+                    }
+                }
+                FileSystemResponse::Compressed(archive, result) => {
+                    let name = archive.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                    self.status_message = match result {
+                        Ok(files) => {
+                            let first = files.first().and_then(|file| file.file_name()).unwrap_or_default();
+                            match files.len() {
+                                1 => tf("Created {name}", &[("name", &first.to_string_lossy())]),
+                                parts => tf("Created {count} parts, from {name}", &[
+                                    ("count", &parts),
+                                    ("name", &first.to_string_lossy()),
+                                ]),
+                            }
+                        }
+                        Err(err) => tf("Could not create {name}: {error}", &[("name", &name), ("error", &err)]),
+                    };
+                }
+                FileSystemResponse::DownloadProgress(id, received, total) => {
+                    if self.downloads.last().is_none_or(|job| job.id != id) {
+                        continue;
+                    }
+                    self.status_message = match total {
+                        Some(total) => {
+                            self.file_op_progress = received as f32 / total.max(1) as f32;
+                            let (received, total) = (human_bytes(received as f64), human_bytes(total as f64));
+                            tf("Downloading {received} of {total}", &[("received", &received), ("total", &total)])
+                        }
+                        None => tf("Downloading {received}", &[("received", &human_bytes(received as f64))]),
+                    };
+                }
+                FileSystemResponse::DownloadEnded(id) => self.downloads.retain(|job| job.id != id),
+                FileSystemResponse::Downloaded(path, result) => {
+                    self.file_op_progress = 0.0;
+                    let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                    self.status_message = match result {
+                        Ok(()) => tf("Downloaded {name}", &[("name", &name)]),
+                        Err(err) => {
+                            let template = "Could not download {name}: {error} (paste the URL again to resume)";
+                            tf(template, &[("name", &name), ("error", &err)])
+                        }
+                    };
+                }
+                FileSystemResponse::ExtractionConflicts(archive) => {
+                    self.file_op_progress = 0.0;
+                    if self.config.confirmations.overwrite {
+                        self.status_message = tf("{count} files in the archive already exist", &[
+                            ("count", &archive.conflicts.len()),
+                        ]);
+                        // A newer archive takes over from one still being asked about.
+                        if let Some((previous, _)) = self.extraction.replace((archive, HashMap::new())) {
+                            if matches!(self.pending_conflict, Some(Conflict::Extract(_))) {
+                                self.pending_conflict = None;
+                            }
+                            self.send_event(FileSystemEvent::DiscardArchive(previous));
+                        }
+                        self.continue_extraction();
+                    } else {
+                        self.send_event(FileSystemEvent::UnpackArchive(archive, HashMap::new()));
+                    }
+                }
+                FileSystemResponse::Extracted(dir, result) => {
+                    self.file_op_progress = 0.0;
+                    self.status_message = match result {
+                        Ok(()) => tf("Extracted into {folder}", &[("folder", &dir.display())]),
+                        Err(err) => tf("Could not download the archive: {error}", &[("error", &err)]),
+                    };
+                }
+                FileSystemResponse::PeerSendProgress(name, sent, total) => {
+                    self.file_op_progress = sent as f32 / total.max(1) as f32;
+                    let (sent, total) = (human_bytes(sent as f64), human_bytes(total as f64));
+                    let template = "Sending to {name}: {sent} of {total}";
+                    self.status_message = tf(template, &[("name", &name), ("sent", &sent), ("total", &total)]);
+                }
+                FileSystemResponse::SentToPeer(name, result) => {
+                    self.file_op_progress = 0.0;
+                    self.status_message = match result {
+                        Ok(()) => tf("Sent to {name}", &[("name", &name)]),
+                        Err(err) => tf("Could not send to {name}: {error}", &[("name", &name), ("error", &err)]),
+                    };
+                }
+                FileSystemResponse::ProjectCreated(name, result) => match result {
+                    Ok(path) => {
+                        self.status_message = tf("Created {name}", &[("name", &name)]);
+                        self.selected_items = HashSet::from([path.clone()]);
+                        self.focused_item = Some(path);
+                        self.scroll_to_focused = true;
+                    }
+                    Err(err) => self.status_message = tf("Could not create {name}: {error}", &[
+                        ("name", &name),
+                        ("error", &err),
+                    ]),
+                },
+                FileSystemResponse::SortPlanned(dir, plan, generation) => {
+                    if dir == self.sort_folder && generation == self.sort_generation {
+                        self.sort_plan = Some(plan);
+                    }
+                }
+                FileSystemResponse::Sorted(dir, outcome) => {
+                    let folders: HashSet<_> = outcome.moved.iter().filter_map(|planned| planned.to.parent()).collect();
+                    self.status_message = match outcome.failed.first() {
+                        None => tf("Sorted {count} files into {folders} folders", &[
+                            ("count", &outcome.moved.len()),
+                            ("folders", &folders.len()),
+                        ]),
+                        Some((path, err)) => {
+                            let template = "Sorted {count} files; {failed} could not be moved, like {path}: {error}";
+                            tf(template, &[
+                                ("count", &outcome.moved.len()),
+                                ("failed", &outcome.failed.len()),
+                                ("path", &path.display()),
+                                ("error", err),
+                            ])
+                        }
+                    };
+                    if dir == self.sort_folder {
+                        self.plan_sort();
+                    }
+                    if self.music_folder.as_ref() == Some(&dir) {
+                        self.plan_music();
+                    }
+                    self.last_sort = Some((dir, outcome));
+                }
+                FileSystemResponse::SortUndone(failed) => {
+                    self.status_message = match failed.first() {
+                        None => t("Moved the sorted files back"),
+                        Some((path, err)) => tf("Could not move {count} files back, like {path}: {error}", &[
+                            ("count", &failed.len()),
+                            ("path", &path.display()),
+                            ("error", err),
+                        ]),
+                    };
+                    if self.show_sort_dialog {
+                        self.plan_sort();
+                    }
+                    self.plan_music();
+                }
+                FileSystemResponse::ChecksumsRead(dir, references) => {
+                    if dir != self.current_path {
+                        continue;
+                    }
+                    // A refresh keeps what was already checked.
+                    let mut previous = std::mem::take(&mut self.checksums);
+                    for reference in references {
+                        let verification = match previous.remove(&reference.file) {
+                            Some((old, verification)) if old == reference => verification,
+                            _ => Verification::Unchecked,
+                        };
+                        self.checksums.entry(reference.file.clone()).or_insert((reference, verification));
+                    }
+                }
+                FileSystemResponse::ChecksumVerified(file, verification) => {
+                    if let Some((_, current)) = self.checksums.get_mut(&file) {
+                        *current = verification;
+                    }
+                }
+                FileSystemResponse::MusicPlanned(library, plan) => {
+                    if self.music_folder.as_ref() == Some(&library) {
+                        self.music_plan = Some(plan);
+                    }
+                }
+                FileSystemResponse::PhotoImportProgress(done, total) => {
+                    if let Some(wizard) = self.waiting_photo_import() {
+                        wizard.step = ImportStep::Working(done, total);
+                    }
+                }
+                FileSystemResponse::PhotoImportPlanned(result) => match result {
+                    Ok(import) => {
+                        if let Some(wizard) = self.waiting_photo_import() {
+                            wizard.step = ImportStep::Review(import);
+                        }
+                    }
+                    Err(err) => {
+                        // A closed window already cancelled it.
+                        if let Some(wizard) = self.waiting_photo_import() {
+                            wizard.step = ImportStep::Choose;
+                            self.status_message = tf("Could not look for photos: {error}", &[("error", &err)]);
+                        }
+                    }
+                },
+                FileSystemResponse::PhotosImported(destination, copied, failed) => {
+                    self.status_message = match failed.len() {
+                        0 => tf("Imported {count} photos into {folder}", &[
+                            ("count", &copied),
+                            ("folder", &destination.display()),
+                        ]),
+                        count => tf("Imported {count} photos into {folder}; {failed} failed", &[
+                            ("count", &copied),
+                            ("folder", &destination.display()),
+                            ("failed", &count),
+                        ]),
+                    };
+                    if let Some(wizard) = self.waiting_photo_import() {
+                        wizard.step = ImportStep::Done(copied, failed);
+                    }
+                }
+                FileSystemResponse::KeptOnDeviceChanged(keep, failed) => {
+                    self.status_message = match failed.first() {
+                        None if keep => t("Downloading to keep on this device"),
+                        None => t("Freeing up space; the files stay available online"),
+                        Some((path, err)) => tf("Could not change {path}: {error}", &[
+                            ("path", &path.display()),
+                            ("error", err),
+                        ]),
+                    };
+                }
+                FileSystemResponse::ClipboardFiles(dir, files) => {
+                    if dir == self.current_path {
+                        self.paste_files(files);
+                    }
+                }
+                FileSystemResponse::Emailed(count, result) => {
+                    self.status_message = match result {
+                        Ok(()) => tf("Attached {count} file(s) to a new email", &[("count", &count)]),
+                        Err(err) => tf("Could not send by email: {error}", &[("error", &err)]),
+                    };
+                }
+                FileSystemResponse::ImagesCompared(first, second, result) => {
+                    if let Some(comparison) = &mut self.comparison {
+                        if comparison.paths == (first, second) {
+                            comparison.loaded(ctx, result);
+                        }
+                    }
+                }
+                FileSystemResponse::TextInspected(files) => {
+                    if let (Some(item), [(path, info)]) = (&self.properties_item, files.as_slice()) {
+                        if item.path == *path {
+                            self.properties_text_info = *info;
+                        }
+                    }
+                    if self.show_convert_text_dialog && self.convert_text_files.is_none() {
+                        self.convert_text_files = Some(files);
+                    }
+                }
+                FileSystemResponse::Versions(path, versions) => {
+                    if self.properties_item.as_ref().is_some_and(|item| item.path == path) {
+                        self.properties_versions = Some(versions);
+                    }
+                }
+                FileSystemResponse::VersionRestored(path, result) => {
+                    let name = path.file_name().unwrap_or_default().to_string_lossy();
+                    self.status_message = match result {
+                        Ok(()) => tf("Restored an earlier version of {name}", &[("name", &name)]),
+                        Err(err) => tf("Could not restore {name}: {error}", &[("name", &name), ("error", &err)]),
+                    };
+                }
+                FileSystemResponse::VersionDiff(version, diff) => {
+                    if let Some((shown, _, pending)) = &mut self.version_diff {
+                        if shown.path == version {
+                            *pending = Some(diff);
+                        }
+                    }
+                }
+                FileSystemResponse::TextConverted(total, failed) => {
+                    self.status_message = match failed.first() {
+                        None => tf("Converted {count} files", &[("count", &total)]),
+                        Some((path, err)) => {
+                            let template = "Converted {count} of {total} files; {failed} failed ({name}: {error})";
+                            tf(template, &[
+                                ("count", &(total - failed.len())),
+                                ("total", &total),
+                                ("failed", &failed.len()),
+                                ("name", &path.file_name().unwrap_or_default().to_string_lossy()),
+                                ("error", err),
+                            ])
+                        }
+                    };
+                }
+                FileSystemResponse::Duplicates(path, groups) => {
+                    self.duplicates = Some((path, groups));
+                }
+                FileSystemResponse::Deduplicated(shared, failed) => {
+                    self.status_message = match failed.first() {
+                        None => tf("{size} now shared through reflinks", &[("size", &human_bytes(shared as f64))]),
+                        Some((path, err)) => {
+                            let template = "{size} now shared; {count} copies were left alone ({name}: {error})";
+                            tf(template, &[
+                                ("size", &human_bytes(shared as f64)),
+                                ("count", &failed.len()),
+                                ("name", &path.file_name().unwrap_or_default().to_string_lossy()),
+                                ("error", err),
+                            ])
+                        }
+                    };
+                }
+                FileSystemResponse::LargestFiles(path, files) => {
+                    self.largest_files = Some((path, files));
+                }
+                FileSystemResponse::CleanupCandidates(path, items) => {
+                    if dirs::download_dir().as_ref() == Some(&path) && !items.is_empty() {
+                        self.old_downloads = Some(items);
+                    }
+                }
+                FileSystemResponse::DeleteProgress(done, total) => {
+                    self.file_op_progress = if total > 0 { done as f32 / total as f32 } else { 1.0 };
+                    self.status_message = tf("Deleting {done} of {total} items", &[("done", &done), ("total", &total)]);
+                }
+                FileSystemResponse::DeleteFinished(failed, cancelled) => {
+                    self.delete_cancel = None;
+                    self.file_op_progress = 0.0;
+                    self.status_message = if cancelled {
+                        t("Deletion cancelled")
+                    } else if failed.is_empty() {
+                        t("Deleted")
+                    } else {
+                        tf("{count} item(s) could not be deleted", &[("count", &failed.len())])
+                    };
+                    self.failed_deletions = failed;
+                }
+                FileSystemResponse::Thumbnail(path, thumbnail) => {
+                    let texture = thumbnail.map(|thumbnail| {
+                        let image = egui::ColorImage::from_rgba_unmultiplied(
+                            [thumbnail.width as usize, thumbnail.height as usize],
+                            &thumbnail.rgba,
+                        );
+                        ctx.load_texture(path.display().to_string(), image, Default::default())
+                    });
+                    self.thumbnails.insert(path, texture);
+                }
+                FileSystemResponse::JobNotSubmitted(kind, from, to, err) => {
+                    let template = "The background daemon didn't take the transfer ({error}); transferring here";
+                    self.status_message = tf(template, &[("error", &err)]);
+                    self.send_event(match kind {
+                        TransferKind::Copy => FileSystemEvent::CopyItem(from, to),
+                        TransferKind::Move => FileSystemEvent::MoveItem(from, to),
+                    });
+                }
+                FileSystemResponse::Jobs(jobs) => {
+                    if let Err(err) = &jobs {
+                        if !self.show_jobs_dialog {
+                            self.status_message = tf("Could not reach the background daemon: {error}", &[
+                                ("error", &err),
+                            ]);
+                        }
+                    }
+                    if let (Ok(previous), Ok(current)) = (&self.daemon_jobs, &jobs) {
+                        for job in current {
+                            let was_unfinished = previous.iter().any(|old| {
+                                old.id == job.id && matches!(old.state, JobState::Queued | JobState::Running)
+                            });
+                            let result = match &job.state {
+                                JobState::Done => Ok(()),
+                                JobState::Failed(err) => Err(err.clone()),
+                                JobState::Queued | JobState::Running => continue,
+                            };
+                            if was_unfinished {
+                                self.notify_transfer(ctx, frame, job.kind, &job.to, &result);
+                            }
+                        }
+                    }
+                    self.daemon_jobs = jobs;
+                }
+                FileSystemResponse::TransferFinished(kind, destination, result, elapsed) => {
+                    const NOTIFY_AFTER: Duration = Duration::from_secs(10);
+                    if elapsed >= NOTIFY_AFTER {
+                        self.notify_transfer(ctx, frame, kind, &destination, &result);
+                    }
+                }
+                FileSystemResponse::OperationLocked(event, path, holders) => {
+                    self.locked_operations.push((event, path, holders));
+                }
+                FileSystemResponse::OperationDenied(event, path) => {
+                    self.denied_operations.push((event, path));
+                }
+                FileSystemResponse::ConfirmLaunch(path, shortcut) => {
+                    if self.config.confirmations.run_executable {
+                        self.pending_launch = Some((path, shortcut));
+                    } else {
+                        self.send_event(FileSystemEvent::LaunchShortcut(shortcut));
+                    }
+                }
+                FileSystemResponse::LaunchRefused(path, reason) => {
+                    self.status_message = tf("Could not open {path}: {error}", &[
+                        ("path", &path.display()),
+                        ("error", &reason),
+                    ]);
+                }
+                FileSystemResponse::ElevatedFinished(succeeded) => {
+                    self.status_message = if succeeded {
+                        t("Elevated operation completed")
+                    } else {
+                        t("Elevated operation failed or was cancelled")
+                    };
+                }
+            }
         }
 
+        self.config.theme.apply(ctx, frame.info().system_theme);
+        self.apply_text_scale(ctx);
         self.handle_key_shortcuts(ctx);
+        self.handle_dropped_files(ctx);
+        self.handle_menu_bar_reveal(ctx);
+        self.apply_window_options(ctx, frame);
+        self.update_tray(ctx, frame);
+        self.update_peers(ctx);
+        self.handle_notification_clicks(frame);
         self.draw_menu_bar(ctx, frame);
 
+        if let Some(mode) = self.pick_mode {
+            egui::TopBottomPanel::bottom("picker_panel").show(ctx, |ui| {
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    if ui.button(t("Cancel")).clicked() {
+                        frame.close();
+                    }
+                    let mut chosen: Vec<PathBuf> =
+                        self.selected_items.iter().filter(|path| mode.accepts(path)).cloned().collect();
+                    if chosen.is_empty() && mode == PickMode::Folder {
+                        chosen.push(self.current_path.clone());
+                    }
+                    chosen.sort();
+                    if ui.add_enabled(!chosen.is_empty(), egui::Button::new("Select")).clicked() {
+                        self.picked_paths = Some(chosen);
+                    }
+                });
+            });
+        }
+
+        self.draw_basket_panel(ctx);
+        self.draw_old_downloads_banner(ctx);
+        self.draw_checksums_banner(ctx);
+        self.draw_sidebar(ctx);
+
         egui::CentralPanel::default()
             .frame(egui::Frame {
                 inner_margin: Margin::same(0.0),
                 ..Default::default()
             })
             .show(ctx, |ui| {
+                if self.tabs.len() > 1 {
+                    self.draw_tab_bar(ui);
+                }
+                if self.config.compact_chrome {
+                    // Toolbar actions stay reachable from the menus; the location bar shows the path.
+                    ui.spacing_mut().item_spacing.y = 1.0;
+                    ui.spacing_mut().button_padding = egui::vec2(2.0, 0.0);
+                } else {
+                    self.draw_toolbar(ui);
+                }
                 self.draw_address_bar(ui);
+                if !self.config.compact_chrome {
+                    self.draw_breadcrumbs(ui);
+                }
                 ui.separator();
                 self.draw_file_list(ui);
             });
@@ -878,8 +6311,15 @@ impl eframe::App for FileManager {
             self.draw_status_bar(ui);
         });
 
+        if let Some(paths) = self.picked_paths.take() {
+            picker::print_selection(&paths);
+            frame.close();
+        }
+
         self.draw_dialogs(ctx);
         self.draw_context_menu(ctx);
+        self.draw_drop_stack(ctx);
+        self.draw_selection_badge(ctx);
 
         ctx.input(|i| {
             if i.pointer.any_click() {
@@ -894,12 +6334,315 @@ impl eframe::App for FileManager {
         });
 
         // Request a repaint if there are ongoing operations
-        if self.file_op_progress > 0.0 && self.file_op_progress < 1.0 {
+        let operation_running = (self.file_op_progress > 0.0 && self.file_op_progress < 1.0)
+            || self.delete_cancel.is_some()
+            || !self.downloads.is_empty();
+        if operation_running {
             ctx.request_repaint();
         }
+        self.taskbar_progress.set(operation_running.then_some(self.file_op_progress));
     }
 
     fn save(&mut self, _storage: &mut dyn eframe::Storage) {
         config::save_config(&self.config).unwrap();
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        let _ = config::save_window_geometry(&self.config);
+        if let Some(cancel) = &self.delete_cancel {
+            cancel.store(true, Ordering::Relaxed);
+        }
+        for job in &self.downloads {
+            job.cancel.store(true, Ordering::Relaxed);
+        }
+        if let Some(wizard) = &self.photo_import {
+            wizard.cancel.store(true, Ordering::Relaxed);
+        }
+        self.cancel_disk_usage();
+        // A downloaded archive still waiting on the conflict dialog would otherwise be left behind.
+        if let Some((archive, _)) = self.extraction.take() {
+            self.send_event(FileSystemEvent::DiscardArchive(archive));
+        }
+        // Stops a volume's helper container rather than leaving it to time out.
+        self.close_remote_browser();
+        self.send_event(FileSystemEvent::Shutdown);
+    }
+}
+
+/// Large thumbnails where normal ones would be upscaled and blurry: on high-density displays or
+/// when zoomed in; `scale` is pixels per point times any icon zoom.
+fn thumbnail_size(scale: f32) -> ThumbnailSize {
+    if scale > 1.5 { ThumbnailSize::Large } else { ThumbnailSize::Normal }
+}
+
+/// Gives icon-only widgets a tooltip and a name screen readers can announce.
+fn accessible(response: egui::Response, name: &str) -> egui::Response {
+    response.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Button, name));
+    response.on_hover_text(name)
+}
+
+/// A selectable row with an icon before its label, as `SelectableLabel` would draw it if it took
+/// an image.
+fn icon_row(
+    ui: &mut egui::Ui,
+    size: egui::Vec2,
+    is_selected: bool,
+    icon: &egui::TextureHandle,
+    label: egui::text::LayoutJob,
+) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(size, Sense::click());
+    let text = label.text.clone();
+    response.widget_info(|| egui::WidgetInfo::selected(egui::WidgetType::SelectableLabel, is_selected, &text));
+    if !ui.is_rect_visible(rect) {
+        return response;
+    }
+    let visuals = ui.style().interact_selectable(&response, is_selected);
+    if is_selected || response.hovered() {
+        ui.painter().rect_filled(rect, visuals.rounding, visuals.weak_bg_fill);
+    }
+    let padding = ui.spacing().button_padding.x;
+    let icon_size = ui.text_style_height(&egui::TextStyle::Body);
+    let icon_rect = egui::Rect::from_min_size(
+        egui::pos2(rect.left() + padding, rect.center().y - icon_size / 2.0),
+        egui::vec2(icon_size, icon_size),
+    );
+    let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+    ui.painter().image(icon.id(), icon_rect, uv, egui::Color32::WHITE);
+    let galley = ui.fonts(|fonts| fonts.layout_job(label));
+    ui.painter().galley(egui::pos2(icon_rect.right() + padding, rect.center().y - galley.size().y / 2.0), galley);
+    response
+}
+
+/// A mark for how a file compares with the manifest or signature that vouches for it.
+fn verification_badge(ui: &mut egui::Ui, reference: &Reference, verification: &Verification) -> egui::Response {
+    let source = reference.source.file_name().unwrap_or_default().to_string_lossy();
+    let source = [("source", &source as &dyn std::fmt::Display)];
+    let weak = ui.visuals().weak_text_color();
+    let good = egui::Color32::from_rgb(80, 170, 80);
+    let (mark, color, hint) = match verification {
+        Verification::Unchecked => ("🔏", weak, tf("Listed in {source}. Click to check.", &source)),
+        Verification::Checking => ("⏳", weak, t("Checking...")),
+        Verification::Verified(None) => ("✔", good, tf("Matches {source}", &source)),
+        Verification::Verified(Some(fingerprint)) => {
+            let signed = tf("Signed by the key {fingerprint}", &[("fingerprint", fingerprint)]);
+            ("✔", good, format!("{}\n{}", signed, tf("Matches {source}", &source)))
+        }
+        Verification::Mismatch => ("✖", ui.visuals().error_fg_color, tf("Doesn't match {source}", &source)),
+        Verification::Failed(err) => ("⚠", ui.visuals().warn_fg_color, err.clone()),
+    };
+    ui.add(egui::Label::new(egui::RichText::new(mark).color(color)).sense(Sense::click())).on_hover_text(hint)
+}
+
+/// Overlays small badges on the bottom-right of an item's icon: starred, symlink, unreadable,
+/// cloud placeholder and sync-provider folders.
+fn paint_emblems(ui: &egui::Ui, rect: egui::Rect, item: &FileSystemItem, starred: bool) {
+    let mut emblems = Vec::new();
+    if starred {
+        emblems.push("★");
+    }
+    if item.is_symlink {
+        emblems.push("↪");
+    }
+    if !item.is_readable {
+        emblems.push("🔒");
+    }
+    if item.is_placeholder {
+        emblems.push("☁");
+    }
+    if item.is_partial_download() {
+        emblems.push("⬇");
+    }
+    if item.is_dir && file_system::sync_provider(&item.path).is_some() {
+        emblems.push("🔄");
+    }
+    if emblems.is_empty() {
+        return;
+    }
+
+    let font_id = egui::FontId::proportional(9.0);
+    let mut pos = rect.left_bottom() + egui::vec2(ui.spacing().button_padding.x + 8.0, 0.0);
+    for emblem in emblems {
+        let galley = ui.painter().layout_no_wrap(emblem.to_string(), font_id.clone(), ui.visuals().strong_text_color());
+        ui.painter().galley(pos - egui::vec2(0.0, galley.size().y), galley.clone());
+        pos.x += galley.size().x;
+    }
+}
+
+/// Paints a QR code, black on white with the quiet zone scanners need around it.
+fn draw_qr_code(ui: &mut egui::Ui, width: usize, dark: &[bool]) {
+    const MODULE_SIZE: f32 = 4.0;
+    const QUIET_ZONE: usize = 4;
+    let side = (width + 2 * QUIET_ZONE) as f32 * MODULE_SIZE;
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(side, side), Sense::hover());
+    ui.painter().rect_filled(rect, 0.0, egui::Color32::WHITE);
+    for (index, _) in dark.iter().enumerate().filter(|(_, dark)| **dark) {
+        let (x, y) = (index % width + QUIET_ZONE, index / width + QUIET_ZONE);
+        let min = rect.min + egui::vec2(x as f32, y as f32) * MODULE_SIZE;
+        let module = egui::Rect::from_min_size(min, egui::vec2(MODULE_SIZE, MODULE_SIZE));
+        ui.painter().rect_filled(module, 0.0, egui::Color32::BLACK);
+    }
+}
+
+/// Fills the cell with a color for how long ago `modified` was, red for today through to blue
+/// for years ago, labelled with the age.
+fn paint_age_cell(ui: &mut egui::Ui, modified: SystemTime) {
+    // On a log scale, so the first days and weeks are told apart as well as the years.
+    const STOPS: [(f32, [u8; 3]); 4] =
+        [(0.0, [220, 60, 40]), (0.35, [235, 150, 40]), (0.65, [200, 190, 70]), (1.0, [70, 110, 190])];
+    const COLDEST_DAYS: f32 = 3650.0;
+
+    let age = SystemTime::now().duration_since(modified).unwrap_or_default();
+    let days = age.as_secs_f32() / 86_400.0;
+    let heat = ((1.0 + days).ln() / (1.0 + COLDEST_DAYS).ln()).clamp(0.0, 1.0);
+    let (low, high) = STOPS
+        .windows(2)
+        .map(|pair| (pair[0], pair[1]))
+        .find(|(_, high)| heat <= high.0)
+        .unwrap_or((STOPS[2], STOPS[3]));
+    let t = (heat - low.0) / (high.0 - low.0);
+    let channel = |i: usize| (f32::from(low.1[i]) + (f32::from(high.1[i]) - f32::from(low.1[i])) * t) as u8;
+    let color = egui::Color32::from_rgb(channel(0), channel(1), channel(2));
+
+    let (rect, _) = ui.allocate_exact_size(ui.available_size(), Sense::hover());
+    let rect = rect.shrink2(egui::vec2(0.0, 2.0));
+    ui.painter().rect_filled(rect, 2.0, color);
+    let label = match days as u64 {
+        0 => "today".to_string(),
+        days @ 1..=59 => format!("{}d", days),
+        days @ 60..=729 => format!("{}mo", days / 30),
+        days => format!("{}y", days / 365),
+    };
+    let font_id = egui::FontId::proportional(11.0);
+    ui.painter().text(rect.center(), egui::Align2::CENTER_CENTER, label, font_id, egui::Color32::BLACK);
+}
+
+/// Lays out `prefix` followed by `name`, with every case-insensitive occurrence of `query`
+/// in `name` drawn on the selection background so it's clear why the item matched.
+fn highlight_matches(
+    prefix: &str,
+    name: &str,
+    query: &str,
+    color: egui::Color32,
+    style: &egui::Style,
+) -> egui::text::LayoutJob {
+    let font_id = egui::TextStyle::Body.resolve(style);
+    let normal = egui::text::TextFormat::simple(font_id.clone(), color);
+    let highlighted = egui::text::TextFormat {
+        background: style.visuals.selection.bg_fill,
+        color: style.visuals.selection.stroke.color,
+        ..egui::text::TextFormat::simple(font_id, style.visuals.text_color())
+    };
+
+    let mut job = egui::text::LayoutJob::default();
+    job.append(prefix, 0.0, normal.clone());
+
+    let lower_name = name.to_lowercase();
+    let lower_query = query.to_lowercase();
+    // Lowercasing can change byte lengths for some scripts; only highlight when offsets line up.
+    if lower_query.is_empty() || lower_name.len() != name.len() {
+        job.append(name, 0.0, normal);
+        return job;
+    }
+
+    let mut last = 0;
+    for (start, matched) in lower_name.match_indices(&lower_query) {
+        if start > last {
+            job.append(&name[last..start], 0.0, normal.clone());
+        }
+        job.append(&name[start..start + matched.len()], 0.0, highlighted.clone());
+        last = start + matched.len();
+    }
+    if last < name.len() {
+        job.append(&name[last..], 0.0, normal);
+    }
+    job
+}
+
+/// Draws shell extension entries, returning the command id that was clicked.
+fn draw_shell_menu(ui: &mut egui::Ui, entries: &[ShellMenuItem]) -> Option<u32> {
+    let mut clicked = None;
+    for entry in entries {
+        if entry.children.is_empty() {
+            if ui.button(entry.label.as_str()).clicked() {
+                clicked = Some(entry.id);
+            }
+        } else {
+            ui.menu_button(entry.label.as_str(), |ui| {
+                clicked = clicked.or(draw_shell_menu(ui, &entry.children));
+            });
+        }
+    }
+    clicked
+}
+
+fn file_details_tooltip(response: &egui::Response, item: &FileSystemItem) {
+    let format_time = |time| DateTime::<Local>::from(time).format("%Y-%m-%d %H:%M:%S").to_string();
+    response.clone().on_hover_ui(|ui| {
+        ui.strong(item.display_name());
+        ui.label(item.path.display().to_string());
+        ui.separator();
+        egui::Grid::new("file_details_tooltip").num_columns(2).show(ui, |ui| {
+            ui.weak(t("Type"));
+            ui.label(item.type_description());
+            ui.end_row();
+            if item.metadata_loaded {
+                ui.weak(t("Size"));
+                ui.label(format!("{} ({} bytes)", human_bytes(item.size as f64), item.size));
+                ui.end_row();
+                if item.allocated_size != item.size {
+                    ui.weak(t("On Disk"));
+                    ui.label(human_bytes(item.allocated_size as f64));
+                    ui.end_row();
+                }
+                ui.weak(t("Modified"));
+                ui.label(format_time(item.modified));
+                ui.end_row();
+                if let Some(created) = item.created {
+                    ui.weak(t("Created"));
+                    ui.label(format_time(created));
+                    ui.end_row();
+                }
+            }
+            if let Some(shortcut) = &item.shortcut {
+                ui.weak(t("Target"));
+                ui.label(shortcut.describe_target());
+                ui.end_row();
+            }
+        });
+    });
+}
+
+/// Shortens `text` to fit `max_width` by replacing its middle with "…", so both the start
+/// and the extension stay visible.
+fn elide_middle(ui: &egui::Ui, text: &str, max_width: f32) -> String {
+    let font_id = egui::TextStyle::Body.resolve(ui.style());
+    let (widths, ellipsis_width) = ui.fonts(|fonts| {
+        let widths: Vec<f32> = text.chars().map(|c| fonts.glyph_width(&font_id, c)).collect();
+        (widths, fonts.glyph_width(&font_id, '…'))
+    });
+    if widths.iter().sum::<f32>() <= max_width {
+        return text.to_string();
+    }
+
+    let chars: Vec<char> = text.chars().collect();
+    let (mut front, mut back) = (0, chars.len());
+    let mut used = ellipsis_width;
+    // Take characters alternately from each end while they fit.
+    while front < back {
+        let take_front = front <= chars.len() - back;
+        let width = if take_front { widths[front] } else { widths[back - 1] };
+        if used + width > max_width {
+            break;
+        }
+        used += width;
+        if take_front {
+            front += 1;
+        } else {
+            back -= 1;
+        }
+    }
+    let mut elided: String = chars[..front].iter().collect();
+    elided.push('…');
+    elided.extend(&chars[back..]);
+    elided
 }
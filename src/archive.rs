@@ -0,0 +1,174 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use zip::write::FileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+/// Ends a copy of this executable with a zip appended, so it unpacks itself when run.
+const SELF_EXTRACTING_MARKER: &[u8; 8] = b"HAPPSFX1";
+
+/// What to make of the zip once it's written.
+#[derive(Clone, Copy, Default)]
+pub struct ArchiveOptions {
+    /// Cuts the archive into numbered parts of at most this many bytes.
+    pub volume_size: Option<u64>,
+    /// Wraps the archive in a copy of this executable, as `archive` with an `.exe` extension.
+    pub self_extracting: bool,
+}
+
+/// The files `create` would write for `archive` that are already there: the zip, the executable
+/// wrapping it and the parts it's split into.
+pub fn existing_outputs(archive: &Path, options: ArchiveOptions) -> Vec<PathBuf> {
+    let output = if options.self_extracting { archive.with_extension("exe") } else { archive.to_path_buf() };
+    let mut outputs = vec![archive.to_path_buf()];
+    if output != archive {
+        outputs.push(output.clone());
+    }
+    if options.volume_size.is_some() {
+        outputs.extend((1..).map(|n| part_path(&output, n)).take_while(|part| fs::symlink_metadata(part).is_ok()));
+    }
+    outputs.retain(|path| fs::symlink_metadata(path).is_ok());
+    outputs
+}
+
+/// `archive`, or else the first of `name (2).zip`, `name (3).zip`… none of whose outputs exist yet.
+pub fn available(archive: &Path, options: ArchiveOptions) -> PathBuf {
+    let stem = archive.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = archive.extension().map(|ext| format!(".{}", ext.to_string_lossy())).unwrap_or_default();
+    std::iter::once(archive.to_path_buf())
+        .chain((2..).map(|n| archive.with_file_name(format!("{} ({}){}", stem, n, extension))))
+        .find(|candidate| existing_outputs(candidate, options).is_empty())
+        .unwrap()
+}
+
+fn part_path(path: &Path, number: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!(".{:03}", number));
+    PathBuf::from(name)
+}
+
+/// Zips `paths` into `archive` and applies `options`, returning the files created.
+pub fn create(paths: &[PathBuf], archive: &Path, options: ArchiveOptions) -> Result<Vec<PathBuf>, String> {
+    create_zip(paths, archive)?;
+    let mut output = archive.to_path_buf();
+    if options.self_extracting {
+        let exe = archive.with_extension("exe");
+        let wrapped = wrap_self_extracting(archive, &exe);
+        let _ = fs::remove_file(archive);
+        wrapped.map_err(|err| err.to_string())?;
+        output = exe;
+    }
+    match options.volume_size {
+        Some(volume_size) => split(&output, volume_size).map_err(|err| err.to_string()),
+        None => Ok(vec![output]),
+    }
+}
+
+/// Writes `paths` (folders recursively) into a new zip at `archive`, each under its own name.
+pub fn create_zip(paths: &[PathBuf], archive: &Path) -> Result<(), String> {
+    let mut zip = ZipWriter::new(File::create(archive).map_err(|err| err.to_string())?);
+    // The archive can be inside a folder being zipped, and mustn't end up inside itself.
+    let skip = fs::canonicalize(archive).map_err(|err| err.to_string())?;
+    for path in paths {
+        let Some(name) = path.file_name() else {
+            continue;
+        };
+        // A link that was picked itself is followed, so it's its target that gets zipped.
+        let path = fs::canonicalize(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        add_entry(&mut zip, &path, Path::new(name), &skip).map_err(|err| format!("{}: {}", path.display(), err))?;
+    }
+    zip.finish().map_err(|err| err.to_string())?;
+    Ok(())
+}
+
+/// Links inside folders are left out, since one pointing back up would never end, and so are
+/// sockets, FIFOs and devices.
+fn add_entry(zip: &mut ZipWriter<File>, path: &Path, name: &Path, skip: &Path) -> io::Result<()> {
+    if path == skip {
+        return Ok(());
+    }
+    // Zip entry names always use forward slashes.
+    let entry_name = name.to_string_lossy().replace('\\', "/");
+    let metadata = fs::symlink_metadata(path)?;
+    if metadata.is_dir() {
+        zip.add_directory(entry_name, FileOptions::default())?;
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            add_entry(zip, &entry.path(), &name.join(entry.file_name()), skip)?;
+        }
+    } else if metadata.is_file() {
+        zip.start_file(entry_name, FileOptions::default())?;
+        io::copy(&mut File::open(path)?, zip)?;
+    }
+    Ok(())
+}
+
+/// Replaces `path` with `path.001`, `path.002`… of at most `volume_size` bytes each. Joining them
+/// back in order, e.g. with `cat` or `copy /b`, or opening the first in 7-Zip, restores it.
+fn split(path: &Path, volume_size: u64) -> io::Result<Vec<PathBuf>> {
+    let mut file = File::open(path)?;
+    let length = file.metadata()?.len();
+    let mut parts = Vec::new();
+    while parts.is_empty() || (parts.len() as u64) * volume_size < length {
+        let part = part_path(path, parts.len() + 1);
+        io::copy(&mut (&mut file).take(volume_size), &mut File::create(&part)?)?;
+        parts.push(part);
+    }
+    drop(file);
+    fs::remove_file(path)?;
+    Ok(parts)
+}
+
+/// Writes a copy of this executable with the zip at `archive` and a marker appended.
+fn wrap_self_extracting(archive: &Path, exe: &Path) -> io::Result<()> {
+    fs::copy(std::env::current_exe()?, exe)?;
+    let mut output = OpenOptions::new().append(true).open(exe)?;
+    io::copy(&mut File::open(archive)?, &mut output)?;
+    output.write_all(SELF_EXTRACTING_MARKER)?;
+    output.flush()
+}
+
+/// If this executable carries an archive, unpacks it into a new folder beside itself and
+/// returns that folder.
+pub fn self_extract() -> Option<Result<PathBuf, String>> {
+    let exe = std::env::current_exe().ok()?;
+    let mut file = File::open(&exe).ok()?;
+    let mut marker = [0; 8];
+    file.seek(SeekFrom::End(-8)).ok()?;
+    file.read_exact(&mut marker).ok()?;
+    if &marker != SELF_EXTRACTING_MARKER {
+        return None;
+    }
+    let stem = exe.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+    let parent = exe.parent().unwrap_or(Path::new("."));
+    let dir = (1..)
+        .map(|n| if n == 1 { stem.clone() } else { format!("{} ({})", stem, n) })
+        .map(|name| parent.join(name))
+        .find(|path| !path.exists())
+        .unwrap();
+    // The zip reader finds the archive from its end and allows for the executable before it.
+    let result = ZipArchive::new(file)
+        .and_then(|mut zip| zip.extract(&dir))
+        .map(|()| dir)
+        .map_err(|err| err.to_string());
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_into_numbered_parts() {
+        let dir = std::env::temp_dir().join(format!("happ-split-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("a.zip");
+        fs::write(&path, [7; 25]).unwrap();
+        let parts = split(&path, 10).unwrap();
+        let sizes: Vec<u64> = parts.iter().map(|part| fs::metadata(part).unwrap().len()).collect();
+        assert_eq!(sizes, [10, 10, 5]);
+        assert_eq!(parts[2], dir.join("a.zip.003"));
+        assert!(!path.exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
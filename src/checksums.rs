@@ -0,0 +1,201 @@
+//! Checking files against the checksum manifests and detached signatures published next to them,
+//! like the `SHA256SUMS` beside a downloaded disk image.
+
+use crate::download;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+/// Manifests that list several files, with or without a `.txt` extension.
+const MANIFEST_NAMES: &[&str] = &["md5sums", "sha256sums", "sha512sums", "checksums"];
+/// Manifests for the single file they're named after, like `image.iso.sha256`.
+const DIGEST_EXTENSIONS: &[&str] = &["md5", "sha256", "sha512"];
+const SIGNATURE_EXTENSIONS: &[&str] = &["sig", "asc", "gpg"];
+
+#[derive(Clone, PartialEq)]
+pub enum Expected {
+    /// A hex MD5, SHA-256 or SHA-512 digest.
+    Digest(String),
+    /// A detached GnuPG signature.
+    Signature(PathBuf),
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Reference {
+    pub file: PathBuf,
+    /// The manifest or signature that vouches for the file.
+    pub source: PathBuf,
+    pub expected: Expected,
+}
+
+#[derive(Clone, PartialEq)]
+pub enum Verification {
+    Unchecked,
+    Checking,
+    /// Matches, and for a signature, the fingerprint of the key that made it.
+    Verified(Option<String>),
+    Mismatch,
+    /// The check couldn't be made, like for a signature from a key that isn't in the keyring.
+    Failed(String),
+}
+
+/// Whether a file with this name could vouch for others in its folder.
+pub fn is_manifest(name: &str) -> bool {
+    let name = name.to_lowercase();
+    let base = name.strip_suffix(".txt").unwrap_or(&name);
+    let extension = Path::new(&name).extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+    MANIFEST_NAMES.contains(&base)
+        || DIGEST_EXTENSIONS.contains(&extension)
+        || SIGNATURE_EXTENSIONS.contains(&extension)
+}
+
+/// The files in `dir` that its manifests and signatures vouch for.
+pub fn read(dir: &Path) -> Vec<Reference> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut sources: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| is_manifest(&entry.file_name().to_string_lossy()))
+        .map(|entry| entry.path())
+        .collect();
+    sources.sort();
+    let mut references = Vec::new();
+    for source in sources {
+        let name = source.file_name().unwrap_or_default().to_string_lossy().to_lowercase();
+        let extension = Path::new(&name).extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+        // What a single-file manifest or signature is named after.
+        let stem = source.with_extension("");
+        if SIGNATURE_EXTENSIONS.contains(&extension) {
+            references.push(Reference { file: stem, source: source.clone(), expected: Expected::Signature(source) });
+            continue;
+        }
+        let Ok(text) = fs::read_to_string(&source) else {
+            continue;
+        };
+        if DIGEST_EXTENSIONS.contains(&extension) && is_digest(text.trim()) {
+            references.push(Reference { file: stem, source, expected: Expected::Digest(text.trim().to_string()) });
+            continue;
+        }
+        for (file, digest) in text.lines().filter_map(parse_line) {
+            if is_inside(file) {
+                let expected = Expected::Digest(digest.to_string());
+                references.push(Reference { file: dir.join(file), source: source.clone(), expected });
+            }
+        }
+    }
+    references.retain(|reference| reference.file.is_file());
+    references
+}
+
+/// Checks the file, which for a large one takes as long as reading it.
+pub fn verify(reference: &Reference) -> Verification {
+    match &reference.expected {
+        Expected::Digest(expected) => match download::digest(&reference.file, expected.len()) {
+            Ok(actual) if actual.eq_ignore_ascii_case(expected) => Verification::Verified(None),
+            Ok(_) => Verification::Mismatch,
+            Err(err) => Verification::Failed(err.to_string()),
+        },
+        Expected::Signature(signature) => {
+            let output = Command::new("gpg")
+                .args(["--batch", "--status-fd", "1", "--verify"])
+                .arg(signature)
+                .arg(&reference.file)
+                .env("LC_ALL", "C")
+                .output();
+            let Ok(output) = output else {
+                return Verification::Failed("GnuPG isn't installed".to_string());
+            };
+            let status = String::from_utf8_lossy(&output.stdout);
+            let errors = String::from_utf8_lossy(&output.stderr);
+            signature_verification(&status, output.status.success())
+                .unwrap_or_else(|| Verification::Failed(errors.lines().last().unwrap_or("gpg failed").to_string()))
+        }
+    }
+}
+
+/// Reads `gpg --status-fd` output. Only a good signature over the file itself counts: one that
+/// carries its own signed text would verify without the file being looked at.
+fn signature_verification(status: &str, succeeded: bool) -> Option<Verification> {
+    let keywords: Vec<(&str, &str)> = status
+        .lines()
+        .filter_map(|line| line.strip_prefix("[GNUPG:] "))
+        .map(|line| line.split_once(' ').unwrap_or((line, "")))
+        .collect();
+    let has = |keyword: &str| keywords.iter().any(|(found, _)| *found == keyword);
+    if has("PLAINTEXT") {
+        return Some(Verification::Failed("The signature isn't a detached one".to_string()));
+    }
+    if has("BADSIG") {
+        return Some(Verification::Mismatch);
+    }
+    if has("NO_PUBKEY") {
+        return Some(Verification::Failed("The signing key isn't in your keyring".to_string()));
+    }
+    let fingerprint = keywords.iter().find(|(keyword, _)| *keyword == "VALIDSIG").map(|(_, rest)| rest);
+    match fingerprint.and_then(|rest| rest.split(' ').next()) {
+        Some(fingerprint) if succeeded && !has("ERRSIG") => Some(Verification::Verified(Some(fingerprint.to_string()))),
+        _ => None,
+    }
+}
+
+/// The file name and digest from a `sha256sum` line, `digest  name` or `digest *name`, or from a
+/// BSD one, `SHA256 (name) = digest`.
+fn parse_line(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim();
+    if line.starts_with('#') {
+        return None;
+    }
+    if let Some((algorithm_and_name, digest)) = line.rsplit_once(" = ") {
+        let name = algorithm_and_name.split_once(" (")?.1.strip_suffix(')')?;
+        return is_digest(digest).then_some((name, digest));
+    }
+    let (digest, name) = line.split_once(char::is_whitespace)?;
+    let name = name.trim_start();
+    let name = name.strip_prefix('*').unwrap_or(name);
+    (is_digest(digest) && !name.is_empty()).then_some((name, digest))
+}
+
+fn is_digest(text: &str) -> bool {
+    matches!(text.len(), 32 | 64 | 128) && text.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Whether a name in a manifest stays within the manifest's folder.
+fn is_inside(name: &str) -> bool {
+    let components: Vec<Component> = Path::new(name).components().collect();
+    components.iter().any(|part| matches!(part, Component::Normal(_)))
+        && components.iter().all(|part| matches!(part, Component::Normal(_) | Component::CurDir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest_lines_name_files() {
+        let digest = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+        let gnu = format!("{}  ubuntu.iso", digest);
+        assert_eq!(parse_line(&gnu), Some(("ubuntu.iso", digest)));
+        let binary = format!("{} *ubuntu.iso", digest);
+        assert_eq!(parse_line(&binary), Some(("ubuntu.iso", digest)));
+        let bsd = format!("SHA256 (my file.tar.gz) = {}", digest);
+        assert_eq!(parse_line(&bsd), Some(("my file.tar.gz", digest)));
+        assert_eq!(parse_line("# generated by hand"), None);
+        assert_eq!(parse_line("not-a-digest  ubuntu.iso"), None);
+        assert!(is_inside("./images/ubuntu.iso"));
+        assert!(!is_inside("../ubuntu.iso"));
+        assert!(!is_inside("/etc/passwd"));
+        assert!(is_manifest("SHA256SUMS") && is_manifest("sha256sums.txt") && is_manifest("ubuntu.iso.sig"));
+        assert!(!is_manifest("notes.txt"));
+    }
+
+    #[test]
+    fn signatures_need_a_valid_detached_signature() {
+        let good = "[GNUPG:] NEWSIG\n[GNUPG:] GOODSIG 1234 Someone\n[GNUPG:] VALIDSIG ABCD1234 2024-01-01 1\n";
+        assert!(signature_verification(good, true) == Some(Verification::Verified(Some("ABCD1234".to_string()))));
+        let inline = format!("[GNUPG:] PLAINTEXT 62 0\n{}", good);
+        assert!(matches!(signature_verification(&inline, true), Some(Verification::Failed(_))));
+        assert!(signature_verification("[GNUPG:] BADSIG 1234 Someone\n", false) == Some(Verification::Mismatch));
+        assert!(signature_verification("[GNUPG:] GOODSIG 1234 Someone\n", true).is_none());
+    }
+}
@@ -0,0 +1,34 @@
+//! Files kept by a sync client such as OneDrive, Dropbox or iCloud Drive, which may only be
+//! placeholders until opened. Pinning asks the client to keep a copy here; unpinning lets it
+//! free the space again.
+
+use std::path::Path;
+use std::process::Command;
+
+/// Whether this platform lets [`set_kept_on_device`] pin and unpin files.
+pub fn is_supported() -> bool {
+    cfg!(any(windows, target_os = "macos"))
+}
+
+/// Pins `path` so its content is downloaded and kept, or unpins it so only a placeholder stays.
+pub fn set_kept_on_device(path: &Path, keep: bool) -> Result<(), String> {
+    let mut command = if cfg!(windows) {
+        // The pinned and unpinned attributes are what Explorer's own menu items set; folders pass
+        // them on to their contents.
+        let mut command = Command::new("attrib");
+        command.args(if keep { ["+P", "-U"] } else { ["-P", "+U"] });
+        command
+    } else if cfg!(target_os = "macos") {
+        let mut command = Command::new("brctl");
+        command.arg(if keep { "download" } else { "evict" });
+        command
+    } else {
+        return Err("not supported on this platform".to_string());
+    };
+    let output = command.arg(path).output().map_err(|err| err.to_string())?;
+    if !output.status.success() {
+        let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(if message.is_empty() { "the sync client refused".to_string() } else { message });
+    }
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use crate::file_system::FileSystemItem;
+use eframe::egui::Color32;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub enum ColorCondition {
+    Extensions(String),
+    Executable,
+    OlderThanDays(u32),
+    Directory,
+    Hidden,
+}
+
+impl ColorCondition {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ColorCondition::Extensions(_) => "Extensions",
+            ColorCondition::Executable => "Executable",
+            ColorCondition::OlderThanDays(_) => "Older Than (days)",
+            ColorCondition::Directory => "Folder",
+            ColorCondition::Hidden => "Hidden",
+        }
+    }
+
+    pub fn matches(&self, item: &FileSystemItem) -> bool {
+        match self {
+            ColorCondition::Extensions(extensions) => item
+                .path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| {
+                    extensions
+                        .split(',')
+                        .map(|e| e.trim().trim_start_matches('.'))
+                        .any(|e| e.eq_ignore_ascii_case(ext))
+                })
+                .unwrap_or(false),
+            ColorCondition::Executable => item.is_executable,
+            ColorCondition::OlderThanDays(days) => SystemTime::now()
+                .duration_since(item.modified)
+                .map(|age| age > Duration::from_secs(u64::from(*days) * 24 * 60 * 60))
+                .unwrap_or(false),
+            ColorCondition::Directory => item.is_dir,
+            ColorCondition::Hidden => item.is_hidden,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct ColorRule {
+    pub enabled: bool,
+    pub condition: ColorCondition,
+    pub color: [u8; 3],
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct ColorRules(pub Vec<ColorRule>);
+
+impl Default for ColorRules {
+    fn default() -> Self {
+        Self(vec![
+            ColorRule {
+                enabled: true,
+                condition: ColorCondition::Executable,
+                color: [80, 200, 120],
+            },
+            ColorRule {
+                enabled: true,
+                condition: ColorCondition::Extensions("zip, tar, gz, xz, bz2, 7z, rar, zst".to_string()),
+                color: [180, 120, 220],
+            },
+            ColorRule {
+                enabled: true,
+                condition: ColorCondition::OlderThanDays(365),
+                color: [140, 140, 140],
+            },
+        ])
+    }
+}
+
+impl ColorRules {
+    /// Returns the color of the first enabled rule matching `item`.
+    pub fn color_for(&self, item: &FileSystemItem) -> Option<Color32> {
+        self.0
+            .iter()
+            .find(|rule| rule.enabled && rule.condition.matches(item))
+            .map(|rule| Color32::from_rgb(rule.color[0], rule.color[1], rule.color[2]))
+    }
+}
@@ -0,0 +1,200 @@
+//! Visual comparison of two images: side by side, a swipe between them, or their difference.
+
+use crate::i18n::{t, tf};
+use crate::thumbnails::Thumbnail;
+use eframe::egui;
+use image::imageops::FilterType;
+use image::RgbaImage;
+use std::path::{Path, PathBuf};
+
+/// Larger images are scaled down to this many pixels on their longest side.
+const MAX_SIDE: u32 = 2048;
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum CompareMode {
+    SideBySide,
+    Swipe,
+    Difference,
+}
+
+impl CompareMode {
+    fn label(self) -> &'static str {
+        match self {
+            CompareMode::SideBySide => "Side by Side",
+            CompareMode::Swipe => "Swipe",
+            CompareMode::Difference => "Difference",
+        }
+    }
+}
+
+/// Both images at the same size, plus their per-channel absolute difference.
+pub struct ComparisonImages {
+    pub first: Thumbnail,
+    pub second: Thumbnail,
+    pub difference: Thumbnail,
+    /// Share of pixels that differ at all, from 0.0 to 1.0.
+    pub changed: f32,
+    /// Whether the second image had to be resized to match the first.
+    pub resized: bool,
+}
+
+pub fn load_pair(first: &Path, second: &Path) -> Result<ComparisonImages, String> {
+    let open = |path: &Path| {
+        image::open(path).map(|image| image.to_rgba8()).map_err(|err| format!("{}: {}", path.display(), err))
+    };
+    let mut first = open(first)?;
+    let mut second = open(second)?;
+    if first.width().max(first.height()) > MAX_SIDE {
+        first = image::DynamicImage::ImageRgba8(first).resize(MAX_SIDE, MAX_SIDE, FilterType::Triangle).to_rgba8();
+    }
+    let resized = second.dimensions() != first.dimensions();
+    if resized {
+        second = image::imageops::resize(&second, first.width(), first.height(), FilterType::Triangle);
+    }
+
+    let mut difference = RgbaImage::new(first.width(), first.height());
+    let mut changed_pixels = 0usize;
+    for ((a, b), out) in first.pixels().zip(second.pixels()).zip(difference.pixels_mut()) {
+        let channel = |i: usize| a.0[i].abs_diff(b.0[i]);
+        if a != b {
+            changed_pixels += 1;
+        }
+        out.0 = [channel(0), channel(1), channel(2), 255];
+    }
+    let pixel_count = (first.width() * first.height()).max(1) as f32;
+    let to_thumbnail = |image: RgbaImage| Thumbnail {
+        width: image.width(),
+        height: image.height(),
+        rgba: image.into_raw(),
+    };
+    Ok(ComparisonImages {
+        first: to_thumbnail(first),
+        second: to_thumbnail(second),
+        difference: to_thumbnail(difference),
+        changed: changed_pixels as f32 / pixel_count,
+        resized,
+    })
+}
+
+struct Textures {
+    first: egui::TextureHandle,
+    second: egui::TextureHandle,
+    difference: egui::TextureHandle,
+    changed: f32,
+    resized: bool,
+}
+
+/// The Compare Images window's state.
+pub struct Comparison {
+    pub paths: (PathBuf, PathBuf),
+    textures: Option<Result<Textures, String>>,
+    mode: CompareMode,
+    /// How far across the second image is revealed in swipe mode, from 0.0 to 1.0.
+    swipe: f32,
+}
+
+impl Comparison {
+    pub fn new(first: PathBuf, second: PathBuf) -> Self {
+        Self {
+            paths: (first, second),
+            textures: None,
+            mode: CompareMode::SideBySide,
+            swipe: 0.5,
+        }
+    }
+
+    pub fn loaded(&mut self, ctx: &egui::Context, images: Result<ComparisonImages, String>) {
+        let load = |name: &str, image: &Thumbnail| {
+            let size = [image.width as usize, image.height as usize];
+            ctx.load_texture(name, egui::ColorImage::from_rgba_unmultiplied(size, &image.rgba), Default::default())
+        };
+        self.textures = Some(images.map(|images| Textures {
+            first: load("compare_first", &images.first),
+            second: load("compare_second", &images.second),
+            difference: load("compare_difference", &images.difference),
+            changed: images.changed,
+            resized: images.resized,
+        }));
+    }
+
+    /// Draws the window; returns false once it's closed.
+    pub fn show(&mut self, ctx: &egui::Context) -> bool {
+        let mut open = true;
+        egui::Window::new(t("Compare Images")).open(&mut open).default_size([800.0, 500.0]).show(ctx, |ui| {
+            let name = |path: &Path| path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            ui.horizontal(|ui| {
+                for mode in [CompareMode::SideBySide, CompareMode::Swipe, CompareMode::Difference] {
+                    ui.radio_value(&mut self.mode, mode, t(mode.label()));
+                }
+            });
+            match &self.textures {
+                None => {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(t("Loading..."));
+                    });
+                }
+                Some(Err(err)) => {
+                    ui.label(err);
+                }
+                Some(Ok(textures)) => {
+                    let percent = format!("{:.1}", textures.changed * 100.0);
+                    let mut summary = tf("{percent}% of pixels differ", &[("percent", &percent)]);
+                    if textures.resized {
+                        let scaled = tf("({name} was scaled to match)", &[("name", &name(&self.paths.1))]);
+                        summary = format!("{} {}", summary, scaled);
+                    }
+                    ui.label(summary);
+                    ui.separator();
+                    match self.mode {
+                        CompareMode::SideBySide => {
+                            let width = (ui.available_width() - ui.spacing().item_spacing.x) / 2.0;
+                            let sides = [(&textures.first, &self.paths.0), (&textures.second, &self.paths.1)];
+                            ui.horizontal_top(|ui| {
+                                for (texture, path) in sides {
+                                    ui.vertical(|ui| {
+                                        ui.label(name(path));
+                                        ui.image(texture, fit(texture.size_vec2(), width, ui.available_height()));
+                                    });
+                                }
+                            });
+                        }
+                        CompareMode::Swipe => {
+                            ui.add(egui::Slider::new(&mut self.swipe, 0.0..=1.0).show_value(false));
+                            let size = fit(textures.first.size_vec2(), ui.available_width(), ui.available_height());
+                            let (rect, response) = ui.allocate_exact_size(size, egui::Sense::drag());
+                            if let Some(pointer) = response.interact_pointer_pos() {
+                                self.swipe = ((pointer.x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+                            }
+                            let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+                            let split = rect.left() + rect.width() * self.swipe;
+                            let painter = ui.painter_at(rect);
+                            let tint = egui::Color32::WHITE;
+                            painter.image(textures.first.id(), rect, uv, tint);
+                            let revealed = rect.intersect(egui::Rect::everything_left_of(split));
+                            painter.with_clip_rect(revealed).image(textures.second.id(), rect, uv, tint);
+                            painter.vline(split, rect.y_range(), ui.visuals().widgets.active.fg_stroke);
+                            ui.horizontal(|ui| {
+                                ui.label(format!("◀ {}", name(&self.paths.1)));
+                                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                    ui.label(format!("{} ▶", name(&self.paths.0)));
+                                });
+                            });
+                        }
+                        CompareMode::Difference => {
+                            let texture = &textures.difference;
+                            ui.image(texture, fit(texture.size_vec2(), ui.available_width(), ui.available_height()));
+                        }
+                    }
+                }
+            }
+        });
+        open
+    }
+}
+
+/// Scales `size` down (never up) to fit within `width` by `height`.
+fn fit(size: egui::Vec2, width: f32, height: f32) -> egui::Vec2 {
+    let scale = (width / size.x).min(height / size.y).min(1.0).max(0.0);
+    size * scale
+}
@@ -1,15 +1,217 @@
-use crate::app::SortBy;
+use crate::app::{FilterOptions, SortBy, TableColumn, ViewMode};
+use crate::color_rules::ColorRules;
+use crate::i18n::Language;
+use crate::keymap::{Keymap, Profile};
+use crate::music;
+use crate::open_with::FolderApps;
+use crate::photos::ImportSettings;
+use crate::power::BatteryPolicy;
+use crate::sorter::SortRules;
+use crate::theme::ThemeSettings;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
 #[derive(Serialize, Deserialize, Default, Clone)]
+pub struct Workspace {
+    pub name: String,
+    pub folders: Vec<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct ConfirmationSettings {
+    pub trash: bool,
+    pub permanent_delete: bool,
+    pub overwrite: bool,
+    pub drag_move: bool,
+    pub run_executable: bool,
+}
+
+impl Default for ConfirmationSettings {
+    fn default() -> Self {
+        Self {
+            trash: true,
+            permanent_delete: true,
+            overwrite: true,
+            drag_move: false,
+            run_executable: true,
+        }
+    }
+}
+
+/// What happens to an existing file or folder that a paste is about to replace.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Default)]
+pub enum OverwriteBackup {
+    #[default]
+    Replace,
+    /// Renamed to `name.bak` next to the new one.
+    Rename,
+    Trash,
+}
+
+impl OverwriteBackup {
+    pub const ALL: [OverwriteBackup; 3] = [OverwriteBackup::Replace, OverwriteBackup::Rename, OverwriteBackup::Trash];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            OverwriteBackup::Replace => "Replace it",
+            OverwriteBackup::Rename => "Keep it as name.bak",
+            OverwriteBackup::Trash => "Move it to the trash",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct DownloadsReminder {
+    pub enabled: bool,
+    pub older_than_days: u32,
+    /// "Not Now" hides the banner until this time.
+    pub snoozed_until: Option<DateTime<Utc>>,
+}
+
+impl Default for DownloadsReminder {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            older_than_days: 60,
+            snoozed_until: None,
+        }
+    }
+}
+
+/// Window placement on one monitor setup; positions are in points.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct WindowGeometry {
+    pub position: Option<[f32; 2]>,
+    pub size: [f32; 2],
+    pub maximized: bool,
+    /// The scale the position was saved at, so it can be restored to the same physical spot.
+    #[serde(default = "default_pixels_per_point")]
+    pub pixels_per_point: f32,
+}
+
+fn default_pixels_per_point() -> f32 {
+    1.0
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct AppConfig {
     pub show_hidden_files: bool,
     pub sort_by: SortBy,
     pub sort_ascending: bool,
+    /// Sorts "file2" before "file10" rather than character by character.
+    pub natural_sort: bool,
+    /// Keeps folders above files whatever the sort.
+    pub folders_first: bool,
     pub history: Vec<PathBuf>,
     pub favorites: Vec<PathBuf>,
+    /// Individual files, kept apart from the folder favorites.
+    pub starred: Vec<PathBuf>,
+    pub filter_options: FilterOptions,
+    pub view_mode: ViewMode,
+    pub zoom_levels: HashMap<PathBuf, f32>,
+    /// Multiplies the display's own scale, for the whole interface.
+    pub ui_scale: f32,
+    /// Multiplies the size of all text, and the rows it sits in.
+    pub text_scale: f32,
+    pub show_folder_item_counts: bool,
+    /// Hides `.part`, `.crdownload` and similar files that are still downloading.
+    pub hide_partial_downloads: bool,
+    pub color_rules: ColorRules,
+    /// How Tools > Sort Folder tidies a folder into subfolders.
+    pub sort_rules: SortRules,
+    pub photo_import: ImportSettings,
+    /// How Tools > Organize Music names folders and tracks, with fields like `{artist}`.
+    pub music_layout: String,
+    pub workspaces: Vec<Workspace>,
+    pub theme: ThemeSettings,
+    pub confirmations: ConfirmationSettings,
+    pub overwrite_backup: OverwriteBackup,
+    pub use_daemon: bool,
+    pub folder_apps: FolderApps,
+    pub show_shell_menu: bool,
+    pub tooltip_delay_ms: u32,
+    pub hide_menu_bar: bool,
+    pub compact_chrome: bool,
+    pub show_folder_tree: bool,
+    /// Sizes count allocated blocks rather than file lengths.
+    pub size_on_disk: bool,
+    pub always_on_top: bool,
+    pub show_tray_icon: bool,
+    /// Finds other copies on the local network and lets them send files here.
+    pub lan_peers: bool,
+    /// Lists Android devices connected over ADB, which starts the ADB server if it isn't running.
+    pub adb_devices: bool,
+    /// Lists Docker containers and volumes, which asks the Docker daemon every few seconds.
+    pub docker_containers: bool,
+    pub battery: BatteryPolicy,
+    /// The details view's columns after Name, in order.
+    pub columns: Vec<TableColumn>,
+    pub downloads_reminder: DownloadsReminder,
+    pub keymap: Keymap,
+    pub language: Language,
+    pub key_profile: Profile,
+    pub track_usage: bool,
+    /// Keyed by monitor size, so each monitor setup keeps its own placement.
+    pub window_geometry: HashMap<String, WindowGeometry>,
+    pub last_monitor: Option<String>,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            show_hidden_files: false,
+            sort_by: SortBy::default(),
+            sort_ascending: false,
+            natural_sort: true,
+            folders_first: false,
+            history: Vec::new(),
+            favorites: Vec::new(),
+            starred: Vec::new(),
+            filter_options: FilterOptions::default(),
+            view_mode: ViewMode::default(),
+            zoom_levels: HashMap::new(),
+            ui_scale: 1.0,
+            text_scale: 1.0,
+            show_folder_item_counts: false,
+            hide_partial_downloads: false,
+            color_rules: ColorRules::default(),
+            sort_rules: SortRules::default(),
+            photo_import: ImportSettings::default(),
+            music_layout: music::DEFAULT_LAYOUT.to_string(),
+            workspaces: Vec::new(),
+            theme: ThemeSettings::default(),
+            confirmations: ConfirmationSettings::default(),
+            overwrite_backup: OverwriteBackup::default(),
+            use_daemon: false,
+            folder_apps: FolderApps::default(),
+            show_shell_menu: false,
+            tooltip_delay_ms: 500,
+            hide_menu_bar: false,
+            compact_chrome: false,
+            show_folder_tree: true,
+            size_on_disk: false,
+            always_on_top: false,
+            show_tray_icon: false,
+            lan_peers: false,
+            adb_devices: false,
+            docker_containers: false,
+            battery: BatteryPolicy::default(),
+            columns: vec![TableColumn::Size, TableColumn::Modified],
+            downloads_reminder: DownloadsReminder::default(),
+            keymap: Keymap::default(),
+            language: Language::default(),
+            key_profile: Profile::default(),
+            track_usage: false,
+            window_geometry: HashMap::new(),
+            last_monitor: None,
+        }
+    }
 }
 
 fn get_config_path() -> PathBuf {
@@ -35,3 +237,12 @@ pub fn save_config(config: &AppConfig) -> Result<(), Box<dyn std::error::Error>>
     fs::write(path, content)?;
     Ok(())
 }
+
+/// Saves only the window placement, into the config as it is on disk now, so closing one window
+/// doesn't undo settings another window changed since this one loaded.
+pub fn save_window_geometry(config: &AppConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let mut saved = load_config()?;
+    saved.window_geometry.extend(config.window_geometry.iter().map(|(monitor, geometry)| (monitor.clone(), *geometry)));
+    saved.last_monitor = config.last_monitor.clone();
+    save_config(&saved)
+}
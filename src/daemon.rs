@@ -0,0 +1,273 @@
+use crate::journal::{self, TransferKind};
+use crate::power;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream as StdTcpStream;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::runtime::Runtime;
+
+/// Loopback only; the daemon never listens on other interfaces. Other local processes can still
+/// connect, so each request must carry the token the daemon writes to a file only the user can read.
+pub const DAEMON_ADDR: &str = "127.0.0.1:47321";
+pub const DAEMON_ARG: &str = "--daemon";
+/// Requests are a line of JSON; anything longer is refused rather than buffered.
+const MAX_LINE: u64 = 64 * 1024;
+/// Finished jobs kept for `jobs` to report; older ones are forgotten.
+const MAX_FINISHED_JOBS: usize = 100;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub enum JobState {
+    Queued,
+    Running,
+    Done,
+    Failed(String),
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Job {
+    pub id: u64,
+    pub kind: TransferKind,
+    pub from: PathBuf,
+    pub to: PathBuf,
+    pub state: JobState,
+}
+
+impl Job {
+    pub fn describe(&self) -> String {
+        let verb = match self.kind {
+            TransferKind::Copy => "Copy",
+            TransferKind::Move => "Move",
+        };
+        format!("{} {} to {}", verb, self.from.display(), self.to.display())
+    }
+}
+
+#[derive(Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Value,
+    #[serde(default)]
+    token: String,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Deserialize)]
+struct SubmitParams {
+    kind: TransferKind,
+    from: PathBuf,
+    to: PathBuf,
+}
+
+type Jobs = Arc<Mutex<Vec<Job>>>;
+
+/// Runs the daemon in the foreground until the process is killed.
+pub fn run() -> io::Result<()> {
+    Runtime::new()?.block_on(serve())
+}
+
+async fn serve() -> io::Result<()> {
+    let listener = TcpListener::bind(DAEMON_ADDR).await?;
+    let token: Arc<str> = create_token()?.into();
+    let jobs = Jobs::default();
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let jobs = jobs.clone();
+        let token = token.clone();
+        tokio::spawn(async move {
+            let _ = handle_client(stream, jobs, &token).await;
+        });
+    }
+}
+
+fn get_token_path() -> PathBuf {
+    dirs::home_dir().unwrap().join(".file_manager_daemon_token")
+}
+
+/// Makes a new secret for this run of the daemon, in a file only the user can read.
+fn create_token() -> io::Result<String> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(io::Error::other)?;
+    let token: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    let path = get_token_path();
+    let _ = fs::remove_file(&path);
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(&path)?.write_all(token.as_bytes())?;
+    Ok(token)
+}
+
+/// Compares in time that doesn't depend on where the first difference is.
+fn same_token(given: &str, expected: &str) -> bool {
+    given.len() == expected.len() && given.bytes().zip(expected.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Speaks newline-delimited JSON-RPC 2.0.
+async fn handle_client(stream: TcpStream, jobs: Jobs, token: &str) -> io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = tokio::io::BufReader::new(reader);
+    loop {
+        let mut line = String::new();
+        let read = (&mut reader).take(MAX_LINE).read_line(&mut line).await?;
+        if read == 0 {
+            break;
+        }
+        if read as u64 == MAX_LINE && !line.ends_with('\n') {
+            return Err(io::Error::other("request too long"));
+        }
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) if !same_token(&request.token, token) => error_response(request.id, -32001, "Unauthorized"),
+            Ok(request) => handle_request(request, &jobs),
+            Err(err) => error_response(Value::Null, -32700, &err.to_string()),
+        };
+        writer.write_all(format!("{}\n", response).as_bytes()).await?;
+    }
+    Ok(())
+}
+
+fn handle_request(request: Request, jobs: &Jobs) -> Value {
+    match request.method.as_str() {
+        "submit" => match serde_json::from_value::<SubmitParams>(request.params) {
+            Ok(params) => {
+                let id = submit_job(jobs, params);
+                json!({ "jsonrpc": "2.0", "id": request.id, "result": id })
+            }
+            Err(err) => error_response(request.id, -32602, &err.to_string()),
+        },
+        "jobs" => {
+            let jobs = jobs.lock().unwrap().clone();
+            json!({ "jsonrpc": "2.0", "id": request.id, "result": jobs })
+        }
+        _ => error_response(request.id, -32601, "Method not found"),
+    }
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn submit_job(jobs: &Jobs, params: SubmitParams) -> u64 {
+    let id = {
+        let mut jobs = jobs.lock().unwrap();
+        let id = jobs.last().map_or(1, |job| job.id + 1);
+        let finished = |job: &Job| matches!(job.state, JobState::Done | JobState::Failed(_));
+        let mut excess = jobs.iter().filter(|job| finished(job)).count().saturating_sub(MAX_FINISHED_JOBS);
+        jobs.retain(|job| {
+            let forget = excess > 0 && finished(job);
+            excess -= forget as usize;
+            !forget
+        });
+        jobs.push(Job {
+            id,
+            kind: params.kind,
+            from: params.from.clone(),
+            to: params.to.clone(),
+            state: JobState::Queued,
+        });
+        id
+    };
+    let jobs = jobs.clone();
+    tokio::task::spawn_blocking(move || {
+        set_state(&jobs, id, JobState::Running);
+        let state = match transfer(params.kind, &params.from, &params.to) {
+            Ok(()) => JobState::Done,
+            Err(err) => JobState::Failed(err),
+        };
+        set_state(&jobs, id, state);
+    });
+    id
+}
+
+fn set_state(jobs: &Jobs, id: u64, state: JobState) {
+    if let Some(job) = jobs.lock().unwrap().iter_mut().find(|job| job.id == id) {
+        job.state = state;
+    }
+}
+
+/// Copies or moves `from` to exactly `to`, which mustn't exist yet: nobody is around to ask
+/// whether to replace it.
+fn transfer(kind: TransferKind, from: &Path, to: &Path) -> Result<(), String> {
+    if to.symlink_metadata().is_ok() {
+        return Err(format!("{} already exists", to.display()));
+    }
+    let _awake = power::keep_awake();
+    let journal_id = journal::begin(kind, from, to);
+    let result = match kind {
+        TransferKind::Copy => copy(from, to),
+        TransferKind::Move => match fs::rename(from, to) {
+            // Another drive: copy, and only remove the original once the copy is whole.
+            Err(err) if err.kind() == io::ErrorKind::CrossesDevices => copy(from, to).and_then(|()| {
+                let removed = if from.is_dir() { fs::remove_dir_all(from) } else { fs::remove_file(from) };
+                removed.map_err(|err| err.to_string())
+            }),
+            result => result.map_err(|err| err.to_string()),
+        },
+    };
+    journal::finish(journal_id);
+    result
+}
+
+/// Copies `from` to `to`, which is created for it; a copy that fails partway is removed again.
+fn copy(from: &Path, to: &Path) -> Result<(), String> {
+    if from.is_dir() {
+        fs::create_dir(to).map_err(|err| err.to_string())?;
+        let mut options = fs_extra::dir::CopyOptions::new();
+        options.content_only = true;
+        let result = fs_extra::dir::copy(from, to, &options).map(|_| ()).map_err(|err| err.to_string());
+        if result.is_err() {
+            let _ = fs::remove_dir_all(to);
+        }
+        result
+    } else {
+        let mut source = fs::File::open(from).map_err(|err| err.to_string())?;
+        let mut target = OpenOptions::new().write(true).create_new(true).open(to).map_err(|err| err.to_string())?;
+        let result = io::copy(&mut source, &mut target)
+            .and_then(|_| target.set_permissions(source.metadata()?.permissions()))
+            .map_err(|err| err.to_string());
+        if result.is_err() {
+            let _ = fs::remove_file(to);
+        }
+        result
+    }
+}
+
+/// Starts a detached daemon process from the current executable.
+pub fn spawn() -> io::Result<()> {
+    Command::new(std::env::current_exe()?).arg(DAEMON_ARG).spawn().map(|_| ())
+}
+
+fn call(method: &str, params: Value) -> Result<Value, String> {
+    let addr = DAEMON_ADDR.parse().unwrap();
+    let mut stream = StdTcpStream::connect_timeout(&addr, Duration::from_millis(500)).map_err(|err| err.to_string())?;
+    stream.set_read_timeout(Some(Duration::from_secs(5))).map_err(|err| err.to_string())?;
+    let token = fs::read_to_string(get_token_path()).map_err(|err| err.to_string())?;
+    let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params, "token": token.trim() });
+    writeln!(stream, "{}", request).map_err(|err| err.to_string())?;
+
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line).map_err(|err| err.to_string())?;
+    let response: Value = serde_json::from_str(&line).map_err(|err| err.to_string())?;
+    match response.get("error") {
+        Some(error) => Err(error["message"].as_str().unwrap_or("Daemon error").to_string()),
+        None => Ok(response["result"].clone()),
+    }
+}
+
+pub fn submit(kind: TransferKind, from: &Path, to: &Path) -> Result<u64, String> {
+    let result = call("submit", json!({ "kind": kind, "from": from, "to": to }))?;
+    result.as_u64().ok_or_else(|| "Unexpected daemon response".to_string())
+}
+
+pub fn jobs() -> Result<Vec<Job>, String> {
+    serde_json::from_value(call("jobs", Value::Null)?).map_err(|err| err.to_string())
+}
@@ -0,0 +1,137 @@
+//! Behaviour that should match the user's Linux desktop: opening files, trashing them and
+//! the shared recent-files list. Elsewhere these fall back to the platform defaults.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// The canonical `file://` URI for `path`, with everything but unreserved characters
+/// percent-encoded.
+pub fn file_uri(path: &Path) -> Option<String> {
+    let path = fs::canonicalize(path).ok()?;
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    Some(uri)
+}
+
+/// The desktop entry id: the windows carry it as their app id, so docks can match them to it.
+pub const APP_ID: &str = "happ";
+
+/// Writes `happ.desktop` into the user's applications folder, pointing at this executable, unless
+/// an up to date one is already there.
+#[cfg(target_os = "linux")]
+pub fn install_desktop_entry() {
+    let (Some(data_dir), Ok(exe)) = (dirs::data_dir(), std::env::current_exe()) else {
+        return;
+    };
+    // Quoted and escaped as the desktop entry spec asks, with `%` doubled so it isn't a field code.
+    let mut exec = String::from("\"");
+    for c in exe.to_string_lossy().chars() {
+        match c {
+            '"' | '`' | '$' | '\\' => exec.push_str(&format!("\\\\{}", c)),
+            '%' => exec.push_str("%%"),
+            c => exec.push(c),
+        }
+    }
+    exec.push('"');
+    let entry = format!(
+        "[Desktop Entry]\nType=Application\nName=File Manager\nIcon=system-file-manager\nExec={} %U\n\
+         Categories=System;FileManager;\nStartupWMClass={}\n",
+        exec, APP_ID
+    );
+    let dir = data_dir.join("applications");
+    let path = dir.join(format!("{}.desktop", APP_ID));
+    if fs::read_to_string(&path).is_ok_and(|existing| existing == entry) {
+        return;
+    }
+    let _ = fs::create_dir_all(&dir);
+    let _ = fs::write(&path, entry);
+}
+
+/// Opens with `xdg-open`, then `gio open`, and only then the `open` crate's guess. The next one is
+/// tried only if one isn't installed: wrappers like `kde-open` often start the handler and still
+/// exit with an error, and trying again would open the file twice.
+pub fn open(path: &Path) -> std::io::Result<()> {
+    if cfg!(target_os = "linux") {
+        for (program, args) in [("xdg-open", &[][..]), ("gio", &["open"][..])] {
+            if Command::new(program).args(args).arg(path).status().is_ok() {
+                add_recent(path);
+                return Ok(());
+            }
+        }
+    }
+    open::that(path)
+}
+
+/// Prefers `gio trash`, which also handles removable drives and mounted network shares,
+/// over the built-in freedesktop trash implementation.
+pub fn trash(path: &Path) -> Result<(), String> {
+    if cfg!(target_os = "linux") {
+        let trashed = Command::new("gio").arg("trash").arg(path).status();
+        if trashed.is_ok_and(|status| status.success()) {
+            return Ok(());
+        }
+    }
+    trash::delete(path).map_err(|err| err.to_string())
+}
+
+/// Records `path` in `recently-used.xbel` so other apps' "Recent" lists show it.
+pub fn add_recent(path: &Path) {
+    let (Some(data_dir), Some(uri)) = (dirs::data_dir(), file_uri(path)) else {
+        return;
+    };
+    let recent_path = data_dir.join("recently-used.xbel");
+    let mut content = fs::read_to_string(&recent_path).unwrap_or_else(|_| {
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<xbel version=\"1.0\"\n      \
+         xmlns:bookmark=\"http://www.freedesktop.org/standards/desktop-bookmarks\"\n      \
+         xmlns:mime=\"http://www.freedesktop.org/standards/shared-mime-info\"\n>\n</xbel>\n"
+            .to_string()
+    });
+
+    // Replace any existing bookmark for this file rather than adding a duplicate.
+    let href = format!("<bookmark href=\"{}\"", xml_escape(&uri));
+    if let Some(start) = content.find(&href) {
+        if let Some(end) = content[start..].find("</bookmark>") {
+            content.replace_range(start..start + end + "</bookmark>".len(), "");
+        }
+    }
+    let Some(insert_at) = content.rfind("</xbel>") else {
+        return;
+    };
+    let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.6fZ").to_string();
+    let bookmark = format!(
+        "  {href} added=\"{now}\" modified=\"{now}\" visited=\"{now}\">\n    <info>\n      \
+         <metadata owner=\"http://freedesktop.org\">\n        <mime:mime-type type=\"{mime}\"/>\n        \
+         <bookmark:applications>\n          <bookmark:application name=\"happ\" exec=\"&apos;happ %u&apos;\" \
+         modified=\"{now}\" count=\"1\"/>\n        </bookmark:applications>\n      </metadata>\n    </info>\n  \
+         </bookmark>\n",
+        mime = xml_escape(&mime_type(path)),
+    );
+    content.insert_str(insert_at, &bookmark);
+
+    let temp = recent_path.with_extension("xbel.tmp");
+    if fs::write(&temp, content).is_ok() {
+        let _ = fs::rename(&temp, &recent_path);
+    }
+}
+
+fn mime_type(path: &Path) -> String {
+    Command::new("xdg-mime")
+        .args(["query", "filetype"])
+        .arg(path)
+        .output()
+        .ok()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|mime| !mime.is_empty())
+        .unwrap_or_else(|| "application/octet-stream".to_string())
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
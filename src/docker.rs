@@ -0,0 +1,110 @@
+//! Docker containers and volumes through the `docker` command line, which talks to the daemon's
+//! API for us.
+
+use crate::remote::{self, RemoteEntry};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Where a volume is mounted inside the helper container that browses it.
+pub const VOLUME_MOUNT: &str = "/volume";
+/// A small image with `ls` and a shell, used to browse volumes.
+const HELPER_IMAGE: &str = "busybox";
+/// The helper exits on its own after this long, in case the app doesn't get to stop it.
+const HELPER_LIFETIME_SECS: &str = "3600";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Container {
+    pub id: String,
+    pub name: String,
+    pub image: String,
+    /// Set for the helper container started to browse this volume.
+    pub volume: Option<String>,
+}
+
+/// Running containers; none if Docker isn't installed or its daemon isn't running.
+pub fn containers() -> Vec<Container> {
+    let format = "{{.ID}}|{{.Names}}|{{.Image}}";
+    let Ok(output) = Command::new("docker").args(["ps", "--format", format]).output() else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.trim().splitn(3, '|');
+            Some(Container {
+                id: fields.next()?.to_string(),
+                name: fields.next()?.to_string(),
+                image: fields.next()?.to_string(),
+                volume: None,
+            })
+        })
+        // Volume helpers are an implementation detail.
+        .filter(|container| !container.name.starts_with("happ-volume-"))
+        .collect()
+}
+
+pub fn volumes() -> Vec<String> {
+    let Ok(output) = Command::new("docker").args(["volume", "ls", "--format", "{{.Name}}"]).output() else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout).lines().map(|line| line.trim().to_string()).collect()
+}
+
+/// Starts a throwaway container with `volume` mounted, so it can be listed and copied to and from
+/// like any other container.
+pub fn open_volume(volume: &str) -> Result<Container, String> {
+    let name = format!("happ-volume-{}-{}", std::process::id(), volume);
+    let mount = format!("{}:{}", volume, VOLUME_MOUNT);
+    let output = Command::new("docker")
+        .args(["run", "--detach", "--rm", "--name", &name, "--volume", &mount, HELPER_IMAGE])
+        .args(["sleep", HELPER_LIFETIME_SECS])
+        .output()
+        .map_err(|err| err.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(Container {
+        id: String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        name,
+        image: HELPER_IMAGE.to_string(),
+        volume: Some(volume.to_string()),
+    })
+}
+
+/// Stops a volume helper; `--rm` then removes it.
+pub fn close_volume(container: &Container) {
+    let _ = Command::new("docker").args(["stop", "--time", "0", &container.id]).output();
+}
+
+pub fn list(id: &str, path: &str) -> Result<Vec<RemoteEntry>, String> {
+    // Unlike `adb shell`, `docker exec` passes arguments straight through without a shell.
+    let target = format!("{}/", path.trim_end_matches('/'));
+    let output = Command::new("docker")
+        .args(["exec", id, "ls", "-la", &target])
+        .output()
+        .map_err(|err| err.to_string())?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(remote::parse_listing(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Copies container paths into a local folder; `docker cp` takes one at a time.
+pub fn pull(id: &str, paths: &[String], local_dir: &Path) -> Result<(), String> {
+    for path in paths {
+        let mut command = Command::new("docker");
+        command.arg("cp").arg(format!("{}:{}", id, path)).arg(local_dir);
+        remote::run(command)?;
+    }
+    Ok(())
+}
+
+/// Copies local paths into a folder in the container.
+pub fn push(id: &str, local: &[PathBuf], remote_dir: &str) -> Result<(), String> {
+    for path in local {
+        let mut command = Command::new("docker");
+        command.arg("cp").arg(path).arg(format!("{}:{}/", id, remote_dir.trim_end_matches('/')));
+        remote::run(command)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,406 @@
+//! Downloading files from the web, resuming where an earlier attempt stopped, and tar archives,
+//! which are checked for files they would replace before they're unpacked.
+
+use crate::file_system::ConflictResolution;
+use crate::sorter;
+use flate2::read::GzDecoder;
+use md5::{Digest, Md5};
+use sha2::{Sha256, Sha512};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often, in bytes received, progress is reported.
+const PROGRESS_INTERVAL: u64 = 256 * 1024;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+/// How long the server may go without sending anything before the download fails.
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+/// The compression of the tar archive at `url`, going by its extension; `None` if it isn't one.
+fn compression(url: &str) -> Option<Compression> {
+    let rest = url.strip_prefix("https://").or_else(|| url.strip_prefix("http://"))?;
+    let path = rest.split(['?', '#']).next().unwrap_or_default().to_lowercase();
+    if path.ends_with(".tar") {
+        Some(Compression::None)
+    } else if path.ends_with(".tar.gz") || path.ends_with(".tgz") {
+        Some(Compression::Gzip)
+    } else if path.ends_with(".tar.zst") || path.ends_with(".tzst") {
+        Some(Compression::Zstd)
+    } else {
+        None
+    }
+}
+
+/// Whether `text` is the address of an archive that [`fetch_archive`] can download.
+pub fn is_archive_url(text: &str) -> bool {
+    compression(text.trim()).is_some()
+}
+
+/// Whether `text` is a single http(s) address.
+pub fn is_url(text: &str) -> bool {
+    let text = text.trim();
+    (text.starts_with("https://") || text.starts_with("http://")) && !text.contains(char::is_whitespace)
+}
+
+/// A name to save the download at `url` under: the last part of its path, decoded.
+pub fn file_name(url: &str) -> String {
+    let path = url.trim().split(['?', '#']).next().unwrap_or_default();
+    let path = path.split_once("://").map_or(path, |(_, rest)| rest);
+    let segment = path.split_once('/').map_or("", |(_, path)| path.rsplit('/').next().unwrap_or_default());
+    let mut bytes = Vec::with_capacity(segment.len());
+    let mut rest = segment.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let escaped = tail.get(..2).and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(decoded) if byte == b'%' => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    let name: String =
+        String::from_utf8_lossy(&bytes).chars().map(|c| if is_forbidden(c) { '_' } else { c }).collect();
+    if is_valid_name(&name) { name } else { "download".to_string() }
+}
+
+/// Whether a download can be saved under `name` in the folder it's meant for, on any system.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(is_forbidden)
+}
+
+/// Characters that would leave the folder or that some systems don't allow in names.
+fn is_forbidden(c: char) -> bool {
+    "/\\:*?\"<>|".contains(c) || c.is_control()
+}
+
+/// Where the ETag or modification date of a partial download is kept, so that it's only resumed
+/// while the file on the server is still the same one.
+fn validator_path(partial: &Path) -> PathBuf {
+    let mut name = partial.file_name().unwrap_or_default().to_os_string();
+    name.push(".validator");
+    partial.with_file_name(name)
+}
+
+/// A download in progress, told apart from others by its id so its progress goes to the right
+/// place, and stopped by setting `cancel`.
+#[derive(Clone)]
+pub struct DownloadJob {
+    pub id: u64,
+    pub cancel: Arc<AtomicBool>,
+}
+
+/// Where a download into `path` is kept until it finishes, and resumed from after a failure.
+pub fn partial_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    path.with_file_name(name)
+}
+
+fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new().timeout_connect(CONNECT_TIMEOUT).timeout_read(READ_TIMEOUT).build()
+}
+
+/// Counts the bytes read through it, reporting them with the total if the server gave one, and
+/// stops with an error once `cancel` is set.
+struct Progress<'a, R, F> {
+    inner: R,
+    received: u64,
+    reported: u64,
+    total: Option<u64>,
+    report: F,
+    cancel: &'a AtomicBool,
+}
+
+impl<R: Read, F: FnMut(u64, Option<u64>)> Read for Progress<'_, R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.cancel.load(Ordering::Relaxed) {
+            return Err(io::Error::other("cancelled"));
+        }
+        let read = self.inner.read(buf)?;
+        self.received += read as u64;
+        if self.received - self.reported >= PROGRESS_INTERVAL || read == 0 {
+            self.reported = self.received;
+            (self.report)(self.received, self.total);
+        }
+        Ok(read)
+    }
+}
+
+/// Downloads `url` to `path`, continuing a partial download if there is one, and checks the result
+/// against `checksum`, an MD5 or SHA-256 hex digest, if given. Setting `cancel` stops it, leaving
+/// what was received to resume from.
+pub fn fetch(
+    url: &str,
+    path: &Path,
+    checksum: Option<&str>,
+    cancel: &AtomicBool,
+    report: impl FnMut(u64, Option<u64>),
+) -> Result<(), String> {
+    let partial = partial_path(path);
+    let validator_file = validator_path(&partial);
+    let validator = fs::read_to_string(&validator_file).ok().filter(|validator| !validator.is_empty());
+    // Without a validator there's no telling whether the partial download is of the same file.
+    let offset = match validator {
+        Some(_) => fs::metadata(&partial).map(|metadata| metadata.len()).unwrap_or(0),
+        None => 0,
+    };
+    let mut request = agent().get(url.trim());
+    if let Some(validator) = validator.filter(|_| offset > 0) {
+        request = request.set("Range", &format!("bytes={}-", offset)).set("If-Range", validator);
+    }
+    let response = match request.call() {
+        Err(ureq::Error::Status(416, response)) if offset > 0 => {
+            let length = response.header("Content-Range").and_then(|range| range.strip_prefix("bytes */"));
+            if length.and_then(|length| length.parse::<u64>().ok()) != Some(offset) {
+                // Not the file that was partly downloaded, so start over.
+                let _ = fs::remove_file(&validator_file);
+                return fetch(url, path, checksum, cancel, report);
+            }
+            // The partial download is already complete.
+            None
+        }
+        result => Some(result.map_err(|err| err.to_string())?),
+    };
+    if let Some(response) = response {
+        // Servers that ignore the range, or whose file changed, send the whole file again.
+        let resumed = response.status() == 206;
+        if !resumed {
+            // Weak ETags can't be used with If-Range.
+            let etag = response.header("ETag").filter(|etag| !etag.starts_with("W/"));
+            let validator = etag.or(response.header("Last-Modified")).unwrap_or_default();
+            fs::write(&validator_file, validator).map_err(|err| err.to_string())?;
+        }
+        let start = if resumed { offset } else { 0 };
+        let length: Option<u64> = response.header("Content-Length").and_then(|length| length.parse().ok());
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(resumed)
+            .truncate(!resumed)
+            .open(&partial)
+            .map_err(|err| err.to_string())?;
+        let mut body = Progress {
+            inner: response.into_reader(),
+            received: start,
+            reported: start,
+            total: length.map(|length| start + length),
+            report,
+            cancel,
+        };
+        io::copy(&mut body, &mut file).map_err(|err| err.to_string())?;
+        file.flush().map_err(|err| err.to_string())?;
+    }
+    if let Some(expected) = checksum.map(str::trim).filter(|checksum| !checksum.is_empty()) {
+        let actual = digest(&partial, expected.len()).map_err(|err| err.to_string())?;
+        if !actual.eq_ignore_ascii_case(expected) {
+            // Resuming would only append to the bad data, so start over next time.
+            let _ = fs::remove_file(&partial);
+            let _ = fs::remove_file(&validator_file);
+            return Err(format!("the checksum doesn't match; got {}", actual));
+        }
+    }
+    fs::rename(&partial, path).map_err(|err| err.to_string())?;
+    let _ = fs::remove_file(&validator_file);
+    Ok(())
+}
+
+/// The hex digest of the file, using MD5, SHA-256 or SHA-512 depending on the expected digest's
+/// length.
+pub fn digest(path: &Path, hex_digits: usize) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    match hex_digits {
+        32 => {
+            let mut hasher = Md5::new();
+            io::copy(&mut file, &mut hasher)?;
+            Ok(format!("{:x}", hasher.finalize()))
+        }
+        64 => {
+            let mut hasher = Sha256::new();
+            io::copy(&mut file, &mut hasher)?;
+            Ok(format!("{:x}", hasher.finalize()))
+        }
+        128 => {
+            let mut hasher = Sha512::new();
+            io::copy(&mut file, &mut hasher)?;
+            Ok(format!("{:x}", hasher.finalize()))
+        }
+        _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "the checksum isn't an MD5 or SHA digest")),
+    }
+}
+
+/// A tar archive downloaded to a temporary file, waiting to be unpacked into `dir`.
+#[derive(Clone)]
+pub struct DownloadedArchive {
+    pub file: PathBuf,
+    compression: Compression,
+    pub dir: PathBuf,
+    /// Entries that would replace files already in `dir`, relative to it.
+    pub conflicts: Vec<PathBuf>,
+}
+
+/// Downloads the archive at `url` to be unpacked into `dir`, calling `report` with the bytes
+/// received so far and the download's size, when known, until `cancel` is set.
+pub fn fetch_archive(
+    url: &str,
+    dir: &Path,
+    cancel: &AtomicBool,
+    report: impl FnMut(u64, Option<u64>),
+) -> Result<DownloadedArchive, String> {
+    let url = url.trim();
+    let compression = compression(url).ok_or_else(|| "not a tar archive address".to_string())?;
+    let response = agent().get(url).call().map_err(|err| err.to_string())?;
+    let total = response.header("Content-Length").and_then(|length| length.parse().ok());
+    let mut body = Progress {
+        inner: response.into_reader(),
+        received: 0,
+        reported: 0,
+        total,
+        report,
+        cancel,
+    };
+    let (file, mut temp) = create_temp(&file_name(url)).map_err(|err| err.to_string())?;
+    let mut archive = DownloadedArchive { file, compression, dir: dir.to_path_buf(), conflicts: Vec::new() };
+    let result = io::copy(&mut body, &mut temp).and_then(|_| find_conflicts(&archive));
+    match result {
+        Ok(conflicts) => {
+            archive.conflicts = conflicts;
+            Ok(archive)
+        }
+        Err(err) => {
+            let _ = fs::remove_file(&archive.file);
+            Err(err.to_string())
+        }
+    }
+}
+
+/// A new file in the temporary folder for a download named `name`. It's never one that was
+/// already there, such as another download of the same address or a link planted in its place.
+fn create_temp(name: &str) -> io::Result<(PathBuf, File)> {
+    let mut attempt = 0;
+    loop {
+        let path = std::env::temp_dir().join(format!("{}-{}-{}", std::process::id(), attempt, name));
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => attempt += 1,
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Unpacks the archive, doing what `resolutions` says with the entries that conflict and
+/// replacing files for those it doesn't mention. `back_up` is called with each file the user
+/// chose to replace first. The download is removed afterwards.
+pub fn unpack(
+    archive: &DownloadedArchive,
+    resolutions: &HashMap<PathBuf, ConflictResolution>,
+    back_up: impl FnMut(&Path) -> Result<(), String>,
+) -> Result<(), String> {
+    let result = unpack_entries(archive, resolutions, back_up).map_err(|err| err.to_string());
+    let _ = fs::remove_file(&archive.file);
+    result
+}
+
+fn unpack_entries(
+    archive: &DownloadedArchive,
+    resolutions: &HashMap<PathBuf, ConflictResolution>,
+    mut back_up: impl FnMut(&Path) -> Result<(), String>,
+) -> io::Result<()> {
+    let mut taken = HashSet::new();
+    for entry in open_archive(archive)?.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        match resolutions.get(&path) {
+            Some(ConflictResolution::Skip) => {}
+            Some(ConflictResolution::KeepBoth) => {
+                // `unpack` doesn't make the checks `unpack_in` does, so links, which could point
+                // anywhere, aren't kept alongside, and nothing goes through a folder that leads
+                // out of this one. Conflicts only ever name entries that stay inside it.
+                let kind = entry.header().entry_type();
+                if kind.is_symlink() || kind.is_hard_link() {
+                    continue;
+                }
+                let renamed = sorter::available(&archive.dir.join(&path), &taken);
+                let parent = renamed.parent().unwrap_or(&archive.dir).canonicalize()?;
+                if !parent.starts_with(archive.dir.canonicalize()?) {
+                    continue;
+                }
+                taken.insert(renamed.clone());
+                entry.unpack(&renamed)?;
+            }
+            Some(ConflictResolution::Replace) => {
+                back_up(&archive.dir.join(&path)).map_err(io::Error::other)?;
+                entry.unpack_in(&archive.dir)?;
+            }
+            // Skips entries that would land outside the folder.
+            None => {
+                entry.unpack_in(&archive.dir)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn find_conflicts(archive: &DownloadedArchive) -> io::Result<Vec<PathBuf>> {
+    let mut conflicts = Vec::new();
+    for entry in open_archive(archive)?.entries()? {
+        let entry = entry?;
+        let path = entry.path()?.into_owned();
+        let inside = path.components().all(|part| matches!(part, Component::Normal(_) | Component::CurDir));
+        // Folders that are already there are merged into.
+        let is_dir = entry.header().entry_type().is_dir();
+        if inside && !is_dir && archive.dir.join(&path).exists() && !conflicts.contains(&path) {
+            conflicts.push(path);
+        }
+    }
+    Ok(conflicts)
+}
+
+fn open_archive(archive: &DownloadedArchive) -> io::Result<tar::Archive<Box<dyn Read>>> {
+    let file = File::open(&archive.file)?;
+    let reader: Box<dyn Read> = match archive.compression {
+        Compression::None => Box::new(file),
+        Compression::Gzip => Box::new(GzDecoder::new(file)),
+        Compression::Zstd => Box::new(zstd::Decoder::new(file)?),
+    };
+    Ok(tar::Archive::new(reader))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_archive_urls() {
+        assert_eq!(compression("https://example.com/a.tar"), Some(Compression::None));
+        assert_eq!(compression("http://example.com/a.TGZ?x=1"), Some(Compression::Gzip));
+        assert_eq!(compression("https://example.com/a.tar.zst#top"), Some(Compression::Zstd));
+        assert_eq!(compression("https://example.com/a.zip"), None);
+        assert_eq!(compression("/home/me/a.tar"), None);
+    }
+
+    #[test]
+    fn names_downloads_after_the_url() {
+        assert_eq!(file_name("https://example.com/files/My%20Report.pdf?dl=1"), "My Report.pdf");
+        assert_eq!(file_name("https://example.com/"), "download");
+        assert_eq!(file_name("https://example.com"), "download");
+        assert_eq!(file_name("https://example.com/a%2Fb"), "a_b");
+        assert_eq!(file_name("https://example.com/C%3A%0Aevil"), "C__evil");
+        assert_eq!(file_name("https://example.com/%2E%2E"), "download");
+        assert!(!is_valid_name("..") && !is_valid_name("a:b") && is_valid_name("report.pdf"));
+    }
+}
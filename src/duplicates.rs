@@ -0,0 +1,182 @@
+//! Finding files with identical contents, and making copies share storage through reflinks on
+//! filesystems that support them (btrfs, XFS, APFS).
+
+use md5::{Digest, Md5};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Groups of two or more identical files under `root` with their size, most space wasted first.
+/// Only the filesystem `root` is on is searched, and hard-linked files are left out: they already
+/// share their storage, and replacing one would split it from its other names.
+pub fn find(root: &Path) -> Vec<(u64, Vec<PathBuf>)> {
+    type Found = (HashMap<u64, Vec<PathBuf>>, HashSet<(u64, u64)>);
+    fn walk(dir: &Path, device: Option<u64>, found: &mut Found) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            // `DirEntry::metadata` doesn't follow symlinks, so linked files aren't counted twice.
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                if device.is_none_or(|device| file_id(&metadata).0 == device) {
+                    walk(&entry.path(), device, found);
+                }
+            } else if metadata.is_file() && metadata.len() > 0 && link_count(&metadata) == 1 {
+                // The same file can still turn up twice through a bind mount.
+                if found.1.insert(file_id(&metadata)) || !cfg!(unix) {
+                    found.0.entry(metadata.len()).or_default().push(entry.path());
+                }
+            }
+        }
+    }
+
+    let device = fs::metadata(root).ok().map(|metadata| file_id(&metadata).0).filter(|_| cfg!(unix));
+    let mut found = (HashMap::new(), HashSet::new());
+    walk(root, device, &mut found);
+    let (by_size, _) = found;
+    let mut groups: Vec<(u64, Vec<PathBuf>)> = Vec::new();
+    // Only files that share a size can match, so most are never read.
+    for (size, paths) in by_size.into_iter().filter(|(_, paths)| paths.len() > 1) {
+        let mut by_hash: HashMap<Vec<u8>, Vec<PathBuf>> = HashMap::new();
+        for path in paths {
+            if let Ok(hash) = hash_file(&path) {
+                by_hash.entry(hash).or_default().push(path);
+            }
+        }
+        groups.extend(by_hash.into_values().filter(|paths| paths.len() > 1).map(|paths| (size, paths)));
+    }
+    for (_, paths) in &mut groups {
+        paths.sort();
+    }
+    groups.sort_by_key(|(size, paths)| std::cmp::Reverse(size * (paths.len() as u64 - 1)));
+    groups
+}
+
+#[cfg(unix)]
+fn file_id(metadata: &fs::Metadata) -> (u64, u64) {
+    use std::os::unix::fs::MetadataExt;
+    (metadata.dev(), metadata.ino())
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &fs::Metadata) -> (u64, u64) {
+    (0, 0)
+}
+
+#[cfg(unix)]
+fn link_count(metadata: &fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.nlink()
+}
+
+#[cfg(not(unix))]
+fn link_count(_metadata: &fs::Metadata) -> u64 {
+    1
+}
+
+fn hash_file(path: &Path) -> io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    let mut hasher = Md5::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(hasher.finalize().to_vec());
+        }
+        hasher.update(&buffer[..read]);
+    }
+}
+
+/// Byte-for-byte comparison, since a file may have changed since it was hashed.
+fn same_content(a: &Path, b: &Path) -> io::Result<bool> {
+    if fs::metadata(a)?.len() != fs::metadata(b)?.len() {
+        return Ok(false);
+    }
+    let (mut a, mut b) = (File::open(a)?, File::open(b)?);
+    let (mut buffer_a, mut buffer_b) = (vec![0; 64 * 1024], vec![0; 64 * 1024]);
+    loop {
+        let read = a.read(&mut buffer_a)?;
+        if read == 0 {
+            return Ok(true);
+        }
+        b.read_exact(&mut buffer_b[..read])?;
+        if buffer_a[..read] != buffer_b[..read] {
+            return Ok(false);
+        }
+    }
+}
+
+/// Whether this platform has a way to make reflinks at all; the filesystem may still refuse.
+pub fn reflinks_supported() -> bool {
+    cfg!(any(target_os = "linux", target_os = "macos"))
+}
+
+/// Replaces each copy of `original` with a reflink to it, keeping the copy's permissions and
+/// times. Returns the bytes now shared and the copies that were left alone, with why.
+pub fn reflink_copies(original: &Path, copies: &[PathBuf]) -> (u64, Vec<(PathBuf, String)>) {
+    let mut shared = 0;
+    let mut failed = Vec::new();
+    for copy in copies {
+        match reflink_over(original, copy) {
+            Ok(size) => shared += size,
+            Err(err) => failed.push((copy.clone(), err)),
+        }
+    }
+    (shared, failed)
+}
+
+fn reflink_over(original: &Path, copy: &Path) -> Result<u64, String> {
+    if !same_content(original, copy).map_err(|err| err.to_string())? {
+        return Err("the file changed since it was scanned".to_string());
+    }
+    let name = copy.file_name().unwrap_or_default().to_string_lossy();
+    let temp = copy.with_file_name(format!(".{}.reflink", name));
+    // Clone into a temporary next to the copy so a failure never leaves it half written.
+    let clone_flag = if cfg!(target_os = "macos") { "-c" } else { "--reflink=always" };
+    let cloned = Command::new("cp")
+        .arg(clone_flag)
+        .arg(original)
+        .arg(&temp)
+        .status()
+        .is_ok_and(|status| status.success());
+    if !cloned {
+        let _ = fs::remove_file(&temp);
+        return Err("this filesystem doesn't support reflinks".to_string());
+    }
+    let metadata = fs::metadata(copy).map_err(|err| err.to_string())?;
+    copy_attributes(copy, &temp, &metadata)
+        .and_then(|_| fs::rename(&temp, copy).map_err(|err| err.to_string()))
+        .inspect_err(|_| {
+            let _ = fs::remove_file(&temp);
+        })?;
+    Ok(metadata.len())
+}
+
+/// Gives the clone the copy's owner, permissions, times and, on Linux, extended attributes, so
+/// that replacing the copy changes nothing but where its data is kept.
+fn copy_attributes(copy: &Path, temp: &Path, metadata: &fs::Metadata) -> Result<(), String> {
+    if cfg!(target_os = "linux") {
+        let copied = Command::new("cp")
+            .args(["--attributes-only", "--preserve=mode,ownership,timestamps,xattr"])
+            .arg(copy)
+            .arg(temp)
+            .status()
+            .is_ok_and(|status| status.success());
+        return copied.then_some(()).ok_or_else(|| "couldn't keep the copy's owner and attributes".to_string());
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        std::os::unix::fs::chown(temp, Some(metadata.uid()), Some(metadata.gid()))
+            .map_err(|err| format!("couldn't keep the copy's owner: {}", err))?;
+    }
+    if let Ok(modified) = metadata.modified() {
+        let _ = File::options().write(true).open(temp).and_then(|file| file.set_modified(modified));
+    }
+    fs::set_permissions(temp, metadata.permissions()).map_err(|err| err.to_string())
+}
@@ -0,0 +1,143 @@
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const ELEVATED_FLAG: &str = "--elevated-op";
+
+/// A file operation that can be re-run by a privileged copy of this executable.
+#[derive(Clone)]
+pub enum ElevatedOperation {
+    Delete(Vec<PathBuf>),
+    Copy(PathBuf, PathBuf),
+    Move(PathBuf, PathBuf),
+}
+
+impl ElevatedOperation {
+    pub fn from_args(args: &[OsString]) -> Option<Self> {
+        if args.first()?.to_str()? != ELEVATED_FLAG {
+            return None;
+        }
+        let name = args.get(1)?.to_str()?;
+        let paths: Vec<PathBuf> = args[2..].iter().map(PathBuf::from).collect();
+        match name {
+            "delete" => Some(ElevatedOperation::Delete(paths)),
+            "copy" if paths.len() == 2 => Some(ElevatedOperation::Copy(paths[0].clone(), paths[1].clone())),
+            "move" if paths.len() == 2 => Some(ElevatedOperation::Move(paths[0].clone(), paths[1].clone())),
+            _ => None,
+        }
+    }
+
+    fn to_args(&self) -> Vec<OsString> {
+        let (name, paths): (&str, Vec<&Path>) = match self {
+            ElevatedOperation::Delete(paths) => ("delete", paths.iter().map(PathBuf::as_path).collect()),
+            ElevatedOperation::Copy(from, to) => ("copy", vec![from, to]),
+            ElevatedOperation::Move(from, to) => ("move", vec![from, to]),
+        };
+        let mut args = vec![OsString::from(ELEVATED_FLAG), OsString::from(name)];
+        args.extend(paths.into_iter().map(|path| path.as_os_str().to_os_string()));
+        args
+    }
+
+    /// The folder whose listing changes once the operation is done.
+    pub fn affected_dir(&self) -> Option<PathBuf> {
+        match self {
+            ElevatedOperation::Delete(paths) => paths.first()?.parent().map(Path::to_path_buf),
+            ElevatedOperation::Copy(_, to) | ElevatedOperation::Move(_, to) => to.parent().map(Path::to_path_buf),
+        }
+    }
+
+    /// Performs the operation in the current (already elevated) process.
+    pub fn run(&self) -> io::Result<()> {
+        match self {
+            ElevatedOperation::Delete(paths) => {
+                for path in paths {
+                    if fs::symlink_metadata(path)?.is_dir() {
+                        fs::remove_dir_all(path)?;
+                    } else {
+                        fs::remove_file(path)?;
+                    }
+                }
+                Ok(())
+            }
+            // Runs as root with nobody asked, so copies and moves go to exactly `to` and never
+            // replace what's there.
+            ElevatedOperation::Copy(from, to) => {
+                refuse_existing(to)?;
+                if from.is_dir() {
+                    fs::create_dir(to)?;
+                    let mut options = fs_extra::dir::CopyOptions::new();
+                    options.content_only = true;
+                    fs_extra::dir::copy(from, to, &options).map_err(|err| io::Error::other(err.to_string()))?;
+                } else {
+                    let mut source = fs::File::open(from)?;
+                    let mut target = fs::OpenOptions::new().write(true).create_new(true).open(to)?;
+                    io::copy(&mut source, &mut target)?;
+                    target.set_permissions(source.metadata()?.permissions())?;
+                }
+                Ok(())
+            }
+            ElevatedOperation::Move(from, to) => {
+                refuse_existing(to)?;
+                fs::rename(from, to)
+            }
+        }
+    }
+}
+
+fn refuse_existing(path: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(_) => Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} already exists", path.display()))),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Re-launches this executable through the platform's elevation prompt to run `operation`,
+/// waiting for it to finish.
+#[cfg(not(windows))]
+pub fn run_elevated(operation: &ElevatedOperation) -> io::Result<bool> {
+    let status = Command::new("pkexec").arg(std::env::current_exe()?).args(operation.to_args()).status()?;
+    Ok(status.success())
+}
+
+/// The paths reach PowerShell through the environment rather than the script, so no file name
+/// can end a quoted string early.
+#[cfg(windows)]
+pub fn run_elevated(operation: &ElevatedOperation) -> io::Result<bool> {
+    let arguments: Vec<String> = operation.to_args().iter().map(|arg| quote_argument(&arg.to_string_lossy())).collect();
+    let script = "$p = Start-Process -FilePath $env:FILE_MANAGER_EXE -ArgumentList $env:FILE_MANAGER_ARGS \
+                  -Verb RunAs -Wait -PassThru; exit $p.ExitCode";
+    let status = Command::new("powershell")
+        .args(["-NoProfile", "-Command", script])
+        .env("FILE_MANAGER_EXE", std::env::current_exe()?)
+        .env("FILE_MANAGER_ARGS", arguments.join(" "))
+        .status()?;
+    Ok(status.success())
+}
+
+/// Quotes an argument the way the Windows C runtime splits a command line back apart.
+#[cfg(windows)]
+fn quote_argument(arg: &str) -> String {
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                quoted.extend(std::iter::repeat_n('\\', backslashes * 2 + 1));
+                backslashes = 0;
+            }
+            _ => {
+                quoted.extend(std::iter::repeat_n('\\', backslashes));
+                backslashes = 0;
+            }
+        }
+        if c != '\\' {
+            quoted.push(c);
+        }
+    }
+    quoted.extend(std::iter::repeat_n('\\', backslashes * 2));
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,267 @@
+//! Text encoding and line ending detection, and conversion between them.
+
+use std::fs;
+use std::path::Path;
+
+/// How much of a file is looked at to guess its encoding.
+const SAMPLE_SIZE: usize = 64 * 1024;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Encoding {
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+    Latin1,
+}
+
+impl Encoding {
+    pub const ALL: [Encoding; 5] =
+        [Encoding::Utf8, Encoding::Utf8Bom, Encoding::Utf16Le, Encoding::Utf16Be, Encoding::Latin1];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf8Bom => "UTF-8 with BOM",
+            Encoding::Utf16Le => "UTF-16 LE",
+            Encoding::Utf16Be => "UTF-16 BE",
+            Encoding::Latin1 => "Latin-1",
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum LineEnding {
+    Lf,
+    CrLf,
+    Cr,
+}
+
+impl LineEnding {
+    pub const ALL: [LineEnding; 3] = [LineEnding::Lf, LineEnding::CrLf, LineEnding::Cr];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "LF",
+            LineEnding::CrLf => "CRLF",
+            LineEnding::Cr => "CR",
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct TextInfo {
+    pub encoding: Encoding,
+    pub has_bom: bool,
+    /// `None` for a single line; several kinds mixed show as the most common one.
+    pub line_ending: Option<LineEnding>,
+    pub mixed_line_endings: bool,
+}
+
+impl TextInfo {
+    /// E.g. "UTF-8, CRLF" or "UTF-16 LE with BOM, mixed (mostly LF)".
+    pub fn describe(&self) -> String {
+        let mut description = self.encoding.label().to_string();
+        if self.has_bom && self.encoding != Encoding::Utf8Bom {
+            description.push_str(" with BOM");
+        }
+        match (self.line_ending, self.mixed_line_endings) {
+            (Some(ending), true) => description.push_str(&format!(", mixed (mostly {})", ending.label())),
+            (Some(ending), false) => description.push_str(&format!(", {}", ending.label())),
+            (None, _) => {}
+        }
+        description
+    }
+}
+
+/// Guesses the encoding of `bytes`, or `None` if they don't look like text.
+pub fn detect(bytes: &[u8]) -> Option<Encoding> {
+    if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        return Some(Encoding::Utf8Bom);
+    }
+    if bytes.starts_with(&[0xFF, 0xFE]) {
+        return Some(Encoding::Utf16Le);
+    }
+    if bytes.starts_with(&[0xFE, 0xFF]) {
+        return Some(Encoding::Utf16Be);
+    }
+    // Without a BOM, mostly-ASCII UTF-16 gives itself away by the zero byte in every other position.
+    let zeros_at = |parity: usize| bytes.iter().skip(parity).step_by(2).filter(|byte| **byte == 0).count();
+    let half = bytes.len() / 2;
+    if half > 0 && zeros_at(1) * 10 > half * 9 && zeros_at(0) == 0 {
+        return Some(Encoding::Utf16Le);
+    }
+    if half > 0 && zeros_at(0) * 10 > half * 9 && zeros_at(1) == 0 {
+        return Some(Encoding::Utf16Be);
+    }
+    if bytes.contains(&0) {
+        return None;
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(_) => Some(Encoding::Utf8),
+        // The sample may have cut a character in half.
+        Err(err) if err.error_len().is_none() => Some(Encoding::Utf8),
+        Err(_) => Some(Encoding::Latin1),
+    }
+}
+
+pub fn decode(bytes: &[u8], encoding: Encoding) -> String {
+    let utf16 = |to_unit: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = bytes.chunks_exact(2).map(|pair| to_unit([pair[0], pair[1]])).collect();
+        String::from_utf16_lossy(&units).trim_start_matches('\u{FEFF}').to_string()
+    };
+    match encoding {
+        Encoding::Utf8 | Encoding::Utf8Bom => {
+            String::from_utf8_lossy(bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(bytes)).into_owned()
+        }
+        Encoding::Utf16Le => utf16(u16::from_le_bytes),
+        Encoding::Utf16Be => utf16(u16::from_be_bytes),
+        Encoding::Latin1 => bytes.iter().map(|byte| char::from(*byte)).collect(),
+    }
+}
+
+/// Like `decode`, but fails rather than replace bytes that aren't valid in `encoding`.
+fn decode_exact(bytes: &[u8], encoding: Encoding) -> Result<String, String> {
+    let invalid = || format!("the file isn't valid {}", encoding.label());
+    let utf16 = |to_unit: fn([u8; 2]) -> u16| {
+        if bytes.len() % 2 != 0 {
+            return Err(invalid());
+        }
+        let units: Vec<u16> = bytes.chunks_exact(2).map(|pair| to_unit([pair[0], pair[1]])).collect();
+        let text = String::from_utf16(&units).map_err(|_| invalid())?;
+        Ok(text.trim_start_matches('\u{FEFF}').to_string())
+    };
+    match encoding {
+        Encoding::Utf8 | Encoding::Utf8Bom => {
+            let bytes = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(bytes);
+            String::from_utf8(bytes.to_vec()).map_err(|_| invalid())
+        }
+        Encoding::Utf16Le => utf16(u16::from_le_bytes),
+        Encoding::Utf16Be => utf16(u16::from_be_bytes),
+        Encoding::Latin1 => Ok(decode(bytes, encoding)),
+    }
+}
+
+/// Encodes `text`, with a BOM for UTF-16; fails on characters Latin-1 can't hold.
+pub fn encode(text: &str, encoding: Encoding) -> Result<Vec<u8>, String> {
+    Ok(match encoding {
+        Encoding::Utf8 => text.as_bytes().to_vec(),
+        Encoding::Utf8Bom => [&[0xEF, 0xBB, 0xBF], text.as_bytes()].concat(),
+        Encoding::Utf16Le => "\u{FEFF}".encode_utf16().chain(text.encode_utf16()).flat_map(u16::to_le_bytes).collect(),
+        Encoding::Utf16Be => "\u{FEFF}".encode_utf16().chain(text.encode_utf16()).flat_map(u16::to_be_bytes).collect(),
+        Encoding::Latin1 => text
+            .chars()
+            .map(|c| u8::try_from(u32::from(c)).map_err(|_| format!("'{}' can't be written as Latin-1", c)))
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+fn count_line_endings(text: &str) -> Vec<(usize, LineEnding)> {
+    let crlf = text.matches("\r\n").count();
+    let lf = text.matches('\n').count() - crlf;
+    let cr = text.matches('\r').count() - crlf;
+    [(crlf, LineEnding::CrLf), (lf, LineEnding::Lf), (cr, LineEnding::Cr)]
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .collect()
+}
+
+pub fn detect_line_ending(text: &str) -> Option<LineEnding> {
+    count_line_endings(text).into_iter().max_by_key(|(count, _)| *count).map(|(_, ending)| ending)
+}
+
+pub fn convert_line_endings(text: &str, ending: LineEnding) -> String {
+    text.replace("\r\n", "\n").replace('\r', "\n").replace('\n', ending.as_str())
+}
+
+/// Looks at the start of a file; `None` if it can't be read or isn't text.
+pub fn inspect(path: &Path) -> Option<TextInfo> {
+    use std::io::Read;
+    let mut sample = Vec::with_capacity(SAMPLE_SIZE);
+    fs::File::open(path).ok()?.take(SAMPLE_SIZE as u64).read_to_end(&mut sample).ok()?;
+    let encoding = detect(&sample)?;
+    let text = decode(&sample, encoding);
+    let boms: [&[u8]; 3] = [&[0xEF, 0xBB, 0xBF], &[0xFF, 0xFE], &[0xFE, 0xFF]];
+    Some(TextInfo {
+        encoding,
+        has_bom: boms.iter().any(|bom| sample.starts_with(bom)),
+        line_ending: detect_line_ending(&text),
+        mixed_line_endings: count_line_endings(&text).len() > 1,
+    })
+}
+
+/// Rewrites a text file in `encoding`, also changing its line endings if `line_ending` is given.
+/// A link is left in place and the file it points to is rewritten, keeping its permissions and owner.
+pub fn convert_file(path: &Path, encoding: Encoding, line_ending: Option<LineEnding>) -> Result<(), String> {
+    let path = fs::canonicalize(path).map_err(|err| err.to_string())?;
+    let metadata = fs::metadata(&path).map_err(|err| err.to_string())?;
+    let bytes = fs::read(&path).map_err(|err| err.to_string())?;
+    let from = detect(&bytes).ok_or_else(|| "not a text file".to_string())?;
+    let mut text = decode_exact(&bytes, from)?;
+    if let Some(ending) = line_ending {
+        text = convert_line_endings(&text, ending);
+    }
+    let converted = encode(&text, encoding)?;
+    let temp = path.with_extension("encoding.tmp");
+    fs::write(&temp, converted)
+        .and_then(|()| keep_owner(&temp, &metadata))
+        .and_then(|()| fs::set_permissions(&temp, metadata.permissions()))
+        .and_then(|()| fs::rename(&temp, &path))
+        .map_err(|err| {
+            let _ = fs::remove_file(&temp);
+            err.to_string()
+        })
+}
+
+#[cfg(unix)]
+fn keep_owner(temp: &Path, metadata: &fs::Metadata) -> std::io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+    std::os::unix::fs::chown(temp, Some(metadata.uid()), Some(metadata.gid()))
+}
+
+#[cfg(not(unix))]
+fn keep_owner(_temp: &Path, _metadata: &fs::Metadata) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_boms_and_plain_text() {
+        assert_eq!(detect(b"\xEF\xBB\xBFhi"), Some(Encoding::Utf8Bom));
+        assert_eq!(detect("héllo".as_bytes()), Some(Encoding::Utf8));
+        assert_eq!(detect(b"h\xE9llo"), Some(Encoding::Latin1));
+        assert_eq!(detect(b"h\0i\0"), Some(Encoding::Utf16Le));
+        assert_eq!(detect(b"\x7FELF\0\x02\x01"), None);
+    }
+
+    #[test]
+    fn round_trips_through_each_encoding() {
+        for encoding in Encoding::ALL {
+            let bytes = encode("héllo\r\nworld", encoding).unwrap();
+            assert_eq!(detect(&bytes), Some(encoding));
+            assert_eq!(decode(&bytes, encoding), "héllo\r\nworld");
+        }
+        assert!(encode("€", Encoding::Latin1).is_err());
+        assert!(decode_exact(b"\xFF\xFEh\0\0\xD8", Encoding::Utf16Le).is_err());
+        assert!(decode_exact(b"h\xC3", Encoding::Utf8).is_err());
+    }
+
+    #[test]
+    fn converts_mixed_line_endings() {
+        assert_eq!(detect_line_ending("a\r\nb\r\nc\n"), Some(LineEnding::CrLf));
+        assert_eq!(count_line_endings("a\r\nb\r\nc\n").len(), 2);
+        assert_eq!(convert_line_endings("a\r\nb\rc\n", LineEnding::Lf), "a\nb\nc\n");
+        assert_eq!(convert_line_endings("a\nb", LineEnding::CrLf), "a\r\nb");
+    }
+}
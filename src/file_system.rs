@@ -1,112 +1,814 @@
+use crate::adb::{self, AdbDevice};
+use crate::archive::{self, ArchiveOptions};
+use crate::checksums::{self, Reference, Verification};
+use crate::cloud;
+use crate::color_rules::ColorCondition;
+use crate::compare::{self, ComparisonImages};
+use crate::config::OverwriteBackup;
+use crate::daemon::{self, Job};
+use crate::desktop;
+use crate::docker::{self, Container};
+use crate::download::{self, DownloadJob, DownloadedArchive};
+use crate::duplicates;
+use crate::elevation::{self, ElevatedOperation};
+use crate::encoding::{self, Encoding, LineEnding, TextInfo};
+use crate::journal::{self, JournalEntry, TransferKind};
+use crate::listing_cache::ListingCache;
+use crate::locks;
+use crate::mail;
+use crate::music;
+use crate::power::{self, BatteryPolicy, Throttle};
+use crate::open_with;
+use crate::peers::{self, Peer};
+use crate::photos::{self, PhotoImport};
+use crate::profiling::{self, Stage};
+use crate::remote::{Remote, RemoteEntry};
+use crate::shortcuts::{self, Shortcut};
+use crate::sorter::{self, PlannedMove, SortOutcome, SortRules};
+use crate::system_clipboard;
+use crate::templates;
+use crate::thumbnails::{self, Thumbnail, ThumbnailSize};
+use crate::versions::{self, DiffLine, Version};
 use chrono::{DateTime, Utc};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{self, AtomicBool};
 use std::sync::mpsc::{Receiver, Sender, TryRecvError};
-use std::time::SystemTime;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 use tokio::task;
 
-#[derive(Debug, Clone)]
+/// Extensions browsers and download clients give files that are still downloading.
+const PARTIAL_DOWNLOAD_EXTENSIONS: [&str; 7] =
+    ["part", "partial", "crdownload", "download", "opdownload", "!ut", "!qb"];
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct FileSystemItem {
     pub path: PathBuf,
     pub is_dir: bool,
     pub size: u64,
+    /// Space taken on disk, which differs from `size` for sparse, compressed and tiny files.
+    pub allocated_size: u64,
     pub modified: SystemTime,
+    pub created: Option<SystemTime>,
     pub is_hidden: bool,
+    pub is_symlink: bool,
+    pub is_readable: bool,
+    pub is_placeholder: bool,
+    pub is_executable: bool,
+    /// E.g. "rwxr-xr-x" on Unix, or "Read-only" on Windows.
+    pub permissions: Option<String>,
+    pub owner: Option<String>,
+    pub metadata_loaded: bool,
+    pub shortcut: Option<Shortcut>,
+}
+
+impl FileSystemItem {
+    pub fn shown_size(&self, on_disk: bool) -> u64 {
+        if on_disk { self.allocated_size } else { self.size }
+    }
+
+    /// The name to show, which for launchers and shortcuts is their own display name.
+    pub fn display_name(&self) -> String {
+        self.shortcut
+            .as_ref()
+            .and_then(|shortcut| shortcut.name.clone())
+            .unwrap_or_else(|| self.path.file_name().unwrap_or_default().to_string_lossy().to_string())
+    }
+
+    /// Judged by the extension; Safari's `.download` bundles are folders, so folders count too.
+    pub fn is_partial_download(&self) -> bool {
+        let extension = self.path.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+        PARTIAL_DOWNLOAD_EXTENSIONS.iter().any(|partial| partial.eq_ignore_ascii_case(extension))
+    }
+
+    pub fn type_description(&self) -> String {
+        if self.is_partial_download() {
+            return "Partial Download".to_string();
+        }
+        if self.is_dir {
+            return "Folder".to_string();
+        }
+        if let Some(shortcut) = &self.shortcut {
+            return if shortcut.url().is_some() { "Internet Shortcut" } else { "Shortcut" }.to_string();
+        }
+        let extension = self.path.extension().map(|ext| ext.to_string_lossy().to_uppercase());
+        match (extension, self.is_executable) {
+            (Some(ext), _) => format!("{} file", ext),
+            (None, true) => "Program".to_string(),
+            (None, false) => "File".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DiskSpace {
+    pub mount_point: PathBuf,
+    pub free: u64,
+    pub total: u64,
+}
+
+/// A drive or mounted volume listed under Devices in the sidebar.
+#[derive(Debug, Clone)]
+pub struct Mount {
+    pub name: String,
+    pub space: DiskSpace,
+    /// Removable media and network shares, as opposed to the system drive.
+    pub removable: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ListingDelta {
+    pub added: Vec<FileSystemItem>,
+    pub removed: Vec<PathBuf>,
+    pub updated: Vec<FileSystemItem>,
+}
+
+impl ListingDelta {
+    pub fn between(old: &[FileSystemItem], new: &[FileSystemItem]) -> Self {
+        let old_by_path: HashMap<&Path, &FileSystemItem> = old.iter().map(|item| (item.path.as_path(), item)).collect();
+        let new_paths: HashSet<&Path> = new.iter().map(|item| item.path.as_path()).collect();
+
+        let mut delta = ListingDelta::default();
+        for item in new {
+            match old_by_path.get(item.path.as_path()) {
+                None => delta.added.push(item.clone()),
+                Some(old_item) if *old_item != item => delta.updated.push(item.clone()),
+                Some(_) => {}
+            }
+        }
+        delta.removed = old
+            .iter()
+            .filter(|item| !new_paths.contains(item.path.as_path()))
+            .map(|item| item.path.clone())
+            .collect();
+        delta
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.updated.is_empty()
+    }
+
+    pub fn apply(self, items: &mut Vec<FileSystemItem>) {
+        let removed: HashSet<PathBuf> = self.removed.into_iter().collect();
+        items.retain(|item| !removed.contains(&item.path));
+        for updated in self.updated {
+            if let Some(item) = items.iter_mut().find(|item| item.path == updated.path) {
+                *item = updated;
+            }
+        }
+        items.extend(self.added);
+    }
+}
+
+/// What to do about a file that's in the way of a paste or an extraction.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ConflictResolution {
+    Replace,
+    Skip,
+    /// Keeps the existing file and gives the new one a numbered name.
+    KeepBoth,
+}
+
+pub enum FileSystemResponse {
+    Listing(PathBuf, Vec<FileSystemItem>),
+    ListingChanged(PathBuf, ListingDelta),
+    Children(PathBuf, Vec<FileSystemItem>),
+    ChildCount(PathBuf, usize),
+    DiskSpace(DiskSpace),
+    /// `None` if they couldn't be listed in time, like when a network share stopped answering.
+    Mounts(Option<Vec<Mount>>),
+    DiskUsage(PathBuf, Vec<(PathBuf, u64)>),
+    DeleteProgress(usize, usize),
+    DeleteFinished(Vec<(PathBuf, String)>, bool),
+    OperationLocked(FileSystemEvent, PathBuf, Vec<String>),
+    OperationDenied(FileSystemEvent, PathBuf),
+    ElevatedFinished(bool),
+    /// A shortcut that could run a program, waiting to be confirmed before it's launched.
+    ConfirmLaunch(PathBuf, Shortcut),
+    LaunchRefused(PathBuf, String),
+    Jobs(Result<Vec<Job>, String>),
+    /// The daemon couldn't take the transfer, so it should happen in this process instead.
+    JobNotSubmitted(TransferKind, PathBuf, PathBuf, String),
+    Thumbnail(PathBuf, Option<Thumbnail>),
+    /// A copy or move to the given destination finished, after the given time.
+    TransferFinished(TransferKind, PathBuf, Result<(), String>, Duration),
+    LargestFiles(PathBuf, Vec<(PathBuf, u64)>),
+    /// Groups of identical files under the folder, with their size.
+    Duplicates(PathBuf, Vec<(u64, Vec<PathBuf>)>),
+    /// Bytes now shared through reflinks, and the copies that could not be replaced.
+    Deduplicated(u64, Vec<(PathBuf, String)>),
+    /// Items in a folder matching a cleanup condition, with folder sizes filled in.
+    CleanupCandidates(PathBuf, Vec<FileSystemItem>),
+    /// The archive that was asked for, and the files actually written for it.
+    Compressed(PathBuf, Result<Vec<PathBuf>, String>),
+    /// A download's id, the bytes downloaded so far, and the download's size if known.
+    DownloadProgress(u64, u64, Option<u64>),
+    /// The download with the id stopped, whether it finished, failed or was cancelled. What became
+    /// of it follows in `Downloaded`, `Extracted` or `ExtractionConflicts`.
+    DownloadEnded(u64),
+    Downloaded(PathBuf, Result<(), String>),
+    /// An archive finished downloading into the folder.
+    Extracted(PathBuf, Result<(), String>),
+    /// An archive downloaded but not unpacked, because it would replace files.
+    ExtractionConflicts(DownloadedArchive),
+    /// Bytes sent to the named peer so far, and the total.
+    PeerSendProgress(String, u64, u64),
+    SentToPeer(String, Result<(), String>),
+    /// The number of files handed to the mail client.
+    Emailed(usize, Result<(), String>),
+    /// The files on the desktop clipboard and whether they were cut, to paste into the folder.
+    ClipboardFiles(PathBuf, Option<(Vec<PathBuf>, bool)>),
+    ProjectCreated(String, Result<PathBuf, String>),
+    /// Where each file in the folder would go, for previewing a sort, with the request's number.
+    SortPlanned(PathBuf, Result<Vec<PlannedMove>, String>, u64),
+    Sorted(PathBuf, SortOutcome),
+    /// The files that couldn't be moved back.
+    SortUndone(Vec<(PathBuf, String)>),
+    /// The files in the folder that its checksum manifests and signatures vouch for.
+    ChecksumsRead(PathBuf, Vec<Reference>),
+    ChecksumVerified(PathBuf, Verification),
+    /// Where each tagged track in the library would go.
+    MusicPlanned(PathBuf, Result<Vec<PlannedMove>, String>),
+    /// Photos checked or copied so far, and the total.
+    PhotoImportProgress(usize, usize),
+    PhotoImportPlanned(Result<PhotoImport, String>),
+    /// The destination, how many photos were copied and the ones that couldn't be.
+    PhotosImported(PathBuf, usize, Vec<(PathBuf, String)>),
+    /// Whether the files were pinned or unpinned, and the ones that couldn't be.
+    KeptOnDeviceChanged(bool, Vec<(PathBuf, String)>),
+    ImagesCompared(PathBuf, PathBuf, Result<ComparisonImages, String>),
+    /// Each file's encoding and line endings, or `None` if it isn't text.
+    TextInspected(Vec<(PathBuf, Option<TextInfo>)>),
+    /// The files that could not be converted, with why.
+    TextConverted(usize, Vec<(PathBuf, String)>),
+    AdbDevices(Vec<AdbDevice>),
+    /// Running containers and the names of all volumes.
+    Containers(Vec<Container>, Vec<String>),
+    /// The helper container that mounts a volume, by volume name.
+    VolumeOpened(String, Result<Container, String>),
+    RemoteListing(Remote, String, Result<Vec<RemoteEntry>, String>),
+    RemoteTransferred(Result<(), String>),
+    /// Older versions of the path found in filesystem snapshots, newest first.
+    Versions(PathBuf, Vec<Version>),
+    VersionRestored(PathBuf, Result<(), String>),
+    /// An older version compared with the current file.
+    VersionDiff(PathBuf, Result<Vec<DiffLine>, String>),
 }
 
+#[derive(Clone)]
 pub enum FileSystemEvent {
     ListDirectory(PathBuf),
+    ListChildren(PathBuf),
+    Prefetch(Vec<PathBuf>),
+    CountChildren(PathBuf),
+    /// Stops without answering once the flag is set.
+    SearchDirectory(PathBuf, String, bool, Arc<AtomicBool>),
+    QueryDiskSpace(PathBuf),
+    ListMounts,
+    ListAdbDevices,
+    ListContainers,
+    OpenVolume(String),
+    CloseVolume(Container),
+    ListRemoteDirectory(Remote, String),
+    /// Copies remote paths into a local folder.
+    RemotePull(Remote, Vec<String>, PathBuf),
+    /// Copies local paths into a remote folder.
+    RemotePush(Remote, Vec<PathBuf>, String),
+    AnalyzeDiskUsage(PathBuf, Arc<AtomicBool>),
+    FindLargestFiles(PathBuf, usize),
+    FindDuplicates(PathBuf),
+    /// Replaces every file in each group but the first with a reflink to the first.
+    DeduplicateWithReflinks(Vec<Vec<PathBuf>>),
+    /// Zips the paths into the given archive, split or made self-extracting as the options say.
+    Compress(Vec<PathBuf>, PathBuf, ArchiveOptions),
+    /// Downloads the URL to the path, checking it against the MD5 or SHA-256 digest if given.
+    DownloadFile(DownloadJob, String, PathBuf, Option<String>),
+    /// Downloads the tar archive at the URL and unpacks it into the folder, unless that would
+    /// replace files.
+    DownloadArchive(DownloadJob, String, PathBuf),
+    /// Unpacks an archive that had conflicts once the user has said what to do about them.
+    UnpackArchive(DownloadedArchive, HashMap<PathBuf, ConflictResolution>),
+    DiscardArchive(DownloadedArchive),
+    /// Offers the paths to another copy of the app and sends them if accepted.
+    SendToPeer(Peer, Vec<PathBuf>),
+    /// Opens a new message in the default mail client with the files attached.
+    EmailFiles(Vec<PathBuf>),
+    /// Puts the files on the desktop clipboard, marked as cut if the flag is set.
+    CopyFilesToClipboard(Vec<PathBuf>, bool),
+    /// Reads the desktop clipboard's files to paste them into the folder.
+    ReadClipboardFiles(PathBuf),
+    /// Copies a project template into a new folder of the given name in the directory.
+    CreateFromTemplate(PathBuf, PathBuf, String),
+    PlanSort(PathBuf, SortRules, u64),
+    ApplySort(PathBuf, Vec<PlannedMove>),
+    /// Moves the files from a sort of the folder back, removing the folders it made.
+    UndoSort(PathBuf, SortOutcome),
+    ReadChecksums(PathBuf),
+    VerifyChecksums(Vec<Reference>),
+    /// Works out where the tracks in a library go by their tags, for the layout. The moves are
+    /// made with `ApplySort`.
+    PlanMusic(PathBuf, String),
+    /// Looks for new photos in the first folder to import into the second, in folders named by the
+    /// date layout. Both stop once the flag is set.
+    PlanPhotoImport(PathBuf, PathBuf, String, Arc<AtomicBool>),
+    ImportPhotos(PhotoImport, Arc<AtomicBool>),
+    /// Pins cloud files so their content stays downloaded, or unpins them to free the space.
+    SetKeptOnDevice(Vec<PathBuf>, bool),
+    /// Lists arbitrary paths as if they were the contents of the given folder.
+    ListPaths(PathBuf, Vec<PathBuf>),
+    /// Lists files under the roots modified since the given time, in place of the given folder.
+    ListModifiedSince(PathBuf, Vec<PathBuf>, SystemTime),
+    FindCleanupCandidates(PathBuf, ColorCondition),
+    CompareImages(PathBuf, PathBuf),
+    InspectText(Vec<PathBuf>),
+    /// Re-encodes text files, also changing their line endings if one is given.
+    ConvertText(Vec<PathBuf>, Encoding, Option<LineEnding>),
+    ListVersions(PathBuf),
+    /// Puts the older version back at the path, trashing the current one.
+    RestoreVersion(PathBuf, PathBuf),
+    DiffVersion(PathBuf, PathBuf),
     CreateFile(PathBuf),
     CreateFolder(PathBuf),
-    DeleteItem(PathBuf),
+    /// Writes a `.url` or `.webloc` file at the path pointing to the URL.
+    CreateInternetShortcut(PathBuf, String),
+    DeleteItems(Vec<PathBuf>, Arc<AtomicBool>),
+    TrashItem(PathBuf),
     RenameItem(PathBuf, PathBuf),
     CopyItem(PathBuf, PathBuf),
     MoveItem(PathBuf, PathBuf),
     OpenFile(PathBuf),
+    /// Launches a shortcut that could run a program, once the user has confirmed it.
+    LaunchShortcut(Shortcut),
     OpenTerminal(PathBuf),
-    NewWindow,
+    RunExecutable(PathBuf, bool),
+    OpenWith(String, PathBuf),
+    /// Opens a new window on the folder, optionally at a position in points.
+    NewWindowAt(PathBuf, Option<[f32; 2]>),
+    RunElevated(ElevatedOperation),
+    RollBackTransfer(JournalEntry),
+    SubmitJob(TransferKind, PathBuf, PathBuf),
+    QueryJobs,
+    SetBatteryPolicy(BatteryPolicy),
+    SetOverwriteBackup(OverwriteBackup),
+    LoadThumbnail(PathBuf, ThumbnailSize),
+    Shutdown,
 }
 
-pub async fn watch_directory(tx: Sender<Vec<FileSystemItem>>, rx: Receiver<FileSystemEvent>) {
+/// Places a new window, as `--window-pos=X,Y` in points.
+pub const WINDOW_POS_ARG: &str = "--window-pos=";
+
+pub async fn watch_directory(tx: Sender<FileSystemResponse>, rx: Receiver<FileSystemEvent>) {
+    let cache = ListingCache::default();
+    let throttle = Throttle::default();
+    let overwrite_backup = Arc::new(Mutex::new(OverwriteBackup::default()));
+    let mut in_flight: Vec<task::JoinHandle<()>> = Vec::new();
     loop {
         match rx.try_recv() {
+            Ok(FileSystemEvent::Shutdown) => break,
+            Ok(FileSystemEvent::SetBatteryPolicy(policy)) => throttle.set_policy(policy),
+            Ok(FileSystemEvent::SetOverwriteBackup(backup)) => *overwrite_backup.lock().unwrap() = backup,
             Ok(event) => {
                 let tx = tx.clone();
-                task::spawn(async move {
+                let cache = cache.clone();
+                let throttle = throttle.clone();
+                let backup = *overwrite_backup.lock().unwrap();
+                in_flight.retain(|handle| !handle.is_finished());
+                in_flight.push(task::spawn(async move {
+                    let retry = event.clone();
                     match event {
                         FileSystemEvent::ListDirectory(path) => {
-                            if let Ok(items) = list_directory(&path) {
-                                tx.send(items).unwrap();
+                            // Show whatever we had immediately, then only resend if the disk differs.
+                            let cached = cache.cached(&path);
+                            if let Some(items) = &cached {
+                                let _ = tx.send(FileSystemResponse::Listing(path.clone(), items.clone()));
+                            }
+                            if cached.is_some() {
+                                send_listing(&tx, &cache, &path);
+                            } else {
+                                profiling::time(&path, Stage::Listing, || list_progressively(&tx, &cache, &path));
+                            }
+                        }
+                        FileSystemEvent::ListChildren(path) => {
+                            // An unreadable folder still answers, so nothing waits on it forever.
+                            let items = cache.list(&path).unwrap_or_default();
+                            let _ = tx.send(FileSystemResponse::Children(path, items));
+                        }
+                        FileSystemEvent::Prefetch(paths) => {
+                            if throttle.pause_prefetch() {
+                                return;
+                            }
+                            for path in paths {
+                                let _ = cache.list(&path);
+                            }
+                        }
+                        FileSystemEvent::CountChildren(path) => {
+                            if let Ok(entries) = fs::read_dir(&path) {
+                                let _ = tx.send(FileSystemResponse::ChildCount(path, entries.count()));
+                            }
+                        }
+                        FileSystemEvent::QueryDiskSpace(path) => {
+                            if let Some(space) = disk_space(&path) {
+                                let _ = tx.send(FileSystemResponse::DiskSpace(space));
+                            }
+                        }
+                        FileSystemEvent::ListMounts => {
+                            let _ = tx.send(FileSystemResponse::Mounts(list_mounts()));
+                        }
+                        FileSystemEvent::ListAdbDevices => {
+                            let _ = tx.send(FileSystemResponse::AdbDevices(adb::devices()));
+                        }
+                        FileSystemEvent::ListContainers => {
+                            let _ = tx.send(FileSystemResponse::Containers(docker::containers(), docker::volumes()));
+                        }
+                        FileSystemEvent::OpenVolume(volume) => {
+                            let helper = docker::open_volume(&volume);
+                            let _ = tx.send(FileSystemResponse::VolumeOpened(volume, helper));
+                        }
+                        FileSystemEvent::CloseVolume(helper) => docker::close_volume(&helper),
+                        FileSystemEvent::ListRemoteDirectory(remote, path) => {
+                            let entries = remote.list(&path);
+                            let _ = tx.send(FileSystemResponse::RemoteListing(remote, path, entries));
+                        }
+                        FileSystemEvent::RemotePull(remote, paths, local_dir) => {
+                            let _slot = throttle.transfer_slot().await;
+                            let result = remote.pull(&paths, &local_dir);
+                            send_listing(&tx, &cache, &local_dir);
+                            let _ = tx.send(FileSystemResponse::RemoteTransferred(result));
+                        }
+                        FileSystemEvent::RemotePush(remote, local, remote_dir) => {
+                            let _slot = throttle.transfer_slot().await;
+                            let result = remote.push(&local, &remote_dir);
+                            let _ = tx.send(FileSystemResponse::RemoteTransferred(result));
+                        }
+                        FileSystemEvent::AnalyzeDiskUsage(path, cancel) => {
+                            if let Some(usage) = disk_usage(&path, &cancel) {
+                                let _ = tx.send(FileSystemResponse::DiskUsage(path, usage));
+                            }
+                        }
+                        FileSystemEvent::ListPaths(path, paths) => {
+                            let items = paths.iter().filter_map(|path| read_item(path).ok()).collect();
+                            let _ = tx.send(FileSystemResponse::Listing(path, items));
+                        }
+                        FileSystemEvent::ListModifiedSince(path, roots, since) => {
+                            let mut results = Vec::new();
+                            for root in &roots {
+                                modified_since(root, since, RECENT_MAX_DEPTH, &mut results);
+                            }
+                            // Roots can hold one another, like a favorite inside Documents.
+                            let mut seen = HashSet::new();
+                            results.retain(|item| seen.insert(item.path.clone()));
+                            results.sort_by(|a, b| b.modified.cmp(&a.modified));
+                            results.truncate(RECENT_MAX_RESULTS);
+                            let _ = tx.send(FileSystemResponse::Listing(path, results));
+                        }
+                        FileSystemEvent::Compress(paths, archive, options) => {
+                            let outputs = archive::existing_outputs(&archive, options);
+                            // Nothing is pasted over them, so there's no source to compare against.
+                            let result = match outputs.iter().find(|output| paths.contains(output)) {
+                                Some(output) => Err(format!("{} can't go into its own archive", output.display())),
+                                None => outputs
+                                    .iter()
+                                    .try_for_each(|output| back_up_existing(Path::new(""), output, backup)),
+                            }
+                            .and_then(|()| {
+                                archive::create(&paths, &archive, options).inspect_err(|_| {
+                                    let _ = fs::remove_file(&archive);
+                                })
+                            });
+                            if let Some(parent) = archive.parent() {
+                                send_listing(&tx, &cache, parent);
+                            }
+                            let _ = tx.send(FileSystemResponse::Compressed(archive, result));
+                        }
+                        FileSystemEvent::DownloadArchive(job, url, dir) => {
+                            let _awake = power::keep_awake();
+                            let downloaded = download::fetch_archive(&url, &dir, &job.cancel, |received, total| {
+                                let _ = tx.send(FileSystemResponse::DownloadProgress(job.id, received, total));
+                            });
+                            let _ = tx.send(FileSystemResponse::DownloadEnded(job.id));
+                            match downloaded {
+                                Ok(archive) if !archive.conflicts.is_empty() => {
+                                    let _ = tx.send(FileSystemResponse::ExtractionConflicts(archive));
+                                }
+                                downloaded => {
+                                    let unpack = |archive| download::unpack(&archive, &HashMap::new(), |_| Ok(()));
+                                    let result = downloaded.and_then(unpack);
+                                    send_listing(&tx, &cache, &dir);
+                                    let _ = tx.send(FileSystemResponse::Extracted(dir, result));
+                                }
+                            }
+                        }
+                        FileSystemEvent::UnpackArchive(archive, resolutions) => {
+                            let back_up = |target: &Path| back_up_existing(&archive.file, target, backup);
+                            let result = download::unpack(&archive, &resolutions, back_up);
+                            send_listing(&tx, &cache, &archive.dir);
+                            let _ = tx.send(FileSystemResponse::Extracted(archive.dir, result));
+                        }
+                        FileSystemEvent::DiscardArchive(archive) => {
+                            let _ = fs::remove_file(&archive.file);
+                        }
+                        FileSystemEvent::SendToPeer(peer, paths) => {
+                            // Waits up to minutes for an answer, so it's kept off the runtime's workers.
+                            let _ = task::spawn_blocking(move || {
+                                let _awake = power::keep_awake();
+                                let result = peers::send(&peer, &paths, |sent, total| {
+                                    let progress = FileSystemResponse::PeerSendProgress(peer.name.clone(), sent, total);
+                                    let _ = tx.send(progress);
+                                });
+                                let _ = tx.send(FileSystemResponse::SentToPeer(peer.name, result));
+                            })
+                            .await;
+                        }
+                        FileSystemEvent::CreateFromTemplate(template, dir, name) => {
+                            let result = templates::create_project(&template, &dir, &name);
+                            send_listing(&tx, &cache, &dir);
+                            let _ = tx.send(FileSystemResponse::ProjectCreated(name, result));
+                        }
+                        FileSystemEvent::PlanSort(dir, rules, generation) => {
+                            let plan = sorter::plan(&dir, &rules);
+                            let _ = tx.send(FileSystemResponse::SortPlanned(dir, plan, generation));
+                        }
+                        FileSystemEvent::ApplySort(dir, moves) => {
+                            let outcome = sorter::apply(&moves);
+                            send_listing(&tx, &cache, &dir);
+                            let _ = tx.send(FileSystemResponse::Sorted(dir, outcome));
+                        }
+                        FileSystemEvent::UndoSort(dir, outcome) => {
+                            let failed = sorter::undo(&outcome);
+                            send_listing(&tx, &cache, &dir);
+                            let _ = tx.send(FileSystemResponse::SortUndone(failed));
+                        }
+                        FileSystemEvent::ReadChecksums(dir) => {
+                            let references = checksums::read(&dir);
+                            let _ = tx.send(FileSystemResponse::ChecksumsRead(dir, references));
+                        }
+                        FileSystemEvent::VerifyChecksums(references) => {
+                            for reference in references {
+                                let verification = checksums::verify(&reference);
+                                let _ = tx.send(FileSystemResponse::ChecksumVerified(reference.file, verification));
+                            }
+                        }
+                        FileSystemEvent::PlanMusic(library, layout) => {
+                            let plan = music::plan(&library, &layout);
+                            let _ = tx.send(FileSystemResponse::MusicPlanned(library, plan));
+                        }
+                        FileSystemEvent::PlanPhotoImport(source, destination, layout, cancel) => {
+                            let result = photos::plan(&source, &destination, &layout, &cancel, |done, total| {
+                                let _ = tx.send(FileSystemResponse::PhotoImportProgress(done, total));
+                            });
+                            let _ = tx.send(FileSystemResponse::PhotoImportPlanned(result));
+                        }
+                        FileSystemEvent::ImportPhotos(import, cancel) => {
+                            let _slot = throttle.transfer_slot().await;
+                            let (copied, failed) = photos::import(&import, &cancel, |done, total| {
+                                let _ = tx.send(FileSystemResponse::PhotoImportProgress(done, total));
+                            });
+                            send_listing(&tx, &cache, &import.destination);
+                            let _ = tx.send(FileSystemResponse::PhotosImported(import.destination, copied, failed));
+                        }
+                        FileSystemEvent::SetKeptOnDevice(paths, keep) => {
+                            let failed: Vec<(PathBuf, String)> = paths
+                                .iter()
+                                .filter_map(|path| Some((path.clone(), cloud::set_kept_on_device(path, keep).err()?)))
+                                .collect();
+                            if let Some(parent) = paths.first().and_then(|path| path.parent()) {
+                                send_listing(&tx, &cache, parent);
+                            }
+                            let _ = tx.send(FileSystemResponse::KeptOnDeviceChanged(keep, failed));
+                        }
+                        FileSystemEvent::EmailFiles(paths) => {
+                            let result = mail::send(&paths);
+                            let _ = tx.send(FileSystemResponse::Emailed(paths.len(), result));
+                        }
+                        FileSystemEvent::CopyFilesToClipboard(paths, cut) => {
+                            system_clipboard::copy_files(&paths, cut);
+                        }
+                        FileSystemEvent::ReadClipboardFiles(dir) => {
+                            let _ = tx.send(FileSystemResponse::ClipboardFiles(dir, system_clipboard::read_files()));
+                        }
+                        FileSystemEvent::DownloadFile(job, url, path, checksum) => {
+                            let _awake = power::keep_awake();
+                            let report = |received, total| {
+                                let _ = tx.send(FileSystemResponse::DownloadProgress(job.id, received, total));
+                            };
+                            let result = download::fetch(&url, &path, checksum.as_deref(), &job.cancel, report);
+                            let _ = tx.send(FileSystemResponse::DownloadEnded(job.id));
+                            if let Some(parent) = path.parent() {
+                                send_listing(&tx, &cache, parent);
+                            }
+                            let _ = tx.send(FileSystemResponse::Downloaded(path, result));
+                        }
+                        FileSystemEvent::CompareImages(first, second) => {
+                            let result = compare::load_pair(&first, &second);
+                            let _ = tx.send(FileSystemResponse::ImagesCompared(first, second, result));
+                        }
+                        FileSystemEvent::InspectText(paths) => {
+                            let info = paths.into_iter().map(|path| (path.clone(), encoding::inspect(&path))).collect();
+                            let _ = tx.send(FileSystemResponse::TextInspected(info));
+                        }
+                        FileSystemEvent::ConvertText(paths, target, line_ending) => {
+                            let mut failed = Vec::new();
+                            for path in &paths {
+                                if let Err(err) = encoding::convert_file(path, target, line_ending) {
+                                    failed.push((path.clone(), err));
+                                }
+                            }
+                            let parents: HashSet<&Path> = paths.iter().filter_map(|path| path.parent()).collect();
+                            for parent in parents {
+                                send_listing(&tx, &cache, parent);
+                            }
+                            let _ = tx.send(FileSystemResponse::TextConverted(paths.len(), failed));
+                        }
+                        FileSystemEvent::ListVersions(path) => {
+                            let found = versions::list(&path);
+                            let _ = tx.send(FileSystemResponse::Versions(path, found));
+                        }
+                        FileSystemEvent::RestoreVersion(version, path) => {
+                            let result = versions::restore(&version, &path);
+                            if let Some(parent) = path.parent() {
+                                send_listing(&tx, &cache, parent);
+                            }
+                            let _ = tx.send(FileSystemResponse::VersionRestored(path, result));
+                        }
+                        FileSystemEvent::DiffVersion(version, path) => {
+                            let diff = versions::diff_files(&version, &path);
+                            let _ = tx.send(FileSystemResponse::VersionDiff(version, diff));
+                        }
+                        FileSystemEvent::FindDuplicates(path) => {
+                            let groups = duplicates::find(&path);
+                            let _ = tx.send(FileSystemResponse::Duplicates(path, groups));
+                        }
+                        FileSystemEvent::DeduplicateWithReflinks(groups) => {
+                            let (mut shared, mut failed) = (0, Vec::new());
+                            for group in &groups {
+                                let Some((original, copies)) = group.split_first() else {
+                                    continue;
+                                };
+                                let (group_shared, group_failed) = duplicates::reflink_copies(original, copies);
+                                shared += group_shared;
+                                failed.extend(group_failed);
+                            }
+                            let _ = tx.send(FileSystemResponse::Deduplicated(shared, failed));
+                        }
+                        FileSystemEvent::FindLargestFiles(path, count) => {
+                            let files = largest_files(&path, count);
+                            let _ = tx.send(FileSystemResponse::LargestFiles(path, files));
+                        }
+                        FileSystemEvent::FindCleanupCandidates(path, condition) => {
+                            let mut items = cache.list(&path).unwrap_or_default();
+                            items.retain(|item| !item.is_hidden && condition.matches(item));
+                            for item in items.iter_mut().filter(|item| item.is_dir) {
+                                item.size = directory_size(&item.path);
+                            }
+                            let _ = tx.send(FileSystemResponse::CleanupCandidates(path, items));
+                        }
+                        FileSystemEvent::SearchDirectory(path, query, extensions_only, cancel) => {
+                            let mut results = Vec::new();
+                            search_directory(&path, &query, extensions_only, &cancel, &mut results);
+                            if !cancel.load(atomic::Ordering::Relaxed) {
+                                let _ = tx.send(FileSystemResponse::Listing(path, results));
                             }
                         }
                         FileSystemEvent::CreateFile(path) => {
                             if fs::File::create(&path).is_ok() {
                                 if let Some(parent) = path.parent() {
-                                    if let Ok(items) = list_directory(parent) {
-                                        tx.send(items).unwrap();
-                                    }
+                                    send_listing(&tx, &cache, parent);
+                                }
+                            }
+                        }
+                        FileSystemEvent::CreateInternetShortcut(path, url) => {
+                            if shortcuts::write_internet_shortcut(&path, &url).is_ok() {
+                                if let Some(parent) = path.parent() {
+                                    send_listing(&tx, &cache, parent);
                                 }
                             }
                         }
                         FileSystemEvent::CreateFolder(path) => {
                             if fs::create_dir(&path).is_ok() {
                                 if let Some(parent) = path.parent() {
-                                    if let Ok(items) = list_directory(parent) {
-                                        tx.send(items).unwrap();
-                                    }
+                                    send_listing(&tx, &cache, parent);
                                 }
                             }
                         }
-                        FileSystemEvent::DeleteItem(path) => {
-                            let parent = path.parent().map(|p| p.to_path_buf());
-                            if path.is_dir() {
-                                let _ = fs::remove_dir_all(&path);
-                            } else {
-                                let _ = fs::remove_file(&path);
+                        FileSystemEvent::DeleteItems(paths, cancel) => {
+                            let mut parents: Vec<PathBuf> =
+                                paths.iter().filter_map(|path| path.parent()).map(Path::to_path_buf).collect();
+                            parents.dedup();
+                            let (failed, cancelled) = delete_trees(&paths, &cancel, &tx);
+                            let _ = tx.send(FileSystemResponse::DeleteFinished(failed, cancelled));
+                            for parent in parents {
+                                send_listing(&tx, &cache, &parent);
                             }
+                        }
+                        FileSystemEvent::TrashItem(path) => {
+                            let parent = path.parent().map(|p| p.to_path_buf());
+                            let _ = desktop::trash(&path);
                             if let Some(parent) = parent {
-                                if let Ok(items) = list_directory(&parent) {
-                                    tx.send(items).unwrap();
-                                }
+                                send_listing(&tx, &cache, &parent);
                             }
                         }
-                        FileSystemEvent::RenameItem(from, to) => {
-                            if fs::rename(&from, &to).is_ok() {
+                        FileSystemEvent::RenameItem(from, to) => match fs::rename(&from, &to) {
+                            Ok(()) => {
                                 if let Some(parent) = to.parent() {
-                                    if let Ok(items) = list_directory(parent) {
-                                        tx.send(items).unwrap();
-                                    }
+                                    send_listing(&tx, &cache, parent);
                                 }
                             }
-                        }
+                            Err(err) => report_locked(&tx, retry, &from, &err),
+                        },
                         FileSystemEvent::CopyItem(from, to) => {
                             let parent = to.parent().map(|p| p.to_path_buf());
-                            if from.is_dir() {
+                            let started = Instant::now();
+                            let _slot = throttle.transfer_slot().await;
+                            let _awake = power::keep_awake();
+                            if let Err(err) = back_up_existing(&from, &to, backup) {
+                                let _ = tx.send(FileSystemResponse::TransferFinished(
+                                    TransferKind::Copy,
+                                    to,
+                                    Err(err),
+                                    started.elapsed(),
+                                ));
+                                return;
+                            }
+                            let journal_id = journal::begin(TransferKind::Copy, &from, &to);
+                            let result = if from.is_dir() {
                                 let mut options = fs_extra::dir::CopyOptions::new();
                                 options.overwrite = true;
-                                let _ = fs_extra::dir::copy(&from, &to.parent().unwrap(), &options);
+                                fs_extra::dir::copy(&from, &to.parent().unwrap(), &options).map(|_| ()).map_err(|err| {
+                                    if let fs_extra::error::ErrorKind::PermissionDenied = err.kind {
+                                        let _ = tx.send(FileSystemResponse::OperationDenied(retry, from.clone()));
+                                    }
+                                    err.to_string()
+                                })
                             } else {
-                                let _ = fs::copy(&from, &to);
-                            }
+                                fs::copy(&from, &to).map(|_| ()).map_err(|err| {
+                                    report_locked(&tx, retry, &from, &err);
+                                    err.to_string()
+                                })
+                            };
+                            journal::finish(journal_id);
+                            let _ = tx.send(FileSystemResponse::TransferFinished(
+                                TransferKind::Copy,
+                                to.clone(),
+                                result,
+                                started.elapsed(),
+                            ));
                             if let Some(parent) = parent {
-                                if let Ok(items) = list_directory(&parent) {
-                                    tx.send(items).unwrap();
-                                }
+                                send_listing(&tx, &cache, &parent);
                             }
                         }
                         FileSystemEvent::MoveItem(from, to) => {
                             let parent = to.parent().map(|p| p.to_path_buf());
-                            if fs::rename(&from, &to).is_ok() {
-                                if let Some(parent) = parent {
-                                    if let Ok(items) = list_directory(&parent) {
-                                        tx.send(items).unwrap();
+                            let started = Instant::now();
+                            let _slot = throttle.transfer_slot().await;
+                            let _awake = power::keep_awake();
+                            if let Err(err) = back_up_existing(&from, &to, backup) {
+                                let _ = tx.send(FileSystemResponse::TransferFinished(
+                                    TransferKind::Move,
+                                    to,
+                                    Err(err),
+                                    started.elapsed(),
+                                ));
+                                return;
+                            }
+                            let journal_id = journal::begin(TransferKind::Move, &from, &to);
+                            let result = fs::rename(&from, &to);
+                            journal::finish(journal_id);
+                            let _ = tx.send(FileSystemResponse::TransferFinished(
+                                TransferKind::Move,
+                                to.clone(),
+                                result.as_ref().map(|_| ()).map_err(|err| err.to_string()),
+                                started.elapsed(),
+                            ));
+                            match result {
+                                Ok(()) => {
+                                    if let Some(parent) = parent {
+                                        send_listing(&tx, &cache, &parent);
                                     }
                                 }
+                                Err(err) => report_locked(&tx, retry, &from, &err),
                             }
                         }
-                        FileSystemEvent::OpenFile(path) => {
-                            let _ = open::that(&path);
+                        FileSystemEvent::OpenFile(path) => match shortcuts::read(&path) {
+                            Some(shortcut) if shortcut.runs_program() && !shortcuts::is_trusted(&path) => {
+                                let reason = "Launchers only run once they're marked executable".to_string();
+                                let _ = tx.send(FileSystemResponse::LaunchRefused(path, reason));
+                            }
+                            Some(shortcut) if shortcut.needs_confirmation() => {
+                                let _ = tx.send(FileSystemResponse::ConfirmLaunch(path, shortcut));
+                            }
+                            Some(shortcut) => {
+                                let _ = shortcuts::launch(&shortcut);
+                            }
+                            None => {
+                                let _ = desktop::open(&path);
+                            }
+                        },
+                        FileSystemEvent::LaunchShortcut(shortcut) => {
+                            let _ = shortcuts::launch(&shortcut);
                         }
                         FileSystemEvent::OpenTerminal(path) => {
                             if cfg!(target_os = "windows") {
@@ -115,6 +817,12 @@ pub async fn watch_directory(tx: Sender<Vec<FileSystemItem>>, rx: Receiver<FileS
                                     .current_dir(&path)
                                     .spawn()
                                     .expect("failed to open terminal");
+                            } else if cfg!(target_os = "macos") {
+                                Command::new("open")
+                                    .args(["-a", "Terminal"])
+                                    .arg(&path)
+                                    .spawn()
+                                    .expect("failed to open terminal");
                             } else {
                                 Command::new("gnome-terminal")
                                     .current_dir(&path)
@@ -122,11 +830,79 @@ pub async fn watch_directory(tx: Sender<Vec<FileSystemItem>>, rx: Receiver<FileS
                                     .expect("failed to open terminal");
                             }
                         }
-                        FileSystemEvent::NewWindow => {
-                            let _ = Command::new(std::env::current_exe().unwrap()).spawn();
+                        FileSystemEvent::OpenWith(command, folder) => {
+                            let mut parts = open_with::split_command(&command).into_iter();
+                            if let Some(program) = parts.next() {
+                                let _ = Command::new(program).args(parts).arg(&folder).spawn();
+                            }
+                        }
+                        FileSystemEvent::RunExecutable(path, in_terminal) => {
+                            let working_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+                            let mut command = if !in_terminal {
+                                Command::new(&path)
+                            } else if cfg!(target_os = "windows") {
+                                let mut command = Command::new("cmd");
+                                command.args(["/C", "start", "cmd", "/K"]).arg(&path);
+                                command
+                            } else if cfg!(target_os = "macos") {
+                                let mut command = Command::new("open");
+                                command.args(["-a", "Terminal"]).arg(&path);
+                                command
+                            } else {
+                                let mut command = Command::new("gnome-terminal");
+                                command.arg("--").arg(&path);
+                                command
+                            };
+                            let _ = command.current_dir(working_dir).spawn();
+                        }
+                        FileSystemEvent::RunElevated(operation) => {
+                            let succeeded = elevation::run_elevated(&operation).unwrap_or(false);
+                            let _ = tx.send(FileSystemResponse::ElevatedFinished(succeeded));
+                            if let Some(dir) = operation.affected_dir() {
+                                send_listing(&tx, &cache, &dir);
+                            }
+                        }
+                        FileSystemEvent::NewWindowAt(path, position) => {
+                            let mut command = Command::new(std::env::current_exe().unwrap());
+                            if let Some([x, y]) = position {
+                                command.arg(format!("{}{},{}", WINDOW_POS_ARG, x, y));
+                            }
+                            let _ = command.arg(&path).spawn();
+                        }
+                        FileSystemEvent::RollBackTransfer(entry) => {
+                            let _ = journal::roll_back(&entry);
+                            for dir in [entry.from.parent(), entry.to.parent()].into_iter().flatten() {
+                                send_listing(&tx, &cache, dir);
+                            }
+                        }
+                        FileSystemEvent::SubmitJob(kind, from, to) => {
+                            if let Err(err) = back_up_existing(&from, &to, backup) {
+                                let _ = tx.send(FileSystemResponse::Jobs(Err(err)));
+                            } else if fs::symlink_metadata(&to).is_ok() {
+                                // The daemon never replaces anything, so replacing is done here.
+                                let err = "it doesn't replace existing files".to_string();
+                                let _ = tx.send(FileSystemResponse::JobNotSubmitted(kind, from, to, err));
+                            } else if let Err(err) = daemon::submit(kind, &from, &to) {
+                                let _ = tx.send(FileSystemResponse::JobNotSubmitted(kind, from, to, err));
+                            } else {
+                                let _ = tx.send(FileSystemResponse::Jobs(daemon::jobs()));
+                            }
+                        }
+                        FileSystemEvent::QueryJobs => {
+                            let _ = tx.send(FileSystemResponse::Jobs(daemon::jobs()));
                         }
+                        FileSystemEvent::LoadThumbnail(path, size) => {
+                            // Reading a placeholder's content would download it.
+                            let placeholder = fs::metadata(&path).is_ok_and(|metadata| is_placeholder(&metadata));
+                            let thumbnail = if placeholder { None } else { thumbnails::get(&path, size) };
+                            let _ = tx.send(FileSystemResponse::Thumbnail(path, thumbnail));
+                        }
+                        // Handled by the loop above.
+                        FileSystemEvent::Shutdown
+                        | FileSystemEvent::SetBatteryPolicy(_)
+                        | FileSystemEvent::SetOverwriteBackup(_) => {}
                     }
-                });
+                }));
             }
             Err(TryRecvError::Empty) => {
                 // No event, continue
@@ -137,26 +913,728 @@ pub async fn watch_directory(tx: Sender<Vec<FileSystemItem>>, rx: Receiver<FileS
         }
         tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
     }
+    // Let running operations finish; the UI cancels deletions before asking us to stop.
+    for handle in in_flight {
+        let _ = handle.await;
+    }
 }
 
-fn list_directory(path: &Path) -> Result<Vec<FileSystemItem>, std::io::Error> {
+pub fn list_directory(path: &Path) -> Result<Vec<FileSystemItem>, std::io::Error> {
     let mut items = Vec::new();
     for entry in fs::read_dir(path)? {
         let entry = entry?;
-        let path = entry.path();
-        let metadata = entry.metadata()?;
-        let is_dir = metadata.is_dir();
-        let size = if is_dir { 0 } else { metadata.len() };
-        let modified = metadata.modified()?;
-        let is_hidden = path.file_name().unwrap().to_str().unwrap().starts_with('.');
+        items.push(read_item(&entry.path())?);
+    }
+    Ok(items)
+}
+
+pub fn read_item(path: &Path) -> Result<FileSystemItem, std::io::Error> {
+    let symlink_metadata = fs::symlink_metadata(path)?;
+    let is_symlink = symlink_metadata.file_type().is_symlink();
+    // Follow symlinks so links to folders behave like folders; fall back for broken links.
+    let metadata = fs::metadata(path).unwrap_or(symlink_metadata);
+    let is_dir = metadata.is_dir();
+    let size = if is_dir { 0 } else { metadata.len() };
+    let allocated_size = if is_dir { 0 } else { allocated_size(path, &metadata) };
+    let modified = metadata.modified()?;
+    let created = metadata.created().ok();
+    let is_hidden = is_hidden(path, &symlink_metadata);
+    let is_placeholder = is_placeholder(&metadata);
+    // Opening a placeholder downloads it, so its readability is taken on trust.
+    let is_readable = if is_dir {
+        fs::read_dir(path).is_ok()
+    } else {
+        is_placeholder || can_read(path)
+    };
+    let is_executable = !is_dir && is_executable(path, &metadata);
+    let permissions = Some(permissions(&symlink_metadata));
+    let owner = owner(&metadata);
+
+    Ok(FileSystemItem {
+        path: path.to_path_buf(),
+        is_dir,
+        size,
+        allocated_size,
+        modified,
+        created,
+        is_hidden,
+        is_symlink,
+        is_readable,
+        is_placeholder,
+        is_executable,
+        permissions,
+        owner,
+        metadata_loaded: true,
+        shortcut: if metadata.is_file() && !is_placeholder { shortcuts::read(path) } else { None },
+    })
+}
 
+/// Lists only names and entry types, which needs no per-entry `stat` on most platforms.
+/// The remaining metadata is filled in later by [`read_item`].
+fn list_names(path: &Path) -> Result<Vec<FileSystemItem>, std::io::Error> {
+    let mut items = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        let is_hidden = is_hidden_name(&path);
         items.push(FileSystemItem {
             path,
-            is_dir,
-            size,
-            modified,
+            is_dir: file_type.is_dir(),
+            size: 0,
+            allocated_size: 0,
+            modified: SystemTime::UNIX_EPOCH,
+            created: None,
             is_hidden,
+            is_symlink: file_type.is_symlink(),
+            is_readable: true,
+            is_placeholder: false,
+            is_executable: false,
+            permissions: None,
+            owner: None,
+            metadata_loaded: false,
+            shortcut: None,
         });
     }
     Ok(items)
 }
+
+/// Dotfiles (including macOS `.DS_Store` and `._` AppleDouble resource-fork files) and
+/// Finder's custom-icon `Icon\r` files.
+fn is_hidden_name(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    name.starts_with('.') || name == "Icon\r"
+}
+
+fn is_hidden(path: &Path, metadata: &fs::Metadata) -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        use std::os::macos::fs::MetadataExt;
+        const UF_HIDDEN: u32 = 0x8000;
+        if metadata.st_flags() & UF_HIDDEN != 0 {
+            return true;
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = metadata;
+    is_hidden_name(path)
+}
+
+/// Compares names the way people read them: "file2" before "file10", ignoring case.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a_chars, mut b_chars) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let take_number = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+                    let mut digits = String::new();
+                    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                        digits.push(digit);
+                    }
+                    digits
+                };
+                let (x, y) = (take_number(&mut a_chars), take_number(&mut b_chars));
+                let (x_value, y_value) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+                // Longer runs without leading zeros are bigger numbers, however many digits they have.
+                let ordering = x_value.len().cmp(&y_value.len()).then_with(|| x_value.cmp(y_value));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let ordering = x.to_lowercase().cmp(y.to_lowercase());
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
+pub fn name_matches(path: &Path, query: &str, extensions_only: bool) -> bool {
+    let query = query.to_lowercase();
+    if extensions_only {
+        let query = query.trim_start_matches('.');
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase().contains(query))
+            .unwrap_or(false)
+    } else {
+        path.file_name()
+            .unwrap_or_default()
+            .to_str()
+            .unwrap_or_default()
+            .to_lowercase()
+            .contains(&query)
+    }
+}
+
+fn search_directory(
+    path: &Path,
+    query: &str,
+    extensions_only: bool,
+    cancel: &AtomicBool,
+    results: &mut Vec<FileSystemItem>,
+) {
+    if cancel.load(atomic::Ordering::Relaxed) {
+        return;
+    }
+    let Ok(items) = list_directory(path) else {
+        return;
+    };
+    for item in items {
+        // Links to folders aren't followed, since one pointing back up would never end.
+        if item.is_dir && !item.is_symlink {
+            search_directory(&item.path, query, extensions_only, cancel, results);
+        }
+        if name_matches(&item.path, query, extensions_only) {
+            results.push(item);
+        }
+    }
+}
+
+const RECENT_MAX_DEPTH: usize = 6;
+const RECENT_MAX_RESULTS: usize = 500;
+
+/// Collects files under `path` modified after `since`, skipping hidden folders.
+fn modified_since(path: &Path, since: SystemTime, depth: usize, results: &mut Vec<FileSystemItem>) {
+    let Ok(items) = list_directory(path) else {
+        return;
+    };
+    for item in items {
+        if item.is_dir {
+            if depth > 0 && !item.is_hidden && !item.is_symlink {
+                modified_since(&item.path, since, depth - 1, results);
+            }
+        } else if item.modified > since {
+            results.push(item);
+        }
+    }
+}
+
+/// Re-reads `dir` and sends only what changed since the cached listing, so the UI keeps its
+/// scroll position and selection.
+fn send_listing(tx: &Sender<FileSystemResponse>, cache: &ListingCache, dir: &Path) {
+    let cached = cache.cached(dir);
+    let Ok(items) = cache.refresh(dir) else {
+        return;
+    };
+    match cached {
+        Some(cached) => {
+            let delta = ListingDelta::between(&cached, &items);
+            if !delta.is_empty() {
+                let _ = tx.send(FileSystemResponse::ListingChanged(dir.to_path_buf(), delta));
+            }
+        }
+        None => {
+            let _ = tx.send(FileSystemResponse::Listing(dir.to_path_buf(), items));
+        }
+    }
+}
+
+/// Sends names first, then fills in metadata in batches so slow (network) folders show up
+/// immediately.
+fn list_progressively(tx: &Sender<FileSystemResponse>, cache: &ListingCache, dir: &Path) {
+    const METADATA_BATCH_SIZE: usize = 256;
+    let Ok(modified) = fs::metadata(dir).and_then(|metadata| metadata.modified()) else {
+        return;
+    };
+    let Ok(names) = list_names(dir) else {
+        return;
+    };
+    let _ = tx.send(FileSystemResponse::Listing(dir.to_path_buf(), names.clone()));
+
+    let mut items = Vec::with_capacity(names.len());
+    for batch in names.chunks(METADATA_BATCH_SIZE) {
+        let mut delta = ListingDelta::default();
+        for name in batch {
+            match read_item(&name.path) {
+                Ok(item) => delta.updated.push(item),
+                Err(_) => delta.removed.push(name.path.clone()),
+            }
+        }
+        items.extend(delta.updated.iter().cloned());
+        let _ = tx.send(FileSystemResponse::ListingChanged(dir.to_path_buf(), delta));
+    }
+    cache.store(dir, modified, items);
+}
+
+/// Moves aside whatever a transfer to `to` would replace, as the user asked for in the settings.
+fn back_up_existing(from: &Path, to: &Path, backup: OverwriteBackup) -> Result<(), String> {
+    // Pasting into the same folder copies a file onto itself, and that must not move it away.
+    if to == from || fs::symlink_metadata(to).is_err() {
+        return Ok(());
+    }
+    match backup {
+        OverwriteBackup::Replace => Ok(()),
+        OverwriteBackup::Trash => desktop::trash(to),
+        OverwriteBackup::Rename => {
+            let mut name = to.file_name().unwrap_or_default().to_os_string();
+            name.push(".bak");
+            let bak = to.with_file_name(name);
+            // Only the most recent backup is kept; older ones go to the trash rather than vanishing.
+            if fs::symlink_metadata(&bak).is_ok() {
+                desktop::trash(&bak)?;
+            }
+            fs::rename(to, &bak).map_err(|err| format!("Could not back up {}: {}", to.display(), err))
+        }
+    }
+}
+
+fn report_locked(tx: &Sender<FileSystemResponse>, retry: FileSystemEvent, path: &Path, err: &std::io::Error) {
+    if locks::is_locked_error(err) {
+        let holders = locks::lock_holders(path);
+        let _ = tx.send(FileSystemResponse::OperationLocked(retry, path.to_path_buf(), holders));
+    } else if err.kind() == std::io::ErrorKind::PermissionDenied {
+        let _ = tx.send(FileSystemResponse::OperationDenied(retry, path.to_path_buf()));
+    }
+}
+
+fn collect_tree(path: &Path, entries: &mut Vec<PathBuf>) {
+    let is_dir = fs::symlink_metadata(path).map(|m| m.is_dir()).unwrap_or(false);
+    if is_dir {
+        if let Ok(children) = fs::read_dir(path) {
+            for child in children.filter_map(|entry| entry.ok()) {
+                collect_tree(&child.path(), entries);
+            }
+        }
+    }
+    entries.push(path.to_path_buf());
+}
+
+/// Deletes `paths` and everything below them one entry at a time, reporting progress after a
+/// pre-scan so large trees give feedback and can be cancelled between entries.
+fn delete_trees(
+    paths: &[PathBuf],
+    cancel: &AtomicBool,
+    tx: &Sender<FileSystemResponse>,
+) -> (Vec<(PathBuf, String)>, bool) {
+    let mut entries = Vec::new();
+    // A folder's tree already holds anything selected inside it.
+    let roots = paths.iter().filter(|path| !paths.iter().any(|other| other != *path && path.starts_with(other)));
+    for path in roots {
+        collect_tree(path, &mut entries);
+    }
+    let total = entries.len();
+    let _ = tx.send(FileSystemResponse::DeleteProgress(0, total));
+
+    let mut failed: Vec<(PathBuf, String)> = Vec::new();
+    for (index, entry) in entries.iter().enumerate() {
+        if cancel.load(atomic::Ordering::Relaxed) {
+            return (failed, true);
+        }
+        let is_dir = fs::symlink_metadata(entry).map(|m| m.is_dir()).unwrap_or(false);
+        let result = if is_dir { fs::remove_dir(entry) } else { fs::remove_file(entry) };
+        if let Err(err) = result {
+            // A folder can't be removed when one of its children failed; only report the child.
+            if !failed.iter().any(|(failed_path, _)| failed_path.starts_with(entry)) {
+                let mut message = err.to_string();
+                if locks::is_locked_error(&err) {
+                    let holders = locks::lock_holders(entry);
+                    if !holders.is_empty() {
+                        message = format!("{} (in use by {})", message, holders.join(", "));
+                    }
+                }
+                failed.push((entry.clone(), message));
+            }
+        }
+        if index % 64 == 0 || index + 1 == total {
+            let _ = tx.send(FileSystemResponse::DeleteProgress(index + 1, total));
+        }
+    }
+    (failed, false)
+}
+
+pub fn directory_size(path: &Path) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    fs::read_dir(path)
+        .map(|entries| entries.filter_map(|entry| entry.ok()).map(|entry| directory_size(&entry.path())).sum())
+        .unwrap_or(0)
+}
+
+/// What each entry of `path` takes up, largest first, or `None` once `cancel` is set. Mount points
+/// below `path` count as empty, so a bind mount or network share isn't walked as part of it.
+pub fn disk_usage(path: &Path, cancel: &AtomicBool) -> Option<Vec<(PathBuf, u64)>> {
+    fn size_on(path: &Path, device: u64, cancel: &AtomicBool) -> u64 {
+        if cancel.load(atomic::Ordering::Relaxed) {
+            return 0;
+        }
+        let Ok(metadata) = fs::symlink_metadata(path) else {
+            return 0;
+        };
+        if !metadata.is_dir() {
+            return metadata.len();
+        }
+        if device_id(&metadata) != device {
+            return 0;
+        }
+        let Ok(entries) = fs::read_dir(path) else {
+            return 0;
+        };
+        entries.filter_map(|entry| entry.ok()).map(|entry| size_on(&entry.path(), device, cancel)).sum()
+    }
+
+    let device = device_id(&fs::metadata(path).ok()?);
+    let mut usage: Vec<(PathBuf, u64)> = fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| {
+                    let size = size_on(&entry.path(), device, cancel);
+                    (entry.path(), size)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    if cancel.load(atomic::Ordering::Relaxed) {
+        return None;
+    }
+    usage.sort_by(|a, b| b.1.cmp(&a.1));
+    Some(usage)
+}
+
+#[cfg(unix)]
+fn device_id(metadata: &fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.dev()
+}
+
+#[cfg(not(unix))]
+fn device_id(_metadata: &fs::Metadata) -> u64 {
+    0
+}
+
+/// The `count` biggest files anywhere under `root`, largest first. Symlinks are not followed.
+pub fn largest_files(root: &Path, count: usize) -> Vec<(PathBuf, u64)> {
+    fn walk(path: &Path, count: usize, largest: &mut BinaryHeap<Reverse<(u64, PathBuf)>>) {
+        let Ok(entries) = fs::read_dir(path) else {
+            return;
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                walk(&entry.path(), count, largest);
+            } else if metadata.is_file() {
+                largest.push(Reverse((metadata.len(), entry.path())));
+                if largest.len() > count {
+                    largest.pop();
+                }
+            }
+        }
+    }
+
+    let mut largest = BinaryHeap::with_capacity(count + 1);
+    walk(root, count, &mut largest);
+    largest.into_sorted_vec().into_iter().map(|Reverse((size, path))| (path, size)).collect()
+}
+
+/// Runs `df -Pk` on `path`, or on everything mounted, giving up after a few seconds since `df`
+/// hangs on a network share whose server has gone away.
+#[cfg(not(windows))]
+fn df(path: Option<&Path>) -> Option<String> {
+    use std::io::Read;
+    const DF_TIMEOUT: Duration = Duration::from_secs(3);
+    let mut child = Command::new("df")
+        .arg("-Pk")
+        .args(path)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .ok()?;
+    // Read alongside, so a long listing can't fill the pipe and stall `df` before it exits.
+    let mut stdout = child.stdout.take()?;
+    let reader = std::thread::spawn(move || {
+        let mut output = String::new();
+        stdout.read_to_string(&mut output).map(|_| output)
+    });
+    let deadline = Instant::now() + DF_TIMEOUT;
+    while child.try_wait().ok()?.is_none() {
+        if Instant::now() >= deadline {
+            // Not waited for, since a process stuck on a share may not even die when killed.
+            let _ = child.kill();
+            return None;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    reader.join().ok()?.ok()
+}
+
+#[cfg(not(windows))]
+pub fn disk_space(path: &Path) -> Option<DiskSpace> {
+    let stdout = df(Some(path))?;
+    let line = stdout.lines().nth(1)?;
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() < 6 {
+        return None;
+    }
+    Some(DiskSpace {
+        mount_point: PathBuf::from(fields[5..].join(" ")),
+        free: fields[3].parse::<u64>().ok()? * 1024,
+        total: fields[1].parse::<u64>().ok()? * 1024,
+    })
+}
+
+#[cfg(windows)]
+pub fn disk_space(path: &Path) -> Option<DiskSpace> {
+    let drive = path.to_str()?.chars().next()?;
+    let script = format!("$d = Get-PSDrive -Name {}; \"$($d.Free) $($d.Used)\"", drive);
+    let output = Command::new("powershell").args(["-NoProfile", "-Command", &script]).output().ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut fields = stdout.split_whitespace();
+    let free = fields.next()?.parse::<u64>().ok()?;
+    let used = fields.next()?.parse::<u64>().ok()?;
+    Some(DiskSpace {
+        mount_point: PathBuf::from(format!("{}:\\", drive)),
+        free,
+        total: free + used,
+    })
+}
+
+/// The root filesystem and anything mounted where desktops put removable media and shares.
+#[cfg(not(windows))]
+pub fn list_mounts() -> Option<Vec<Mount>> {
+    const MOUNT_PARENTS: [&str; 4] = ["/media/", "/run/media/", "/mnt/", "/Volumes/"];
+    let stdout = df(None)?;
+    let mut mounts = Vec::new();
+    for line in stdout.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 6 {
+            continue;
+        }
+        let mount_point = fields[5..].join(" ");
+        let removable = MOUNT_PARENTS.iter().any(|parent| mount_point.starts_with(parent));
+        if mount_point != "/" && !removable {
+            continue;
+        }
+        let (Ok(total), Ok(free)) = (fields[1].parse::<u64>(), fields[3].parse::<u64>()) else {
+            continue;
+        };
+        let mount_point = PathBuf::from(mount_point);
+        mounts.push(Mount {
+            name: match mount_point.file_name() {
+                Some(name) => name.to_string_lossy().into_owned(),
+                None => "File System".to_string(),
+            },
+            space: DiskSpace {
+                mount_point,
+                free: free * 1024,
+                total: total * 1024,
+            },
+            removable,
+        });
+    }
+    Some(mounts)
+}
+
+#[cfg(windows)]
+pub fn list_mounts() -> Option<Vec<Mount>> {
+    // DriveType 2 is removable media and 4 a network share.
+    let script = "Get-CimInstance Win32_LogicalDisk | ForEach-Object { \
+                  \"$($_.DeviceID)|$($_.FreeSpace)|$($_.Size)|$($_.DriveType)|$($_.VolumeName)\" }";
+    let output = Command::new("powershell").args(["-NoProfile", "-Command", script]).output().ok()?;
+    let mounts = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.trim().splitn(5, '|').collect();
+            let [drive, free, total, drive_type, label] = fields.as_slice() else {
+                return None;
+            };
+            let name = if label.is_empty() { "Local Disk" } else { label };
+            Some(Mount {
+                name: format!("{} ({})", name, drive),
+                space: DiskSpace {
+                    mount_point: PathBuf::from(format!("{}\\", drive)),
+                    free: free.parse().ok()?,
+                    total: total.parse().ok()?,
+                },
+                removable: matches!(*drive_type, "2" | "4"),
+            })
+        })
+        .collect();
+    Some(mounts)
+}
+
+/// Where the folder tree starts: home, then the filesystem root or each drive.
+pub fn tree_roots() -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = dirs::home_dir().into_iter().collect();
+    if cfg!(windows) {
+        let drives = (b'A'..=b'Z').map(|letter| PathBuf::from(format!("{}:\\", letter as char)));
+        roots.extend(drives.filter(|drive| drive.is_dir()));
+    } else {
+        roots.push(PathBuf::from("/"));
+    }
+    roots
+}
+
+#[cfg(windows)]
+fn is_placeholder(metadata: &fs::Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_OFFLINE: u32 = 0x1000;
+    const FILE_ATTRIBUTE_RECALL_ON_OPEN: u32 = 0x40000;
+    const FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS: u32 = 0x400000;
+    metadata.file_attributes()
+        & (FILE_ATTRIBUTE_OFFLINE | FILE_ATTRIBUTE_RECALL_ON_OPEN | FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS)
+        != 0
+}
+
+/// iCloud Drive and File Provider files whose content hasn't been downloaded.
+#[cfg(target_os = "macos")]
+fn is_placeholder(metadata: &fs::Metadata) -> bool {
+    use std::os::macos::fs::MetadataExt;
+    const SF_DATALESS: u32 = 0x40000000;
+    metadata.st_flags() & SF_DATALESS != 0
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn is_placeholder(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn allocated_size(_path: &Path, metadata: &fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    // `st_blocks` is always in 512-byte units, whatever the filesystem's block size.
+    metadata.blocks() * 512
+}
+
+/// Whether the file could be opened for reading, asked of the permissions rather than by opening
+/// it: an open per entry is slow on network mounts and sets off on-access scanners.
+#[cfg(unix)]
+fn can_read(path: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+    const R_OK: i32 = 4;
+
+    unsafe extern "C" {
+        fn access(path: *const std::ffi::c_char, mode: i32) -> i32;
+    }
+
+    let Ok(path) = std::ffi::CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    unsafe { access(path.as_ptr(), R_OK) == 0 }
+}
+
+/// Windows has no cheap equivalent of `access`, so everything is taken to be readable.
+#[cfg(not(unix))]
+fn can_read(_path: &Path) -> bool {
+    true
+}
+
+/// The NTFS-compressed or sparse size; falls back to the apparent size if it can't be read.
+#[cfg(windows)]
+fn allocated_size(path: &Path, metadata: &fs::Metadata) -> u64 {
+    use std::os::windows::ffi::OsStrExt;
+    const INVALID_FILE_SIZE: u32 = u32::MAX;
+
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn GetCompressedFileSizeW(file_name: *const u16, high: *mut u32) -> u32;
+    }
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut high = 0u32;
+    let low = unsafe { GetCompressedFileSizeW(wide.as_ptr(), &mut high) };
+    if low == INVALID_FILE_SIZE && high == 0 {
+        return metadata.len();
+    }
+    (u64::from(high) << 32) | u64::from(low)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn allocated_size(_path: &Path, metadata: &fs::Metadata) -> u64 {
+    metadata.len()
+}
+
+#[cfg(unix)]
+fn is_executable(_path: &Path, metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path, _metadata: &fs::Metadata) -> bool {
+    const EXTENSIONS: [&str; 6] = ["exe", "bat", "cmd", "com", "msi", "ps1"];
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| EXTENSIONS.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+        .unwrap_or(false)
+}
+
+#[cfg(unix)]
+fn permissions(metadata: &fs::Metadata) -> String {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = metadata.permissions().mode();
+    let mut permissions = String::with_capacity(9);
+    for shift in [6, 3, 0] {
+        let bits = mode >> shift;
+        permissions.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        permissions.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        permissions.push(if bits & 0o1 != 0 { 'x' } else { '-' });
+    }
+    permissions
+}
+
+#[cfg(not(unix))]
+fn permissions(metadata: &fs::Metadata) -> String {
+    if metadata.permissions().readonly() { "Read-only" } else { "Read/write" }.to_string()
+}
+
+/// The owning user's name, looked up in `/etc/passwd`, or their uid if they aren't in it.
+#[cfg(unix)]
+fn owner(metadata: &fs::Metadata) -> Option<String> {
+    use std::os::unix::fs::MetadataExt;
+    use std::sync::OnceLock;
+    static USERS: OnceLock<HashMap<u32, String>> = OnceLock::new();
+    let users = USERS.get_or_init(|| {
+        let passwd = fs::read_to_string("/etc/passwd").unwrap_or_default();
+        passwd
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split(':');
+                let name = fields.next()?;
+                let uid = fields.nth(1)?.parse().ok()?;
+                Some((uid, name.to_string()))
+            })
+            .collect()
+    });
+    let uid = metadata.uid();
+    Some(users.get(&uid).cloned().unwrap_or_else(|| uid.to_string()))
+}
+
+/// Reading the owner on Windows needs the security descriptor APIs, which aren't worth it here.
+#[cfg(not(unix))]
+fn owner(_metadata: &fs::Metadata) -> Option<String> {
+    None
+}
+
+pub fn sync_provider(path: &Path) -> Option<&'static str> {
+    const PROVIDERS: [&str; 5] = ["OneDrive", "Dropbox", "Google Drive", "iCloud Drive", "Nextcloud"];
+    path.components().find_map(|component| {
+        let name = component.as_os_str().to_str()?;
+        PROVIDERS.iter().copied().find(|provider| name.starts_with(provider))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn natural_order_compares_numbers_by_value() {
+        let mut names = vec!["file10.txt", "file2.txt", "File1.txt", "file02.txt", "file"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(names, ["file", "File1.txt", "file02.txt", "file2.txt", "file10.txt"]);
+    }
+}
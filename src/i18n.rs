@@ -0,0 +1,96 @@
+//! Translations of the interface. The English text is the key, so untranslated strings fall
+//! back to it; catalogs map it to each language's text and are built into the binary.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::RwLock;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum Language {
+    /// Whatever the system's locale asks for, if there's a translation for it.
+    #[default]
+    System,
+    English,
+    Spanish,
+}
+
+impl Language {
+    pub const ALL: [Language; 3] = [Language::System, Language::English, Language::Spanish];
+
+    /// The language's own name for itself, so it can be found whatever the current language.
+    pub fn label(self) -> &'static str {
+        match self {
+            Language::System => "System",
+            Language::English => "English",
+            Language::Spanish => "Español",
+        }
+    }
+
+    fn catalog(self) -> Option<&'static str> {
+        match self {
+            Language::System => system_language().catalog(),
+            Language::English => None,
+            Language::Spanish => Some(include_str!("../locales/es.json")),
+        }
+    }
+}
+
+/// Goes by the usual locale variables, e.g. `es_AR.UTF-8` in `LANG`.
+fn system_language() -> Language {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+        .unwrap_or_default();
+    match locale.get(..2) {
+        Some("es") => Language::Spanish,
+        _ => Language::English,
+    }
+}
+
+static TRANSLATIONS: RwLock<Option<HashMap<String, String>>> = RwLock::new(None);
+
+/// Switches every later [`t`] and [`tf`] to `language`.
+pub fn set_language(language: Language) {
+    let catalog = language.catalog().and_then(|catalog| serde_json::from_str(catalog).ok());
+    *TRANSLATIONS.write().unwrap() = catalog;
+}
+
+/// `text` in the current language.
+pub fn t(text: &str) -> String {
+    let translations = TRANSLATIONS.read().unwrap();
+    let translated = translations.as_ref().and_then(|translations| translations.get(text));
+    translated.map_or(text, String::as_str).to_string()
+}
+
+/// Translates `template`, then fills in its `{name}` placeholders.
+pub fn tf(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    args.iter().fold(t(template), |text, (name, value)| {
+        text.replace(&format!("{{{}}}", name), &value.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders(text: &str) -> Vec<&str> {
+        let mut found: Vec<&str> =
+            text.split('{').skip(1).filter_map(|rest| rest.split_once('}')).map(|(name, _)| name).collect();
+        found.sort();
+        found
+    }
+
+    #[test]
+    fn translations_keep_their_placeholders() {
+        for language in Language::ALL {
+            let Some(catalog) = language.catalog() else {
+                continue;
+            };
+            let translations: HashMap<String, String> = serde_json::from_str(catalog).unwrap();
+            for (english, translated) in &translations {
+                assert_eq!(placeholders(english), placeholders(translated), "{}", english);
+            }
+        }
+    }
+}
@@ -0,0 +1,429 @@
+//! Icons for files and folders by type. Each kind is drawn once per size into a texture, so a
+//! list only looks them up, and they look the same whatever emoji the system's fonts have.
+
+use crate::file_system::FileSystemItem;
+use eframe::egui::{self, Color32, ColorImage, TextureHandle, TextureOptions};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SpecialFolder {
+    Home,
+    Desktop,
+    Documents,
+    Downloads,
+    Pictures,
+    Music,
+    Videos,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum FileIcon {
+    Folder,
+    SpecialFolder(SpecialFolder),
+    Downloading,
+    WebLink,
+    Link,
+    Executable,
+    Code,
+    Image,
+    Audio,
+    Video,
+    Archive,
+    Document,
+    Text,
+    File,
+}
+
+/// File types by extension, roughly the groups their MIME types fall into. Checked in order.
+const CATEGORIES: &[(FileIcon, &[&str])] = &[
+    (
+        FileIcon::Code,
+        &[
+            "rs", "c", "h", "cc", "cpp", "hpp", "cs", "go", "java", "kt", "py", "rb", "js", "mjs", "ts", "tsx", "jsx",
+            "php", "swift", "sh", "bash", "zsh", "ps1", "bat", "lua", "pl", "scala", "html", "css", "scss", "json",
+            "toml", "yaml", "yml", "xml", "sql",
+        ],
+    ),
+    (
+        FileIcon::Image,
+        &["png", "jpg", "jpeg", "gif", "bmp", "webp", "svg", "ico", "tif", "tiff", "heic", "psd", "raw"],
+    ),
+    (FileIcon::Audio, &["mp3", "wav", "flac", "ogg", "oga", "m4a", "aac", "opus", "wma", "mid"]),
+    (FileIcon::Video, &["mp4", "mkv", "avi", "mov", "webm", "wmv", "flv", "m4v", "mpg", "mpeg"]),
+    (
+        FileIcon::Archive,
+        &["zip", "tar", "gz", "tgz", "xz", "bz2", "7z", "rar", "zst", "iso", "dmg", "deb", "rpm", "jar"],
+    ),
+    (
+        FileIcon::Document,
+        &["pdf", "doc", "docx", "odt", "rtf", "xls", "xlsx", "ods", "ppt", "pptx", "odp", "epub"],
+    ),
+    (FileIcon::Text, &["txt", "md", "log", "csv", "ini", "cfg", "conf"]),
+];
+
+impl FileIcon {
+    fn for_extension(extension: &str) -> FileIcon {
+        let extension = extension.to_lowercase();
+        CATEGORIES
+            .iter()
+            .find(|(_, extensions)| extensions.contains(&extension.as_str()))
+            .map_or(FileIcon::File, |(icon, _)| *icon)
+    }
+}
+
+/// Icon textures by kind and size in pixels, made as they're first needed.
+pub struct Icons {
+    special_folders: Vec<(PathBuf, SpecialFolder)>,
+    textures: HashMap<(FileIcon, u32), TextureHandle>,
+}
+
+impl Default for Icons {
+    fn default() -> Self {
+        let special_folders = [
+            (dirs::home_dir(), SpecialFolder::Home),
+            (dirs::desktop_dir(), SpecialFolder::Desktop),
+            (dirs::document_dir(), SpecialFolder::Documents),
+            (dirs::download_dir(), SpecialFolder::Downloads),
+            (dirs::picture_dir(), SpecialFolder::Pictures),
+            (dirs::audio_dir(), SpecialFolder::Music),
+            (dirs::video_dir(), SpecialFolder::Videos),
+        ];
+        Self {
+            special_folders: special_folders.into_iter().filter_map(|(path, kind)| Some((path?, kind))).collect(),
+            textures: HashMap::new(),
+        }
+    }
+}
+
+impl Icons {
+    pub fn kind(&self, item: &FileSystemItem) -> FileIcon {
+        if item.is_partial_download() {
+            FileIcon::Downloading
+        } else if item.is_dir {
+            self.special_folders
+                .iter()
+                .find(|(path, _)| *path == item.path)
+                .map_or(FileIcon::Folder, |(_, kind)| FileIcon::SpecialFolder(*kind))
+        } else if item.shortcut.as_ref().is_some_and(|shortcut| shortcut.url().is_some()) {
+            FileIcon::WebLink
+        } else if item.shortcut.is_some() {
+            FileIcon::Link
+        } else if item.is_executable {
+            FileIcon::Executable
+        } else {
+            item.path.extension().map_or(FileIcon::File, |ext| FileIcon::for_extension(&ext.to_string_lossy()))
+        }
+    }
+
+    /// The icon for `item`, `size` points square.
+    pub fn texture(&mut self, ctx: &egui::Context, item: &FileSystemItem, size: f32) -> TextureHandle {
+        let icon = self.kind(item);
+        // Even sizes only, so slightly different rows share textures.
+        let pixels = ((size * ctx.pixels_per_point() / 2.0).round() as u32 * 2).max(8);
+        self.textures
+            .entry((icon, pixels))
+            .or_insert_with(|| {
+                let image = draw(icon, pixels as usize);
+                ctx.load_texture(format!("icon-{:?}-{}", icon, pixels), image, TextureOptions::LINEAR)
+            })
+            .clone()
+    }
+}
+
+/// Where on the icon a pictogram goes, as a square at `(x, y)` with side `side`, all in
+/// fractions of the icon.
+#[derive(Clone, Copy)]
+struct Frame {
+    x: f32,
+    y: f32,
+    side: f32,
+}
+
+impl Frame {
+    fn at(self, x: f32, y: f32) -> (f32, f32) {
+        (self.x + x * self.side, self.y + y * self.side)
+    }
+
+    fn len(self, length: f32) -> f32 {
+        length * self.side
+    }
+}
+
+/// A square of premultiplied pixels that shapes are filled into, in coordinates from 0 to 1.
+struct Canvas {
+    size: usize,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Canvas {
+    fn new(size: usize) -> Self {
+        Self { size, pixels: vec![[0.0; 4]; size * size] }
+    }
+
+    /// Blends `color` over every point `inside` says is in the shape, sampling each pixel on a
+    /// grid for smooth edges.
+    fn fill(&mut self, color: Color32, inside: impl Fn(f32, f32) -> bool) {
+        const SAMPLES: usize = 4;
+        let color = color.to_array().map(|channel| f32::from(channel) / 255.0);
+        let step = 1.0 / (self.size * SAMPLES) as f32;
+        for row in 0..self.size {
+            for column in 0..self.size {
+                let mut hits = 0;
+                for sy in 0..SAMPLES {
+                    for sx in 0..SAMPLES {
+                        let x = ((column * SAMPLES + sx) as f32 + 0.5) * step;
+                        let y = ((row * SAMPLES + sy) as f32 + 0.5) * step;
+                        hits += usize::from(inside(x, y));
+                    }
+                }
+                if hits == 0 {
+                    continue;
+                }
+                let coverage = hits as f32 / (SAMPLES * SAMPLES) as f32;
+                let pixel = &mut self.pixels[row * self.size + column];
+                let keep = 1.0 - color[3] * coverage;
+                for (channel, value) in pixel.iter_mut().zip(color) {
+                    *channel = value * coverage + *channel * keep;
+                }
+            }
+        }
+    }
+
+    fn into_image(self) -> ColorImage {
+        let pixels = self
+            .pixels
+            .iter()
+            .map(|pixel| {
+                let [r, g, b, a] = pixel.map(|channel| (channel * 255.0).round().clamp(0.0, 255.0) as u8);
+                Color32::from_rgba_premultiplied(r, g, b, a)
+            })
+            .collect();
+        ColorImage { size: [self.size, self.size], pixels }
+    }
+}
+
+fn rect(x0: f32, y0: f32, x1: f32, y1: f32, rounding: f32) -> impl Fn(f32, f32) -> bool {
+    move |x, y| {
+        let dx = (x0 + rounding - x).max(x - (x1 - rounding)).max(0.0);
+        let dy = (y0 + rounding - y).max(y - (y1 - rounding)).max(0.0);
+        (x0..=x1).contains(&x) && (y0..=y1).contains(&y) && dx * dx + dy * dy <= rounding * rounding
+    }
+}
+
+fn circle((cx, cy): (f32, f32), radius: f32) -> impl Fn(f32, f32) -> bool {
+    move |x, y| (x - cx).powi(2) + (y - cy).powi(2) <= radius * radius
+}
+
+/// The outline of an ellipse, `width` thick.
+fn ring((cx, cy): (f32, f32), rx: f32, ry: f32, width: f32) -> impl Fn(f32, f32) -> bool {
+    move |x, y| {
+        let distance = (((x - cx) / rx).powi(2) + ((y - cy) / ry).powi(2)).sqrt();
+        (distance - 1.0).abs() * rx.min(ry) <= width / 2.0
+    }
+}
+
+/// A convex polygon, its corners in either order around it.
+fn polygon<const N: usize>(corners: [(f32, f32); N]) -> impl Fn(f32, f32) -> bool {
+    move |x, y| {
+        let sides = (0..N).map(|i| {
+            let ((ax, ay), (bx, by)) = (corners[i], corners[(i + 1) % N]);
+            (bx - ax) * (y - ay) - (by - ay) * (x - ax)
+        });
+        let (mut left, mut right) = (false, false);
+        for side in sides {
+            left |= side < 0.0;
+            right |= side > 0.0;
+        }
+        !(left && right)
+    }
+}
+
+fn line((ax, ay): (f32, f32), (bx, by): (f32, f32), width: f32) -> impl Fn(f32, f32) -> bool {
+    move |x, y| {
+        let (dx, dy) = (bx - ax, by - ay);
+        let along = (((x - ax) * dx + (y - ay) * dy) / (dx * dx + dy * dy)).clamp(0.0, 1.0);
+        (x - ax - along * dx).powi(2) + (y - ay - along * dy).powi(2) <= (width / 2.0).powi(2)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Pictogram {
+    Code,
+    Picture,
+    Note,
+    Play,
+    Zipper,
+    Lines,
+    Window,
+    Globe,
+    Arrow,
+    Progress,
+    House,
+    Screen,
+    Download,
+}
+
+fn draw(icon: FileIcon, size: usize) -> ColorImage {
+    let mut canvas = Canvas::new(size);
+    let page = |pictogram, color| Some((pictogram, color));
+    let design = match icon {
+        FileIcon::Folder | FileIcon::SpecialFolder(_) => {
+            draw_folder(&mut canvas);
+            let pictogram = match icon {
+                FileIcon::SpecialFolder(SpecialFolder::Home) => Pictogram::House,
+                FileIcon::SpecialFolder(SpecialFolder::Desktop) => Pictogram::Screen,
+                FileIcon::SpecialFolder(SpecialFolder::Documents) => Pictogram::Lines,
+                FileIcon::SpecialFolder(SpecialFolder::Downloads) => Pictogram::Download,
+                FileIcon::SpecialFolder(SpecialFolder::Pictures) => Pictogram::Picture,
+                FileIcon::SpecialFolder(SpecialFolder::Music) => Pictogram::Note,
+                FileIcon::SpecialFolder(SpecialFolder::Videos) => Pictogram::Play,
+                _ => return canvas.into_image(),
+            };
+            let frame = Frame { x: 0.32, y: 0.42, side: 0.36 };
+            draw_pictogram(&mut canvas, pictogram, frame, Color32::from_rgb(168, 112, 16));
+            return canvas.into_image();
+        }
+        FileIcon::Downloading => page(Pictogram::Progress, Color32::from_rgb(30, 136, 229)),
+        FileIcon::WebLink => page(Pictogram::Globe, Color32::from_rgb(0, 137, 209)),
+        FileIcon::Link => page(Pictogram::Arrow, Color32::from_rgb(84, 110, 122)),
+        FileIcon::Executable => page(Pictogram::Window, Color32::from_rgb(0, 150, 136)),
+        FileIcon::Code => page(Pictogram::Code, Color32::from_rgb(66, 133, 244)),
+        FileIcon::Image => page(Pictogram::Picture, Color32::from_rgb(52, 168, 83)),
+        FileIcon::Audio => page(Pictogram::Note, Color32::from_rgb(156, 39, 176)),
+        FileIcon::Video => page(Pictogram::Play, Color32::from_rgb(229, 57, 53)),
+        FileIcon::Archive => page(Pictogram::Zipper, Color32::from_rgb(191, 134, 36)),
+        FileIcon::Document => page(Pictogram::Lines, Color32::from_rgb(25, 118, 210)),
+        FileIcon::Text => page(Pictogram::Lines, Color32::from_rgb(120, 120, 120)),
+        FileIcon::File => None,
+    };
+    draw_page(&mut canvas);
+    if let Some((pictogram, color)) = design {
+        draw_pictogram(&mut canvas, pictogram, Frame { x: 0.27, y: 0.36, side: 0.48 }, color);
+    }
+    canvas.into_image()
+}
+
+/// A sheet with its top right corner folded over.
+fn draw_page(canvas: &mut Canvas) {
+    let outline = |inset: f32| {
+        let (left, top, right, bottom) = (0.16 + inset, 0.04 + inset, 0.84 - inset, 0.96 - inset);
+        polygon([(left, top), (0.62, top), (right, 0.26), (right, bottom), (left, bottom)])
+    };
+    canvas.fill(Color32::from_gray(120), outline(0.0));
+    canvas.fill(Color32::from_gray(248), outline(0.035));
+    canvas.fill(Color32::from_gray(200), polygon([(0.6, 0.04), (0.84, 0.28), (0.6, 0.28)]));
+}
+
+fn draw_folder(canvas: &mut Canvas) {
+    let back = Color32::from_rgb(222, 164, 40);
+    canvas.fill(back, rect(0.06, 0.14, 0.44, 0.34, 0.05));
+    canvas.fill(back, rect(0.06, 0.22, 0.94, 0.88, 0.06));
+    canvas.fill(Color32::from_rgb(252, 202, 76), rect(0.06, 0.32, 0.94, 0.88, 0.06));
+}
+
+fn draw_pictogram(canvas: &mut Canvas, pictogram: Pictogram, f: Frame, color: Color32) {
+    let stroke = f.len(0.13);
+    match pictogram {
+        Pictogram::Code => {
+            canvas.fill(color, line(f.at(0.36, 0.2), f.at(0.08, 0.5), stroke));
+            canvas.fill(color, line(f.at(0.08, 0.5), f.at(0.36, 0.8), stroke));
+            canvas.fill(color, line(f.at(0.64, 0.2), f.at(0.92, 0.5), stroke));
+            canvas.fill(color, line(f.at(0.92, 0.5), f.at(0.64, 0.8), stroke));
+        }
+        Pictogram::Picture => {
+            canvas.fill(color, polygon([f.at(0.0, 0.92), f.at(0.36, 0.36), f.at(0.72, 0.92)]));
+            canvas.fill(color, polygon([f.at(0.44, 0.92), f.at(0.7, 0.56), f.at(1.0, 0.92)]));
+            canvas.fill(Color32::from_rgb(251, 188, 5), circle(f.at(0.78, 0.2), f.len(0.14)));
+        }
+        Pictogram::Note => {
+            canvas.fill(color, circle(f.at(0.32, 0.78), f.len(0.18)));
+            canvas.fill(color, line(f.at(0.48, 0.78), f.at(0.48, 0.08), stroke));
+            canvas.fill(color, line(f.at(0.48, 0.08), f.at(0.84, 0.28), stroke));
+        }
+        Pictogram::Play => {
+            canvas.fill(color, rect(f.x, f.y + f.len(0.08), f.x + f.side, f.y + f.len(0.92), f.len(0.14)));
+            canvas.fill(Color32::WHITE, polygon([f.at(0.38, 0.28), f.at(0.38, 0.72), f.at(0.74, 0.5)]));
+        }
+        Pictogram::Zipper => {
+            for step in 0..4 {
+                let y = -0.54 + step as f32 * 0.2;
+                let x = if step % 2 == 0 { 0.38 } else { 0.5 };
+                let (x0, y0) = f.at(x, y);
+                canvas.fill(color, rect(x0, y0, x0 + f.len(0.12), y0 + f.len(0.12), 0.0));
+            }
+            let (x0, y0) = f.at(0.32, 0.3);
+            let (x1, y1) = f.at(0.68, 0.84);
+            canvas.fill(color, rect(x0, y0, x1, y1, f.len(0.06)));
+            let inside = rect(x0 + stroke / 2.0, y0 + f.len(0.3), x1 - stroke / 2.0, y1 - stroke / 2.0, 0.0);
+            canvas.fill(Color32::from_gray(248), inside);
+        }
+        Pictogram::Lines => {
+            for (index, width) in [1.0, 1.0, 1.0, 0.6].into_iter().enumerate() {
+                let y = 0.14 + index as f32 * 0.24;
+                canvas.fill(color, line(f.at(0.06, y), f.at(0.06 + width * 0.88, y), f.len(0.1)));
+            }
+        }
+        Pictogram::Window => {
+            let (x0, y0) = f.at(0.0, 0.1);
+            let (x1, y1) = f.at(1.0, 0.9);
+            canvas.fill(color, rect(x0, y0, x1, y1, f.len(0.08)));
+            let inside = rect(x0 + stroke / 2.0, y0 + f.len(0.24), x1 - stroke / 2.0, y1 - stroke / 2.0, 0.0);
+            canvas.fill(Color32::from_gray(248), inside);
+            canvas.fill(color, line(f.at(0.2, 0.46), f.at(0.36, 0.6), f.len(0.08)));
+            canvas.fill(color, line(f.at(0.36, 0.6), f.at(0.2, 0.74), f.len(0.08)));
+        }
+        Pictogram::Globe => {
+            let center = f.at(0.5, 0.5);
+            canvas.fill(color, ring(center, f.len(0.46), f.len(0.46), f.len(0.09)));
+            canvas.fill(color, ring(center, f.len(0.2), f.len(0.46), f.len(0.08)));
+            canvas.fill(color, line(f.at(0.06, 0.5), f.at(0.94, 0.5), f.len(0.08)));
+        }
+        Pictogram::Arrow => {
+            canvas.fill(color, line(f.at(0.12, 0.88), f.at(0.62, 0.38), stroke));
+            canvas.fill(color, polygon([f.at(0.9, 0.1), f.at(0.42, 0.18), f.at(0.82, 0.58)]));
+        }
+        Pictogram::Progress => {
+            let (x0, y0) = f.at(0.0, 0.62);
+            let (x1, y1) = f.at(1.0, 0.86);
+            canvas.fill(Color32::from_gray(200), rect(x0, y0, x1, y1, f.len(0.08)));
+            canvas.fill(color, rect(x0, y0, f.at(0.55, 0.0).0, y1, f.len(0.08)));
+            canvas.fill(color, line(f.at(0.5, 0.02), f.at(0.5, 0.34), stroke));
+            canvas.fill(color, polygon([f.at(0.28, 0.3), f.at(0.72, 0.3), f.at(0.5, 0.52)]));
+        }
+        Pictogram::House => {
+            canvas.fill(color, polygon([f.at(0.0, 0.5), f.at(0.5, 0.04), f.at(1.0, 0.5)]));
+            let (x0, y0) = f.at(0.16, 0.46);
+            let (x1, y1) = f.at(0.84, 0.96);
+            canvas.fill(color, rect(x0, y0, x1, y1, 0.0));
+        }
+        Pictogram::Screen => {
+            let (x0, y0) = f.at(0.0, 0.06);
+            let (x1, y1) = f.at(1.0, 0.7);
+            canvas.fill(color, rect(x0, y0, x1, y1, f.len(0.06)));
+            let (x0, y0) = f.at(0.24, 0.82);
+            let (x1, y1) = f.at(0.76, 0.94);
+            canvas.fill(color, rect(x0, y0, x1, y1, 0.0));
+            canvas.fill(color, line(f.at(0.5, 0.7), f.at(0.5, 0.84), stroke));
+        }
+        Pictogram::Download => {
+            canvas.fill(color, line(f.at(0.5, 0.02), f.at(0.5, 0.5), stroke * 1.2));
+            canvas.fill(color, polygon([f.at(0.16, 0.44), f.at(0.84, 0.44), f.at(0.5, 0.8)]));
+            canvas.fill(color, line(f.at(0.08, 0.94), f.at(0.92, 0.94), stroke));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extensions_pick_categories() {
+        assert_eq!(FileIcon::for_extension("RS"), FileIcon::Code);
+        assert_eq!(FileIcon::for_extension("jpeg"), FileIcon::Image);
+        assert_eq!(FileIcon::for_extension("flac"), FileIcon::Audio);
+        assert_eq!(FileIcon::for_extension("zst"), FileIcon::Archive);
+        assert_eq!(FileIcon::for_extension("unknown"), FileIcon::File);
+    }
+}
@@ -0,0 +1,184 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum TransferKind {
+    Copy,
+    Move,
+}
+
+/// A copy or move that was started but not yet recorded as finished.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct JournalEntry {
+    pub id: u64,
+    /// The process that started it; see [`instance`].
+    #[serde(default)]
+    pub instance: u64,
+    pub kind: TransferKind,
+    pub from: PathBuf,
+    pub to: PathBuf,
+    /// Whether `to` was already there, in which case rolling back must not remove it.
+    pub destination_existed: bool,
+}
+
+impl JournalEntry {
+    pub fn describe(&self) -> String {
+        let verb = match self.kind {
+            TransferKind::Copy => "Copying",
+            TransferKind::Move => "Moving",
+        };
+        format!("{} {} to {}", verb, self.from.display(), self.to.display())
+    }
+}
+
+// Serializes read-modify-write of the journal file between worker tasks and the UI.
+static JOURNAL_LOCK: Mutex<()> = Mutex::new(());
+
+fn get_journal_path() -> PathBuf {
+    dirs::home_dir().unwrap().join(".file_manager_journal.json")
+}
+
+/// Where each running process keeps the file it holds locked, named by its instance.
+fn instances_dir() -> PathBuf {
+    dirs::home_dir().unwrap().join(".file_manager_instances")
+}
+
+fn random_u64() -> u64 {
+    let mut bytes = [0; 8];
+    let _ = getrandom::getrandom(&mut bytes);
+    u64::from_le_bytes(bytes)
+}
+
+/// Identifies this process in the journal. It holds a lock on a file named after it until it
+/// exits, so others can tell it's still running even once its PID has been reused.
+fn instance() -> u64 {
+    static INSTANCE: OnceLock<(u64, Option<File>)> = OnceLock::new();
+    INSTANCE
+        .get_or_init(|| {
+            let instance = random_u64();
+            let _ = fs::create_dir_all(instances_dir());
+            let file = File::create(instances_dir().join(format!("{:016x}", instance)));
+            (instance, file.ok().filter(|file| file.try_lock().is_ok()))
+        })
+        .0
+}
+
+/// The instances still running, removing the files of ones that have exited.
+fn live_instances() -> HashSet<u64> {
+    let Ok(entries) = fs::read_dir(instances_dir()) else {
+        return HashSet::new();
+    };
+    let mut live = HashSet::new();
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let Ok(instance) = u64::from_str_radix(&entry.file_name().to_string_lossy(), 16) else {
+            continue;
+        };
+        match File::options().write(true).open(entry.path()) {
+            Ok(file) if file.try_lock().is_err() => {
+                live.insert(instance);
+            }
+            _ => {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+    live
+}
+
+/// Holds the journal for a read-modify-write, against other processes as well as this one's threads.
+fn lock_journal() -> (std::sync::MutexGuard<'static, ()>, Option<File>) {
+    let guard = JOURNAL_LOCK.lock().unwrap();
+    let file = File::create(get_journal_path().with_extension("lock")).ok().filter(|file| file.lock().is_ok());
+    (guard, file)
+}
+
+fn load() -> Vec<JournalEntry> {
+    fs::read_to_string(get_journal_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save(entries: &[JournalEntry]) {
+    let path = get_journal_path();
+    let Ok(content) = serde_json::to_string_pretty(entries) else {
+        return;
+    };
+    // Write then rename so a crash mid-write never leaves a truncated journal.
+    let temp = path.with_extension("json.tmp");
+    if fs::write(&temp, content).is_ok() {
+        let _ = fs::rename(&temp, &path);
+    }
+}
+
+/// Records a transfer before it starts; pass the returned id to [`finish`] once it's done.
+pub fn begin(kind: TransferKind, from: &Path, to: &Path) -> u64 {
+    // A counter keeps this process's ids apart; the random half keeps them apart from other processes'.
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed) << 32 | random_u64() >> 32;
+    let instance = instance();
+    let _lock = lock_journal();
+    let mut entries = load();
+    entries.push(JournalEntry {
+        id,
+        instance,
+        kind,
+        from: from.to_path_buf(),
+        to: to.to_path_buf(),
+        destination_existed: to.exists(),
+    });
+    save(&entries);
+    id
+}
+
+/// Removes the entry, returning whether it was still there; a window that finds it gone leaves
+/// the transfer to whichever got to it first.
+pub fn finish(id: u64) -> bool {
+    let _lock = lock_journal();
+    let mut entries = load();
+    let count = entries.len();
+    entries.retain(|entry| entry.id != id);
+    save(&entries);
+    entries.len() < count
+}
+
+/// Transfers left behind by processes that are no longer running, i.e. ones that crashed.
+pub fn interrupted() -> Vec<JournalEntry> {
+    let mut live = live_instances();
+    live.insert(instance());
+    let _lock = lock_journal();
+    load().into_iter().filter(|entry| !live.contains(&entry.instance)).collect()
+}
+
+/// Undoes whatever part of an interrupted transfer reached the destination, then forgets it.
+/// Holds the journal meanwhile so two windows can't both roll back the same transfer.
+pub fn roll_back(entry: &JournalEntry) -> io::Result<()> {
+    let _lock = lock_journal();
+    let mut entries = load();
+    if !entries.iter().any(|journaled| journaled.id == entry.id) {
+        return Ok(());
+    }
+    undo(entry)?;
+    entries.retain(|journaled| journaled.id != entry.id);
+    save(&entries);
+    Ok(())
+}
+
+fn undo(entry: &JournalEntry) -> io::Result<()> {
+    if entry.kind == TransferKind::Move && !entry.from.exists() && entry.to.exists() {
+        return fs::rename(&entry.to, &entry.from);
+    }
+    if entry.destination_existed || !entry.to.exists() {
+        return Ok(());
+    }
+    if entry.to.is_dir() {
+        fs::remove_dir_all(&entry.to)
+    } else {
+        fs::remove_file(&entry.to)
+    }
+}
@@ -0,0 +1,397 @@
+//! Keyboard shortcuts for the app's actions, which can be rebound in Settings.
+
+use eframe::egui::{self, Key};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    Back,
+    /// A second shortcut for Back, since both Alt+Left and Backspace are common.
+    BackAlternate,
+    Forward,
+    Up,
+    Home,
+    Refresh,
+    Open,
+    Properties,
+    ContextMenu,
+    Rename,
+    MoveToTrash,
+    DeletePermanently,
+    FocusAddressBar,
+    Search,
+    GoTo,
+    ToggleHiddenFiles,
+    ToggleSidebar,
+    ToggleFullScreen,
+    NewFile,
+    NewFolder,
+    SelectAll,
+    Copy,
+    Cut,
+    Paste,
+    AddToBasket,
+    NewTab,
+    CloseTab,
+    /// Zooms the current folder's icons and rows.
+    ZoomIn,
+    ZoomOut,
+    ResetZoom,
+    /// Zooms the whole interface.
+    ScaleUp,
+    ScaleDown,
+    ResetScale,
+}
+
+impl Action {
+    pub const ALL: [Action; 33] = [
+        Action::Back,
+        Action::BackAlternate,
+        Action::Forward,
+        Action::Up,
+        Action::Home,
+        Action::Refresh,
+        Action::Open,
+        Action::Properties,
+        Action::ContextMenu,
+        Action::Rename,
+        Action::MoveToTrash,
+        Action::DeletePermanently,
+        Action::FocusAddressBar,
+        Action::Search,
+        Action::GoTo,
+        Action::ToggleHiddenFiles,
+        Action::ToggleSidebar,
+        Action::ToggleFullScreen,
+        Action::NewFile,
+        Action::NewFolder,
+        Action::SelectAll,
+        Action::Copy,
+        Action::Cut,
+        Action::Paste,
+        Action::AddToBasket,
+        Action::NewTab,
+        Action::CloseTab,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::ResetZoom,
+        Action::ScaleUp,
+        Action::ScaleDown,
+        Action::ResetScale,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::Back => "Back",
+            Action::BackAlternate => "Back (alternate)",
+            Action::Forward => "Forward",
+            Action::Up => "Parent Folder",
+            Action::Home => "Home Folder",
+            Action::Refresh => "Refresh",
+            Action::Open => "Open",
+            Action::Properties => "Properties",
+            Action::ContextMenu => "Context Menu",
+            Action::Rename => "Rename",
+            Action::MoveToTrash => "Move to Trash",
+            Action::DeletePermanently => "Delete Permanently",
+            Action::FocusAddressBar => "Edit Location",
+            Action::Search => "Search",
+            Action::GoTo => "Go To",
+            Action::ToggleHiddenFiles => "Show Hidden Files",
+            Action::ToggleSidebar => "Sidebar",
+            Action::ToggleFullScreen => "Full Screen",
+            Action::NewFile => "New File",
+            Action::NewFolder => "New Folder",
+            Action::SelectAll => "Select All",
+            Action::Copy => "Copy",
+            Action::Cut => "Cut",
+            Action::Paste => "Paste",
+            Action::AddToBasket => "Add to Basket",
+            Action::NewTab => "New Tab",
+            Action::CloseTab => "Close Tab",
+            Action::ZoomIn => "Larger Icons",
+            Action::ZoomOut => "Smaller Icons",
+            Action::ResetZoom => "Reset Icon Size",
+            Action::ScaleUp => "Zoom In",
+            Action::ScaleDown => "Zoom Out",
+            Action::ResetScale => "Reset Zoom",
+        }
+    }
+
+    pub fn default_shortcut(self) -> Shortcut {
+        match self {
+            Action::Back => Shortcut::alt(Key::ArrowLeft),
+            Action::BackAlternate => Shortcut::plain(Key::Backspace),
+            Action::Forward => Shortcut::alt(Key::ArrowRight),
+            Action::Up => Shortcut::alt(Key::ArrowUp),
+            Action::Home => Shortcut::plain(Key::Home),
+            Action::Refresh => Shortcut::plain(Key::F5),
+            Action::Open => Shortcut::plain(Key::Enter),
+            Action::Properties => Shortcut::alt(Key::Enter),
+            Action::ContextMenu => Shortcut { shift: true, ..Shortcut::plain(Key::F10) },
+            Action::Rename => Shortcut::plain(Key::F2),
+            Action::MoveToTrash => Shortcut::plain(Key::Delete),
+            Action::DeletePermanently => Shortcut { shift: true, ..Shortcut::plain(Key::Delete) },
+            Action::FocusAddressBar => Shortcut::ctrl(Key::L),
+            Action::Search => Shortcut::ctrl(Key::F),
+            Action::GoTo => Shortcut::ctrl(Key::G),
+            Action::ToggleHiddenFiles => Shortcut::ctrl(Key::H),
+            Action::ToggleSidebar => Shortcut::plain(Key::F9),
+            Action::ToggleFullScreen => Shortcut::plain(Key::F11),
+            Action::NewFile => Shortcut::ctrl(Key::N),
+            Action::NewFolder => Shortcut { shift: true, ..Shortcut::ctrl(Key::N) },
+            Action::SelectAll => Shortcut::ctrl(Key::A),
+            Action::Copy => Shortcut::ctrl(Key::C),
+            Action::Cut => Shortcut::ctrl(Key::X),
+            Action::Paste => Shortcut::ctrl(Key::V),
+            Action::AddToBasket => Shortcut::ctrl(Key::B),
+            Action::NewTab => Shortcut::ctrl(Key::T),
+            Action::CloseTab => Shortcut::ctrl(Key::W),
+            Action::ZoomIn => Shortcut { shift: true, ..Shortcut::ctrl(Key::PlusEquals) },
+            Action::ZoomOut => Shortcut { shift: true, ..Shortcut::ctrl(Key::Minus) },
+            Action::ResetZoom => Shortcut { shift: true, ..Shortcut::ctrl(Key::Num0) },
+            Action::ScaleUp => Shortcut::ctrl(Key::PlusEquals),
+            Action::ScaleDown => Shortcut::ctrl(Key::Minus),
+            Action::ResetScale => Shortcut::ctrl(Key::Num0),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Shortcut {
+    pub key: Key,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl Shortcut {
+    fn plain(key: Key) -> Shortcut {
+        Shortcut {
+            key,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    fn ctrl(key: Key) -> Shortcut {
+        Shortcut { ctrl: true, ..Shortcut::plain(key) }
+    }
+
+    fn alt(key: Key) -> Shortcut {
+        Shortcut { alt: true, ..Shortcut::plain(key) }
+    }
+
+    pub fn new(key: Key, modifiers: egui::Modifiers) -> Shortcut {
+        Shortcut {
+            key,
+            ctrl: modifiers.ctrl,
+            shift: modifiers.shift,
+            alt: modifiers.alt,
+        }
+    }
+
+    /// Whether it was pressed this frame, with exactly these modifiers so that Ctrl+N doesn't
+    /// also fire for Ctrl+Shift+N.
+    pub fn pressed(&self, input: &egui::InputState) -> bool {
+        let modifiers = input.modifiers;
+        input.key_pressed(self.key)
+            && modifiers.ctrl == self.ctrl
+            && modifiers.shift == self.shift
+            && modifiers.alt == self.alt
+    }
+
+    /// E.g. "Ctrl+Shift+N".
+    pub fn label(&self) -> String {
+        let mut parts = Vec::new();
+        if self.ctrl {
+            parts.push("Ctrl".to_string());
+        }
+        if self.shift {
+            parts.push("Shift".to_string());
+        }
+        if self.alt {
+            parts.push("Alt".to_string());
+        }
+        let key = match self.key {
+            Key::PlusEquals => "+".to_string(),
+            Key::Minus => "-".to_string(),
+            Key::ArrowLeft => "Left".to_string(),
+            Key::ArrowRight => "Right".to_string(),
+            Key::ArrowUp => "Up".to_string(),
+            Key::ArrowDown => "Down".to_string(),
+            key => {
+                let name = format!("{:?}", key);
+                name.strip_prefix("Num").map(str::to_string).unwrap_or(name)
+            }
+        };
+        parts.push(key);
+        parts.join("+")
+    }
+}
+
+/// The shortcuts that differ from the defaults, so new actions get theirs without a config reset.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(transparent)]
+pub struct Keymap(HashMap<Action, Shortcut>);
+
+impl Keymap {
+    pub fn shortcut(&self, action: Action) -> Shortcut {
+        self.0.get(&action).copied().unwrap_or_else(|| action.default_shortcut())
+    }
+
+    pub fn is_default(&self, action: Action) -> bool {
+        self.shortcut(action) == action.default_shortcut()
+    }
+
+    /// The other action already using `shortcut`, if any.
+    pub fn conflict(&self, action: Action, shortcut: Shortcut) -> Option<Action> {
+        Action::ALL.into_iter().find(|other| *other != action && self.shortcut(*other) == shortcut)
+    }
+
+    /// Binds `shortcut` to `action`, unless another action has it.
+    pub fn set(&mut self, action: Action, shortcut: Shortcut) -> Result<(), Action> {
+        if let Some(other) = self.conflict(action, shortcut) {
+            return Err(other);
+        }
+        if shortcut == action.default_shortcut() {
+            self.0.remove(&action);
+        } else {
+            self.0.insert(action, shortcut);
+        }
+        Ok(())
+    }
+
+    /// Puts `action` back on its default shortcut, unless another action has taken it since.
+    pub fn reset(&mut self, action: Action) -> Result<(), Action> {
+        self.set(action, action.default_shortcut())
+    }
+
+    pub fn reset_all(&mut self) {
+        self.0.clear();
+    }
+
+    /// The actions whose shortcuts were pressed this frame.
+    pub fn pressed(&self, input: &egui::InputState) -> Vec<Action> {
+        Action::ALL.into_iter().filter(|action| self.shortcut(*action).pressed(input)).collect()
+    }
+}
+
+/// Extra keys layered over the shortcuts.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum Profile {
+    #[default]
+    Standard,
+    /// Plain letters act as Vim-style commands instead of jumping to names.
+    Vim,
+}
+
+impl Profile {
+    pub fn label(self) -> &'static str {
+        match self {
+            Profile::Standard => "Standard",
+            Profile::Vim => "Vim",
+        }
+    }
+}
+
+/// What a Vim-style command does to the file list.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum VimCommand {
+    /// Moves the focus by this many rows.
+    Move(isize),
+    First,
+    Last,
+    Run(Action),
+}
+
+/// Vim's normal mode for the file list. It only sees keys typed while no text field has focus, so
+/// the address bar and search box work as an insert mode.
+#[derive(Default)]
+pub struct VimKeys {
+    /// The first key of a two-key command, like the `d` of `dd`.
+    pending: Option<char>,
+}
+
+impl VimKeys {
+    pub const HELP: [(&'static str, &'static str); 9] = [
+        ("j / k", "Next / previous item"),
+        ("h", "Parent folder"),
+        ("l", "Open"),
+        ("gg / G", "First / last item"),
+        ("/", "Search"),
+        ("dd", "Move to Trash"),
+        ("yy", "Copy"),
+        ("p", "Paste"),
+        ("Esc", "Cancel a half-typed command"),
+    ];
+
+    /// Takes typed text, returning the commands it completes.
+    pub fn feed(&mut self, text: &str) -> Vec<VimCommand> {
+        text.chars().filter_map(|key| self.key(key)).collect()
+    }
+
+    fn key(&mut self, key: char) -> Option<VimCommand> {
+        let command = match (self.pending.take(), key) {
+            (Some('g'), 'g') => VimCommand::First,
+            (Some('d'), 'd') => VimCommand::Run(Action::MoveToTrash),
+            (Some('y'), 'y') => VimCommand::Run(Action::Copy),
+            (_, 'g' | 'd' | 'y') => {
+                self.pending = Some(key);
+                return None;
+            }
+            (_, 'j') => VimCommand::Move(1),
+            (_, 'k') => VimCommand::Move(-1),
+            (_, 'h') => VimCommand::Run(Action::Up),
+            (_, 'l') => VimCommand::Run(Action::Open),
+            (_, 'G') => VimCommand::Last,
+            (_, '/') => VimCommand::Run(Action::Search),
+            (_, 'p') => VimCommand::Run(Action::Paste),
+            _ => return None,
+        };
+        Some(command)
+    }
+
+    pub fn cancel(&mut self) {
+        self.pending = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_do_not_conflict() {
+        let keymap = Keymap::default();
+        for action in Action::ALL {
+            assert_eq!(keymap.conflict(action, keymap.shortcut(action)), None, "{:?}", action);
+        }
+    }
+
+    #[test]
+    fn refuses_a_shortcut_in_use() {
+        let mut keymap = Keymap::default();
+        assert_eq!(keymap.set(Action::NewTab, Shortcut::ctrl(Key::C)), Err(Action::Copy));
+        assert_eq!(keymap.set(Action::NewTab, Shortcut::ctrl(Key::E)), Ok(()));
+        assert_eq!(keymap.shortcut(Action::NewTab), Shortcut::ctrl(Key::E));
+        assert_eq!(keymap.set(Action::CloseTab, Shortcut::ctrl(Key::T)), Ok(()));
+        assert_eq!(keymap.reset(Action::NewTab), Err(Action::CloseTab));
+        keymap.reset_all();
+        assert!(keymap.is_default(Action::NewTab));
+    }
+
+    #[test]
+    fn vim_keys_combine_pairs() {
+        let mut vim = VimKeys::default();
+        assert_eq!(vim.feed("jk"), [VimCommand::Move(1), VimCommand::Move(-1)]);
+        assert!(vim.feed("g").is_empty());
+        assert_eq!(vim.feed("g"), [VimCommand::First]);
+        assert_eq!(vim.feed("dyyG"), [VimCommand::Run(Action::Copy), VimCommand::Last]);
+        vim.feed("d");
+        vim.cancel();
+        assert!(vim.feed("d").is_empty());
+    }
+}
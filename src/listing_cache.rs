@@ -0,0 +1,64 @@
+use crate::file_system::{self, FileSystemItem};
+use crate::profiling::{self, Stage};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime};
+
+const MAX_CACHED_LISTINGS: usize = 64;
+
+struct CachedListing {
+    modified: SystemTime,
+    items: Vec<FileSystemItem>,
+    /// When it was last stored or read, so the least recently used listing is the one dropped.
+    used: Instant,
+}
+
+/// Directory listings shared by the worker tasks, invalidated when a folder's mtime changes.
+#[derive(Clone, Default)]
+pub struct ListingCache {
+    entries: Arc<Mutex<HashMap<PathBuf, CachedListing>>>,
+}
+
+impl ListingCache {
+    /// Returns the cached listing when the folder hasn't changed since it was read,
+    /// otherwise lists it again.
+    pub fn list(&self, path: &Path) -> io::Result<Vec<FileSystemItem>> {
+        let modified = fs::metadata(path)?.modified()?;
+        if let Some(cached) = self.entries.lock().unwrap().get_mut(path) {
+            if cached.modified == modified {
+                cached.used = Instant::now();
+                return Ok(cached.items.clone());
+            }
+        }
+        self.refresh(path)
+    }
+
+    /// The last listing read for `path`, even if the folder changed since.
+    pub fn cached(&self, path: &Path) -> Option<Vec<FileSystemItem>> {
+        self.entries.lock().unwrap().get_mut(path).map(|cached| {
+            cached.used = Instant::now();
+            cached.items.clone()
+        })
+    }
+
+    /// Lists `path` from disk and stores the result, bypassing any cached entry.
+    pub fn refresh(&self, path: &Path) -> io::Result<Vec<FileSystemItem>> {
+        let modified = fs::metadata(path)?.modified()?;
+        let items = profiling::time(path, Stage::Listing, || file_system::list_directory(path))?;
+        self.store(path, modified, items.clone());
+        Ok(items)
+    }
+
+    pub fn store(&self, path: &Path, modified: SystemTime, items: Vec<FileSystemItem>) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= MAX_CACHED_LISTINGS && !entries.contains_key(path) {
+            if let Some(oldest) = entries.iter().min_by_key(|(_, cached)| cached.used).map(|(path, _)| path.clone()) {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(path.to_path_buf(), CachedListing { modified, items, used: Instant::now() });
+    }
+}
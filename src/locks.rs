@@ -0,0 +1,110 @@
+use std::io;
+use std::path::Path;
+
+/// Whether `err` means another process holds the file open (Windows sharing or lock
+/// violation, `EBUSY`/`ETXTBSY` elsewhere).
+pub fn is_locked_error(err: &io::Error) -> bool {
+    match err.raw_os_error() {
+        Some(code) if cfg!(windows) => code == 32 || code == 33,
+        Some(code) => code == 16 || code == 26,
+        None => false,
+    }
+}
+
+/// Names of the processes currently holding `path` open, as `name (pid)`.
+#[cfg(not(windows))]
+pub fn lock_holders(path: &Path) -> Vec<String> {
+    let Ok(output) = std::process::Command::new("lsof").arg("-F").arg("pc").arg(path).output() else {
+        return Vec::new();
+    };
+    let mut holders = Vec::new();
+    let mut pid = String::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Some(value) = line.strip_prefix('p') {
+            pid = value.to_string();
+        } else if let Some(command) = line.strip_prefix('c') {
+            holders.push(format!("{} ({})", command, pid));
+        }
+    }
+    holders
+}
+
+#[cfg(windows)]
+pub fn lock_holders(path: &Path) -> Vec<String> {
+    use restart_manager::*;
+    use std::os::windows::ffi::OsStrExt;
+
+    let wide_path: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut holders = Vec::new();
+    unsafe {
+        let mut session = 0u32;
+        let mut session_key = [0u16; CCH_RM_SESSION_KEY + 1];
+        if RmStartSession(&mut session, 0, session_key.as_mut_ptr()) != 0 {
+            return holders;
+        }
+        let files = [wide_path.as_ptr()];
+        if RmRegisterResources(session, 1, files.as_ptr(), 0, std::ptr::null(), 0, std::ptr::null()) == 0 {
+            let mut needed = 0u32;
+            let mut count = 16u32;
+            let mut infos: Vec<RmProcessInfo> = (0..count).map(|_| std::mem::zeroed()).collect();
+            let mut reasons = 0u32;
+            if RmGetList(session, &mut needed, &mut count, infos.as_mut_ptr(), &mut reasons) == 0 {
+                for info in &infos[..count as usize] {
+                    let len = info.app_name.iter().position(|&c| c == 0).unwrap_or(info.app_name.len());
+                    let name = String::from_utf16_lossy(&info.app_name[..len]);
+                    holders.push(format!("{} ({})", name, info.process.process_id));
+                }
+            }
+        }
+        RmEndSession(session);
+    }
+    holders
+}
+
+#[cfg(windows)]
+#[allow(non_snake_case)]
+mod restart_manager {
+    pub const CCH_RM_SESSION_KEY: usize = 32;
+    const CCH_RM_MAX_APP_NAME: usize = 255;
+    const CCH_RM_MAX_SVC_NAME: usize = 63;
+
+    #[repr(C)]
+    pub struct RmUniqueProcess {
+        pub process_id: u32,
+        pub start_time_low: u32,
+        pub start_time_high: u32,
+    }
+
+    #[repr(C)]
+    pub struct RmProcessInfo {
+        pub process: RmUniqueProcess,
+        pub app_name: [u16; CCH_RM_MAX_APP_NAME + 1],
+        pub service_short_name: [u16; CCH_RM_MAX_SVC_NAME + 1],
+        pub application_type: i32,
+        pub app_status: u32,
+        pub ts_session_id: u32,
+        pub restartable: i32,
+    }
+
+    #[link(name = "rstrtmgr")]
+    unsafe extern "system" {
+        pub fn RmStartSession(session: *mut u32, flags: u32, session_key: *mut u16) -> u32;
+        pub fn RmRegisterResources(
+            session: u32,
+            file_count: u32,
+            files: *const *const u16,
+            application_count: u32,
+            applications: *const RmUniqueProcess,
+            service_count: u32,
+            services: *const *const u16,
+        ) -> u32;
+        pub fn RmGetList(
+            session: u32,
+            needed: *mut u32,
+            count: *mut u32,
+            infos: *mut RmProcessInfo,
+            reboot_reasons: *mut u32,
+        ) -> u32;
+        pub fn RmEndSession(session: u32) -> u32;
+    }
+}
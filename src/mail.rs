@@ -0,0 +1,88 @@
+//! Handing files to the default mail client as attachments to a new message. `mailto:` links
+//! can't carry attachments, so each platform gets its own route.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Opens a new message in the default mail client with `paths` attached, returning once the
+/// client has it.
+#[cfg(target_os = "linux")]
+pub fn send(paths: &[PathBuf]) -> Result<(), String> {
+    let mut command = Command::new("xdg-email");
+    for path in paths {
+        command.arg("--attach").arg(path);
+    }
+    let status = command.status().map_err(|err| format!("xdg-email: {}", err))?;
+    if !status.success() {
+        return Err("no mail client accepted the attachments".to_string());
+    }
+    Ok(())
+}
+
+/// Only Mail takes attachments from `open`; other clients would treat them as documents.
+#[cfg(target_os = "macos")]
+pub fn send(paths: &[PathBuf]) -> Result<(), String> {
+    let status = Command::new("open").args(["-a", "Mail"]).args(paths).status().map_err(|err| err.to_string())?;
+    if !status.success() {
+        return Err("Mail could not open the attachments".to_string());
+    }
+    Ok(())
+}
+
+/// Simple MAPI, which every Windows mail client registers with, driven through PowerShell. The
+/// paths go through the environment, one per line, so none of them ends up in the script itself.
+#[cfg(windows)]
+pub fn send(paths: &[PathBuf]) -> Result<(), String> {
+    let lines: Vec<String> = paths.iter().map(|path| path.display().to_string()).collect();
+    let script = format!("{}\nexit [Mapi]::Send(@($env:FILE_MANAGER_ATTACHMENTS -split \"`n\"))", MAPI_SCRIPT);
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-Command", &script])
+        .env("FILE_MANAGER_ATTACHMENTS", lines.join("\n"))
+        .output()
+        .map_err(|err| err.to_string())?;
+    match output.status.code() {
+        // Success, or the message was discarded.
+        Some(0) | Some(1) => Ok(()),
+        Some(2) => Err("no mail client is set up".to_string()),
+        Some(code) => Err(format!("the mail client refused the message (MAPI error {})", code)),
+        None => Err(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+pub fn send(_paths: &[PathBuf]) -> Result<(), String> {
+    Err("not supported on this platform".to_string())
+}
+
+/// `MAPISendMailW` with a file descriptor per attachment, showing the client's compose window.
+#[cfg(windows)]
+const MAPI_SCRIPT: &str = r#"Add-Type @'
+using System;
+using System.Runtime.InteropServices;
+public static class Mapi {
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    class FileDesc { public int Reserved; public int Flags; public int Position = -1; public string Path;
+        public string Name; public IntPtr Type; }
+    [StructLayout(LayoutKind.Sequential, CharSet = CharSet.Unicode)]
+    class Message { public int Reserved; public string Subject; public string Body; public string Type;
+        public string Date; public string Conversation; public int Flags; public IntPtr Originator;
+        public int RecipientCount; public IntPtr Recipients; public int FileCount; public IntPtr Files; }
+    [DllImport("MAPI32.DLL", CharSet = CharSet.Unicode)]
+    static extern int MAPISendMailW(IntPtr session, IntPtr window, Message message, int flags, int reserved);
+    public static int Send(string[] paths) {
+        int size = Marshal.SizeOf(typeof(FileDesc));
+        IntPtr files = Marshal.AllocHGlobal(size * paths.Length);
+        try {
+            for (int i = 0; i < paths.Length; i++) {
+                var file = new FileDesc { Path = paths[i], Name = System.IO.Path.GetFileName(paths[i]) };
+                Marshal.StructureToPtr(file, files + i * size, false);
+            }
+            var message = new Message { FileCount = paths.Length, Files = files };
+            // MAPI_LOGON_UI | MAPI_DIALOG
+            return MAPISendMailW(IntPtr.Zero, IntPtr.Zero, message, 0x1 | 0x8, 0);
+        } finally {
+            Marshal.FreeHGlobal(files);
+        }
+    }
+}
+'@"#;
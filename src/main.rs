@@ -1,35 +1,147 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod adb;
 mod app;
+mod archive;
+mod checksums;
+mod cloud;
+mod color_rules;
+mod compare;
 mod config;
+mod daemon;
+mod desktop;
+mod docker;
+mod download;
+mod duplicates;
+mod elevation;
+mod encoding;
 mod file_system;
+mod i18n;
+mod icons;
+mod journal;
+mod keymap;
+mod listing_cache;
+mod locks;
+mod mail;
+mod music;
+mod navigation;
+mod notifications;
+mod open_with;
+mod peers;
+mod photos;
+mod picker;
+mod power;
+mod profiling;
+mod remote;
+mod share;
+mod shell_menu;
+mod shortcuts;
+mod sorter;
+mod system_clipboard;
+mod tabs;
+mod taskbar;
+mod templates;
+mod theme;
+mod thumbnails;
+mod tray;
+mod usage;
+mod versions;
 
 use crate::app::FileManager;
+use crate::i18n::{t, tf};
 use eframe::{egui, NativeOptions};
+use std::path::PathBuf;
 use std::sync::mpsc;
 use std::thread;
 use tokio::runtime::Runtime;
 
 fn main() {
+    let args: Vec<_> = std::env::args_os().skip(1).collect();
+    if let Some(operation) = elevation::ElevatedOperation::from_args(&args) {
+        std::process::exit(if operation.run().is_ok() { 0 } else { 1 });
+    }
+    if args.first().is_some_and(|arg| arg == daemon::DAEMON_ARG) {
+        std::process::exit(if daemon::run().is_ok() { 0 } else { 1 });
+    }
+    let pick_mode = args.first().and_then(|arg| picker::PickMode::from_arg(arg));
+    let path_args = if pick_mode.is_some() { &args[1..] } else { &args[..] };
+    let window_pos = path_args.iter().find_map(|arg| {
+        let (x, y) = arg.to_str()?.strip_prefix(file_system::WINDOW_POS_ARG)?.split_once(',')?;
+        Some(egui::pos2(x.parse().ok()?, y.parse().ok()?))
+    });
+    let mut initial_path = path_args
+        .iter()
+        .find(|arg| !arg.to_string_lossy().starts_with(file_system::WINDOW_POS_ARG))
+        .map(PathBuf::from);
+    // A self-extracting archive unpacks itself and shows what came out.
+    match archive::self_extract() {
+        Some(Ok(dir)) => initial_path = Some(dir),
+        Some(Err(err)) => {
+            // Release builds have no console, so the error gets a window of its own.
+            i18n::set_language(config::load_config().unwrap_or_default().language);
+            show_error(tf("Could not extract the archive: {error}", &[("error", &err)]));
+            std::process::exit(1);
+        }
+        None => {}
+    }
     let (tx, rx) = mpsc::channel();
     let (event_tx, event_rx) = mpsc::channel();
 
     let rt = Runtime::new().expect("Failed to create Tokio runtime");
 
     let file_system_handle = rt.handle().clone();
-    thread::spawn(move || {
+    let worker = thread::spawn(move || {
         file_system_handle.block_on(async {
             file_system::watch_directory(tx, event_rx).await;
         });
     });
 
+    #[cfg(target_os = "linux")]
+    desktop::install_desktop_entry();
     let mut native_options = NativeOptions::default();
+    native_options.app_id = Some(desktop::APP_ID.to_string());
     native_options.initial_window_size = Some(egui::vec2(800.0, 600.0));
     native_options.min_window_size = Some(egui::vec2(400.0, 300.0));
+    let saved = config::load_config().unwrap_or_default();
+    if let Some(geometry) = saved.last_monitor.as_ref().and_then(|monitor| saved.window_geometry.get(monitor)) {
+        native_options.initial_window_size = Some(egui::vec2(geometry.size[0], geometry.size[1]));
+        native_options.initial_window_pos = geometry.position.map(|[x, y]| egui::pos2(x, y));
+        native_options.maximized = geometry.maximized;
+    }
+    // New windows open next to the window they were opened from, on the same monitor.
+    if window_pos.is_some() {
+        native_options.initial_window_pos = window_pos;
+        native_options.maximized = false;
+    }
 
-    eframe::run_native(
-        "File Manager",
+    let _ = eframe::run_native(
+        pick_mode.map_or("File Manager", |mode| mode.title()),
         native_options,
-        Box::new(|_cc| Box::new(FileManager::new(rx, event_tx))),
+        Box::new(move |_cc| Box::new(FileManager::new(rx, event_tx, initial_path, pick_mode, window_pos.is_some()))),
     );
+
+    // The app sends a shutdown event on exit (and drops its sender), so this returns once
+    // in-flight operations are done.
+    let _ = worker.join();
+    if pick_mode.is_some() && !picker::picked() {
+        std::process::exit(1);
+    }
+}
+
+/// Shows `message` with a Close button, for errors from before the file manager's window opens.
+fn show_error(message: String) {
+    let options = NativeOptions {
+        initial_window_size: Some(egui::vec2(420.0, 120.0)),
+        resizable: false,
+        ..NativeOptions::default()
+    };
+    let _ = eframe::run_simple_native("File Manager", options, move |ctx, frame| {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.label(&message);
+            ui.add_space(8.0);
+            if ui.button(t("Close")).clicked() {
+                frame.close();
+            }
+        });
+    });
 }
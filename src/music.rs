@@ -0,0 +1,142 @@
+//! Organizing a music library by its tags: tracks go to folders by artist and album and are
+//! renamed after their number and title. The moves are planned like a folder sort, so they can
+//! be previewed and undone the same way.
+
+use crate::sorter::{self, PlannedMove};
+use id3::TagLike;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const AUDIO_EXTENSIONS: &[&str] = &["mp3", "wav", "aif", "aiff"];
+
+pub const DEFAULT_LAYOUT: &str = "{artist}/{album}/{track} - {title}";
+
+/// The tags a layout can use.
+#[derive(Default)]
+pub struct TrackTags {
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub title: Option<String>,
+    pub track: Option<u32>,
+    pub year: Option<i32>,
+}
+
+impl TrackTags {
+    fn read(path: &Path) -> Option<Self> {
+        let tag = id3::Tag::read_from_path(path).ok()?;
+        Some(Self {
+            // Compilations keep together under the album artist.
+            artist: tag.album_artist().or(tag.artist()).map(str::to_string),
+            album: tag.album().map(str::to_string),
+            title: tag.title().map(str::to_string),
+            track: tag.track(),
+            year: tag.year(),
+        })
+    }
+
+    /// Where the track goes relative to the library, without its extension. Tracks without an
+    /// artist or title stay where they are.
+    pub fn relative_path(&self, layout: &str) -> Option<PathBuf> {
+        let artist = self.artist.as_deref().filter(|artist| !artist.trim().is_empty())?;
+        let title = self.title.as_deref().filter(|title| !title.trim().is_empty())?;
+        let filled = layout
+            .replace("{artist}", &file_name_safe(artist))
+            .replace("{album}", &file_name_safe(self.album.as_deref().unwrap_or("Unknown Album")))
+            .replace("{title}", &file_name_safe(title))
+            .replace("{track}", &format!("{:02}", self.track.unwrap_or(0)))
+            .replace("{year}", &self.year.map(|year| year.to_string()).unwrap_or_default());
+        let parts: Vec<&str> = filled.split('/').map(str::trim).collect();
+        // Blank folders from missing tags would otherwise collapse, or climb out with "..".
+        if parts.iter().any(|part| part.is_empty() || part.chars().all(|c| c == '.')) {
+            return None;
+        }
+        Some(parts.iter().collect())
+    }
+}
+
+/// Where each tagged track under `library` would go.
+pub fn plan(library: &Path, layout: &str) -> Result<Vec<PlannedMove>, String> {
+    if !layout.contains("{title}") && !layout.contains("{track}") {
+        return Err("The layout needs {title} or {track} to tell tracks apart".to_string());
+    }
+    fs::read_dir(library).map_err(|err| err.to_string())?;
+    let mut tracks = Vec::new();
+    find_tracks(library, &mut tracks);
+    tracks.sort();
+    let mut moves = Vec::new();
+    let mut taken = HashSet::new();
+    for from in tracks {
+        let Some(relative) = TrackTags::read(&from).and_then(|tags| tags.relative_path(layout)) else {
+            continue;
+        };
+        let extension = from.extension().unwrap_or_default().to_string_lossy().to_lowercase();
+        // Not `with_extension`, which would cut a title like "Mr. Blue" short.
+        let mut target = library.join(relative).into_os_string();
+        target.push(format!(".{}", extension));
+        let target = PathBuf::from(target);
+        if target == from {
+            continue;
+        }
+        let to = sorter::available(&target, &taken);
+        taken.insert(to.clone());
+        moves.push(PlannedMove { from, to });
+    }
+    Ok(moves)
+}
+
+fn find_tracks(dir: &Path, found: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            find_tracks(&path, found);
+        } else if file_type.is_file() && is_audio(&path) {
+            found.push(path);
+        }
+    }
+}
+
+fn is_audio(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| AUDIO_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Swaps out characters that aren't allowed in file names on some systems.
+fn file_name_safe(text: &str) -> String {
+    let safe: String =
+        text.chars().map(|c| if "/\\:*?\"<>|".contains(c) || c.is_control() { '_' } else { c }).collect();
+    safe.trim().trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tags_fill_the_layout() {
+        let tags = TrackTags {
+            artist: Some("AC/DC".to_string()),
+            album: None,
+            title: Some("Back in Black".to_string()),
+            track: Some(6),
+            year: Some(1980),
+        };
+        let path = tags.relative_path(DEFAULT_LAYOUT);
+        assert_eq!(path, Some(PathBuf::from("AC_DC/Unknown Album/06 - Back in Black")));
+        assert_eq!(tags.relative_path("{year}/{title}"), Some(PathBuf::from("1980/Back in Black")));
+        let undated = TrackTags { year: None, ..tags };
+        assert_eq!(undated.relative_path("{year}/{title}"), None);
+        let untitled = TrackTags { title: None, ..undated };
+        assert_eq!(untitled.relative_path(DEFAULT_LAYOUT), None);
+    }
+}
@@ -0,0 +1,112 @@
+use std::path::{Path, PathBuf};
+
+const MAX_RECENT_PATHS: usize = 100;
+
+/// Back/forward stack for a single view.
+#[derive(Default, Clone)]
+pub struct NavigationHistory {
+    entries: Vec<PathBuf>,
+    index: usize,
+}
+
+impl NavigationHistory {
+    pub fn visit(&mut self, path: &Path) {
+        if self.current() == Some(path) {
+            return;
+        }
+        if !self.entries.is_empty() {
+            self.entries.truncate(self.index + 1);
+        }
+        self.entries.push(path.to_path_buf());
+        self.index = self.entries.len() - 1;
+    }
+
+    pub fn back(&mut self) -> Option<PathBuf> {
+        if self.can_go_back() {
+            self.index -= 1;
+            Some(self.entries[self.index].clone())
+        } else {
+            None
+        }
+    }
+
+    pub fn forward(&mut self) -> Option<PathBuf> {
+        if self.can_go_forward() {
+            self.index += 1;
+            Some(self.entries[self.index].clone())
+        } else {
+            None
+        }
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        self.index > 0
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        self.index + 1 < self.entries.len()
+    }
+
+    pub fn current(&self) -> Option<&Path> {
+        self.entries.get(self.index).map(PathBuf::as_path)
+    }
+}
+
+/// Records `path` as the most recent entry of the global history, dropping older duplicates.
+pub fn record_recent(recent: &mut Vec<PathBuf>, path: &Path) {
+    recent.retain(|p| p != path);
+    recent.push(path.to_path_buf());
+    if recent.len() > MAX_RECENT_PATHS {
+        recent.drain(..recent.len() - MAX_RECENT_PATHS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn back_and_forward_walk_the_stack() {
+        let mut history = NavigationHistory::default();
+        history.visit(Path::new("/a"));
+        history.visit(Path::new("/b"));
+        history.visit(Path::new("/c"));
+
+        assert_eq!(history.back(), Some(PathBuf::from("/b")));
+        assert_eq!(history.back(), Some(PathBuf::from("/a")));
+        assert_eq!(history.back(), None);
+        assert_eq!(history.forward(), Some(PathBuf::from("/b")));
+        assert_eq!(history.current(), Some(Path::new("/b")));
+    }
+
+    #[test]
+    fn visiting_after_back_drops_forward_entries() {
+        let mut history = NavigationHistory::default();
+        history.visit(Path::new("/a"));
+        history.visit(Path::new("/b"));
+        history.back();
+        history.visit(Path::new("/c"));
+
+        assert!(!history.can_go_forward());
+        assert_eq!(history.back(), Some(PathBuf::from("/a")));
+    }
+
+    #[test]
+    fn visiting_current_path_is_ignored() {
+        let mut history = NavigationHistory::default();
+        history.visit(Path::new("/a"));
+        history.visit(Path::new("/a"));
+
+        assert!(!history.can_go_back());
+    }
+
+    #[test]
+    fn recent_paths_are_deduplicated() {
+        let mut recent = Vec::new();
+        record_recent(&mut recent, Path::new("/a"));
+        record_recent(&mut recent, Path::new("/b"));
+        record_recent(&mut recent, Path::new("/a"));
+
+        assert_eq!(recent, vec![PathBuf::from("/b"), PathBuf::from("/a")]);
+    }
+}
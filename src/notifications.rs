@@ -0,0 +1,31 @@
+use eframe::egui;
+use notify_rust::Notification;
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use std::thread;
+
+/// Shows a desktop notification; on freedesktop desktops clicking it sends `destination` to
+/// `clicked`. Runs on its own thread since waiting for the click blocks.
+pub fn notify(summary: String, body: String, destination: PathBuf, clicked: Sender<PathBuf>, ctx: egui::Context) {
+    thread::spawn(move || {
+        let mut notification = Notification::new();
+        notification.appname("File Manager").summary(&summary).body(&body);
+        #[cfg(all(unix, not(target_os = "macos")))]
+        {
+            notification.action("default", "Open").action("open", "Open Destination");
+            if let Ok(handle) = notification.show() {
+                handle.wait_for_action(|action| {
+                    if action == "default" || action == "open" {
+                        let _ = clicked.send(destination);
+                        ctx.request_repaint();
+                    }
+                });
+            }
+        }
+        #[cfg(not(all(unix, not(target_os = "macos"))))]
+        {
+            let _ = (destination, clicked, ctx);
+            let _ = notification.show();
+        }
+    });
+}
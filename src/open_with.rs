@@ -0,0 +1,170 @@
+use crate::shortcuts::{self, ShortcutTarget};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum FolderKind {
+    GitRepository,
+    RustProject,
+    NodeProject,
+    Pictures,
+}
+
+impl FolderKind {
+    pub const ALL: [FolderKind; 4] =
+        [FolderKind::GitRepository, FolderKind::RustProject, FolderKind::NodeProject, FolderKind::Pictures];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FolderKind::GitRepository => "Git Repository",
+            FolderKind::RustProject => "Rust Project",
+            FolderKind::NodeProject => "Node Project",
+            FolderKind::Pictures => "Pictures",
+        }
+    }
+
+    /// Guesses what a folder holds from marker files, or from its name for pictures.
+    pub fn detect(folder: &Path) -> Vec<FolderKind> {
+        let mut kinds = Vec::new();
+        if folder.join(".git").exists() {
+            kinds.push(FolderKind::GitRepository);
+        }
+        if folder.join("Cargo.toml").is_file() {
+            kinds.push(FolderKind::RustProject);
+        }
+        if folder.join("package.json").is_file() {
+            kinds.push(FolderKind::NodeProject);
+        }
+        let name = folder.file_name().unwrap_or_default().to_string_lossy().to_lowercase();
+        if ["pictures", "photos", "images", "screenshots"].contains(&name.as_str()) {
+            kinds.push(FolderKind::Pictures);
+        }
+        kinds
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct FolderApp {
+    pub name: String,
+    /// Command line the folder path is appended to.
+    pub command: String,
+    /// Folder kinds this app is suggested for; empty means every folder.
+    pub kinds: Vec<FolderKind>,
+}
+
+impl FolderApp {
+    pub fn applies_to(&self, kinds: &[FolderKind]) -> bool {
+        self.kinds.is_empty() || self.kinds.iter().any(|kind| kinds.contains(kind))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct FolderApps(pub Vec<FolderApp>);
+
+impl Default for FolderApps {
+    fn default() -> Self {
+        let (code, gimp) = if cfg!(target_os = "macos") {
+            ("open -a \"Visual Studio Code\"", "open -a GIMP")
+        } else {
+            ("code", "gimp")
+        };
+        Self(vec![
+            FolderApp {
+                name: "Visual Studio Code".to_string(),
+                command: code.to_string(),
+                kinds: vec![FolderKind::GitRepository, FolderKind::RustProject, FolderKind::NodeProject],
+            },
+            FolderApp {
+                name: "GIMP".to_string(),
+                command: gimp.to_string(),
+                kinds: vec![FolderKind::Pictures],
+            },
+        ])
+    }
+}
+
+/// Splits a command line on whitespace, keeping double-quoted parts together.
+pub fn split_command(command: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in command.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    parts.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+/// Applications that can open folders: on macOS every app bundle, elsewhere those whose
+/// desktop entry accepts `inode/directory`.
+pub fn installed_folder_apps() -> Vec<FolderApp> {
+    if cfg!(target_os = "macos") {
+        return installed_mac_apps();
+    }
+    let mut data_dirs: Vec<PathBuf> = dirs::data_dir().into_iter().collect();
+    let system_dirs = std::env::var("XDG_DATA_DIRS").unwrap_or_else(|_| "/usr/local/share:/usr/share".to_string());
+    data_dirs.extend(system_dirs.split(':').filter(|dir| !dir.is_empty()).map(PathBuf::from));
+
+    let mut apps: Vec<FolderApp> = Vec::new();
+    for entry in data_dirs.iter().filter_map(|dir| fs::read_dir(dir.join("applications")).ok()).flatten().flatten() {
+        let path = entry.path();
+        let opens_folders = fs::read_to_string(&path)
+            .map(|content| {
+                content.lines().any(|line| line.starts_with("MimeType=") && line.contains("inode/directory"))
+            })
+            .unwrap_or(false);
+        if !opens_folders {
+            continue;
+        }
+        let Some(shortcut) = shortcuts::read(&path) else {
+            continue;
+        };
+        let (Some(name), ShortcutTarget::Command { command, .. }) = (shortcut.name, shortcut.target) else {
+            continue;
+        };
+        if !apps.iter().any(|app| app.name == name) {
+            apps.push(FolderApp {
+                name,
+                command,
+                kinds: Vec::new(),
+            });
+        }
+    }
+    apps.sort_by(|a, b| a.name.cmp(&b.name));
+    apps
+}
+
+fn installed_mac_apps() -> Vec<FolderApp> {
+    let mut roots = vec![PathBuf::from("/Applications")];
+    roots.extend(dirs::home_dir().map(|home| home.join("Applications")));
+    let mut apps: Vec<FolderApp> = roots
+        .iter()
+        .filter_map(|root| fs::read_dir(root).ok())
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension()? != "app" {
+                return None;
+            }
+            Some(FolderApp {
+                name: path.file_stem()?.to_string_lossy().to_string(),
+                command: format!("open -a \"{}\"", path.display()),
+                kinds: Vec::new(),
+            })
+        })
+        .collect();
+    apps.sort_by(|a, b| a.name.cmp(&b.name));
+    apps
+}
@@ -0,0 +1,310 @@
+//! Other copies of the app on the local network, found over mDNS, and sending files straight to
+//! them. Nothing is written on the receiving side until someone there accepts.
+
+use eframe::egui;
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const SERVICE_TYPE: &str = "_happ._tcp.local.";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long a connection may go without sending anything.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long an offer waits for someone to accept or decline it.
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(300);
+/// The longest offer read, which is enough to list many thousands of files.
+const MAX_OFFER: u64 = 4 * 1024 * 1024;
+/// Offers handled at once; more are turned away rather than each getting a thread and a dialog.
+const MAX_CONNECTIONS: usize = 4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Peer {
+    pub name: String,
+    pub addr: SocketAddr,
+}
+
+/// What a sender proposes: each file's path relative to the receiving folder, using `/`, with
+/// its size. The files' contents follow in the same order once the offer is accepted.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Offer {
+    pub from: String,
+    pub files: Vec<(String, u64)>,
+}
+
+impl Offer {
+    pub fn size(&self) -> u64 {
+        self.files.iter().map(|(_, size)| size).sum()
+    }
+}
+
+pub enum PeerEvent {
+    /// Someone wants to send files from the address; reply with the folder to save them in, or
+    /// `None` to decline. The name in the offer is whatever the sender says it is.
+    Offer(Offer, IpAddr, Sender<Option<PathBuf>>),
+    /// Files from the named peer were saved into the folder, or failed to be.
+    Received(String, PathBuf, Result<usize, String>),
+}
+
+/// Advertises this copy, keeps track of the others and receives what they send, until dropped.
+pub struct Peers {
+    daemon: ServiceDaemon,
+    fullname: String,
+    found: Arc<Mutex<Vec<(String, Peer)>>>,
+    events: Receiver<PeerEvent>,
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+}
+
+impl Peers {
+    pub fn start(ctx: egui::Context) -> Result<Peers, String> {
+        let listener = TcpListener::bind("0.0.0.0:0").map_err(|err| err.to_string())?;
+        let port = listener.local_addr().map_err(|err| err.to_string())?.port();
+        let daemon = ServiceDaemon::new().map_err(|err| err.to_string())?;
+        let name = hostname();
+        // mDNS host names are plain labels; the readable name travels as a property.
+        let label: String = name.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '-' }).collect();
+        let instance = format!("{}-{}", label, std::process::id());
+        let properties = [("name", name.as_str())];
+        let host = format!("{}.local.", label);
+        let service = ServiceInfo::new(SERVICE_TYPE, &instance, &host, "", port, &properties[..])
+            .map_err(|err| err.to_string())?
+            .enable_addr_auto();
+        let fullname = service.get_fullname().to_string();
+        daemon.register(service).map_err(|err| err.to_string())?;
+        let browse = daemon.browse(SERVICE_TYPE).map_err(|err| err.to_string())?;
+
+        let found = Arc::new(Mutex::new(Vec::new()));
+        let (own_name, browse_found, browse_ctx) = (fullname.clone(), found.clone(), ctx.clone());
+        thread::spawn(move || {
+            while let Ok(event) = browse.recv() {
+                let mut found = browse_found.lock().unwrap();
+                match event {
+                    ServiceEvent::ServiceResolved(info) if info.get_fullname() != own_name => {
+                        let Some(ip) = info.get_addresses_v4().into_iter().next() else {
+                            continue;
+                        };
+                        let name = info.get_property_val_str("name").unwrap_or(info.get_hostname()).to_string();
+                        let addr = SocketAddr::new(IpAddr::V4(*ip), info.get_port());
+                        found.retain(|(fullname, _)| fullname != info.get_fullname());
+                        found.push((info.get_fullname().to_string(), Peer { name, addr }));
+                    }
+                    ServiceEvent::ServiceRemoved(_, removed) => found.retain(|(fullname, _)| *fullname != removed),
+                    _ => continue,
+                }
+                browse_ctx.request_repaint();
+            }
+        });
+
+        let (events_tx, events) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let connections = Arc::new(AtomicUsize::new(0));
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stopped.load(Ordering::Relaxed) {
+                    break;
+                }
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                if connections.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                    connections.fetch_sub(1, Ordering::SeqCst);
+                    continue;
+                }
+                let (events_tx, ctx, connections) = (events_tx.clone(), ctx.clone(), connections.clone());
+                thread::spawn(move || {
+                    let _ = receive(stream, &events_tx, &ctx);
+                    connections.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+
+        Ok(Peers {
+            daemon,
+            fullname,
+            found,
+            events,
+            addr: SocketAddr::from(([127, 0, 0, 1], port)),
+            stop,
+        })
+    }
+
+    /// The other copies currently on the network, by name.
+    pub fn peers(&self) -> Vec<Peer> {
+        let mut peers: Vec<Peer> = self.found.lock().unwrap().iter().map(|(_, peer)| peer.clone()).collect();
+        peers.sort_by(|a, b| a.name.cmp(&b.name));
+        peers
+    }
+
+    pub fn poll(&self) -> Option<PeerEvent> {
+        self.events.try_recv().ok()
+    }
+}
+
+impl Drop for Peers {
+    fn drop(&mut self) {
+        let _ = self.daemon.unregister(&self.fullname);
+        let _ = self.daemon.shutdown();
+        self.stop.store(true, Ordering::Relaxed);
+        // Wake the accept loop so it sees the flag.
+        let _ = TcpStream::connect_timeout(&self.addr, CONNECT_TIMEOUT);
+    }
+}
+
+pub fn hostname() -> String {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .ok()
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "Unknown".to_string())
+}
+
+/// Offers `paths` (folders with everything in them) to `peer` and, once accepted there, streams
+/// them, calling `report` with the bytes sent so far and the total.
+pub fn send(peer: &Peer, paths: &[PathBuf], mut report: impl FnMut(u64, u64)) -> Result<(), String> {
+    let mut files = Vec::new();
+    for path in paths {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        collect_files(path, name, &mut files).map_err(|err| format!("{}: {}", path.display(), err))?;
+    }
+    let offer = Offer {
+        from: hostname(),
+        files: files.iter().map(|(relative, _, size)| (relative.clone(), *size)).collect(),
+    };
+    let total = offer.size();
+    let stream = TcpStream::connect_timeout(&peer.addr, CONNECT_TIMEOUT).map_err(|err| err.to_string())?;
+    let mut writer = &stream;
+    let offer = serde_json::to_string(&offer).map_err(|err| err.to_string())?;
+    writeln!(writer, "{}", offer).map_err(|err| err.to_string())?;
+    // Waits for the person on the other end to answer, but not while they're away.
+    stream.set_read_timeout(Some(ANSWER_TIMEOUT)).map_err(|err| err.to_string())?;
+    let mut answer = String::new();
+    BufReader::new(&stream).read_line(&mut answer).map_err(|err| match err.kind() {
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => format!("{} didn't answer", peer.name),
+        _ => err.to_string(),
+    })?;
+    if answer.trim() != "ACCEPT" {
+        return Err(format!("{} declined", peer.name));
+    }
+    let mut sent = 0;
+    for (_, path, size) in files {
+        let mut file = File::open(&path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        let copied = io::copy(&mut (&mut file).take(size), &mut writer).map_err(|err| err.to_string())?;
+        if copied < size {
+            return Err(format!("{} got shorter while sending", path.display()));
+        }
+        sent += size;
+        report(sent, total);
+    }
+    writer.flush().map_err(|err| err.to_string())
+}
+
+/// Lists the files at and under `path` with their relative paths and sizes. Links inside folders
+/// are left out, since one pointing back up would never end; a link that was picked is followed.
+fn collect_files(path: &Path, relative: String, files: &mut Vec<(String, PathBuf, u64)>) -> io::Result<()> {
+    let metadata = fs::metadata(path)?;
+    if metadata.is_dir() {
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            if entry.file_type()?.is_symlink() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().into_owned();
+            collect_files(&entry.path(), format!("{}/{}", relative, name), files)?;
+        }
+    } else {
+        files.push((relative, path.to_path_buf(), metadata.len()));
+    }
+    Ok(())
+}
+
+fn receive(stream: TcpStream, events: &Sender<PeerEvent>, ctx: &egui::Context) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let sender = stream.peer_addr()?.ip();
+    let mut reader = BufReader::new(&stream);
+    let mut line = String::new();
+    (&mut reader).take(MAX_OFFER).read_line(&mut line)?;
+    let offer: Offer = serde_json::from_str(&line).map_err(io::Error::other)?;
+    let (reply_tx, reply) = mpsc::channel();
+    if events.send(PeerEvent::Offer(offer.clone(), sender, reply_tx)).is_err() {
+        return Ok(());
+    }
+    ctx.request_repaint();
+    let mut writer = &stream;
+    let Some(dir) = reply.recv_timeout(ANSWER_TIMEOUT).ok().flatten() else {
+        return writer.write_all(b"DECLINE\n");
+    };
+    writer.write_all(b"ACCEPT\n")?;
+    let result = save_files(&mut reader, &offer, &dir);
+    let from = format!("{} ({})", offer.from, sender);
+    let _ = events.send(PeerEvent::Received(from, dir, result));
+    ctx.request_repaint();
+    Ok(())
+}
+
+/// Writes the offered files into `dir`, giving any top-level name that's taken a new one.
+fn save_files(reader: &mut impl Read, offer: &Offer, dir: &Path) -> Result<usize, String> {
+    let mut renamed: HashMap<String, PathBuf> = HashMap::new();
+    for (relative, size) in &offer.files {
+        let parts: Vec<&str> = relative.split('/').collect();
+        if !parts.iter().all(|part| is_plain_name(part)) {
+            return Err(format!("refused the unsafe path {}", relative));
+        }
+        let root = renamed.entry(parts[0].to_string()).or_insert_with(|| unused_path(&dir.join(parts[0])));
+        let path = parts[1..].iter().fold(root.clone(), |path, part| path.join(part));
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        let mut file = File::create(&path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        let copied = io::copy(&mut reader.by_ref().take(*size), &mut file).map_err(|err| err.to_string())?;
+        if copied < *size {
+            return Err("the sender stopped partway".to_string());
+        }
+    }
+    Ok(offer.files.len())
+}
+
+/// Only single, ordinary names, so a sender can't write outside the receiving folder.
+fn is_plain_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!((components.next(), components.next()), (Some(Component::Normal(_)), None))
+}
+
+/// `path`, or `name (2).ext`, `name (3).ext`… if it's taken.
+fn unused_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path.extension().map(|extension| format!(".{}", extension.to_string_lossy()));
+    let mut candidate = path.to_path_buf();
+    for n in 2.. {
+        if !candidate.exists() {
+            break;
+        }
+        candidate = path.with_file_name(format!("{} ({}){}", stem, n, extension.as_deref().unwrap_or_default()));
+    }
+    candidate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuses_paths_that_climb_out() {
+        assert!(is_plain_name("photo.jpg"));
+        assert!(!is_plain_name(".."));
+        assert!(!is_plain_name(""));
+        assert!(!is_plain_name("/etc"));
+        assert!(!is_plain_name("a/b"));
+    }
+}
@@ -0,0 +1,229 @@
+//! Importing photos from a camera or memory card into folders by the date they were taken. A
+//! manifest of content hashes in the destination remembers what came in before, so a card can
+//! be imported again after more shots without copying the old ones twice.
+
+use crate::sorter;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, Write as _};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+const PHOTO_EXTENSIONS: &[&str] = &[
+    "jpg", "jpeg", "heic", "heif", "png", "tif", "tiff", "webp", "dng", "cr2", "cr3", "nef", "arw", "orf", "rw2",
+    "raf",
+];
+
+/// Kept in the destination, one hex SHA-256 digest per line.
+const MANIFEST_NAME: &str = ".file_manager_imported";
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ImportSettings {
+    pub destination: Option<PathBuf>,
+    /// The folders under the destination, as a `chrono` format of the capture date.
+    pub layout: String,
+}
+
+impl Default for ImportSettings {
+    fn default() -> Self {
+        Self {
+            destination: dirs::picture_dir(),
+            layout: "%Y/%Y-%m-%d".to_string(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Photo {
+    pub source: PathBuf,
+    pub target: PathBuf,
+    hash: String,
+}
+
+/// The photos on a card that haven't been imported yet, and where each will go.
+#[derive(Clone)]
+pub struct PhotoImport {
+    pub destination: PathBuf,
+    pub photos: Vec<Photo>,
+    /// Photos skipped because their contents are already in the destination.
+    pub already_imported: usize,
+    /// Photos that couldn't be read to check, and why.
+    pub unreadable: Vec<(PathBuf, String)>,
+}
+
+/// The folder, relative to the destination, for photos taken at `taken`.
+pub fn folder_for(taken: NaiveDateTime, layout: &str) -> Result<String, String> {
+    let mut folder = String::new();
+    write!(folder, "{}", taken.format(layout)).map_err(|_| format!("\"{}\" is not a valid date layout", layout))?;
+    // The folder must stay inside the destination.
+    if folder.is_empty() || !Path::new(&folder).components().all(|part| matches!(part, Component::Normal(_))) {
+        return Err(format!("\"{}\" must name folders inside the destination", layout));
+    }
+    Ok(folder)
+}
+
+/// Finds the photos under `source` and works out which are new, calling `report` with how many
+/// have been checked so far and the total. Stops with an error once `cancel` is set.
+pub fn plan(
+    source: &Path,
+    destination: &Path,
+    layout: &str,
+    cancel: &AtomicBool,
+    mut report: impl FnMut(usize, usize),
+) -> Result<PhotoImport, String> {
+    folder_for(Local::now().naive_local(), layout)?;
+    let mut found = Vec::new();
+    find_photos(source, &mut found);
+    found.sort();
+    let mut imported = read_manifest(destination);
+    let mut taken = HashSet::new();
+    let mut import = PhotoImport {
+        destination: destination.to_path_buf(),
+        photos: Vec::new(),
+        already_imported: 0,
+        unreadable: Vec::new(),
+    };
+    for (index, source) in found.iter().enumerate() {
+        if cancel.load(Ordering::Relaxed) {
+            return Err("cancelled".to_string());
+        }
+        report(index, found.len());
+        let hash = match hash_file(source) {
+            Ok(hash) => hash,
+            Err(err) => {
+                import.unreadable.push((source.clone(), err.to_string()));
+                continue;
+            }
+        };
+        // Also skips a second copy of the same shot on the card.
+        if !imported.insert(hash.clone()) {
+            import.already_imported += 1;
+            continue;
+        }
+        let folder = folder_for(taken_at(source), layout)?;
+        let target = sorter::available(&destination.join(folder).join(source.file_name().unwrap()), &taken);
+        taken.insert(target.clone());
+        import.photos.push(Photo { source: source.clone(), target, hash });
+    }
+    report(found.len(), found.len());
+    Ok(import)
+}
+
+/// Copies the photos, adding each to the manifest once it's in place, until `cancel` is set.
+/// Returns how many were copied and the ones that couldn't be.
+pub fn import(
+    import: &PhotoImport,
+    cancel: &AtomicBool,
+    mut report: impl FnMut(usize, usize),
+) -> (usize, Vec<(PathBuf, String)>) {
+    let (mut copied, mut failed) = (0, Vec::new());
+    for (index, photo) in import.photos.iter().enumerate() {
+        if cancel.load(Ordering::Relaxed) {
+            break;
+        }
+        report(index, import.photos.len());
+        let result = fs::create_dir_all(photo.target.parent().unwrap())
+            .and_then(|()| copy_new(&photo.source, &photo.target))
+            .and_then(|()| add_to_manifest(&import.destination, &photo.hash));
+        match result {
+            Ok(()) => copied += 1,
+            Err(err) => failed.push((photo.source.clone(), err.to_string())),
+        }
+    }
+    report(import.photos.len(), import.photos.len());
+    (copied, failed)
+}
+
+/// Copies `source` to `target`, never over a file that turned up there since the plan.
+fn copy_new(source: &Path, target: &Path) -> io::Result<()> {
+    let mut reader = File::open(source)?;
+    let mut writer = OpenOptions::new().write(true).create_new(true).open(target)?;
+    io::copy(&mut reader, &mut writer).and_then(|_| writer.sync_all()).inspect_err(|_| {
+        let _ = fs::remove_file(target);
+    })
+}
+
+fn find_photos(dir: &Path, found: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        // Cameras keep thumbnails and trash in hidden folders.
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            find_photos(&path, found);
+        } else if file_type.is_file() && is_photo(&path) {
+            found.push(path);
+        }
+    }
+}
+
+fn is_photo(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| PHOTO_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// When the photo was taken according to its EXIF data, or else when the file was last changed.
+fn taken_at(path: &Path) -> NaiveDateTime {
+    exif_date(path).unwrap_or_else(|| {
+        let modified = fs::metadata(path).and_then(|metadata| metadata.modified());
+        modified.map_or_else(|_| Local::now().naive_local(), |time| DateTime::<Local>::from(time).naive_local())
+    })
+}
+
+fn exif_date(path: &Path) -> Option<NaiveDateTime> {
+    let mut reader = BufReader::new(File::open(path).ok()?);
+    let exif = exif::Reader::new().read_from_container(&mut reader).ok()?;
+    let field = [exif::Tag::DateTimeOriginal, exif::Tag::DateTime]
+        .into_iter()
+        .find_map(|tag| exif.get_field(tag, exif::In::PRIMARY))?;
+    let exif::Value::Ascii(values) = &field.value else {
+        return None;
+    };
+    let date = exif::DateTime::from_ascii(values.first()?).ok()?;
+    let day = NaiveDate::from_ymd_opt(date.year.into(), date.month.into(), date.day.into())?;
+    day.and_hms_opt(date.hour.into(), date.minute.into(), date.second.into())
+}
+
+fn hash_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn read_manifest(destination: &Path) -> HashSet<String> {
+    let manifest = fs::read_to_string(destination.join(MANIFEST_NAME)).unwrap_or_default();
+    manifest.lines().map(|line| line.trim().to_string()).filter(|line| !line.is_empty()).collect()
+}
+
+fn add_to_manifest(destination: &Path, hash: &str) -> io::Result<()> {
+    let mut manifest = OpenOptions::new().create(true).append(true).open(destination.join(MANIFEST_NAME))?;
+    writeln!(manifest, "{}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layouts_name_folders_by_date() {
+        let taken = NaiveDate::from_ymd_opt(2024, 5, 17).unwrap().and_hms_opt(9, 30, 0).unwrap();
+        assert_eq!(folder_for(taken, "%Y/%Y-%m-%d"), Ok("2024/2024-05-17".to_string()));
+        assert!(folder_for(taken, "%Y/%Q").is_err());
+        assert!(folder_for(taken, "../%Y").is_err());
+        assert!(folder_for(taken, "/tmp/%Y").is_err());
+        assert!(folder_for(taken, "").is_err());
+    }
+}
@@ -0,0 +1,46 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static PICKED: AtomicBool = AtomicBool::new(false);
+
+/// Runs the UI as a chooser that prints the chosen paths to stdout, one per line.
+#[derive(Clone, Copy, PartialEq)]
+pub enum PickMode {
+    File,
+    Folder,
+}
+
+impl PickMode {
+    pub fn from_arg(arg: &std::ffi::OsStr) -> Option<Self> {
+        match arg.to_str()? {
+            "--pick-file" => Some(PickMode::File),
+            "--pick-folder" => Some(PickMode::Folder),
+            _ => None,
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            PickMode::File => "Choose a File",
+            PickMode::Folder => "Choose a Folder",
+        }
+    }
+
+    pub fn accepts(self, path: &Path) -> bool {
+        match self {
+            PickMode::File => !path.is_dir(),
+            PickMode::Folder => path.is_dir(),
+        }
+    }
+}
+
+pub fn print_selection(paths: &[PathBuf]) {
+    for path in paths {
+        println!("{}", path.display());
+    }
+    PICKED.store(true, Ordering::Relaxed);
+}
+
+pub fn picked() -> bool {
+    PICKED.load(Ordering::Relaxed)
+}
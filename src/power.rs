@@ -0,0 +1,239 @@
+//! Power management: keeping the machine awake during transfers and easing off background
+//! work on battery.
+//!
+//! One system inhibitor is held for as long as any [`AwakeGuard`] is alive, so overlapping
+//! transfers share it.
+
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+struct Inhibitor {
+    holders: usize,
+    lock: Option<SystemLock>,
+}
+
+static INHIBITOR: Mutex<Inhibitor> = Mutex::new(Inhibitor { holders: 0, lock: None });
+
+/// Releases its hold on the inhibitor when dropped.
+pub struct AwakeGuard(());
+
+pub fn keep_awake() -> AwakeGuard {
+    let mut inhibitor = INHIBITOR.lock().unwrap();
+    if inhibitor.holders == 0 {
+        inhibitor.lock = SystemLock::acquire();
+    }
+    inhibitor.holders += 1;
+    AwakeGuard(())
+}
+
+impl Drop for AwakeGuard {
+    fn drop(&mut self) {
+        let mut inhibitor = INHIBITOR.lock().unwrap();
+        inhibitor.holders -= 1;
+        if inhibitor.holders == 0 {
+            inhibitor.lock = None;
+        }
+    }
+}
+
+enum SystemLock {
+    /// `systemd-inhibit` or `caffeinate`, holding the lock until its stdin closes or it is killed.
+    #[cfg(not(windows))]
+    Process(std::process::Child),
+    /// A thread that set `SetThreadExecutionState`; the state is per thread and ends when the
+    /// thread does, which happens once this sender is dropped.
+    #[cfg(windows)]
+    Thread(std::sync::mpsc::Sender<()>),
+}
+
+impl SystemLock {
+    #[cfg(windows)]
+    fn acquire() -> Option<SystemLock> {
+        const ES_CONTINUOUS: u32 = 0x8000_0000;
+        const ES_SYSTEM_REQUIRED: u32 = 0x0000_0001;
+
+        #[link(name = "kernel32")]
+        unsafe extern "system" {
+            fn SetThreadExecutionState(flags: u32) -> u32;
+        }
+
+        let (release, released) = std::sync::mpsc::channel::<()>();
+        std::thread::spawn(move || {
+            unsafe { SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED) };
+            let _ = released.recv();
+            unsafe { SetThreadExecutionState(ES_CONTINUOUS) };
+        });
+        Some(SystemLock::Thread(release))
+    }
+
+    #[cfg(not(windows))]
+    fn acquire() -> Option<SystemLock> {
+        use std::process::{Command, Stdio};
+
+        let mut command = if cfg!(target_os = "macos") {
+            // `-w` also ends it if we crash.
+            let mut command = Command::new("caffeinate");
+            command.args(["-i", "-w"]).arg(std::process::id().to_string());
+            command
+        } else {
+            // `cat` holds the lock until its stdin (our pipe) closes, including when we crash.
+            let mut command = Command::new("systemd-inhibit");
+            command.args(["--what=sleep", "--who=File Manager", "--why=Transferring files", "--mode=block", "cat"]);
+            command
+        };
+        command.stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::null());
+        command.spawn().ok().map(SystemLock::Process)
+    }
+}
+
+impl Drop for SystemLock {
+    fn drop(&mut self) {
+        match self {
+            #[cfg(not(windows))]
+            SystemLock::Process(child) => {
+                drop(child.stdin.take());
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+            #[cfg(windows)]
+            SystemLock::Thread(_) => {}
+        }
+    }
+}
+
+/// What to hold back while running on battery.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct BatteryPolicy {
+    pub enabled: bool,
+    /// Copies and moves allowed to run at once; the rest wait their turn.
+    pub max_transfers: usize,
+    /// Skips speculative listing of nearby folders.
+    pub pause_prefetch: bool,
+}
+
+impl Default for BatteryPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_transfers: 1,
+            pause_prefetch: true,
+        }
+    }
+}
+
+/// Applies the [`BatteryPolicy`] to the worker's tasks.
+#[derive(Clone, Default)]
+pub struct Throttle(Arc<Mutex<ThrottleState>>);
+
+#[derive(Default)]
+struct ThrottleState {
+    policy: BatteryPolicy,
+    on_battery: Option<(bool, Instant)>,
+    transfers: usize,
+}
+
+impl ThrottleState {
+    fn throttled(&mut self) -> bool {
+        // Reading the power supply is cheap but not free, so the answer is reused for a while.
+        const RECHECK_AFTER: Duration = Duration::from_secs(30);
+        if !self.policy.enabled {
+            return false;
+        }
+        match self.on_battery {
+            Some((on_battery, checked)) if checked.elapsed() < RECHECK_AFTER => on_battery,
+            _ => {
+                let on_battery = on_battery();
+                self.on_battery = Some((on_battery, Instant::now()));
+                on_battery
+            }
+        }
+    }
+}
+
+impl Throttle {
+    pub fn set_policy(&self, policy: BatteryPolicy) {
+        self.0.lock().unwrap().policy = policy;
+    }
+
+    pub fn pause_prefetch(&self) -> bool {
+        let mut state = self.0.lock().unwrap();
+        state.policy.pause_prefetch && state.throttled()
+    }
+
+    /// Waits until another transfer may start; the slot is given back when dropped.
+    pub async fn transfer_slot(&self) -> TransferSlot {
+        loop {
+            {
+                let mut state = self.0.lock().unwrap();
+                let limit = if state.throttled() { state.policy.max_transfers.max(1) } else { usize::MAX };
+                if state.transfers < limit {
+                    state.transfers += 1;
+                    return TransferSlot(self.clone());
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+    }
+}
+
+pub struct TransferSlot(Throttle);
+
+impl Drop for TransferSlot {
+    fn drop(&mut self) {
+        self.0.0.lock().unwrap().transfers -= 1;
+    }
+}
+
+/// Whether the machine is running on battery; machines without one never are.
+#[cfg(target_os = "linux")]
+pub fn on_battery() -> bool {
+    let Ok(supplies) = std::fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+    let mut discharging = false;
+    for supply in supplies.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+        let read = |name: &str| std::fs::read_to_string(supply.join(name)).unwrap_or_default().trim().to_string();
+        match read("type").as_str() {
+            "Mains" if read("online") == "1" => return false,
+            "Battery" => discharging |= read("status") == "Discharging",
+            _ => {}
+        }
+    }
+    discharging
+}
+
+#[cfg(target_os = "macos")]
+pub fn on_battery() -> bool {
+    std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains("'Battery Power'"))
+}
+
+#[cfg(windows)]
+pub fn on_battery() -> bool {
+    #[repr(C)]
+    #[derive(Default)]
+    struct SystemPowerStatus {
+        ac_line_status: u8,
+        battery_flag: u8,
+        battery_life_percent: u8,
+        system_status_flag: u8,
+        battery_life_time: u32,
+        battery_full_life_time: u32,
+    }
+
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn GetSystemPowerStatus(status: *mut SystemPowerStatus) -> i32;
+    }
+
+    let mut status = SystemPowerStatus::default();
+    unsafe { GetSystemPowerStatus(&mut status) != 0 && status.ac_line_status == 0 }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+pub fn on_battery() -> bool {
+    false
+}
@@ -0,0 +1,97 @@
+use eframe::egui;
+use std::path::Path;
+#[cfg(feature = "profiling")]
+use std::time::Instant;
+
+#[derive(Clone, Copy)]
+pub enum Stage {
+    Listing,
+    Sort,
+    Render,
+}
+
+/// Runs `f`, recording how long it took for `path` when the `profiling` feature is enabled.
+#[cfg(feature = "profiling")]
+pub fn time<T>(path: &Path, stage: Stage, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let result = f();
+    enabled::record(path, stage, start.elapsed());
+    result
+}
+
+#[cfg(not(feature = "profiling"))]
+#[inline(always)]
+pub fn time<T>(_path: &Path, _stage: Stage, f: impl FnOnce() -> T) -> T {
+    f()
+}
+
+#[cfg(feature = "profiling")]
+pub fn draw_panel(ctx: &egui::Context, open: &mut bool) {
+    enabled::draw_panel(ctx, open);
+}
+
+#[cfg(not(feature = "profiling"))]
+pub fn draw_panel(_ctx: &egui::Context, _open: &mut bool) {}
+
+#[cfg(feature = "profiling")]
+mod enabled {
+    use super::{Path, Stage};
+    use crate::i18n::t;
+    use eframe::egui;
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    #[derive(Default, Clone, Copy)]
+    struct StageTiming {
+        last: Duration,
+        total: Duration,
+        samples: u32,
+    }
+
+    impl StageTiming {
+        fn average(&self) -> Duration {
+            if self.samples == 0 { Duration::ZERO } else { self.total / self.samples }
+        }
+    }
+
+    static TIMINGS: Mutex<BTreeMap<PathBuf, [StageTiming; 3]>> = Mutex::new(BTreeMap::new());
+
+    pub fn record(path: &Path, stage: Stage, elapsed: Duration) {
+        let mut timings = TIMINGS.lock().unwrap();
+        let entry = &mut timings.entry(path.to_path_buf()).or_default()[stage as usize];
+        entry.last = elapsed;
+        entry.total += elapsed;
+        entry.samples += 1;
+    }
+
+    pub fn draw_panel(ctx: &egui::Context, open: &mut bool) {
+        egui::Window::new(t("Performance")).open(open).default_width(500.0).show(ctx, |ui| {
+            if ui.button(t("Clear")).clicked() {
+                TIMINGS.lock().unwrap().clear();
+            }
+            let timings = TIMINGS.lock().unwrap().clone();
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                egui::Grid::new("profiling_grid").striped(true).show(ui, |ui| {
+                    ui.strong(t("Folder"));
+                    ui.strong(t("Listing (last / avg)"));
+                    ui.strong(t("Sort (last / avg)"));
+                    ui.strong(t("Render (last / avg)"));
+                    ui.end_row();
+                    for (path, stages) in &timings {
+                        ui.label(path.display().to_string());
+                        for stage in stages {
+                            ui.label(format!(
+                                "{:.2} / {:.2} ms",
+                                stage.last.as_secs_f64() * 1000.0,
+                                stage.average().as_secs_f64() * 1000.0
+                            ));
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+    }
+}
@@ -0,0 +1,274 @@
+//! Browsing file systems the app can only reach through a command line tool: Android devices
+//! over `adb` and Docker containers and volumes over `docker`.
+
+use crate::adb::{self, AdbDevice};
+use crate::docker::{self, Container};
+use crate::i18n::{t, tf};
+use eframe::egui;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Remote {
+    Adb(AdbDevice),
+    Container(Container),
+}
+
+impl Remote {
+    /// Where browsing starts.
+    pub fn home(&self) -> &str {
+        match self {
+            Remote::Adb(_) => "/sdcard",
+            Remote::Container(container) if container.volume.is_some() => docker::VOLUME_MOUNT,
+            Remote::Container(_) => "/",
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match self {
+            Remote::Adb(device) => format!("📱 {}", device.model),
+            Remote::Container(Container { volume: Some(volume), .. }) => format!("🛢 {}", volume),
+            Remote::Container(container) => format!("🐳 {}", container.name),
+        }
+    }
+
+    pub fn list(&self, path: &str) -> Result<Vec<RemoteEntry>, String> {
+        let mut entries = match self {
+            Remote::Adb(device) => adb::list(&device.serial, path),
+            Remote::Container(container) => docker::list(&container.id, path),
+        }?;
+        entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase())));
+        Ok(entries)
+    }
+
+    /// Copies remote paths into a local folder.
+    pub fn pull(&self, paths: &[String], local_dir: &Path) -> Result<(), String> {
+        match self {
+            Remote::Adb(device) => adb::pull(&device.serial, paths, local_dir),
+            Remote::Container(container) => docker::pull(&container.id, paths, local_dir),
+        }
+    }
+
+    /// Copies local paths into a remote folder.
+    pub fn push(&self, local: &[PathBuf], remote_dir: &str) -> Result<(), String> {
+        match self {
+            Remote::Adb(device) => adb::push(&device.serial, local, remote_dir),
+            Remote::Container(container) => docker::push(&container.id, local, remote_dir),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteEntry {
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+    /// As the remote `ls` prints it, e.g. "2024-05-01 12:00" or "May  1 12:00".
+    pub modified: String,
+}
+
+/// Quotes `path` for a POSIX shell on the other side.
+pub fn quote(path: &str) -> String {
+    format!("'{}'", path.replace('\'', r"'\''"))
+}
+
+/// Runs a copy command, turning a failure into its error output.
+pub fn run(mut command: Command) -> Result<(), String> {
+    let output = command.output().map_err(|err| err.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// Parses `ls -la` lines from toybox (`... 3452 2024-05-01 12:00 DCIM`) or from GNU and busybox
+/// (`... 3452 May  1 12:00 DCIM`).
+pub fn parse_listing(output: &str) -> Vec<RemoteEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut rest = line.trim_end();
+            let mut split_field = || {
+                let (field, tail) = rest.split_once(char::is_whitespace)?;
+                rest = tail.trim_start();
+                Some(field)
+            };
+            let mode = split_field()?;
+            let _links = split_field()?;
+            let _owner = split_field()?;
+            let _group = split_field()?;
+            let size = split_field()?;
+            let day_or_date = split_field()?;
+            // Toybox prints an ISO date; the others spell out the month and then the day.
+            let modified = if day_or_date.contains('-') {
+                format!("{} {}", day_or_date, split_field()?)
+            } else {
+                format!("{} {} {}", day_or_date, split_field()?, split_field()?)
+            };
+            let kind = mode.chars().next()?;
+            let name = if kind == 'l' { rest.split(" -> ").next()? } else { rest };
+            if name.is_empty() || name == "." || name == ".." || !matches!(kind, '-' | 'd' | 'l') {
+                return None;
+            }
+            Some(RemoteEntry {
+                name: name.to_string(),
+                // Links are nearly always to folders, such as Android's /sdcard itself.
+                is_dir: kind != '-',
+                size: size.parse().unwrap_or(0),
+                modified,
+            })
+        })
+        .collect()
+}
+
+/// What the browser window needs from the worker.
+pub enum BrowserRequest {
+    List(String),
+    /// Copies these remote paths into the current local folder.
+    Pull(Vec<String>),
+    /// Copies the locally selected items into the folder being browsed.
+    PushSelection,
+}
+
+/// The state of a window browsing a device or container.
+pub struct RemoteBrowser {
+    pub remote: Remote,
+    pub path: String,
+    entries: Option<Result<Vec<RemoteEntry>, String>>,
+    selected: HashSet<String>,
+}
+
+impl RemoteBrowser {
+    pub fn new(remote: Remote) -> Self {
+        Self {
+            path: remote.home().to_string(),
+            remote,
+            entries: None,
+            selected: HashSet::new(),
+        }
+    }
+
+    pub fn listed(&mut self, path: &str, entries: Result<Vec<RemoteEntry>, String>) {
+        if path == self.path {
+            self.entries = Some(entries);
+        }
+    }
+
+    fn child(&self, name: &str) -> String {
+        format!("{}/{}", self.path.trim_end_matches('/'), name)
+    }
+
+    fn go_to(&mut self, path: String) -> Option<BrowserRequest> {
+        self.path = path.clone();
+        self.entries = None;
+        self.selected.clear();
+        Some(BrowserRequest::List(path))
+    }
+
+    /// Draws the window; `open` turns false once it's closed.
+    pub fn show(&mut self, ctx: &egui::Context, local_dir: &Path, open: &mut bool) -> Option<BrowserRequest> {
+        let mut request = None;
+        let local_name = local_dir.file_name().unwrap_or(local_dir.as_os_str()).to_string_lossy().into_owned();
+        egui::Window::new(self.remote.title())
+            .open(open)
+            .default_size([450.0, 400.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let parent = Path::new(&self.path).parent().map(|parent| parent.to_string_lossy().into_owned());
+                    if ui.add_enabled(parent.is_some(), egui::Button::new("⬆")).clicked() {
+                        request = self.go_to(parent.unwrap_or_default());
+                    }
+                    if ui.button("⟳").on_hover_text(t("Refresh")).clicked() {
+                        request = self.go_to(self.path.clone());
+                    }
+                    ui.monospace(&self.path);
+                });
+                ui.horizontal(|ui| {
+                    let pull = egui::Button::new(tf("Copy to \"{folder}\"", &[("folder", &local_name)]));
+                    if ui.add_enabled(!self.selected.is_empty(), pull).clicked() {
+                        let mut paths: Vec<String> = self.selected.iter().map(|name| self.child(name)).collect();
+                        paths.sort();
+                        request = Some(BrowserRequest::Pull(paths));
+                    }
+                    let push = ui.button(t("Copy Selection Here"));
+                    if push.on_hover_text(t("Sends the items selected in the file list")).clicked() {
+                        request = Some(BrowserRequest::PushSelection);
+                    }
+                });
+                ui.separator();
+                match &self.entries {
+                    None => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label(t("Listing..."));
+                        });
+                    }
+                    Some(Err(err)) => {
+                        ui.label(err);
+                    }
+                    Some(Ok(entries)) => {
+                        let entries = entries.clone();
+                        egui::ScrollArea::vertical().auto_shrink([false; 2]).show(ui, |ui| {
+                            for entry in &entries {
+                                let icon = if entry.is_dir { "📁" } else { "📄" };
+                                let selected = self.selected.contains(&entry.name);
+                                let response = ui.selectable_label(selected, format!("{} {}", icon, entry.name));
+                                let response = if entry.is_dir {
+                                    response.on_hover_text(&entry.modified)
+                                } else {
+                                    response.on_hover_text(format!("{} bytes, {}", entry.size, entry.modified))
+                                };
+                                if response.double_clicked() && entry.is_dir {
+                                    request = self.go_to(self.child(&entry.name));
+                                } else if response.clicked() {
+                                    if !ui.input(|i| i.modifiers.ctrl) {
+                                        self.selected.clear();
+                                    }
+                                    if !self.selected.remove(&entry.name) {
+                                        self.selected.insert(entry.name.clone());
+                                    }
+                                }
+                            }
+                        });
+                    }
+                }
+            });
+        request
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_toybox_listing() {
+        let output = "total 24\n\
+            drwxrwx--x  4 root sdcard_rw 3452 2024-05-01 12:00 .\n\
+            drwxrwx--x 12 root sdcard_rw 3452 2024-05-01 12:00 My Photos\n\
+            -rw-rw----  1 root sdcard_rw 1024 2024-04-30 09:15 notes.txt\n\
+            lrwxrwxrwx  1 root root        21 2009-01-01 00:00 sdcard -> /storage/self/primary\n";
+        let entries = parse_listing(output);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].name, "My Photos");
+        assert!(entries[0].is_dir);
+        assert_eq!(entries[1].size, 1024);
+        assert_eq!(entries[1].modified, "2024-04-30 09:15");
+        assert_eq!(entries[2].name, "sdcard");
+    }
+
+    #[test]
+    fn parses_gnu_and_busybox_listing() {
+        let output = "total 8\n\
+            drwxr-xr-x    1 root     root          4096 May  1 12:00 etc\n\
+            -rw-r--r--    1 root     root           220 Jan 12  2023 .profile\n";
+        let entries = parse_listing(output);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, "etc");
+        assert_eq!(entries[0].modified, "May 1 12:00");
+        assert_eq!(entries[1].name, ".profile");
+        assert_eq!(entries[1].modified, "Jan 12 2023");
+    }
+}
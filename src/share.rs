@@ -0,0 +1,306 @@
+//! Serves files over a temporary HTTP server on the local network, so a phone can fetch them
+//! from its browser without a cable.
+
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// Requests are a line and a few headers; anything longer is dropped.
+const MAX_LINE: u64 = 8 * 1024;
+const MAX_HEADERS: usize = 100;
+/// Connections served at once; more are turned away rather than each getting a thread.
+const MAX_CONNECTIONS: usize = 16;
+
+/// Running while this is alive; dropping it stops the server.
+pub struct Share {
+    pub url: String,
+    pub paths: Vec<PathBuf>,
+    /// The URL as a QR code: its width in modules and whether each one is dark, row by row.
+    pub qr_code: Option<(usize, Vec<bool>)>,
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+}
+
+impl Share {
+    pub fn start(paths: Vec<PathBuf>) -> io::Result<Share> {
+        let ip = local_ip().ok_or_else(|| io::Error::other("not connected to a network"))?;
+        let listener = TcpListener::bind((ip, 0))?;
+        let addr = listener.local_addr()?;
+        // Only someone who was shown the address can guess it.
+        let mut bytes = [0u8; 16];
+        getrandom::getrandom(&mut bytes).map_err(io::Error::other)?;
+        let token: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+        let url = format!("http://{}/{}/", addr, token);
+        let qr_code = qrcode::QrCode::new(url.as_bytes()).ok().map(|code| {
+            let dark = code.to_colors().into_iter().map(|color| color == qrcode::Color::Dark).collect();
+            (code.width(), dark)
+        });
+        let stop = Arc::new(AtomicBool::new(false));
+        let roots = Arc::new(paths.clone());
+        let stopped = stop.clone();
+        let connections = Arc::new(AtomicUsize::new(0));
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stopped.load(Ordering::Relaxed) {
+                    break;
+                }
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                if connections.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                    connections.fetch_sub(1, Ordering::SeqCst);
+                    continue;
+                }
+                let (roots, token, connections) = (roots.clone(), token.clone(), connections.clone());
+                thread::spawn(move || {
+                    let _ = serve(stream, &roots, &token);
+                    connections.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+        Ok(Share {
+            url,
+            paths,
+            qr_code,
+            addr,
+            stop,
+        })
+    }
+}
+
+impl Drop for Share {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // Wake the accept loop so it sees the flag.
+        let _ = TcpStream::connect_timeout(&self.addr, Duration::from_secs(1));
+    }
+}
+
+/// The address other devices on the network reach this one at. Connecting a UDP socket sends
+/// nothing; it only picks the interface that routes outwards.
+fn local_ip() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:80").ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    (!ip.is_unspecified()).then_some(ip)
+}
+
+fn serve(stream: TcpStream, roots: &[PathBuf], token: &str) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    read_line(&mut reader, &mut request_line)?;
+    // The headers don't matter, but must be read before replying.
+    let mut header = String::new();
+    let mut headers = 0;
+    while read_line(&mut reader, &mut header)? > 2 {
+        headers += 1;
+        if headers > MAX_HEADERS {
+            return Err(io::Error::other("too many headers"));
+        }
+        header.clear();
+    }
+    let mut writer = &stream;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return respond(&mut writer, "400 Bad Request", "text/plain", b"Bad request");
+    };
+    if method != "GET" {
+        return respond(&mut writer, "405 Method Not Allowed", "text/plain", b"Only GET is supported");
+    }
+    let target = target.split('?').next().unwrap_or_default();
+    let resolved = resolve(target, roots, token).filter(|path| path.as_ref().is_none_or(|path| is_shared(path, roots)));
+    let Some(path) = resolved else {
+        return respond(&mut writer, "404 Not Found", "text/plain", b"Not found");
+    };
+    match path {
+        None => respond(&mut writer, "200 OK", "text/html; charset=utf-8", index_page(target, roots).as_bytes()),
+        Some(path) if path.is_dir() => {
+            let page = folder_page(target, &path)?;
+            respond(&mut writer, "200 OK", "text/html; charset=utf-8", page.as_bytes())
+        }
+        Some(path) => send_file(&mut writer, &path),
+    }
+}
+
+/// The shared file or folder a request is for: `None` for the list of everything shared, or
+/// nothing at all if the path is outside what's shared.
+fn resolve(target: &str, roots: &[PathBuf], token: &str) -> Option<Option<PathBuf>> {
+    let rest = target.strip_prefix('/')?.strip_prefix(token)?;
+    if !rest.is_empty() && !rest.starts_with('/') {
+        return None;
+    }
+    let mut segments = rest.split('/').filter(|segment| !segment.is_empty()).map(percent_decode);
+    let Some(index) = segments.next() else {
+        return Some(None);
+    };
+    let mut path = roots.get(index.parse::<usize>().ok()?)?.clone();
+    for segment in segments {
+        let mut components = Path::new(&segment).components();
+        // Only plain names, so `..` or an absolute path can't climb out of the shared folder.
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(name)), None) => path.push(name),
+            _ => return None,
+        }
+    }
+    Some(Some(path))
+}
+
+/// Whether the path is really inside what's shared, once symlinks below a shared folder are
+/// followed to wherever they lead.
+fn is_shared(path: &Path, roots: &[PathBuf]) -> bool {
+    let Ok(path) = path.canonicalize() else {
+        return false;
+    };
+    roots.iter().filter_map(|root| root.canonicalize().ok()).any(|root| path.starts_with(root))
+}
+
+/// Reads a line of at most `MAX_LINE` bytes.
+fn read_line(reader: &mut impl BufRead, line: &mut String) -> io::Result<usize> {
+    let read = reader.take(MAX_LINE).read_line(line)?;
+    if read as u64 == MAX_LINE && !line.ends_with('\n') {
+        return Err(io::Error::other("line too long"));
+    }
+    Ok(read)
+}
+
+fn index_page(target: &str, roots: &[PathBuf]) -> String {
+    let base = with_trailing_slash(target);
+    let links: Vec<String> = roots
+        .iter()
+        .enumerate()
+        .map(|(index, root)| {
+            let name = root.file_name().unwrap_or_default().to_string_lossy();
+            let slash = if root.is_dir() { "/" } else { "" };
+            let href = format!("{}{}{}", base, index, slash);
+            format!("<li><a href=\"{}\">{}{}</a></li>", html_escape(&href), html_escape(&name), slash)
+        })
+        .collect();
+    page("Shared files", &links)
+}
+
+fn folder_page(target: &str, dir: &Path) -> io::Result<String> {
+    let base = with_trailing_slash(target);
+    let mut entries: Vec<(String, bool)> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| (entry.file_name().to_string_lossy().into_owned(), entry.path().is_dir()))
+        .collect();
+    entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.to_lowercase().cmp(&b.0.to_lowercase())));
+    let links: Vec<String> = entries
+        .iter()
+        .map(|(name, is_dir)| {
+            let slash = if *is_dir { "/" } else { "" };
+            let href = format!("{}{}{}", base, percent_encode(name), slash);
+            format!("<li><a href=\"{}\">{}{}</a></li>", html_escape(&href), html_escape(name), slash)
+        })
+        .collect();
+    let title = dir.file_name().unwrap_or_default().to_string_lossy();
+    Ok(page(&title, &links))
+}
+
+fn page(title: &str, links: &[String]) -> String {
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\">\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\
+         <title>{0}</title></head><body><h1>{0}</h1><ul>{1}</ul></body></html>",
+        html_escape(title),
+        links.concat()
+    )
+}
+
+fn send_file(writer: &mut impl Write, path: &Path) -> io::Result<()> {
+    let mut file = File::open(path)?;
+    let name = path.file_name().unwrap_or_default().to_string_lossy().replace('"', "'");
+    write!(
+        writer,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/octet-stream\r\nContent-Length: {}\r\n\
+         Content-Disposition: attachment; filename=\"{}\"\r\nConnection: close\r\n\r\n",
+        file.metadata()?.len(),
+        name
+    )?;
+    io::copy(&mut file, writer)?;
+    writer.flush()
+}
+
+fn respond(writer: &mut impl Write, status: &str, content_type: &str, body: &[u8]) -> io::Result<()> {
+    write!(
+        writer,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    writer.write_all(body)?;
+    writer.flush()
+}
+
+fn with_trailing_slash(target: &str) -> String {
+    if target.ends_with('/') {
+        target.to_string()
+    } else {
+        format!("{}/", target)
+    }
+}
+
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|hex| std::str::from_utf8(hex).ok());
+        match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+            Some(byte) if bytes[i] == b'%' => {
+                decoded.push(byte);
+                i += 3;
+            }
+            _ => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_only_inside_shared_paths() {
+        let roots = vec![PathBuf::from("/shared/photos")];
+        assert_eq!(resolve("/abc/", &roots, "abc"), Some(None));
+        let photo = PathBuf::from("/shared/photos/a b.jpg");
+        assert_eq!(resolve("/abc/0/a%20b.jpg", &roots, "abc"), Some(Some(photo)));
+        assert_eq!(resolve("/abc0/", &roots, "abc"), None);
+        assert_eq!(resolve("/abc/0/..", &roots, "abc"), None);
+        assert_eq!(resolve("/abc/0/%2E%2E/x", &roots, "abc"), None);
+        assert_eq!(resolve("/abc/0/a%2Fb", &roots, "abc"), None);
+        assert_eq!(resolve("/abc/1/", &roots, "abc"), None);
+        assert_eq!(resolve("/xyz/0/", &roots, "abc"), None);
+    }
+
+    #[test]
+    fn percent_encoding_round_trips() {
+        let name = "Ünïcode & spaces #1.txt";
+        assert_eq!(percent_decode(&percent_encode(name)), name);
+    }
+}
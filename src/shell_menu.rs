@@ -0,0 +1,248 @@
+//! Entries that Windows shell extensions (7-Zip, TortoiseGit, ...) add to Explorer's
+//! context menu, read through `IContextMenu`.
+
+use std::path::Path;
+
+#[derive(Clone)]
+pub struct ShellMenuItem {
+    pub id: u32,
+    pub label: String,
+    pub children: Vec<ShellMenuItem>,
+}
+
+#[cfg(not(windows))]
+pub fn entries(_path: &Path) -> Vec<ShellMenuItem> {
+    Vec::new()
+}
+
+#[cfg(not(windows))]
+pub fn invoke(_path: &Path, _id: u32) {}
+
+#[cfg(windows)]
+pub fn entries(path: &Path) -> Vec<ShellMenuItem> {
+    ffi::with_context_menu(path, |_, menu| unsafe { ffi::read_menu(menu) }).unwrap_or_default()
+}
+
+/// Runs the command `id` from [`entries`]; the menu is rebuilt because command ids are
+/// only valid for the menu they were queried into.
+#[cfg(windows)]
+pub fn invoke(path: &Path, id: u32) {
+    ffi::with_context_menu(path, |context_menu, _| unsafe { ffi::invoke_command(context_menu, id) });
+}
+
+#[cfg(windows)]
+mod ffi {
+    use super::ShellMenuItem;
+    use std::ffi::c_void;
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::Path;
+
+    const COINIT_APARTMENTTHREADED: u32 = 0x2;
+    const CMF_NORMAL: u32 = 0;
+    const FIRST_COMMAND_ID: u32 = 1;
+    const LAST_COMMAND_ID: u32 = 0x7FFF;
+    const MIIM_ID: u32 = 0x2;
+    const MIIM_SUBMENU: u32 = 0x4;
+    const MIIM_STRING: u32 = 0x40;
+    const MIIM_FTYPE: u32 = 0x100;
+    const MFT_SEPARATOR: u32 = 0x800;
+    const SW_SHOWNORMAL: i32 = 1;
+
+    #[repr(C)]
+    struct Guid(u32, u16, u16, [u8; 8]);
+
+    const IID_ISHELLFOLDER: Guid = Guid(0x000214E6, 0, 0, [0xC0, 0, 0, 0, 0, 0, 0, 0x46]);
+    const IID_ICONTEXTMENU: Guid = Guid(0x000214E4, 0, 0, [0xC0, 0, 0, 0, 0, 0, 0, 0x46]);
+
+    #[repr(C)]
+    pub struct ComObject<V> {
+        vtable: *const V,
+    }
+
+    #[repr(C)]
+    struct ShellFolderVtbl {
+        query_interface: usize,
+        add_ref: usize,
+        release: unsafe extern "system" fn(*mut c_void) -> u32,
+        // ParseDisplayName through GetAttributesOf.
+        _unused: [usize; 7],
+        get_ui_object_of: unsafe extern "system" fn(
+            *mut c_void,
+            isize,
+            u32,
+            *const *const c_void,
+            *const Guid,
+            *mut u32,
+            *mut *mut c_void,
+        ) -> i32,
+    }
+
+    #[repr(C)]
+    pub struct ContextMenuVtbl {
+        query_interface: usize,
+        add_ref: usize,
+        release: unsafe extern "system" fn(*mut c_void) -> u32,
+        query_context_menu: unsafe extern "system" fn(*mut c_void, isize, u32, u32, u32, u32) -> i32,
+        invoke_command: unsafe extern "system" fn(*mut c_void, *const InvokeCommandInfo) -> i32,
+    }
+
+    #[repr(C)]
+    pub struct InvokeCommandInfo {
+        cb_size: u32,
+        mask: u32,
+        hwnd: isize,
+        verb: *const u8,
+        parameters: *const u8,
+        directory: *const u8,
+        show: i32,
+        hot_key: u32,
+        icon: isize,
+    }
+
+    #[repr(C)]
+    struct MenuItemInfo {
+        cb_size: u32,
+        mask: u32,
+        item_type: u32,
+        state: u32,
+        id: u32,
+        sub_menu: isize,
+        bmp_checked: isize,
+        bmp_unchecked: isize,
+        item_data: usize,
+        type_data: *mut u16,
+        cch: u32,
+        bmp_item: isize,
+    }
+
+    #[link(name = "ole32")]
+    unsafe extern "system" {
+        fn CoInitializeEx(reserved: *mut c_void, co_init: u32) -> i32;
+        fn CoTaskMemFree(pv: *mut c_void);
+    }
+
+    #[link(name = "shell32")]
+    unsafe extern "system" {
+        fn SHParseDisplayName(
+            name: *const u16,
+            bind_ctx: *mut c_void,
+            pidl: *mut *mut c_void,
+            sfgao_in: u32,
+            sfgao_out: *mut u32,
+        ) -> i32;
+        fn SHBindToParent(
+            pidl: *const c_void,
+            riid: *const Guid,
+            ppv: *mut *mut c_void,
+            pidl_last: *mut *const c_void,
+        ) -> i32;
+    }
+
+    #[link(name = "user32")]
+    unsafe extern "system" {
+        fn CreatePopupMenu() -> isize;
+        fn DestroyMenu(menu: isize) -> i32;
+        fn GetMenuItemCount(menu: isize) -> i32;
+        fn GetMenuItemInfoW(menu: isize, item: u32, by_position: i32, info: *mut MenuItemInfo) -> i32;
+    }
+
+    pub type ContextMenu = *mut ComObject<ContextMenuVtbl>;
+
+    /// Builds the shell context menu for `path` and hands it to `f`, releasing everything after.
+    pub fn with_context_menu<T>(path: &Path, f: impl FnOnce(ContextMenu, isize) -> T) -> Option<T> {
+        let wide_path: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+        unsafe {
+            // Fails harmlessly when COM is already initialized on this thread.
+            CoInitializeEx(std::ptr::null_mut(), COINIT_APARTMENTTHREADED);
+
+            let mut pidl = std::ptr::null_mut();
+            if SHParseDisplayName(wide_path.as_ptr(), std::ptr::null_mut(), &mut pidl, 0, std::ptr::null_mut()) < 0 {
+                return None;
+            }
+            let mut folder: *mut c_void = std::ptr::null_mut();
+            let mut child = std::ptr::null();
+            let mut result = None;
+            if SHBindToParent(pidl, &IID_ISHELLFOLDER, &mut folder, &mut child) >= 0 {
+                let folder = folder as *mut ComObject<ShellFolderVtbl>;
+                let mut context_menu: *mut c_void = std::ptr::null_mut();
+                let hr = ((*(*folder).vtable).get_ui_object_of)(
+                    folder as *mut c_void,
+                    0,
+                    1,
+                    &child,
+                    &IID_ICONTEXTMENU,
+                    std::ptr::null_mut(),
+                    &mut context_menu,
+                );
+                if hr >= 0 {
+                    let context_menu = context_menu as ContextMenu;
+                    let menu = CreatePopupMenu();
+                    let vtable = &*(*context_menu).vtable;
+                    if menu != 0
+                        && (vtable.query_context_menu)(
+                            context_menu as *mut c_void,
+                            menu,
+                            0,
+                            FIRST_COMMAND_ID,
+                            LAST_COMMAND_ID,
+                            CMF_NORMAL,
+                        ) >= 0
+                    {
+                        result = Some(f(context_menu, menu));
+                    }
+                    if menu != 0 {
+                        DestroyMenu(menu);
+                    }
+                    (vtable.release)(context_menu as *mut c_void);
+                }
+                ((*(*folder).vtable).release)(folder as *mut c_void);
+            }
+            CoTaskMemFree(pidl);
+            result
+        }
+    }
+
+    pub unsafe fn read_menu(menu: isize) -> Vec<ShellMenuItem> {
+        let mut items = Vec::new();
+        for position in 0..unsafe { GetMenuItemCount(menu) }.max(0) as u32 {
+            let mut label = [0u16; 256];
+            let mut info: MenuItemInfo = unsafe { std::mem::zeroed() };
+            info.cb_size = std::mem::size_of::<MenuItemInfo>() as u32;
+            info.mask = MIIM_ID | MIIM_SUBMENU | MIIM_STRING | MIIM_FTYPE;
+            info.type_data = label.as_mut_ptr();
+            info.cch = label.len() as u32;
+            if unsafe { GetMenuItemInfoW(menu, position, 1, &mut info) } == 0 || info.item_type & MFT_SEPARATOR != 0 {
+                continue;
+            }
+            // Drop the `&` accelerator markers Win32 menus use.
+            let label = String::from_utf16_lossy(&label[..info.cch as usize]).replace('&', "");
+            let children = if info.sub_menu != 0 { unsafe { read_menu(info.sub_menu) } } else { Vec::new() };
+            if !label.is_empty() {
+                items.push(ShellMenuItem {
+                    id: info.id,
+                    label,
+                    children,
+                });
+            }
+        }
+        items
+    }
+
+    pub unsafe fn invoke_command(context_menu: ContextMenu, id: u32) {
+        let info = InvokeCommandInfo {
+            cb_size: std::mem::size_of::<InvokeCommandInfo>() as u32,
+            mask: 0,
+            hwnd: 0,
+            // MAKEINTRESOURCE: the verb is the command's offset from the first id.
+            verb: (id - FIRST_COMMAND_ID) as usize as *const u8,
+            parameters: std::ptr::null(),
+            directory: std::ptr::null(),
+            show: SW_SHOWNORMAL,
+            hot_key: 0,
+            icon: 0,
+        };
+        unsafe {
+            ((*(*context_menu).vtable).invoke_command)(context_menu as *mut c_void, &info);
+        }
+    }
+}
@@ -0,0 +1,355 @@
+//! Linux `.desktop` launchers, Windows `.lnk` shortcuts, and `.url`/`.webloc` internet shortcuts.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShortcutTarget {
+    /// A command line from a `.desktop` `Exec` key, with field codes removed.
+    Command { command: String, terminal: bool },
+    /// A URL from a `.desktop` link or internet shortcut, or a path from a `.lnk`.
+    Location(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shortcut {
+    pub name: Option<String>,
+    pub icon: Option<PathBuf>,
+    pub target: ShortcutTarget,
+}
+
+impl Shortcut {
+    pub fn describe_target(&self) -> &str {
+        match &self.target {
+            ShortcutTarget::Command { command, .. } => command,
+            ShortcutTarget::Location(location) => location,
+        }
+    }
+
+    /// The web address this points to, if it's a URL rather than a command or a local path.
+    pub fn url(&self) -> Option<&str> {
+        match &self.target {
+            ShortcutTarget::Location(location) if is_url(location) => Some(location),
+            _ => None,
+        }
+    }
+
+    pub fn runs_program(&self) -> bool {
+        matches!(self.target, ShortcutTarget::Command { .. })
+    }
+
+    /// Whether opening this needs the same confirmation as running a program. Only web pages open
+    /// straight away; a `file://` or other target could name a program.
+    pub fn needs_confirmation(&self) -> bool {
+        match &self.target {
+            ShortcutTarget::Command { .. } => true,
+            ShortcutTarget::Location(location) => {
+                let scheme = location.split_once("://").map_or("", |(scheme, _)| scheme);
+                !scheme.eq_ignore_ascii_case("http") && !scheme.eq_ignore_ascii_case("https")
+            }
+        }
+    }
+}
+
+/// Whether a launcher may run at all. Like desktop environments, only ones marked executable do,
+/// so a downloaded `.desktop` file can't run anything just by being opened.
+#[cfg(unix)]
+pub fn is_trusted(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+pub fn is_trusted(_path: &Path) -> bool {
+    true
+}
+
+fn is_url(location: &str) -> bool {
+    location.split_once("://").is_some_and(|(scheme, _)| scheme != "file" && !scheme.is_empty())
+        || location.starts_with("mailto:")
+}
+
+pub fn read(path: &Path) -> Option<Shortcut> {
+    match path.extension()?.to_str()?.to_lowercase().as_str() {
+        "desktop" => read_desktop_entry(path),
+        "lnk" => read_lnk(path),
+        "url" => read_internet_shortcut(path),
+        "webloc" => read_webloc(path),
+        _ => None,
+    }
+}
+
+/// Where a new internet shortcut to `url` in `dir` goes: named after the site, in the platform's format.
+pub fn internet_shortcut_path(dir: &Path, url: &str) -> PathBuf {
+    let extension = if cfg!(target_os = "macos") { "webloc" } else { "url" };
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let host = without_scheme.split(['/', '?', '#']).next().unwrap_or_default();
+    let stem: String = host.chars().filter(|c| !"\\:*?\"<>|".contains(*c)).collect();
+    let stem = if stem.is_empty() { "Link".to_string() } else { stem };
+    let mut path = dir.join(format!("{}.{}", stem, extension));
+    let mut copy = 2;
+    while path.exists() {
+        path = dir.join(format!("{} ({}).{}", stem, copy, extension));
+        copy += 1;
+    }
+    path
+}
+
+const PLIST_HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+"#;
+
+pub fn write_internet_shortcut(path: &Path, url: &str) -> std::io::Result<()> {
+    let content = if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("webloc")) {
+        let escaped = url.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+        format!("{}<dict>\n\t<key>URL</key>\n\t<string>{}</string>\n</dict>\n</plist>\n", PLIST_HEADER, escaped)
+    } else {
+        format!("[InternetShortcut]\r\nURL={}\r\n", url)
+    };
+    fs::write(path, content)
+}
+
+/// Windows `.url` files, which are INI files with an `[InternetShortcut]` section.
+fn read_internet_shortcut(path: &Path) -> Option<Shortcut> {
+    let content = fs::read_to_string(path).ok()?;
+    let mut in_section = false;
+    let (mut url, mut icon) = (None, None);
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_section = line.eq_ignore_ascii_case("[InternetShortcut]");
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=').filter(|_| in_section) else {
+            continue;
+        };
+        match key.trim() {
+            "URL" => url = Some(value.trim().to_string()),
+            "IconFile" => icon = local_icon(value.trim()),
+            _ => {}
+        }
+    }
+    Some(Shortcut {
+        name: None,
+        icon,
+        target: ShortcutTarget::Location(url.filter(|url| !url.is_empty())?),
+    })
+}
+
+/// macOS `.webloc` files in the XML plist format; binary plists are not read.
+fn read_webloc(path: &Path) -> Option<Shortcut> {
+    let content = fs::read_to_string(path).ok()?;
+    let after_key = &content[content.find("<key>URL</key>")? + "<key>URL</key>".len()..];
+    let start = after_key.find("<string>")? + "<string>".len();
+    let end = start + after_key[start..].find("</string>")?;
+    let url = after_key[start..end].trim().replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&");
+    Some(Shortcut {
+        name: None,
+        icon: None,
+        target: ShortcutTarget::Location(url),
+    })
+}
+
+pub fn launch(shortcut: &Shortcut) -> std::io::Result<()> {
+    match &shortcut.target {
+        ShortcutTarget::Command { command, terminal } => {
+            let mut child = if *terminal {
+                let mut child = Command::new("x-terminal-emulator");
+                child.args(["-e", "sh", "-c", command]);
+                child
+            } else {
+                let mut child = Command::new("sh");
+                child.args(["-c", command]);
+                child
+            };
+            child.spawn().map(|_| ())
+        }
+        ShortcutTarget::Location(location) => open::that(location),
+    }
+}
+
+fn read_desktop_entry(path: &Path) -> Option<Shortcut> {
+    let content = fs::read_to_string(path).ok()?;
+    let locale = std::env::var("LANG").ok();
+    let language = locale.as_deref().and_then(|lang| lang.split(['_', '.']).next()).unwrap_or_default();
+    let localized_key = format!("Name[{}]", language);
+
+    let mut in_entry = false;
+    let (mut name, mut localized_name, mut icon, mut exec, mut url) = (None, None, None, None, None);
+    let mut terminal = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=').filter(|_| in_entry) else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        match key.trim() {
+            "Name" => name = Some(value),
+            key if key == localized_key => localized_name = Some(value),
+            "Icon" => icon = resolve_icon(&value),
+            "Exec" => exec = Some(value),
+            "URL" => url = Some(value),
+            "Terminal" => terminal = value == "true",
+            _ => {}
+        }
+    }
+
+    let target = match (url, exec) {
+        (Some(url), _) => ShortcutTarget::Location(url),
+        (None, Some(exec)) => ShortcutTarget::Command {
+            command: strip_field_codes(&exec),
+            terminal,
+        },
+        (None, None) => return None,
+    };
+    Some(Shortcut {
+        name: localized_name.or(name),
+        icon,
+        target,
+    })
+}
+
+/// Drops `%f`, `%U` and friends, since we launch without arguments; `%%` is a literal `%`.
+fn strip_field_codes(exec: &str) -> String {
+    let mut command = String::new();
+    let mut chars = exec.chars();
+    while let Some(c) = chars.next() {
+        if c == '%' {
+            if let Some('%') = chars.next() {
+                command.push('%');
+            }
+        } else {
+            command.push(c);
+        }
+    }
+    command.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// An icon file named by a shortcut, if it's on this machine. A network path isn't even checked
+/// for, since on Windows that alone sends the user's credentials to the server.
+fn local_icon(icon: &str) -> Option<PathBuf> {
+    let remote = icon.starts_with("\\\\") || icon.starts_with("//") || icon.contains("://");
+    let path = PathBuf::from(icon);
+    (!remote && path.is_absolute() && path.exists()).then_some(path)
+}
+
+/// Finds an icon by absolute path or by name in the hicolor theme and pixmaps.
+fn resolve_icon(icon: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(icon);
+    if path.is_absolute() {
+        return local_icon(icon);
+    }
+    let mut theme_dirs: Vec<PathBuf> = dirs::data_dir().into_iter().map(|dir| dir.join("icons/hicolor")).collect();
+    theme_dirs.push(PathBuf::from("/usr/share/icons/hicolor"));
+    for size in ["48x48", "64x64", "128x128", "256x256", "32x32"] {
+        for theme_dir in &theme_dirs {
+            let candidate = theme_dir.join(size).join("apps").join(format!("{}.png", icon));
+            if candidate.exists() {
+                return Some(candidate);
+            }
+        }
+    }
+    let pixmap = PathBuf::from("/usr/share/pixmaps").join(format!("{}.png", icon));
+    pixmap.exists().then_some(pixmap)
+}
+
+// Shell Link (MS-SHLLINK) header flags.
+const HAS_LINK_TARGET_ID_LIST: u32 = 0x1;
+const HAS_LINK_INFO: u32 = 0x2;
+const HAS_NAME: u32 = 0x4;
+const HAS_RELATIVE_PATH: u32 = 0x8;
+const HAS_WORKING_DIR: u32 = 0x10;
+const HAS_ARGUMENTS: u32 = 0x20;
+const HAS_ICON_LOCATION: u32 = 0x40;
+const IS_UNICODE: u32 = 0x80;
+
+fn read_lnk(path: &Path) -> Option<Shortcut> {
+    let data = fs::read(path).ok()?;
+    let u16_at = |offset: usize| Some(u16::from_le_bytes(data.get(offset..offset + 2)?.try_into().ok()?));
+    let u32_at = |offset: usize| Some(u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?));
+    if u32_at(0)? != 0x4C {
+        return None;
+    }
+    let flags = u32_at(0x14)?;
+    let mut offset = 0x4C;
+    if flags & HAS_LINK_TARGET_ID_LIST != 0 {
+        offset += 2 + u16_at(offset)? as usize;
+    }
+
+    let mut target = None;
+    if flags & HAS_LINK_INFO != 0 {
+        let info_size = u32_at(offset)? as usize;
+        let header_size = u32_at(offset + 4)? as usize;
+        let base_path = if header_size >= 0x24 {
+            let unicode_offset = u32_at(offset + 0x1C)? as usize;
+            read_utf16_nul(&data, offset + unicode_offset)
+        } else {
+            let base_offset = u32_at(offset + 0x10)? as usize;
+            read_ansi_nul(&data, offset + base_offset)
+        };
+        target = base_path.filter(|path| !path.is_empty());
+        offset += info_size;
+    }
+
+    let unicode = flags & IS_UNICODE != 0;
+    let read_string = |offset: &mut usize| -> Option<String> {
+        let count = u16_at(*offset)? as usize;
+        *offset += 2;
+        let bytes = if unicode { count * 2 } else { count };
+        let raw = data.get(*offset..*offset + bytes)?;
+        *offset += bytes;
+        Some(if unicode {
+            let units: Vec<u16> = raw.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect();
+            String::from_utf16_lossy(&units)
+        } else {
+            String::from_utf8_lossy(raw).to_string()
+        })
+    };
+    let mut icon = None;
+    let mut relative_path = None;
+    for flag in [HAS_NAME, HAS_RELATIVE_PATH, HAS_WORKING_DIR, HAS_ARGUMENTS, HAS_ICON_LOCATION] {
+        if flags & flag == 0 {
+            continue;
+        }
+        let value = read_string(&mut offset)?;
+        match flag {
+            HAS_RELATIVE_PATH => relative_path = Some(value),
+            HAS_ICON_LOCATION => icon = local_icon(&value),
+            _ => {}
+        }
+    }
+
+    // Fall back to the relative path for links that carry no LinkInfo, e.g. ones made on
+    // another machine.
+    let target = target.or_else(|| {
+        let relative = relative_path?;
+        Some(path.parent()?.join(relative.replace('\\', "/")).to_string_lossy().to_string())
+    })?;
+    Some(Shortcut {
+        name: None,
+        icon,
+        target: ShortcutTarget::Location(target),
+    })
+}
+
+fn read_ansi_nul(data: &[u8], start: usize) -> Option<String> {
+    let bytes = data.get(start..)?;
+    let end = bytes.iter().position(|&byte| byte == 0)?;
+    Some(String::from_utf8_lossy(&bytes[..end]).to_string())
+}
+
+fn read_utf16_nul(data: &[u8], start: usize) -> Option<String> {
+    let units: Vec<u16> = data
+        .get(start..)?
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .take_while(|&unit| unit != 0)
+        .collect();
+    Some(String::from_utf16_lossy(&units))
+}
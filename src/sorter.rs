@@ -0,0 +1,214 @@
+//! Tidying a folder, like Downloads or Screenshots, into subfolders by rules. A sort is planned
+//! first so it can be previewed, and what it did is kept so it can be undone.
+
+use chrono::{DateTime, Local};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub enum SortTarget {
+    /// A folder per modification date, named with a `chrono` format like `%Y-%m`.
+    Date(String),
+    /// A folder per extension, like `pdf`.
+    Extension,
+    /// Names matching the pattern go to the folder, which can use its captures as `$1`.
+    Pattern(String, String),
+}
+
+impl SortTarget {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortTarget::Date(_) => "By Date",
+            SortTarget::Extension => "By Extension",
+            SortTarget::Pattern(..) => "By Name Pattern",
+        }
+    }
+
+    /// Whether the date format or pattern can be used at all.
+    pub fn is_valid(&self) -> bool {
+        match self {
+            SortTarget::Date(format) => write!(String::new(), "{}", Local::now().format(format)).is_ok(),
+            SortTarget::Extension => true,
+            SortTarget::Pattern(pattern, _) => Regex::new(pattern).is_ok(),
+        }
+    }
+
+    /// The subfolder a file goes to, if this rule applies to it.
+    fn folder_for(&self, name: &str, modified: SystemTime) -> Option<String> {
+        let folder = match self {
+            SortTarget::Date(format) => {
+                let mut folder = String::new();
+                // An invalid format is an error here rather than a panic.
+                write!(folder, "{}", DateTime::<Local>::from(modified).format(format)).ok()?;
+                folder
+            }
+            SortTarget::Extension => Path::new(name).extension()?.to_str()?.to_lowercase(),
+            SortTarget::Pattern(pattern, target) => {
+                let captures = Regex::new(pattern).ok()?.captures(name)?;
+                let mut folder = String::new();
+                captures.expand(target, &mut folder);
+                folder
+            }
+        };
+        // Subfolders only, so a rule can't send files anywhere else.
+        let mut components = Path::new(&folder).components().peekable();
+        let nested = components.peek().is_some() && components.all(|part| matches!(part, Component::Normal(_)));
+        nested.then_some(folder)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct SortRule {
+    pub enabled: bool,
+    pub target: SortTarget,
+}
+
+/// Tried in order; a file goes where the first enabled rule that applies to it says.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct SortRules(pub Vec<SortRule>);
+
+impl Default for SortRules {
+    fn default() -> Self {
+        Self(vec![
+            SortRule {
+                enabled: true,
+                target: SortTarget::Pattern(r"(?i)^screen ?shot".to_string(), "Screenshots".to_string()),
+            },
+            SortRule {
+                enabled: false,
+                target: SortTarget::Date("%Y-%m".to_string()),
+            },
+            SortRule {
+                enabled: true,
+                target: SortTarget::Extension,
+            },
+        ])
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct PlannedMove {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// What a sort did, which is what undoing it needs.
+#[derive(Clone, Default)]
+pub struct SortOutcome {
+    pub moved: Vec<PlannedMove>,
+    /// Folders the sort made, parents first.
+    pub created: Vec<PathBuf>,
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+/// Where each file directly in `dir` would go. Folders and hidden files stay put.
+pub fn plan(dir: &Path, rules: &SortRules) -> Result<Vec<PlannedMove>, String> {
+    let mut moves = Vec::new();
+    let mut taken = HashSet::new();
+    let mut entries: Vec<_> = fs::read_dir(dir).map_err(|err| err.to_string())?.filter_map(|e| e.ok()).collect();
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if !metadata.is_file() || name.starts_with('.') {
+            continue;
+        }
+        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        let mut rules = rules.0.iter().filter(|rule| rule.enabled);
+        if let Some(folder) = rules.find_map(|rule| rule.target.folder_for(&name, modified)) {
+            let to = available(&dir.join(folder).join(&name), &taken);
+            taken.insert(to.clone());
+            moves.push(PlannedMove { from: entry.path(), to });
+        }
+    }
+    Ok(moves)
+}
+
+/// `path`, or `name (2).ext` and so on if something is already there.
+pub fn available(path: &Path, taken: &HashSet<PathBuf>) -> PathBuf {
+    let free = |path: &Path| !path.exists() && !taken.contains(path);
+    if free(path) {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path.extension().map(|ext| format!(".{}", ext.to_string_lossy())).unwrap_or_default();
+    (2..)
+        .map(|n| path.with_file_name(format!("{} ({}){}", stem, n, extension)))
+        .find(|candidate| free(candidate))
+        .unwrap()
+}
+
+/// Makes the moves, carrying on past any that fail.
+pub fn apply(moves: &[PlannedMove]) -> SortOutcome {
+    let mut outcome = SortOutcome::default();
+    for planned in moves {
+        let result = if planned.to.exists() {
+            Err(format!("{} already exists", planned.to.display()))
+        } else {
+            // Planned moves always go into a subfolder.
+            create_dirs(planned.to.parent().unwrap(), &mut outcome.created)
+                .and_then(|()| fs::rename(&planned.from, &planned.to).map_err(|err| err.to_string()))
+        };
+        match result {
+            Ok(()) => outcome.moved.push(planned.clone()),
+            Err(err) => outcome.failed.push((planned.from.clone(), err)),
+        }
+    }
+    outcome
+}
+
+fn create_dirs(dir: &Path, created: &mut Vec<PathBuf>) -> Result<(), String> {
+    if dir.is_dir() {
+        return Ok(());
+    }
+    if let Some(parent) = dir.parent() {
+        create_dirs(parent, created)?;
+    }
+    fs::create_dir(dir).map_err(|err| err.to_string())?;
+    created.push(dir.to_path_buf());
+    Ok(())
+}
+
+/// Moves everything back and removes the folders the sort made, if they're empty again.
+pub fn undo(outcome: &SortOutcome) -> Vec<(PathBuf, String)> {
+    let mut failed = Vec::new();
+    for planned in outcome.moved.iter().rev() {
+        let result = if planned.from.exists() {
+            Err(format!("{} already exists", planned.from.display()))
+        } else {
+            fs::rename(&planned.to, &planned.from).map_err(|err| err.to_string())
+        };
+        if let Err(err) = result {
+            failed.push((planned.to.clone(), err));
+        }
+    }
+    for dir in outcome.created.iter().rev() {
+        let _ = fs::remove_dir(dir);
+    }
+    failed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_pick_subfolders() {
+        let epoch = SystemTime::UNIX_EPOCH;
+        let pattern = SortTarget::Pattern(r"^IMG_(\d{4})".to_string(), "Photos/$1".to_string());
+        assert_eq!(pattern.folder_for("IMG_2023_01.jpg", epoch), Some("Photos/2023".to_string()));
+        assert_eq!(pattern.folder_for("notes.txt", epoch), None);
+        assert_eq!(SortTarget::Extension.folder_for("Report.PDF", epoch), Some("pdf".to_string()));
+        assert_eq!(SortTarget::Extension.folder_for("Makefile", epoch), None);
+        let escape = SortTarget::Pattern(".*".to_string(), "../elsewhere".to_string());
+        assert_eq!(escape.folder_for("a.txt", epoch), None);
+        assert_eq!(SortTarget::Date("%Q".to_string()).folder_for("a.txt", epoch), None);
+    }
+}
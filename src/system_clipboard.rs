@@ -0,0 +1,105 @@
+//! Files on the desktop clipboard, so copy and paste work with other file managers.
+//!
+//! Uses `x-special/gnome-copied-files`, which Nautilus, Nemo, Thunar, Caja and Dolphin all
+//! read, falling back to `text/uri-list` when pasting. Wayland sessions go through
+//! `wl-copy`/`wl-paste` (wl-data-device); X11 sessions through `xclip`.
+
+use crate::desktop;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+const GNOME_FILES_TYPE: &str = "x-special/gnome-copied-files";
+const URI_LIST_TYPE: &str = "text/uri-list";
+
+fn is_wayland() -> bool {
+    std::env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+/// Puts `paths` on the clipboard; `cut` tells the pasting app to move rather than copy.
+pub fn copy_files(paths: &[PathBuf], cut: bool) {
+    if !cfg!(target_os = "linux") {
+        return;
+    }
+    let uris: Vec<String> = paths.iter().filter_map(|path| desktop::file_uri(path)).collect();
+    let payload = format!("{}\n{}", if cut { "cut" } else { "copy" }, uris.join("\n"));
+    let mut command = if is_wayland() {
+        let mut command = Command::new("wl-copy");
+        command.args(["--type", GNOME_FILES_TYPE]);
+        command
+    } else {
+        let mut command = Command::new("xclip");
+        command.args(["-selection", "clipboard", "-t", GNOME_FILES_TYPE]);
+        command
+    };
+    // Both tools fork to serve the selection once they've read it, so this only waits for that.
+    if let Ok(mut child) = command.stdin(Stdio::piped()).stdout(Stdio::null()).spawn() {
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all(payload.as_bytes());
+        }
+        let _ = child.wait();
+    }
+}
+
+/// The files currently on the clipboard and whether they were cut, if it holds any.
+pub fn read_files() -> Option<(Vec<PathBuf>, bool)> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let types = read_type("TARGETS")?;
+    let (content, gnome_format) = if types.lines().any(|line| line.trim() == GNOME_FILES_TYPE) {
+        (read_type(GNOME_FILES_TYPE)?, true)
+    } else if types.lines().any(|line| line.trim() == URI_LIST_TYPE) {
+        (read_type(URI_LIST_TYPE)?, false)
+    } else {
+        return None;
+    };
+
+    let mut lines = content.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#'));
+    let cut = gnome_format && lines.next() == Some("cut");
+    let paths: Vec<PathBuf> = lines.filter_map(path_from_uri).collect();
+    (!paths.is_empty()).then_some((paths, cut))
+}
+
+fn read_type(mime_type: &str) -> Option<String> {
+    let output = if is_wayland() {
+        if mime_type == "TARGETS" {
+            Command::new("wl-paste").args(["--list-types"]).output()
+        } else {
+            Command::new("wl-paste").args(["--no-newline", "--type", mime_type]).output()
+        }
+    } else {
+        Command::new("xclip").args(["-selection", "clipboard", "-o", "-t", mime_type]).output()
+    }
+    .ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn path_from_uri(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?;
+    // Skip an optional host part, e.g. `file://localhost/home`.
+    let encoded = &encoded[encoded.find('/')?..];
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let Ok(byte) = u8::from_str_radix(std::str::from_utf8(&bytes[i + 1..i + 3]).ok()?, 16) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+        Some(PathBuf::from(std::ffi::OsString::from_vec(decoded)))
+    }
+    #[cfg(not(unix))]
+    {
+        Some(PathBuf::from(String::from_utf8_lossy(&decoded).to_string()))
+    }
+}
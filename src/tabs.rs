@@ -0,0 +1,61 @@
+use crate::app::SortBy;
+use crate::navigation::NavigationHistory;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// What a tab keeps while another tab is showing.
+#[derive(Clone)]
+pub struct Tab {
+    pub path: PathBuf,
+    pub navigation: NavigationHistory,
+    pub selected_items: HashSet<PathBuf>,
+    pub sort_by: SortBy,
+    pub sort_ascending: bool,
+}
+
+impl Tab {
+    pub fn new(path: &Path, sort_by: SortBy, sort_ascending: bool) -> Self {
+        let mut navigation = NavigationHistory::default();
+        navigation.visit(path);
+        Self {
+            path: path.to_path_buf(),
+            navigation,
+            selected_items: HashSet::new(),
+            sort_by,
+            sort_ascending,
+        }
+    }
+}
+
+/// A tab's label: the folder's name, or the whole path for a root.
+pub fn title(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// The tab to show after closing tab `closed` while `active` was showing, given the tabs left.
+pub fn active_after_close(active: usize, closed: usize, remaining: usize) -> usize {
+    if closed < active || (closed == active && active == remaining) {
+        active.saturating_sub(1)
+    } else {
+        active
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closing_keeps_the_same_tab_showing() {
+        assert_eq!(active_after_close(2, 0, 3), 1);
+        assert_eq!(active_after_close(0, 2, 3), 0);
+    }
+
+    #[test]
+    fn closing_the_active_tab_shows_its_neighbour() {
+        assert_eq!(active_after_close(1, 1, 2), 1);
+        assert_eq!(active_after_close(2, 2, 2), 1);
+    }
+}
@@ -0,0 +1,153 @@
+//! Operation progress on the Windows taskbar button (`ITaskbarList3`) and on launchers and
+//! docks that implement Unity's `LauncherEntry` API (Ubuntu Dock, Dash to Dock, Plank, KDE).
+
+#[derive(Default)]
+pub struct TaskbarProgress {
+    shown: Option<f32>,
+}
+
+impl TaskbarProgress {
+    /// Shows `progress` (0.0 to 1.0), or hides the indicator with `None`. Only whole-percent
+    /// changes are passed on, since every update is a COM call or a D-Bus message.
+    pub fn set(&mut self, progress: Option<f32>) {
+        let progress = progress.map(|p| (p.clamp(0.0, 1.0) * 100.0).round() / 100.0);
+        if progress == self.shown {
+            return;
+        }
+        self.shown = progress;
+        platform_set(progress);
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn platform_set(progress: Option<f32>) {
+    let properties = format!(
+        "{{'progress': <{:.2}>, 'progress-visible': <{}>}}",
+        progress.unwrap_or(0.0),
+        progress.is_some()
+    );
+    // The launcher matches this to our window through the desktop entry the window's app id names.
+    let uri = format!("application://{}.desktop", crate::desktop::APP_ID);
+    let child = std::process::Command::new("gdbus")
+        .args(["emit", "--session", "--object-path", "/com/canonical/unity/launcherentry/1"])
+        .args(["--signal", "com.canonical.Unity.LauncherEntry.Update", &uri])
+        .arg(properties)
+        .spawn();
+    // Reaped off the UI thread, since `gdbus` can take a while without a session bus.
+    if let Ok(mut child) = child {
+        std::thread::spawn(move || child.wait());
+    }
+}
+
+#[cfg(windows)]
+fn platform_set(progress: Option<f32>) {
+    unsafe { ffi::set_progress(progress) };
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn platform_set(_progress: Option<f32>) {}
+
+#[cfg(windows)]
+mod ffi {
+    use std::ffi::c_void;
+
+    const COINIT_APARTMENTTHREADED: u32 = 0x2;
+    const CLSCTX_INPROC_SERVER: u32 = 0x1;
+    const TBPF_NOPROGRESS: u32 = 0;
+    const TBPF_NORMAL: u32 = 0x2;
+    const PROGRESS_TOTAL: u64 = 100;
+
+    #[repr(C)]
+    struct Guid(u32, u16, u16, [u8; 8]);
+
+    const CLSID_TASKBARLIST: Guid = Guid(0x56FDF344, 0xFD6D, 0x11D0, [0x95, 0x8A, 0x00, 0x60, 0x97, 0xC9, 0xA0, 0x90]);
+    const IID_ITASKBARLIST3: Guid = Guid(0xEA1AFB91, 0x9E28, 0x4B86, [0x90, 0xE9, 0x9E, 0x9F, 0x8A, 0x5E, 0xEF, 0xAF]);
+
+    #[repr(C)]
+    struct ComObject<V> {
+        vtable: *const V,
+    }
+
+    #[repr(C)]
+    struct TaskbarListVtbl {
+        query_interface: usize,
+        add_ref: usize,
+        release: unsafe extern "system" fn(*mut c_void) -> u32,
+        hr_init: unsafe extern "system" fn(*mut c_void) -> i32,
+        // AddTab through MarkFullscreenWindow.
+        _unused: [usize; 5],
+        set_progress_value: unsafe extern "system" fn(*mut c_void, isize, u64, u64) -> i32,
+        set_progress_state: unsafe extern "system" fn(*mut c_void, isize, u32) -> i32,
+    }
+
+    #[link(name = "ole32")]
+    unsafe extern "system" {
+        fn CoInitializeEx(reserved: *mut c_void, co_init: u32) -> i32;
+        fn CoCreateInstance(
+            clsid: *const Guid,
+            outer: *mut c_void,
+            context: u32,
+            iid: *const Guid,
+            ppv: *mut *mut c_void,
+        ) -> i32;
+    }
+
+    #[link(name = "user32")]
+    unsafe extern "system" {
+        fn EnumThreadWindows(thread_id: u32, callback: extern "system" fn(isize, isize) -> i32, lparam: isize) -> i32;
+        fn GetWindow(hwnd: isize, command: u32) -> isize;
+    }
+
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        fn GetCurrentThreadId() -> u32;
+    }
+
+    const GW_OWNER: u32 = 4;
+
+    extern "system" fn find_main_window(hwnd: isize, found: isize) -> i32 {
+        // The first unowned window on the UI thread is the app window.
+        if unsafe { GetWindow(hwnd, GW_OWNER) } != 0 {
+            return 1;
+        }
+        unsafe { *(found as *mut isize) = hwnd };
+        0
+    }
+
+    pub unsafe fn set_progress(progress: Option<f32>) {
+        unsafe {
+            let mut hwnd: isize = 0;
+            EnumThreadWindows(GetCurrentThreadId(), find_main_window, &mut hwnd as *mut isize as isize);
+            if hwnd == 0 {
+                return;
+            }
+            // Fails harmlessly when COM is already initialized on this thread.
+            CoInitializeEx(std::ptr::null_mut(), COINIT_APARTMENTTHREADED);
+            let mut taskbar: *mut c_void = std::ptr::null_mut();
+            let hr = CoCreateInstance(
+                &CLSID_TASKBARLIST,
+                std::ptr::null_mut(),
+                CLSCTX_INPROC_SERVER,
+                &IID_ITASKBARLIST3,
+                &mut taskbar,
+            );
+            if hr < 0 {
+                return;
+            }
+            let vtable = &*(*(taskbar as *mut ComObject<TaskbarListVtbl>)).vtable;
+            if (vtable.hr_init)(taskbar) >= 0 {
+                match progress {
+                    Some(progress) => {
+                        (vtable.set_progress_state)(taskbar, hwnd, TBPF_NORMAL);
+                        let done = (progress * PROGRESS_TOTAL as f32) as u64;
+                        (vtable.set_progress_value)(taskbar, hwnd, done, PROGRESS_TOTAL);
+                    }
+                    None => {
+                        (vtable.set_progress_state)(taskbar, hwnd, TBPF_NOPROGRESS);
+                    }
+                }
+            }
+            (vtable.release)(taskbar);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Project templates: folders kept in one place and copied out whole for a new project, with
+//! tokens like `{{name}}` and `{{date}}` filled in throughout names and text files.
+
+use chrono::Local;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Where templates live, one folder each.
+pub fn templates_dir() -> PathBuf {
+    dirs::home_dir().unwrap().join(".file_manager_templates")
+}
+
+/// The template folders, by name.
+pub fn list() -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(templates_dir()) else {
+        return Vec::new();
+    };
+    let mut templates: Vec<PathBuf> =
+        entries.filter_map(|entry| Some(entry.ok()?.path())).filter(|path| path.is_dir()).collect();
+    templates.sort();
+    templates
+}
+
+/// Copies `template` to a new folder `name` in `dir`, returning the folder.
+pub fn create_project(template: &Path, dir: &Path, name: &str) -> Result<PathBuf, String> {
+    let now = Local::now();
+    let user = std::env::var("USER").or_else(|_| std::env::var("USERNAME")).unwrap_or_default();
+    let tokens = [
+        ("{{name}}", name.to_string()),
+        ("{{date}}", now.format("%Y-%m-%d").to_string()),
+        ("{{year}}", now.format("%Y").to_string()),
+        ("{{user}}", user),
+    ];
+    let target = dir.join(name);
+    if target.exists() {
+        return Err(format!("{} already exists", name));
+    }
+    let template = fs::canonicalize(template).map_err(|err| err.to_string())?;
+    fs::create_dir(&target).map_err(|err| err.to_string())?;
+    // Leaves nothing half-made behind.
+    copy_contents(&template, &target, &tokens).map_err(|err| {
+        let _ = fs::remove_dir_all(&target);
+        err.to_string()
+    })?;
+    Ok(target)
+}
+
+fn copy_contents(source: &Path, target: &Path, tokens: &[(&str, String)]) -> io::Result<()> {
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let name = replace_tokens(&entry.file_name().to_string_lossy(), tokens);
+        copy_replacing(&entry.path(), &target.join(name), tokens)?;
+    }
+    fs::set_permissions(target, fs::metadata(source)?.permissions())
+}
+
+fn copy_replacing(source: &Path, target: &Path, tokens: &[(&str, String)]) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(source)?;
+    // Links are copied as links; following them could loop back into the template.
+    if metadata.is_symlink() {
+        return copy_link(source, target);
+    }
+    if metadata.is_dir() {
+        fs::create_dir(target)?;
+        return copy_contents(source, target, tokens);
+    }
+    let content = fs::read(source)?;
+    // Binary files, like images, are copied as they are.
+    match String::from_utf8(content) {
+        Ok(text) if !text.contains('\0') => fs::write(target, replace_tokens(&text, tokens)),
+        Ok(text) => fs::write(target, text),
+        Err(err) => fs::write(target, err.into_bytes()),
+    }?;
+    // Keeps scripts executable.
+    fs::set_permissions(target, metadata.permissions())
+}
+
+#[cfg(unix)]
+fn copy_link(source: &Path, target: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(source)?, target)
+}
+
+#[cfg(not(unix))]
+fn copy_link(_source: &Path, _target: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+fn replace_tokens(text: &str, tokens: &[(&str, String)]) -> String {
+    tokens.iter().fold(text.to_string(), |text, (token, value)| text.replace(token, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_every_token() {
+        let tokens = [("{{name}}", "demo".to_string()), ("{{year}}", "2024".to_string())];
+        let replaced = replace_tokens("{{name}}/{{name}}.rs © {{year}} {{other}}", &tokens);
+        assert_eq!(replaced, "demo/demo.rs © 2024 {{other}}");
+    }
+}
@@ -0,0 +1,88 @@
+use chrono::{Local, Timelike};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Default)]
+pub enum ThemePreference {
+    #[default]
+    System,
+    Schedule,
+    Light,
+    Dark,
+}
+
+impl ThemePreference {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ThemePreference::System => "Follow System",
+            ThemePreference::Schedule => "Schedule",
+            ThemePreference::Light => "Light",
+            ThemePreference::Dark => "Dark",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct ThemeSettings {
+    pub preference: ThemePreference,
+    pub dark_from_hour: u32,
+    pub light_from_hour: u32,
+    /// Replaces the blue of selections, links and hovered widgets.
+    #[serde(default)]
+    pub accent: Option<[u8; 3]>,
+}
+
+impl Default for ThemeSettings {
+    fn default() -> Self {
+        Self {
+            preference: ThemePreference::System,
+            dark_from_hour: 19,
+            light_from_hour: 7,
+            accent: None,
+        }
+    }
+}
+
+impl ThemeSettings {
+    pub fn wants_dark(&self, system_theme: Option<eframe::Theme>) -> bool {
+        match self.preference {
+            ThemePreference::Light => false,
+            ThemePreference::Dark => true,
+            ThemePreference::System => system_theme.map_or(true, |theme| theme == eframe::Theme::Dark),
+            ThemePreference::Schedule => {
+                let hour = Local::now().hour();
+                if self.dark_from_hour > self.light_from_hour {
+                    hour >= self.dark_from_hour || hour < self.light_from_hour
+                } else {
+                    hour >= self.dark_from_hour && hour < self.light_from_hour
+                }
+            }
+        }
+    }
+
+    /// Switches the egui visuals when the wanted theme or accent differs from the active one.
+    pub fn apply(&self, ctx: &egui::Context, system_theme: Option<eframe::Theme>) {
+        let mut visuals = if self.wants_dark(system_theme) { egui::Visuals::dark() } else { egui::Visuals::light() };
+        if let Some([r, g, b]) = self.accent {
+            set_accent(&mut visuals, egui::Color32::from_rgb(r, g, b));
+        }
+        if ctx.style().visuals != visuals {
+            ctx.set_visuals(visuals);
+        }
+        if self.preference == ThemePreference::Schedule {
+            ctx.request_repaint_after(std::time::Duration::from_secs(60));
+        }
+    }
+}
+
+fn set_accent(visuals: &mut egui::Visuals, accent: egui::Color32) {
+    // Selected text sits on the accent, so it needs whichever of black or white stands out more.
+    let [r, g, b, _] = accent.to_array();
+    let luminance = 0.299 * f32::from(r) + 0.587 * f32::from(g) + 0.114 * f32::from(b);
+    let on_accent = if luminance > 150.0 { egui::Color32::BLACK } else { egui::Color32::WHITE };
+    visuals.selection.bg_fill = accent;
+    visuals.selection.stroke.color = on_accent;
+    visuals.hyperlink_color = accent;
+    visuals.widgets.hovered.bg_stroke.color = accent;
+    visuals.widgets.active.bg_stroke.color = accent;
+}
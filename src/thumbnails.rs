@@ -0,0 +1,134 @@
+//! Thumbnails shared with other file managers through the freedesktop thumbnail cache
+//! (`$XDG_CACHE_HOME/thumbnails`).
+
+use crate::desktop;
+use md5::{Digest, Md5};
+use std::fs;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::UNIX_EPOCH;
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "ico", "webp"];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ThumbnailSize {
+    Normal,
+    Large,
+}
+
+impl ThumbnailSize {
+    pub fn pixels(self) -> u32 {
+        match self {
+            ThumbnailSize::Normal => 128,
+            ThumbnailSize::Large => 256,
+        }
+    }
+
+    fn directory(self) -> &'static str {
+        match self {
+            ThumbnailSize::Normal => "normal",
+            ThumbnailSize::Large => "large",
+        }
+    }
+}
+
+/// A decoded RGBA thumbnail.
+pub struct Thumbnail {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+pub fn is_supported(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Returns the cached thumbnail if it's still current, generating and caching one otherwise.
+pub fn get(path: &Path, size: ThumbnailSize) -> Option<Thumbnail> {
+    let mtime = fs::metadata(path).ok()?.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_secs();
+    let uri = desktop::file_uri(path)?;
+    let cache_path = cache_path(&uri, size);
+    if let Some(thumbnail) = cache_path.as_deref().and_then(|cache_path| load(cache_path, mtime)) {
+        return Some(thumbnail);
+    }
+
+    let image = image::open(path).ok()?.thumbnail(size.pixels(), size.pixels()).to_rgba8();
+    let thumbnail = Thumbnail {
+        width: image.width(),
+        height: image.height(),
+        rgba: image.into_raw(),
+    };
+    if let Some(cache_path) = cache_path {
+        let _ = save(&cache_path, &thumbnail, &uri, mtime);
+    }
+    Some(thumbnail)
+}
+
+fn cache_path(uri: &str, size: ThumbnailSize) -> Option<PathBuf> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    let hash = Md5::digest(uri.as_bytes());
+    let name: String = hash.iter().map(|byte| format!("{:02x}", byte)).collect();
+    Some(dirs::cache_dir()?.join("thumbnails").join(size.directory()).join(format!("{}.png", name)))
+}
+
+fn load(cache_path: &Path, mtime: u64) -> Option<Thumbnail> {
+    let decoder = png::Decoder::new(fs::File::open(cache_path).ok()?);
+    let mut reader = decoder.read_info().ok()?;
+    // A thumbnail is stale once the source's mtime no longer matches what was recorded.
+    let current = reader
+        .info()
+        .uncompressed_latin1_text
+        .iter()
+        .any(|chunk| chunk.keyword == "Thumb::MTime" && chunk.text == mtime.to_string());
+    if !current {
+        return None;
+    }
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let frame = reader.next_frame(&mut buffer).ok()?;
+    if frame.color_type != png::ColorType::Rgba || frame.bit_depth != png::BitDepth::Eight {
+        return None;
+    }
+    buffer.truncate(frame.buffer_size());
+    Some(Thumbnail {
+        width: frame.width,
+        height: frame.height,
+        rgba: buffer,
+    })
+}
+
+fn save(cache_path: &Path, thumbnail: &Thumbnail, uri: &str, mtime: u64) -> Result<(), Box<dyn std::error::Error>> {
+    let directory = cache_path.parent().ok_or("thumbnail path has no parent")?;
+    fs::create_dir_all(directory)?;
+    // Write to a temporary file and rename so other readers never see a partial thumbnail. Each
+    // gets its own name, since several are made at once.
+    static NEXT_TEMP: AtomicU64 = AtomicU64::new(0);
+    let temp_name = format!(".happ-{}-{}.png", std::process::id(), NEXT_TEMP.fetch_add(1, Ordering::Relaxed));
+    let temp = directory.join(temp_name);
+    let file = fs::File::options().write(true).create_new(true).open(&temp)?;
+    let result = write_png(file, thumbnail, uri, mtime).and_then(|()| fs::rename(&temp, cache_path).map_err(Into::into));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+fn write_png(file: fs::File, thumbnail: &Thumbnail, uri: &str, mtime: u64) -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    let mut encoder = png::Encoder::new(BufWriter::new(file), thumbnail.width, thumbnail.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.add_text_chunk("Thumb::URI".to_string(), uri.to_string())?;
+    encoder.add_text_chunk("Thumb::MTime".to_string(), mtime.to_string())?;
+    encoder.add_text_chunk("Software".to_string(), "happ".to_string())?;
+    encoder.write_header()?.write_image_data(&thumbnail.rgba)?;
+    Ok(())
+}
@@ -0,0 +1,152 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tray_icon::menu::{Menu, MenuEvent, MenuId, MenuItem, PredefinedMenuItem, Submenu};
+use tray_icon::{Icon, TrayIconBuilder};
+
+const ICON_SIZE: u32 = 32;
+
+#[derive(Clone)]
+pub enum TrayCommand {
+    ToggleWindow,
+    Open(PathBuf),
+    Quit,
+}
+
+/// Locations listed in the tray menu.
+#[derive(Clone, PartialEq, Default)]
+pub struct TrayLocations {
+    pub favorites: Vec<PathBuf>,
+    pub recent: Vec<PathBuf>,
+}
+
+type Commands = Arc<Mutex<Vec<(MenuId, TrayCommand)>>>;
+
+/// The tray icon. On Linux it lives on its own GTK thread; elsewhere it must be created and
+/// updated from the thread running the window's event loop.
+pub struct Tray {
+    commands: Commands,
+    #[cfg(target_os = "linux")]
+    updates: std::sync::mpsc::Sender<TrayLocations>,
+    #[cfg(not(target_os = "linux"))]
+    icon: tray_icon::TrayIcon,
+}
+
+impl Tray {
+    pub fn new(locations: TrayLocations) -> Result<Tray, String> {
+        let commands = Commands::default();
+        #[cfg(target_os = "linux")]
+        {
+            let (updates, rx) = std::sync::mpsc::channel::<TrayLocations>();
+            let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+            let thread_commands = commands.clone();
+            std::thread::spawn(move || {
+                if let Err(e) = gtk::init() {
+                    let _ = ready_tx.send(Err(e.to_string()));
+                    return;
+                }
+                let icon = match build_icon(&locations, &thread_commands) {
+                    Ok(icon) => icon,
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e));
+                        return;
+                    }
+                };
+                let _ = ready_tx.send(Ok(()));
+                // Menus are not Send, so rebuilds happen here when new locations arrive.
+                gtk::glib::timeout_add_local(std::time::Duration::from_millis(250), move || {
+                    use std::sync::mpsc::TryRecvError;
+                    loop {
+                        match rx.try_recv() {
+                            Ok(locations) => icon.set_menu(Some(Box::new(build_menu(&locations, &thread_commands)))),
+                            Err(TryRecvError::Empty) => return gtk::glib::ControlFlow::Continue,
+                            Err(TryRecvError::Disconnected) => {
+                                gtk::main_quit();
+                                return gtk::glib::ControlFlow::Break;
+                            }
+                        }
+                    }
+                });
+                gtk::main();
+            });
+            ready_rx.recv().map_err(|e| e.to_string())??;
+            Ok(Tray { commands, updates })
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let icon = build_icon(&locations, &commands)?;
+            Ok(Tray { commands, icon })
+        }
+    }
+
+    pub fn set_locations(&self, locations: TrayLocations) {
+        #[cfg(target_os = "linux")]
+        let _ = self.updates.send(locations);
+        #[cfg(not(target_os = "linux"))]
+        self.icon.set_menu(Some(Box::new(build_menu(&locations, &self.commands))));
+    }
+
+    /// Returns the next menu command the user picked, if any.
+    pub fn poll(&self) -> Option<TrayCommand> {
+        while let Ok(event) = MenuEvent::receiver().try_recv() {
+            let commands = self.commands.lock().unwrap();
+            if let Some((_, command)) = commands.iter().find(|(id, _)| *id == event.id) {
+                return Some(command.clone());
+            }
+        }
+        None
+    }
+}
+
+fn build_icon(locations: &TrayLocations, commands: &Commands) -> Result<tray_icon::TrayIcon, String> {
+    let icon = Icon::from_rgba(folder_icon(), ICON_SIZE, ICON_SIZE).map_err(|e| e.to_string())?;
+    TrayIconBuilder::new()
+        .with_tooltip("File Manager")
+        .with_icon(icon)
+        .with_menu(Box::new(build_menu(locations, commands)))
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+fn build_menu(locations: &TrayLocations, commands: &Commands) -> Menu {
+    let mut commands = commands.lock().unwrap();
+    commands.clear();
+    let mut item = |label: &str, command: TrayCommand| {
+        let item = MenuItem::new(label, true, None);
+        commands.push((item.id().clone(), command));
+        item
+    };
+
+    let menu = Menu::new();
+    let _ = menu.append(&item("Show / Hide Window", TrayCommand::ToggleWindow));
+    let _ = menu.append(&PredefinedMenuItem::separator());
+    for (title, paths) in [("Favorites", &locations.favorites), ("Recent Folders", &locations.recent)] {
+        let submenu = Submenu::new(title, !paths.is_empty());
+        for path in paths {
+            let _ = submenu.append(&item(&location_label(path), TrayCommand::Open(path.clone())));
+        }
+        let _ = menu.append(&submenu);
+    }
+    let _ = menu.append(&PredefinedMenuItem::separator());
+    let _ = menu.append(&item("Quit", TrayCommand::Quit));
+    menu
+}
+
+fn location_label(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// A plain yellow folder, so the tray needs no bundled image.
+fn folder_icon() -> Vec<u8> {
+    let mut rgba = Vec::with_capacity((ICON_SIZE * ICON_SIZE * 4) as usize);
+    for y in 0..ICON_SIZE {
+        for x in 0..ICON_SIZE {
+            let tab = (6..10).contains(&y) && (3..14).contains(&x);
+            let body = (10..27).contains(&y) && (3..29).contains(&x);
+            let pixel = if tab || body { [240, 190, 60, 255] } else { [0, 0, 0, 0] };
+            rgba.extend_from_slice(&pixel);
+        }
+    }
+    rgba
+}
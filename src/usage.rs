@@ -0,0 +1,69 @@
+//! Opt-in record of how often and when files were opened through the app, behind the
+//! Frequently Used view and the Last Opened column.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct Usage {
+    pub count: u32,
+    pub last_opened: DateTime<Utc>,
+}
+
+#[derive(Default)]
+pub struct UsageLog(HashMap<PathBuf, Usage>);
+
+fn get_usage_path() -> PathBuf {
+    dirs::home_dir().unwrap().join(".file_manager_usage.json")
+}
+
+impl UsageLog {
+    pub fn load() -> Self {
+        let entries = fs::read_to_string(get_usage_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self(entries)
+    }
+
+    fn save(&self) {
+        let path = get_usage_path();
+        let Ok(content) = serde_json::to_string(&self.0) else {
+            return;
+        };
+        let temp = path.with_extension("json.tmp");
+        if fs::write(&temp, content).is_ok() {
+            let _ = fs::rename(&temp, &path);
+        }
+    }
+
+    pub fn record(&mut self, path: &Path) {
+        let usage = self.0.entry(path.to_path_buf()).or_insert(Usage {
+            count: 0,
+            last_opened: Utc::now(),
+        });
+        usage.count += 1;
+        usage.last_opened = Utc::now();
+        self.save();
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&Usage> {
+        self.0.get(path)
+    }
+
+    /// The `count` most opened files that still exist, most opened first; ties go to the
+    /// most recently opened.
+    pub fn most_used(&self, count: usize) -> Vec<PathBuf> {
+        let mut entries: Vec<(&PathBuf, &Usage)> = self.0.iter().filter(|(path, _)| path.exists()).collect();
+        entries.sort_by(|a, b| b.1.count.cmp(&a.1.count).then(b.1.last_opened.cmp(&a.1.last_opened)));
+        entries.into_iter().take(count).map(|(path, _)| path.clone()).collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+        self.save();
+    }
+}
@@ -0,0 +1,243 @@
+//! Older versions of files kept by filesystem snapshots: ZFS's `.zfs/snapshot`, snapper's
+//! btrfs `.snapshots`, and Windows shadow copies.
+
+use crate::desktop;
+use crate::encoding;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Comparing more changed lines than this would take too long and too much memory; lines the
+/// files start and end with in common don't count.
+const MAX_DIFF_LINES: usize = 2000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Version {
+    /// Where the old copy can be read, inside the snapshot.
+    pub path: PathBuf,
+    /// The snapshot it came from, e.g. "ZFS snapshot daily-2024-05-01".
+    pub snapshot: String,
+    pub modified: SystemTime,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffLine {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+/// Every distinct older version of `path`, newest first; snapshots holding the same version as
+/// the current file, or as a newer snapshot, are left out.
+pub fn list(path: &Path) -> Vec<Version> {
+    let current = fs::metadata(path).ok().map(|metadata| (metadata.modified().ok(), metadata.len()));
+    let mut versions: Vec<Version> = snapshot_copies(path)
+        .into_iter()
+        .filter_map(|(snapshot, copy)| {
+            let metadata = fs::metadata(&copy).ok()?;
+            Some(Version {
+                path: copy,
+                snapshot,
+                modified: metadata.modified().ok()?,
+                size: if metadata.is_dir() { 0 } else { metadata.len() },
+            })
+        })
+        .collect();
+    versions.sort_by(|a, b| b.modified.cmp(&a.modified));
+    versions.dedup_by(|a, b| a.modified == b.modified && a.size == b.size);
+    if !path.is_dir() {
+        versions.retain(|version| Some((Some(version.modified), version.size)) != current);
+    }
+    versions
+}
+
+/// The snapshots `path` might be in, with where it would be inside each.
+#[cfg(not(windows))]
+fn snapshot_copies(path: &Path) -> Vec<(String, PathBuf)> {
+    let snapshot_dirs = |dir: PathBuf| -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect()
+    };
+    // Snapshots live at the root of the dataset or subvolume, so look upwards for one.
+    for root in path.ancestors().skip(1) {
+        let Ok(relative) = path.strip_prefix(root) else {
+            continue;
+        };
+        let zfs = root.join(".zfs/snapshot");
+        if zfs.is_dir() {
+            return snapshot_dirs(zfs)
+                .into_iter()
+                .map(|dir| {
+                    let name = dir.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                    (format!("ZFS snapshot {}", name), dir.join(relative))
+                })
+                .collect();
+        }
+        let snapper = root.join(".snapshots");
+        if snapper.is_dir() {
+            return snapshot_dirs(snapper)
+                .into_iter()
+                .map(|dir| {
+                    let number = dir.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                    let date = fs::read_to_string(dir.join("info.xml")).ok().and_then(|info| {
+                        let start = info.find("<date>")? + "<date>".len();
+                        let end = start + info[start..].find("</date>")?;
+                        Some(info[start..end].to_string())
+                    });
+                    let name = match date {
+                        Some(date) => format!("Snapshot {} ({} UTC)", number, date),
+                        None => format!("Snapshot {}", number),
+                    };
+                    (name, dir.join("snapshot").join(relative))
+                })
+                .collect();
+        }
+    }
+    Vec::new()
+}
+
+#[cfg(windows)]
+fn snapshot_copies(path: &Path) -> Vec<(String, PathBuf)> {
+    use std::process::Command;
+    let Some(path) = path.to_str() else {
+        return Vec::new();
+    };
+    let Some((drive, relative)) = path.split_once(':') else {
+        return Vec::new();
+    };
+    let script = format!(
+        "$v = (Get-CimInstance Win32_Volume -Filter \"DriveLetter='{}:'\").DeviceID; \
+         Get-CimInstance Win32_ShadowCopy | Where-Object VolumeName -eq $v | ForEach-Object {{ \
+         \"$($_.DeviceObject)|$($_.InstallDate.ToString('yyyy-MM-dd HH:mm'))\" }}",
+        drive
+    );
+    let Ok(output) = Command::new("powershell").args(["-NoProfile", "-Command", &script]).output() else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (device, date) = line.trim().split_once('|')?;
+            Some((format!("Shadow copy {}", date), PathBuf::from(format!("{}{}", device, relative))))
+        })
+        .collect()
+}
+
+/// Puts `version` back at `path`; whatever is there now goes to the trash. The version is copied
+/// next to `path` first, so a copy that fails partway leaves the current one where it was.
+pub fn restore(version: &Path, path: &Path) -> Result<(), String> {
+    let name = path.file_name().ok_or_else(|| "the path has no name".to_string())?.to_string_lossy();
+    let temp = path.with_file_name(format!(".{}.restoring", name));
+    let copied = if version.is_dir() {
+        let mut options = fs_extra::dir::CopyOptions::new();
+        options.content_only = true;
+        fs::create_dir(&temp)
+            .map_err(|err| err.to_string())
+            .and_then(|_| fs_extra::dir::copy(version, &temp, &options).map(|_| ()).map_err(|err| err.to_string()))
+    } else {
+        fs::copy(version, &temp).map(|_| ()).map_err(|err| err.to_string())
+    };
+    let swapped = copied.and_then(|_| {
+        if fs::symlink_metadata(path).is_ok() {
+            desktop::trash(path)?;
+        }
+        fs::rename(&temp, path).map_err(|err| err.to_string())
+    });
+    if swapped.is_err() {
+        let _ = if temp.is_dir() { fs::remove_dir_all(&temp) } else { fs::remove_file(&temp) };
+    }
+    swapped
+}
+
+/// A line-by-line comparison of an older version of a text file with the current one.
+pub fn diff_files(old: &Path, new: &Path) -> Result<Vec<DiffLine>, String> {
+    let read = |path: &Path| -> Result<String, String> {
+        let bytes = fs::read(path).map_err(|err| err.to_string())?;
+        let encoding = encoding::detect(&bytes).ok_or_else(|| "only text files can be compared".to_string())?;
+        Ok(encoding::decode(&bytes, encoding))
+    };
+    let (old, new) = (read(old)?, read(new)?);
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let (prefix, suffix) = common_ends(&old, &new);
+    if (old.len() - prefix - suffix).max(new.len() - prefix - suffix) > MAX_DIFF_LINES {
+        return Err(format!("more than {} changed lines are too many to compare", MAX_DIFF_LINES));
+    }
+    Ok(diff_lines(&old, &new))
+}
+
+/// How many lines the two start with in common, and how many more they end with.
+fn common_ends(old: &[&str], new: &[&str]) -> (usize, usize) {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let rest = old.len().min(new.len()) - prefix;
+    let suffix = old.iter().rev().zip(new.iter().rev()).take(rest).take_while(|(a, b)| a == b).count();
+    (prefix, suffix)
+}
+
+/// Finds the longest common subsequence of what lies between the common start and end, then
+/// walks it to mark what was removed and added.
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<DiffLine> {
+    let (prefix, suffix) = common_ends(old, new);
+    let same = |lines: &[&str]| lines.iter().map(|line| DiffLine::Same(line.to_string())).collect::<Vec<_>>();
+    let mut lines = same(&old[..prefix]);
+    lines.extend(diff_middle(&old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix]));
+    lines.extend(same(&old[old.len() - suffix..]));
+    lines
+}
+
+fn diff_middle(old: &[&str], new: &[&str]) -> Vec<DiffLine> {
+    let mut common = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || common[i][j + 1] >= common[i + 1][j]) {
+            lines.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        } else {
+            lines.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marks_removed_and_added_lines() {
+        let lines = diff_lines(&["a", "b", "c"], &["a", "c", "d"]);
+        assert_eq!(
+            lines,
+            vec![
+                DiffLine::Same("a".to_string()),
+                DiffLine::Removed("b".to_string()),
+                DiffLine::Same("c".to_string()),
+                DiffLine::Added("d".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn handles_empty_sides() {
+        assert_eq!(diff_lines(&[], &["x"]), vec![DiffLine::Added("x".to_string())]);
+        assert_eq!(diff_lines(&["x"], &[]), vec![DiffLine::Removed("x".to_string())]);
+    }
+}